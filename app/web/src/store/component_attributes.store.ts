@@ -32,6 +32,29 @@ export interface InsertPropertyEditorValueArgs {
   key?: string;
 }
 
+export interface AttributeValueImpactLeaf {
+  componentId: ComponentId;
+  name: string;
+}
+
+export interface AttributeValueImpact {
+  attributeValueIds: string[];
+  values: {
+    attributeValueId: string;
+    componentId: ComponentId;
+    source: { kind: string; path?: string; name?: string };
+  }[];
+  components: { componentId: ComponentId; name: string; valueCount: number }[];
+  codeGeneration: AttributeValueImpactLeaf[];
+  qualifications: AttributeValueImpactLeaf[];
+  actions: {
+    actionId: string;
+    componentId: ComponentId;
+    kind: string;
+    name: string | null;
+  }[];
+}
+
 export interface SetTypeArgs {
   componentId: string;
   value?: unknown;
@@ -53,6 +76,7 @@ export const useComponentAttributesStore = (componentId: ComponentId) => {
         schema: null as PropertyEditorSchema | null,
         validations: null as PropertyEditorValidation[] | null,
         values: null as PropertyEditorValues | null,
+        impactByValueId: {} as Record<string, AttributeValueImpact>,
       }),
       getters: {
         currentValueForValueId:
@@ -61,7 +85,7 @@ export const useComponentAttributesStore = (componentId: ComponentId) => {
             state.values?.values[valueId],
         // puts the schema, validations, values all together in a format used by the property editor
         editorContext: (state) => {
-          const { schema, validations, values } = state;
+          const { schema, validations, values, impactByValueId } = state;
           if (!schema || !validations || !values) return undefined;
 
           // previously called hackAwayTheZeroElementOfContainers - not entirely clear what it's doing
@@ -107,6 +131,7 @@ export const useComponentAttributesStore = (componentId: ComponentId) => {
               ...values,
               childValues: filteredChildValues,
             },
+            impactByValueId,
           };
         },
 
@@ -182,7 +207,23 @@ export const useComponentAttributesStore = (componentId: ComponentId) => {
           });
         },
 
+        // reports what will be recomputed if the value is changed, without changing it
+        async FETCH_ATTRIBUTE_VALUE_IMPACT(attributeValueId: string) {
+          return new ApiRequest<AttributeValueImpact>({
+            url: "component/attribute_value_impact",
+            keyRequestStatusBy: attributeValueId,
+            params: {
+              attributeValueId,
+              ...visibilityParams,
+            },
+            onSuccess: (response) => {
+              this.impactByValueId[attributeValueId] = response;
+            },
+          });
+        },
+
         reloadPropertyEditorData() {
+          this.impactByValueId = {};
           this.FETCH_PROPERTY_EDITOR_SCHEMA();
           this.FETCH_PROPERTY_EDITOR_VALUES();
           this.FETCH_PROPERTY_EDITOR_VALIDATIONS();
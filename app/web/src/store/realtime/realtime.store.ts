@@ -35,15 +35,25 @@ type TrackedSubscription = EventTypeAndCallback & {
 type RealtimeEventMetadata = {
   version: number;
   workspace_pk: string;
+  sequence?: number;
+  stream_id?: string;
 };
 
 export const useRealtimeStore = defineStore("realtime", () => {
   const authStore = useAuthStore();
 
+  // the last event received, which lets the server replay the events we miss while reconnecting
+  let lastStreamId: string | undefined;
+  let lastSequence: number | undefined;
+
   // ReconnectingWebsocket is a small wrapper around the native Websocket that should
   // handle basic reconnection logic
   const socket = new ReconnectingWebSocket(
-    () => `${API_WS_URL}/workspace_updates?token=Bearer+${authStore.token}`,
+    () => {
+      const url = `${API_WS_URL}/workspace_updates?token=Bearer+${authStore.token}`;
+      if (lastStreamId === undefined || lastSequence === undefined) return url;
+      return `${url}&streamId=${lastStreamId}&resumeFrom=${lastSequence}`;
+    },
     [],
     {
       // see options https://www.npmjs.com/package/reconnecting-websocket#available-options
@@ -150,6 +160,22 @@ export const useRealtimeStore = defineStore("realtime", () => {
     }
   }
 
+  // only ask the server for the kinds of events we have subscribers for
+  const subscribedEventKinds = computed(() =>
+    _.sortBy(_.uniq(_.map(subscriptions, "eventType"))),
+  );
+  function sendEventFilter() {
+    if (rawConnectionStatus.value !== "open") return;
+    socket.send(
+      JSON.stringify({
+        kind: "Subscribe",
+        payloadKinds: subscribedEventKinds.value,
+      }),
+    );
+  }
+  watch(subscribedEventKinds, sendEventFilter);
+  socket.addEventListener("open", sendEventFilter);
+
   // TODO: add optional arg to unsubscribe to specific event types, topics, or by subscription id
   function unsubscribe(subscriberId: SubscriberId) {
     _.each(subscriptionsBySubscriberId.value[subscriberId], (sub) => {
@@ -180,6 +206,13 @@ export const useRealtimeStore = defineStore("realtime", () => {
 
   socket.addEventListener("message", (messageEvent) => {
     const messageEventData = JSON.parse(messageEvent.data);
+    if (messageEventData.payload.kind === "ResyncRequired") {
+      lastStreamId = messageEventData.payload.data.streamId;
+      lastSequence = messageEventData.payload.data.latestSequence ?? 0;
+    } else if (messageEventData.sequence !== undefined) {
+      lastStreamId = messageEventData.stream_id;
+      lastSequence = messageEventData.sequence;
+    }
     handleEvent(
       messageEventData.payload.kind,
       messageEventData.payload.data,
@@ -31,6 +31,8 @@ export type WsEventPayloadMap = {
     componentId: string;
   };
 
+  KeyPairRotated: string;
+
   LogLine: {
     stream: {
       stream: string;
@@ -46,6 +48,11 @@ export type WsEventPayloadMap = {
   ResourceRefreshed: {
     componentId: string;
   };
+  // the server couldn't replay the events missed while disconnected, so everything needs refetching
+  ResyncRequired: {
+    streamId: string;
+    latestSequence: number | null;
+  };
   // UpdatedDependentValue: {
   //   componentId: string;
   // }
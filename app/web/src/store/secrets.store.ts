@@ -60,6 +60,8 @@ export type Secret = {
   definition: SecretDefinitionId;
   name: string;
   description?: string;
+  // where the secret message lives, anything but "database" is only a reference
+  backend: "database" | "file" | "vault";
   createdInfo: ActorAndTimestamp;
   updatedInfo?: ActorAndTimestamp;
   expiration?: string;
@@ -219,6 +221,7 @@ export function useSecretsStore() {
                 definition: definitionId,
                 name,
                 description,
+                backend: "database",
                 createdInfo: {
                   actor: { kind: "user", label: userName, id: userId },
                   timestamp: Date(),
@@ -314,6 +317,17 @@ export function useSecretsStore() {
             },
           },
         ]);
+        realtimeStore.subscribe(this.$id, `workspace/${workspaceId}`, [
+          {
+            eventType: "KeyPairRotated",
+            callback: (keyPairPk) => {
+              // secrets sealed for a rotated out key pair will be rejected, so make sure
+              // we encrypt new secrets with the current public key
+              if (this.publicKey?.pk === keyPairPk) return;
+              this.GET_PUBLIC_KEY();
+            },
+          },
+        ]);
       },
     }),
   )();
@@ -1,6 +1,6 @@
 use clap::{ArgAction, Parser};
 
-use council_server::server::config::{Config, ConfigError, ConfigFile, StandardConfigFile};
+use council_server::server::config::{ConfigError, ConfigFile, StandardConfigFile};
 
 const NAME: &str = "council";
 
@@ -28,16 +28,15 @@ pub(crate) struct Args {
     pub(crate) disable_opentelemetry: bool,
 }
 
-impl TryFrom<Args> for Config {
+impl TryFrom<&Args> for ConfigFile {
     type Error = ConfigError;
 
-    fn try_from(args: Args) -> Result<Self, Self::Error> {
+    fn try_from(args: &Args) -> Result<Self, Self::Error> {
         ConfigFile::layered_load(NAME, |config_map| {
-            if let Some(url) = args.nats_url {
+            if let Some(url) = args.nats_url.clone() {
                 config_map.set("nats.url", url);
             }
-        })?
-        .try_into()
+        })
     }
 }
 
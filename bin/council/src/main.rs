@@ -1,8 +1,11 @@
 use color_eyre::Result;
+use council_server::server::config::{
+    start_config_reload_signal_handler_task, Config, ConfigFile, ConfigReload,
+};
 use telemetry_application::{
     prelude::*, ApplicationTelemetryClient, TelemetryClient, TelemetryConfig,
 };
-use tokio::sync::watch;
+use tokio::sync::{mpsc, watch};
 
 mod args;
 
@@ -57,7 +60,17 @@ async fn run(
         telemetry.disable_opentelemetry().await?;
     }
 
-    let config = council_server::server::Config::try_from(args)?;
+    let config_file = ConfigFile::try_from(&args)?;
+    let config = Config::try_from(config_file.clone())?;
+
+    if let Some(log_filter) = config_file.log_filter() {
+        telemetry.set_custom_tracing(log_filter).await?;
+    }
+
+    let reloads =
+        start_config_reload_signal_handler_task(config_file, move || ConfigFile::try_from(&args))?;
+    drop(tokio::spawn(apply_config_reloads(reloads, telemetry)));
+
     let server = council_server::Server::new_with_config(config).await?;
     let (subscriber_started_tx, _subscriber_started_rx) = watch::channel(());
     server
@@ -65,3 +78,23 @@ async fn run(
         .await?;
     Ok(())
 }
+
+async fn apply_config_reloads(
+    mut reloads: mpsc::Receiver<ConfigReload<ConfigFile>>,
+    mut telemetry: ApplicationTelemetryClient,
+) {
+    while let Some(reload) = reloads.recv().await {
+        if reload.has_changed("log_filter") {
+            match reload.config_file.log_filter() {
+                Some(log_filter) => {
+                    if let Err(err) = telemetry.set_custom_tracing(log_filter).await {
+                        warn!(error = ?err, "failed to apply reloaded log filter");
+                    }
+                }
+                None => warn!(
+                    "log_filter was removed; keeping the current tracing level until a restart"
+                ),
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use std::{net::SocketAddr, path::PathBuf, time::Duration};
 
 use clap::{ArgAction, Parser};
-use cyclone_server::{Config, ConfigError, IncomingStream};
+use cyclone_server::{
+    Config, ConfigError, IncomingStream, SecretBackendsConfig, VaultSecretBackendConfig,
+};
 
 const NAME: &str = "cyclone";
 
@@ -103,6 +105,32 @@ pub(crate) struct Args {
     /// Cyclone decryption key file location [example: /run/cyclone/cyclone.key]
     #[arg(long)]
     pub(crate) decryption_key: PathBuf,
+
+    /// Root directory of the secrets referenced in the file secret backend
+    #[arg(long, env = "SI_SECRET_BACKEND_FILE_ROOT", hide_env = true)]
+    pub(crate) secret_backend_file_root: Option<PathBuf>,
+
+    /// Address of the Vault-compatible secret backend [example: https://vault.example.com:8200/]
+    #[arg(
+        long,
+        env = "SI_SECRET_BACKEND_VAULT_ADDRESS",
+        hide_env = true,
+        requires = "secret_backend_vault_token"
+    )]
+    pub(crate) secret_backend_vault_address: Option<String>,
+
+    /// Mount path of the KV (version 2) secrets engine of the Vault-compatible secret backend
+    #[arg(long, env = "SI_SECRET_BACKEND_VAULT_MOUNT", default_value = "secret")]
+    pub(crate) secret_backend_vault_mount: String,
+
+    /// Token used to read from the Vault-compatible secret backend
+    #[arg(
+        long,
+        env = "SI_SECRET_BACKEND_VAULT_TOKEN",
+        hide_env_values = true,
+        requires = "secret_backend_vault_address"
+    )]
+    pub(crate) secret_backend_vault_token: Option<String>,
 }
 
 impl TryFrom<Args> for Config {
@@ -138,6 +166,21 @@ impl TryFrom<Args> for Config {
             builder.enable_resolver(false);
         }
 
+        builder.secret_backends(SecretBackendsConfig {
+            file_root: args.secret_backend_file_root,
+            vault: match (
+                args.secret_backend_vault_address,
+                args.secret_backend_vault_token,
+            ) {
+                (Some(address), Some(token)) => Some(VaultSecretBackendConfig {
+                    address,
+                    mount: args.secret_backend_vault_mount,
+                    token: token.into(),
+                }),
+                _ => None,
+            },
+        });
+
         if args.oneshot {
             builder.limit_requests(1);
         } else if let Some(limit_requests) = args.limit_requests {
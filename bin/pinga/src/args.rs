@@ -1,5 +1,5 @@
 use clap::{ArgAction, Parser};
-use pinga_server::{Config, ConfigError, ConfigFile, StandardConfigFile};
+use pinga_server::{ConfigError, ConfigFile, StandardConfigFile};
 
 const NAME: &str = "pinga";
 
@@ -65,15 +65,15 @@ pub(crate) struct Args {
     pub(crate) instance_id: Option<String>,
 }
 
-impl TryFrom<Args> for Config {
+impl TryFrom<&Args> for ConfigFile {
     type Error = ConfigError;
 
-    fn try_from(args: Args) -> Result<Self, Self::Error> {
+    fn try_from(args: &Args) -> Result<Self, Self::Error> {
         ConfigFile::layered_load(NAME, |config_map| {
-            if let Some(dbname) = args.pg_dbname {
+            if let Some(dbname) = args.pg_dbname.clone() {
                 config_map.set("pg.dbname", dbname);
             }
-            if let Some(hostname) = args.pg_hostname {
+            if let Some(hostname) = args.pg_hostname.clone() {
                 config_map.set("pg.hostname", hostname);
             }
             if let Some(pool_max_size) = args.pg_pool_max_size {
@@ -82,25 +82,24 @@ impl TryFrom<Args> for Config {
             if let Some(port) = args.pg_port {
                 config_map.set("pg.port", i64::from(port));
             }
-            if let Some(user) = args.pg_user {
+            if let Some(user) = args.pg_user.clone() {
                 config_map.set("pg.user", user);
             }
-            if let Some(url) = args.nats_url {
+            if let Some(url) = args.nats_url.clone() {
                 config_map.set("nats.url", url);
             }
-            if let Some(cyclone_encyption_key_path) = args.cyclone_encryption_key_path {
+            if let Some(cyclone_encyption_key_path) = args.cyclone_encryption_key_path.clone() {
                 config_map.set("cyclone_encryption_key_path", cyclone_encyption_key_path);
             }
             if let Some(concurrency) = args.concurrency {
                 config_map.set("concurrency_limit", i64::from(concurrency));
             }
-            if let Some(instance_id) = args.instance_id {
+            if let Some(instance_id) = args.instance_id.clone() {
                 config_map.set("instance_id", instance_id);
             }
 
             config_map.set("pg.application_name", NAME);
-        })?
-        .try_into()
+        })
     }
 }
 
@@ -1,9 +1,13 @@
 use color_eyre::Result;
-use pinga_server::{Config, Server};
+use pinga_server::{
+    start_config_reload_signal_handler_task, Config, ConfigFile, ConfigReload,
+    PingaConfigReloadHandle, Server,
+};
 use telemetry_application::{
     prelude::*, start_tracing_level_signal_handler_task, ApplicationTelemetryClient,
     TelemetryClient, TelemetryConfig,
 };
+use tokio::sync::mpsc::Receiver;
 
 mod args;
 
@@ -47,11 +51,54 @@ async fn run(args: args::Args, mut telemetry: ApplicationTelemetryClient) -> Res
         telemetry.disable_opentelemetry().await?;
     }
 
-    let config = Config::try_from(args)?;
+    let config_file = ConfigFile::try_from(&args)?;
+    let config = Config::try_from(config_file.clone())?;
 
+    if let Some(log_filter) = config_file.log_filter() {
+        telemetry.set_custom_tracing(log_filter).await?;
+    }
     start_tracing_level_signal_handler_task(&telemetry)?;
 
-    Server::from_config(config).await?.run().await?;
+    // The instance id is random unless it is set, so it is pinned to the running one for reloads
+    let mut reload_args = args;
+    reload_args
+        .instance_id
+        .get_or_insert_with(|| config.instance_id().to_owned());
+
+    let server = Server::from_config(config).await?;
+
+    let reloads = start_config_reload_signal_handler_task(config_file, move || {
+        ConfigFile::try_from(&reload_args)
+    })?;
+    drop(tokio::spawn(apply_config_reloads(
+        reloads,
+        telemetry,
+        server.config_reload_handle(),
+    )));
+
+    server.run().await?;
 
     Ok(())
 }
+
+async fn apply_config_reloads(
+    mut reloads: Receiver<ConfigReload<ConfigFile>>,
+    mut telemetry: ApplicationTelemetryClient,
+    handle: PingaConfigReloadHandle,
+) {
+    while let Some(reload) = reloads.recv().await {
+        if reload.has_changed("log_filter") {
+            match reload.config_file.log_filter() {
+                Some(log_filter) => {
+                    if let Err(err) = telemetry.set_custom_tracing(log_filter).await {
+                        warn!(error = ?err, "failed to apply reloaded log filter");
+                    }
+                }
+                None => warn!(
+                    "log_filter was removed; keeping the current tracing level until a restart"
+                ),
+            }
+        }
+        handle.apply(&reload);
+    }
+}
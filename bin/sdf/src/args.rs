@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use clap::{builder::PossibleValuesParser, ArgAction, Parser};
-use sdf_server::{Config, ConfigError, ConfigFile, MigrationMode, StandardConfigFile};
+use sdf_server::{ConfigError, ConfigFile, MigrationMode, StandardConfigFile};
 
 const NAME: &str = "sdf";
 
@@ -71,6 +71,15 @@ pub(crate) struct Args {
     #[arg(long, requires = "generate_cyclone_secret_key_path")]
     pub(crate) generate_cyclone_public_key_path: Option<PathBuf>,
 
+    /// Enqueues a job re-encrypting all secrets with the active symmetric key (does not run
+    /// server)
+    #[arg(long)]
+    pub(crate) rotate_symmetric_keys: bool,
+
+    /// Number of secrets re-encrypted per transaction when rotating symmetric keys
+    #[arg(long, requires = "rotate_symmetric_keys")]
+    pub(crate) symmetric_key_rotation_batch_size: Option<i64>,
+
     /// Location on disk of available packages
     pub(crate) pkgs_path: Option<String>,
 
@@ -79,15 +88,15 @@ pub(crate) struct Args {
     pub(crate) module_index_url: Option<String>,
 }
 
-impl TryFrom<Args> for Config {
+impl TryFrom<&Args> for ConfigFile {
     type Error = ConfigError;
 
-    fn try_from(args: Args) -> Result<Self, Self::Error> {
+    fn try_from(args: &Args) -> Result<Self, Self::Error> {
         ConfigFile::layered_load(NAME, |config_map| {
-            if let Some(dbname) = args.pg_dbname {
+            if let Some(dbname) = args.pg_dbname.clone() {
                 config_map.set("pg.dbname", dbname);
             }
-            if let Some(hostname) = args.pg_hostname {
+            if let Some(hostname) = args.pg_hostname.clone() {
                 config_map.set("pg.hostname", hostname);
             }
             if let Some(pool_max_size) = args.pg_pool_max_size {
@@ -96,28 +105,27 @@ impl TryFrom<Args> for Config {
             if let Some(port) = args.pg_port {
                 config_map.set("pg.port", i64::from(port));
             }
-            if let Some(user) = args.pg_user {
+            if let Some(user) = args.pg_user.clone() {
                 config_map.set("pg.user", user);
             }
-            if let Some(migration_mode) = args.migration_mode {
+            if let Some(migration_mode) = args.migration_mode.clone() {
                 config_map.set("migration_mode", migration_mode);
             }
-            if let Some(url) = args.nats_url {
+            if let Some(url) = args.nats_url.clone() {
                 config_map.set("nats.url", url);
             }
-            if let Some(cyclone_encyption_key_path) = args.cyclone_encryption_key_path {
+            if let Some(cyclone_encyption_key_path) = args.cyclone_encryption_key_path.clone() {
                 config_map.set("cyclone_encryption_key_path", cyclone_encyption_key_path);
             }
-            if let Some(pkgs_path) = args.pkgs_path {
+            if let Some(pkgs_path) = args.pkgs_path.clone() {
                 config_map.set("pkgs_path", pkgs_path);
             }
-            if let Some(module_index_url) = args.module_index_url {
+            if let Some(module_index_url) = args.module_index_url.clone() {
                 config_map.set("module_index_url", module_index_url);
             }
 
             config_map.set("pg.application_name", NAME);
-        })?
-        .try_into()
+        })
     }
 }
 
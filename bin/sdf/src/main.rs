@@ -5,13 +5,14 @@ use std::sync::Arc;
 
 use color_eyre::Result;
 use sdf_server::{
-    Config, IncomingStream, JobProcessorClientCloser, JobProcessorConnector, MigrationMode, Server,
-    ServicesContext,
+    start_config_reload_signal_handler_task, Config, ConfigFile, ConfigReload, IncomingStream,
+    JobProcessorClientCloser, JobProcessorConnector, MigrationMode, Server, ServicesContext,
 };
 use telemetry_application::{
     prelude::*, start_tracing_level_signal_handler_task, ApplicationTelemetryClient,
     TelemetryClient, TelemetryConfig,
 };
+use tokio::sync::mpsc::Receiver;
 
 mod args;
 
@@ -71,7 +72,11 @@ async fn run(args: args::Args, mut telemetry: ApplicationTelemetryClient) -> Res
         return Ok(());
     }
 
-    let config = Config::try_from(args)?;
+    let args_rotate_symmetric_keys = args.rotate_symmetric_keys;
+    let args_symmetric_key_rotation_batch_size = args.symmetric_key_rotation_batch_size;
+
+    let config_file = ConfigFile::try_from(&args)?;
+    let config = Config::try_from(config_file.clone())?;
 
     let encryption_key = Server::load_encryption_key(config.cyclone_encryption_key_path()).await?;
     let jwt_public_signing_key =
@@ -116,8 +121,25 @@ async fn run(args: args::Args, mut telemetry: ApplicationTelemetryClient) -> Res
         trace!("migration mode is skip, not running migrations");
     }
 
+    if args_rotate_symmetric_keys {
+        Server::rotate_symmetric_keys(&services_context, args_symmetric_key_rotation_batch_size)
+            .await?;
+        return Ok(());
+    }
+
+    if let Some(log_filter) = config_file.log_filter.as_deref() {
+        telemetry.set_custom_tracing(log_filter).await?;
+    }
     start_tracing_level_signal_handler_task(&telemetry)?;
 
+    let reloads =
+        start_config_reload_signal_handler_task(config_file, move || ConfigFile::try_from(&args))?;
+    drop(tokio::spawn(apply_config_reloads(
+        reloads,
+        telemetry,
+        services_context.clone(),
+    )));
+
     let posthog_client = Server::start_posthog(config.posthog()).await?;
 
     match config.incoming_stream() {
@@ -169,3 +191,37 @@ async fn run(args: args::Args, mut telemetry: ApplicationTelemetryClient) -> Res
 
     Ok(())
 }
+
+async fn apply_config_reloads(
+    mut reloads: Receiver<ConfigReload<ConfigFile>>,
+    mut telemetry: ApplicationTelemetryClient,
+    services_context: ServicesContext,
+) {
+    while let Some(reload) = reloads.recv().await {
+        if reload.has_changed("log_filter") {
+            match reload.config_file.log_filter.as_deref() {
+                Some(log_filter) => {
+                    if let Err(err) = telemetry.set_custom_tracing(log_filter).await {
+                        warn!(error = ?err, "failed to apply reloaded log filter");
+                    }
+                }
+                None => warn!(
+                    "log_filter was removed; keeping the current tracing level until a restart"
+                ),
+            }
+        }
+        if reload.has_changed("module_index_url") {
+            info!(
+                module_index_url = %reload.config_file.module_index_url,
+                "applying reloaded module index url",
+            );
+            services_context
+                .set_module_index_url(Some(reload.config_file.module_index_url.clone()));
+        }
+        if reload.has_changed("pg.pool_max_size") {
+            services_context
+                .pg_pool()
+                .resize(reload.config_file.pg.pool_max_size);
+        }
+    }
+}
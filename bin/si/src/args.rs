@@ -1,4 +1,5 @@
 use clap::{builder::PossibleValuesParser, Parser, Subcommand};
+use std::path::PathBuf;
 use std::str::FromStr;
 use strum::{Display, EnumString, EnumVariantNames};
 
@@ -51,21 +52,31 @@ pub(crate) struct Args {
     #[arg(long, short = 'p', default_value = "false")]
     pub is_preview: bool,
 
-    /// Allows starting the web service and binding to a specific IP
-    #[arg(long = "web-host", env = "SI_WEB_ADDRESS", default_value = "127.0.0.1")]
-    pub web_host: String,
+    /// A TOML profile declaring the images, host ports, resource limits and extra environment of
+    /// every service. Defaults to `si.toml` in the current directory or the user's config dir, or
+    /// the file at `SI_PROFILE`
+    #[arg(long, global = true)]
+    pub profile: Option<PathBuf>,
 
-    /// Allows starting the web service and binding to a specific port
-    #[arg(long = "web-port", env = "SI_WEB_PORT", default_value = "8080")]
-    pub web_port: u32,
+    /// Allows starting the web service and binding to a specific IP [default: 127.0.0.1]. Takes
+    /// precedence over the profile
+    #[arg(long = "web-host", env = "SI_WEB_ADDRESS")]
+    pub web_host: Option<String>,
 
-    /// Allows starting the sdf service and binding to a specific IP
-    #[arg(long = "sdf-host", env = "SI_SDF_ADDRESS", default_value = "127.0.0.1")]
-    pub sdf_host: String,
+    /// Allows starting the web service and binding to a specific port [default: 8080]. Takes
+    /// precedence over the profile
+    #[arg(long = "web-port", env = "SI_WEB_PORT")]
+    pub web_port: Option<u32>,
 
-    /// Allows starting the sdf service and binding to a specific port
-    #[arg(long = "sdf-port", env = "SI_SDF_PORT", default_value = "5156")]
-    pub sdf_port: u32,
+    /// Allows starting the sdf service and binding to a specific IP [default: 127.0.0.1]. Takes
+    /// precedence over the profile
+    #[arg(long = "sdf-host", env = "SI_SDF_ADDRESS")]
+    pub sdf_host: Option<String>,
+
+    /// Allows starting the sdf service and binding to a specific port [default: 5156]. Takes
+    /// precedence over the profile
+    #[arg(long = "sdf-port", env = "SI_SDF_PORT")]
+    pub sdf_port: Option<u32>,
 
     /// The engine in which to launch System Initiate Containers
     #[arg(value_parser = PossibleValuesParser::new(Engine::variants()))]
@@ -84,6 +95,14 @@ pub(crate) struct Args {
     /// Enable debug logs for function executions via veritech
     #[clap(long)]
     pub with_function_debug_logs: bool,
+    /// The URL of the sdf that the `changeset`, `component`, `pkg` and `fix` commands drive.
+    /// Defaults to the sdf address of the profile
+    #[arg(long, env = "SI_SDF_URL", global = true)]
+    pub sdf_url: Option<String>,
+    /// The API token that the `changeset`, `component`, `pkg`, `fix` and `status --deep` commands
+    /// authenticate to sdf with
+    #[arg(long, env = "SI_API_TOKEN", global = true, hide_env_values = true)]
+    pub api_token: Option<String>,
     #[command(subcommand)]
     pub(crate) command: Commands,
 }
@@ -110,8 +129,105 @@ pub(crate) enum Commands {
     Update(UpdateArgs),
     /// Checks the status of the specified installation mode
     Status(StatusArgs),
+    /// Backs up the database, keys and credentials of the installation into a single archive
+    Backup(BackupArgs),
+    /// Restores an installation from an archive created with `si backup`
+    Restore(RestoreArgs),
+    /// Exports the stack as docker-compose or Kubernetes manifests
+    Export(ExportArgs),
     // Reports an error to System Initiative.
     // Report(ReportArgs),
+    #[command(flatten)]
+    Headless(HeadlessCommands),
+}
+
+/// Commands that drive a running System Initiative through sdf with an API token, printing their
+/// results as JSON.
+#[derive(Debug, Subcommand)]
+pub(crate) enum HeadlessCommands {
+    /// Creates, applies and lists change sets
+    #[command(subcommand)]
+    Changeset(ChangesetCommands),
+    /// Sets the values of components
+    #[command(subcommand)]
+    Component(ComponentCommands),
+    /// Exports and installs package files
+    #[command(subcommand)]
+    Pkg(PkgCommands),
+    /// Shows the status of fixes
+    #[command(subcommand)]
+    Fix(FixCommands),
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ChangesetCommands {
+    /// Creates a change set
+    Create {
+        /// The name of the change set
+        name: String,
+    },
+    /// Applies a change set to head
+    Apply {
+        /// The pk of the change set
+        change_set_pk: String,
+    },
+    /// Lists the open change sets
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ComponentCommands {
+    /// Sets a value of a component. A value that isn't valid JSON is set as a string
+    Set {
+        /// The name of the component
+        name: String,
+        /// The path of the value, such as `/root/domain/image`
+        path: String,
+        /// The value to set
+        value: String,
+        /// The pk of the change set to set the value in. Setting a value at head creates a change
+        /// set for it
+        #[arg(long)]
+        change_set: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum PkgCommands {
+    /// Packages schemas as a module and writes it to a file
+    Export {
+        /// The path of the package file to write
+        file: PathBuf,
+        /// The name of the package
+        #[arg(long)]
+        name: String,
+        /// The version of the package
+        #[arg(long)]
+        version: String,
+        /// The description of the package
+        #[arg(long)]
+        description: Option<String>,
+        /// The name of a schema to package, which can be repeated
+        #[arg(long = "schema", required = true)]
+        schemas: Vec<String>,
+        /// The pk of the change set to package the schemas of
+        #[arg(long)]
+        change_set: Option<String>,
+    },
+    /// Installs a package file
+    Install {
+        /// The path of the package file
+        file: PathBuf,
+        /// The pk of the change set to install the package in
+        #[arg(long)]
+        change_set: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum FixCommands {
+    /// Shows the fix batches of the workspace and the status of their fixes
+    Status,
 }
 
 #[derive(Debug, clap::Args)]
@@ -131,6 +247,10 @@ pub(crate) struct ConfigureArgs {
     /// Please Note, you will need to restart the applications to apply these new credentials
     #[clap(short, long)]
     pub force_reconfigure: bool,
+    /// Writes a profile for the stack to the `--profile` path, or to `si.toml` in the user's
+    /// config dir, instead of configuring credentials
+    #[clap(long)]
+    pub write_profile: bool,
 }
 
 #[derive(Debug, clap::Args)]
@@ -142,6 +262,15 @@ pub(crate) struct StatusArgs {
     /// The number of log lines to show when `show_logs` is used
     #[arg(long, short = 'l', default_value = "10")]
     pub log_lines: usize,
+
+    /// Runs end-to-end checks of every service through sdf: Postgres, NATS, council, veritech
+    /// and pinga. Requires an API token
+    #[clap(long)]
+    pub deep: bool,
+
+    /// Prints the status as JSON, for scripts
+    #[clap(long, conflicts_with = "show_logs")]
+    pub json: bool,
 }
 
 #[derive(Debug, clap::Args)]
@@ -173,6 +302,39 @@ pub(crate) struct UpdateArgs {
     pub binary: bool,
 }
 
+#[derive(Debug, clap::Args)]
+pub(crate) struct BackupArgs {
+    /// The path of the archive to write. Defaults to `si-backup-<timestamp>.tar.gz`
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct RestoreArgs {
+    /// The path of an archive created with `si backup`
+    pub archive: PathBuf,
+    /// Skip the confirmation check as part of the restore command
+    #[clap(short = 'y', long)]
+    pub skip_confirmation: bool,
+}
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct ExportArgs {
+    /// The format of the manifests
+    #[arg(value_parser = PossibleValuesParser::new(ExportFormat::variants()))]
+    #[arg(long, short, default_value = "compose")]
+    format: String,
+    /// The path of the file to write. Defaults to `docker-compose.yml` or `si-kubernetes.yml`
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+impl ExportArgs {
+    pub(crate) fn format(&self) -> ExportFormat {
+        ExportFormat::from_str(&self.format).expect("format is a validated input str")
+    }
+}
+
 #[derive(Debug, clap::Args)]
 pub(crate) struct InstallArgs {
     /// Skip the system check as part of the install command
@@ -204,6 +366,23 @@ pub enum Engine {
     Podman,
 }
 
+#[derive(Clone, Copy, Debug, Display, EnumString, EnumVariantNames, PartialEq)]
+pub enum ExportFormat {
+    #[strum(serialize = "compose")]
+    Compose,
+    #[strum(serialize = "k8s")]
+    Kubernetes,
+}
+
+impl From<ExportFormat> for si_cli::cmd::ExportFormat {
+    fn from(value: ExportFormat) -> Self {
+        match value {
+            ExportFormat::Compose => Self::Compose,
+            ExportFormat::Kubernetes => Self::Kubernetes,
+        }
+    }
+}
+
 impl Mode {
     #[must_use]
     pub const fn variants() -> &'static [&'static str] {
@@ -217,3 +396,10 @@ impl Engine {
         <Self as strum::VariantNames>::VARIANTS
     }
 }
+
+impl ExportFormat {
+    #[must_use]
+    pub const fn variants() -> &'static [&'static str] {
+        <Self as strum::VariantNames>::VARIANTS
+    }
+}
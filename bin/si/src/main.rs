@@ -1,7 +1,12 @@
-use crate::args::{Commands, Engine};
+use crate::args::{
+    ChangesetCommands, Commands, ComponentCommands, Engine, FixCommands, HeadlessCommands,
+    PkgCommands,
+};
 use color_eyre::Result;
 use si_cli::engine::docker_engine::DockerEngine;
 use si_cli::engine::podman_engine::PodmanEngine;
+use si_cli::headless::Headless;
+use si_cli::profile::Profile;
 use si_cli::state::AppState;
 use std::sync::Arc;
 use telemetry_application::{prelude::*, TelemetryConfig};
@@ -42,16 +47,33 @@ async fn main() -> Result<()> {
         }
     }
 
-    let engine = match engine {
-        Engine::Docker => DockerEngine::new(args.docker_sock.clone()).await?,
-        Engine::Podman => PodmanEngine::new(args.podman_sock.clone()).await?,
-    };
+    let mut profile = Profile::load(args.profile.as_deref())?;
+    if let Some(web_host) = args.web_host.clone() {
+        profile.services.web.host_ip = Some(web_host);
+    }
+    if let Some(web_port) = args.web_port {
+        profile.services.web.host_port = Some(web_port);
+    }
+    if let Some(sdf_host) = args.sdf_host.clone() {
+        profile.services.sdf.host_ip = Some(sdf_host);
+    }
+    if let Some(sdf_port) = args.sdf_port {
+        profile.services.sdf.host_port = Some(sdf_port);
+    }
+    profile.validate()?;
 
-    let web_host = args.web_host.clone();
-    let web_port = args.web_port;
+    // Commands driving sdf need neither a container engine nor update checks, and print nothing
+    // but their JSON
+    if let Commands::Headless(command) = &args.command {
+        let headless = Headless::new(args.sdf_url.as_deref(), args.api_token.as_deref(), &profile)?;
+        run_headless(&headless, command).await?;
+        return Ok(());
+    }
 
-    let sdf_host = args.sdf_host.clone();
-    let sdf_port = args.sdf_port;
+    let engine = match engine {
+        Engine::Docker => DockerEngine::new(args.docker_sock.clone(), &profile.stack).await?,
+        Engine::Podman => PodmanEngine::new(args.podman_sock.clone(), &profile.stack).await?,
+    };
 
     let current_version = VERSION.trim();
 
@@ -67,10 +89,7 @@ async fn main() -> Result<()> {
         Arc::from(current_version),
         Arc::from(mode.to_string()),
         is_preview,
-        web_host,
-        web_port,
-        sdf_host,
-        sdf_port,
+        profile,
         args.with_function_debug_logs,
         Arc::from(engine),
     );
@@ -109,6 +128,7 @@ async fn main() -> Result<()> {
         println!("Preview mode... System Initiative would have taken the following actions");
     }
 
+    let api_token = args.api_token;
     match args.command {
         Commands::Install(_args) => {
             state.install().await?;
@@ -122,8 +142,12 @@ async fn main() -> Result<()> {
         Commands::Start(_args) => {
             state.start().await?;
         }
-        Commands::Configure(args) => {
-            state.configure(args.force_reconfigure).await?;
+        Commands::Configure(configure_args) => {
+            if configure_args.write_profile {
+                state.write_profile(args.profile.clone()).await?;
+            } else {
+                state.configure(configure_args.force_reconfigure).await?;
+            }
         }
         Commands::Delete(args) => {
             state.delete(args.keep_images).await?;
@@ -145,7 +169,27 @@ async fn main() -> Result<()> {
                 .await?;
         }
         Commands::Status(args) => {
-            state.status(args.show_logs, args.log_lines).await?;
+            state
+                .status(
+                    args.show_logs,
+                    args.log_lines,
+                    args.deep,
+                    args.json,
+                    api_token.as_deref(),
+                )
+                .await?;
+        }
+        Commands::Backup(args) => {
+            state.backup(args.output).await?;
+        }
+        Commands::Restore(args) => {
+            state.restore(&args.archive, args.skip_confirmation).await?;
+        }
+        Commands::Export(args) => {
+            state.export(args.format().into(), args.output).await?;
+        }
+        Commands::Headless(_) => {
+            unreachable!("headless commands are run before the container engine is set up")
         } // Commands::Report(_args) => {
           //     state.report().await?;
           // }
@@ -159,6 +203,58 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+async fn run_headless(headless: &Headless, command: &HeadlessCommands) -> Result<()> {
+    match command {
+        HeadlessCommands::Changeset(command) => match command {
+            ChangesetCommands::Create { name } => headless.create_change_set(name.clone()).await?,
+            ChangesetCommands::Apply { change_set_pk } => {
+                headless.apply_change_set(change_set_pk).await?
+            }
+            ChangesetCommands::List => headless.list_change_sets().await?,
+        },
+        HeadlessCommands::Component(command) => match command {
+            ComponentCommands::Set {
+                name,
+                path,
+                value,
+                change_set,
+            } => {
+                headless
+                    .set_component_value(name, path, value, change_set.as_deref())
+                    .await?
+            }
+        },
+        HeadlessCommands::Pkg(command) => match command {
+            PkgCommands::Export {
+                file,
+                name,
+                version,
+                description,
+                schemas,
+                change_set,
+            } => {
+                headless
+                    .export_pkg(
+                        file,
+                        name.clone(),
+                        version.clone(),
+                        description.clone(),
+                        schemas,
+                        change_set.as_deref(),
+                    )
+                    .await?
+            }
+            PkgCommands::Install { file, change_set } => {
+                headless.install_pkg(file, change_set.as_deref()).await?
+            }
+        },
+        HeadlessCommands::Fix(command) => match command {
+            FixCommands::Status => headless.fix_status().await?,
+        },
+    }
+    Ok(())
+}
+
 async fn wait_for_posthog_flush(done_sender: Sender<()>, sender: si_posthog::PosthogSender) {
     sender.run().await;
     done_sender
@@ -1,5 +1,5 @@
 use clap::{ArgAction, Parser};
-use veritech_server::{Config, ConfigError, ConfigFile, StandardConfigFile};
+use veritech_server::{ConfigError, ConfigFile, StandardConfigFile};
 
 const NAME: &str = "veritech";
 
@@ -26,16 +26,15 @@ pub(crate) struct Args {
     pub(crate) disable_opentelemetry: bool,
 }
 
-impl TryFrom<Args> for Config {
+impl TryFrom<&Args> for ConfigFile {
     type Error = ConfigError;
 
-    fn try_from(args: Args) -> Result<Self, Self::Error> {
-        ConfigFile::layered_load(NAME, move |config_map| {
-            if let Some(url) = args.nats_url {
+    fn try_from(args: &Args) -> Result<Self, Self::Error> {
+        ConfigFile::layered_load(NAME, |config_map| {
+            if let Some(url) = args.nats_url.clone() {
                 config_map.set("nats.url", url);
             }
-        })?
-        .try_into()
+        })
     }
 }
 
@@ -3,7 +3,10 @@ use telemetry_application::{
     prelude::*, start_tracing_level_signal_handler_task, ApplicationTelemetryClient,
     TelemetryClient, TelemetryConfig,
 };
-use veritech_server::{Config, CycloneSpec, Server};
+use tokio::sync::mpsc::Receiver;
+use veritech_server::{
+    start_config_reload_signal_handler_task, Config, ConfigFile, ConfigReload, CycloneSpec, Server,
+};
 
 mod args;
 
@@ -31,10 +34,18 @@ async fn run(args: args::Args, mut telemetry: ApplicationTelemetryClient) -> Res
     if args.disable_opentelemetry {
         telemetry.disable_opentelemetry().await?;
     }
-    let config = Config::try_from(args)?;
+    let config_file = ConfigFile::try_from(&args)?;
+    let config = Config::try_from(config_file.clone())?;
 
+    if let Some(log_filter) = config_file.log_filter.as_deref() {
+        telemetry.set_custom_tracing(log_filter).await?;
+    }
     start_tracing_level_signal_handler_task(&telemetry)?;
 
+    let reloads =
+        start_config_reload_signal_handler_task(config_file, move || ConfigFile::try_from(&args))?;
+    drop(tokio::spawn(apply_config_reloads(reloads, telemetry)));
+
     match config.cyclone_spec() {
         CycloneSpec::LocalHttp(_) => {
             Server::for_cyclone_http(config).await?.run().await?;
@@ -46,3 +57,23 @@ async fn run(args: args::Args, mut telemetry: ApplicationTelemetryClient) -> Res
 
     Ok(())
 }
+
+async fn apply_config_reloads(
+    mut reloads: Receiver<ConfigReload<ConfigFile>>,
+    mut telemetry: ApplicationTelemetryClient,
+) {
+    while let Some(reload) = reloads.recv().await {
+        if reload.has_changed("log_filter") {
+            match reload.config_file.log_filter.as_deref() {
+                Some(log_filter) => {
+                    if let Err(err) = telemetry.set_custom_tracing(log_filter).await {
+                        warn!(error = ?err, "failed to apply reloaded log filter");
+                    }
+                }
+                None => warn!(
+                    "log_filter was removed; keeping the current tracing level until a restart"
+                ),
+            }
+        }
+    }
+}
@@ -167,6 +167,21 @@ impl Client {
     }
 }
 
+/// Checks that council answers requests on `subject_prefix` (`council` or `<prefix>.council`).
+pub async fn ping(nats: &NatsClient, subject_prefix: &str) -> Result<()> {
+    let message = serde_json::to_vec(&Request::Ping)?;
+    let msg = nats
+        .request(format!("{subject_prefix}.ping"), message)
+        .await?;
+    if msg.payload().is_empty() {
+        return Err(Error::NoListenerAvailable);
+    }
+    match serde_json::from_slice::<Response>(msg.payload())? {
+        Response::Pong => Ok(()),
+        resp => Err(Error::UnexpectedResponse(format!("{resp:?}"))),
+    }
+}
+
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[remain::sorted]
@@ -178,4 +193,6 @@ pub enum Error {
     NoListenerAvailable,
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
+    #[error("unexpected response from council: {0}")]
+    UnexpectedResponse(String),
 }
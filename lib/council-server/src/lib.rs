@@ -60,6 +60,8 @@ pub enum Request {
         change_set_id: Id,
     },
     CreateValues,
+    /// Answered right away with [`Response::Pong`], to check that council is responsive.
+    Ping,
     ProcessedValue {
         change_set_id: Id,
         node_id: Id,
@@ -79,9 +81,20 @@ pub enum Request {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "kind")]
 pub enum Response {
-    BeenProcessed { node_id: Id },
-    Failed { node_id: Id },
+    BeenProcessed {
+        node_id: Id,
+    },
+    Failed {
+        node_id: Id,
+    },
     OkToCreate,
-    OkToProcess { node_ids: Vec<Id> },
+    OkToProcess {
+        node_ids: Vec<Id>,
+        /// The subset of `node_ids` that other jobs are also waiting on. Jobs must not skip
+        /// processing these nodes based only on their own view of the graph.
+        #[serde(default)]
+        shared_node_ids: Vec<Id>,
+    },
+    Pong,
     Shutdown,
 }
@@ -82,13 +82,14 @@ impl Server {
                     .unwrap();
             }
 
-            for (reply_channel, node_id) in complete_graph.fetch_all_available() {
-                info!(%reply_channel, %node_id, "Ok to process AttributeValue");
+            for (reply_channel, node_id, shared) in complete_graph.fetch_all_available() {
+                info!(%reply_channel, %node_id, %shared, "Ok to process AttributeValue");
                 self.nats
                     .publish(
                         reply_channel,
                         serde_json::to_vec(&Response::OkToProcess {
                             node_ids: vec![node_id],
+                            shared_node_ids: if shared { vec![node_id] } else { Vec::new() },
                         })
                         .unwrap(),
                     )
@@ -145,6 +146,16 @@ impl Server {
                     .await
                     .unwrap();
                 }
+                Request::Ping => {
+                    debug!(%reply_channel, "Council pinged");
+                    if let Err(err) = self
+                        .nats
+                        .publish(reply_channel, serde_json::to_vec(&Response::Pong).unwrap())
+                        .await
+                    {
+                        error!("Unable to answer ping: {err}");
+                    }
+                }
                 Request::ValueCreationDone => {
                     job_finished_value_creation(&mut value_create_queue, reply_channel)
                         .await
@@ -1,7 +1,10 @@
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use si_data_nats::NatsConfig;
-pub use si_settings::{StandardConfig, StandardConfigFile};
+pub use si_settings::{
+    start_config_reload_signal_handler_task, ConfigReload, ReloadableConfigFile, StandardConfig,
+    StandardConfigFile,
+};
 
 #[remain::sorted]
 #[derive(Debug, thiserror::Error)]
@@ -27,12 +30,25 @@ impl StandardConfig for Config {
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct ConfigFile {
     nats: NatsConfig,
+    /// Tracing directives replacing the ones from `SI_LOG`, such as `info,council_server=debug`.
+    log_filter: Option<String>,
+}
+
+impl ConfigFile {
+    /// Gets the tracing directives to run with, if any.
+    pub fn log_filter(&self) -> Option<&str> {
+        self.log_filter.as_deref()
+    }
 }
 
 impl StandardConfigFile for ConfigFile {
     type Error = ConfigError;
 }
 
+impl ReloadableConfigFile for ConfigFile {
+    const LIVE_SETTINGS: &'static [&'static str] = &["log_filter"];
+}
+
 impl TryFrom<ConfigFile> for Config {
     type Error = ConfigError;
 
@@ -56,12 +56,14 @@ impl ChangeSetGraph {
         self.dependency_data.is_empty()
     }
 
-    pub fn fetch_all_available(&mut self) -> Vec<(String, Id)> {
+    /// Returns the reply channel that should process each available node, the node and whether
+    /// other jobs are also waiting on that node.
+    pub fn fetch_all_available(&mut self) -> Vec<(String, Id, bool)> {
         let mut result = Vec::new();
         for graph in self.dependency_data.values_mut() {
             for (id, metadata) in graph.iter_mut() {
                 if let Some(reply_channel) = metadata.next_to_process() {
-                    result.push((reply_channel, *id));
+                    result.push((reply_channel, *id, metadata.is_wanted_by_others()));
                 }
             }
         }
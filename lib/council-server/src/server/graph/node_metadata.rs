@@ -47,6 +47,13 @@ impl NodeMetadata {
         self.wanted_by_reply_channels.is_empty() && self.processing_reply_channel.is_none()
     }
 
+    /// Whether any job other than the one processing this node is also waiting on it.
+    pub fn is_wanted_by_others(&self) -> bool {
+        self.wanted_by_reply_channels
+            .iter()
+            .any(|reply_channel| Some(reply_channel) != self.processing_reply_channel.as_ref())
+    }
+
     pub fn is_processing_stale(&self) -> bool {
         if let Some(processing_started_at) = self.processing_started_at {
             // If we've been updated more recently than when we last set the reply channel
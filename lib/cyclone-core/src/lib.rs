@@ -22,6 +22,7 @@ mod readiness;
 mod reconciliation;
 mod resolver_function;
 mod schema_variant_definition;
+mod secret_reference;
 mod sensitive_container;
 mod validation;
 
@@ -43,5 +44,6 @@ pub use resolver_function::{
 pub use schema_variant_definition::{
     SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess,
 };
+pub use secret_reference::{SecretBackendKind, SecretReference, WorkspaceSecretReference};
 pub use sensitive_container::{SensitiveContainer, SensitiveString};
 pub use validation::{ValidationRequest, ValidationResultSuccess};
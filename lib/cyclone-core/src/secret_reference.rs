@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, Display, EnumString};
+
+/// Where the message of a secret lives.
+#[remain::sorted]
+#[derive(
+    AsRefStr, Clone, Copy, Debug, Deserialize, Display, EnumString, Eq, PartialEq, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum SecretBackendKind {
+    /// The message is encrypted and stored in SI's database
+    Database,
+    /// The message is a JSON file under the root directory configured in cyclone
+    File,
+    /// The message is stored in a Vault-compatible KV (version 2) secrets engine configured in
+    /// cyclone
+    Vault,
+}
+
+impl Default for SecretBackendKind {
+    fn default() -> Self {
+        Self::Database
+    }
+}
+
+/// Points at a secret message stored in a backend other than [`SecretBackendKind::Database`].
+///
+/// Only the reference is sent to cyclone, encrypted like any other secret message, and cyclone
+/// resolves it into the actual message right before executing a function.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretReference {
+    /// The path of the secret, relative to the prefix of the secret's workspace in the backend.
+    pub path: String,
+    /// If provided, only this field of the secret is used as the message.
+    pub key: Option<String>,
+}
+
+/// A [`SecretReference`] along with the workspace of the secret holding it, which is what cyclone
+/// receives.
+///
+/// Cyclone only resolves the reference below the workspace's prefix of the backend, i.e.
+/// `<workspace_pk>/<path>`, so that a workspace can't read the secrets of another one.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSecretReference {
+    pub workspace_pk: String,
+    #[serde(flatten)]
+    pub reference: SecretReference,
+}
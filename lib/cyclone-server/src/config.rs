@@ -8,6 +8,8 @@ use derive_builder::Builder;
 use si_std::{CanonicalFile, CanonicalFileError};
 use thiserror::Error;
 
+use crate::SecretBackendsConfig;
+
 #[remain::sorted]
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -54,6 +56,9 @@ pub struct Config {
 
     #[builder(setter(into), default)]
     limit_requests: Option<u32>,
+
+    #[builder(default)]
+    secret_backends: SecretBackendsConfig,
 }
 
 impl Config {
@@ -122,6 +127,12 @@ impl Config {
     pub fn limit_requests(&self) -> Option<u32> {
         self.limit_requests
     }
+
+    /// Gets a reference to the config's secret backends.
+    #[must_use]
+    pub fn secret_backends(&self) -> &SecretBackendsConfig {
+        &self.secret_backends
+    }
 }
 
 impl ConfigBuilder {
@@ -62,6 +62,13 @@ impl DecryptionKey {
         sodiumoxide::crypto::sealedbox::open(&crypted, &self.public_key, &self.secret_key)
             .map_err(|_| DecryptionKeyError::DecryptionFailed)
     }
+
+    /// Encrypts a message so that it can only be decrypted with this key, like the secrets sent
+    /// to cyclone are.
+    pub fn encrypt_and_encode(&self, message: impl AsRef<[u8]>) -> String {
+        let crypted = sodiumoxide::crypto::sealedbox::seal(message.as_ref(), &self.public_key);
+        general_purpose::STANDARD_NO_PAD.encode(crypted)
+    }
 }
 
 impl From<BoxSecretKey> for DecryptionKey {
@@ -12,11 +12,9 @@ use bytes_lines_codec::BytesLinesCodec;
 use cyclone_core::{
     process::{self, ShutdownError},
     FunctionResult, FunctionResultFailure, FunctionResultFailureError, Message, OutputStream,
-    SensitiveString,
 };
 use futures::{SinkExt, StreamExt, TryStreamExt};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use serde_json::Value;
 use telemetry::prelude::*;
 use thiserror::Error;
 use tokio::{
@@ -27,8 +25,9 @@ use tokio_serde::{formats::SymmetricalJson, Deserializer, Framed, SymmetricallyF
 use tokio_util::codec::{Decoder, FramedRead, FramedWrite};
 
 use crate::{
+    redact::Redactor,
     request::{DecryptRequest, ListSecrets},
-    DecryptionKey, DecryptionKeyError, WebSocketMessage,
+    DecryptionKey, DecryptionKeyError, SecretBackendError, SecretBackends, WebSocketMessage,
 };
 
 const TX_TIMEOUT_SECS: Duration = Duration::from_secs(5);
@@ -37,12 +36,14 @@ pub fn new<Request, LangServerSuccess, Success>(
     lang_server_path: impl Into<PathBuf>,
     lang_server_debugging: bool,
     key: Arc<DecryptionKey>,
+    secret_backends: Arc<SecretBackends>,
     command: String,
 ) -> Execution<Request, LangServerSuccess, Success> {
     Execution {
         lang_server_path: lang_server_path.into(),
         lang_server_debugging,
         key,
+        secret_backends,
         command,
         request_marker: PhantomData,
         lang_server_success_marker: PhantomData,
@@ -69,6 +70,8 @@ pub enum ExecutionError {
     JSONSerialize(#[source] serde_json::Error),
     #[error("key pair error: {0}")]
     KeyPair(#[from] DecryptionKeyError),
+    #[error("secret backend error: {0}")]
+    SecretBackend(#[from] SecretBackendError),
     #[error("send timeout")]
     SendTimeout(#[source] tokio::time::error::Elapsed),
     #[error("unexpected websocket message type: {0:?}")]
@@ -90,6 +93,7 @@ pub struct Execution<Request, LangServerSuccess, Success> {
     lang_server_path: PathBuf,
     lang_server_debugging: bool,
     key: Arc<DecryptionKey>,
+    secret_backends: Arc<SecretBackends>,
     command: String,
     request_marker: PhantomData<Request>,
     lang_server_success_marker: PhantomData<LangServerSuccess>,
@@ -109,8 +113,8 @@ where
         // Send start is the initial communication before we read the request.
         Self::ws_send_start(ws).await?;
         // Now that the server said to start, I am going to read my message!
-        let request = Self::read_request(ws).await?;
-        let credentials: Vec<SensitiveString> = request.list_secrets(&self.key)?;
+        let request = self.read_request(ws).await?;
+        let redactor = Arc::new(Redactor::new(&request.list_secrets(&self.key)?));
         let mut command = Command::new(&self.lang_server_path);
         command
             .arg(&self.command)
@@ -149,12 +153,14 @@ where
             child,
             stdout,
             stderr,
-            credentials,
+            redactor,
             success_marker: self.success_marker,
         })
     }
 
-    async fn read_request(ws: &mut WebSocket) -> Result<Request> {
+    /// Reads the request, resolving the secrets it references from the secret backends so they
+    /// are decrypted and redacted like any other secret.
+    async fn read_request(&self, ws: &mut WebSocket) -> Result<Request> {
         let request = match ws.next().await {
             Some(Ok(WebSocketMessage::Text(json_str))) => {
                 serde_json::from_str(&json_str).map_err(ExecutionError::JSONDeserialize)?
@@ -163,7 +169,11 @@ where
             Some(Err(err)) => return Err(ExecutionError::WSRecvIO(err)),
             None => return Err(ExecutionError::WSRecvClosed),
         };
-        Ok(request)
+        let request = self
+            .secret_backends
+            .resolve_references(&self.key, request)
+            .await?;
+        serde_json::from_value(request).map_err(ExecutionError::JSONDeserialize)
     }
 
     async fn ws_send_start(ws: &mut WebSocket) -> Result<()> {
@@ -211,35 +221,25 @@ pub struct ExecutionStarted<LangServerSuccess, Success> {
     child: Child,
     stdout: SiFramed<SiMessage<LangServerSuccess>>,
     stderr: FramedRead<ChildStderr, BytesLinesCodec>,
-    credentials: Vec<SensitiveString>,
+    redactor: Arc<Redactor>,
     success_marker: PhantomData<Success>,
 }
 
 // TODO: implement shutdown oneshot
-async fn handle_stderr(
-    stderr: FramedRead<ChildStderr, BytesLinesCodec>,
-    credentials: Vec<SensitiveString>,
-) {
+async fn handle_stderr(stderr: FramedRead<ChildStderr, BytesLinesCodec>, redactor: Arc<Redactor>) {
     async fn handle_stderr_fallible(
         mut stderr: FramedRead<ChildStderr, BytesLinesCodec>,
-        credentials: Vec<SensitiveString>,
+        redactor: Arc<Redactor>,
     ) -> Result<()> {
         while let Some(line) = stderr.next().await {
             let line = line.map_err(ExecutionError::ChildRecvIO)?;
-            let mut line = String::from_utf8_lossy(line.as_ref());
-            for credential in &credentials {
-                // Note: This brings a possibility of random substrings being matched out of
-                // context, exposing that we have a secret by censoring it But trying to infer word
-                // boundary might leak the plaintext credential which is arguably worse
-                if line.contains(credential.as_str()) {
-                    line = line.replace(credential.as_str(), "[redacted]").into();
-                }
-            }
+            let mut line = String::from_utf8_lossy(line.as_ref()).into_owned();
+            redactor.redact(&mut line);
             eprintln!("{line}");
         }
         Ok(())
     }
-    if let Err(error) = handle_stderr_fallible(stderr, credentials).await {
+    if let Err(error) = handle_stderr_fallible(stderr, redactor).await {
         error!("Unable to collect stderr: {}", error);
     }
 }
@@ -252,18 +252,18 @@ where
     SiDecoderError: From<SiJsonError<LangServerSuccess>>,
 {
     pub async fn process(self, ws: &mut WebSocket) -> Result<ExecutionClosing<Success>> {
-        tokio::spawn(handle_stderr(self.stderr, self.credentials.clone()));
+        tokio::spawn(handle_stderr(self.stderr, self.redactor.clone()));
 
         let mut stream = self
             .stdout
             .map(|ls_result| match ls_result {
                 Ok(ls_msg) => match ls_msg {
                     LangServerMessage::Output(mut output) => {
-                        Self::filter_output(&mut output, &self.credentials)?;
+                        Self::filter_output(&mut output, &self.redactor)?;
                         Ok(Message::OutputStream(output.into()))
                     }
                     LangServerMessage::Result(mut result) => {
-                        Self::filter_result(&mut result, &self.credentials)?;
+                        Self::filter_result(&mut result, &self.redactor)?;
                         Ok(Message::Result(result.into()))
                     }
                 },
@@ -290,14 +290,10 @@ where
         })
     }
 
-    fn filter_output(output: &mut LangServerOutput, credentials: &[SensitiveString]) -> Result<()> {
-        // Note: This brings a possibility of random substrings being matched out of context,
-        // exposing that we have a secret by censoring it But trying to infer word boundary might
-        // leak the plaintext credential which is arguably worse
-        for credential in credentials {
-            if output.message.contains(credential.as_str()) {
-                output.message = output.message.replace(credential.as_str(), "[redacted]");
-            }
+    fn filter_output(output: &mut LangServerOutput, redactor: &Redactor) -> Result<()> {
+        redactor.redact(&mut output.message);
+        if let Some(group) = output.group.as_mut() {
+            redactor.redact(group);
         }
 
         Ok(())
@@ -305,29 +301,14 @@ where
 
     fn filter_result(
         result: &mut LangServerResult<LangServerSuccess>,
-        credentials: &[SensitiveString],
+        redactor: &Redactor,
     ) -> Result<()> {
-        let mut value = serde_json::to_value(&result).map_err(ExecutionError::JSONSerialize)?;
-        // Note: This brings a possibility of random substrings being matched out of context,
-        // exposing that we have a secret by censoring it But trying to infer word boundary might
-        // leak the plaintext credential which is arguably worse
-        for credential in credentials {
-            let mut work_queue = vec![&mut value];
-            while let Some(work) = work_queue.pop() {
-                match work {
-                    Value::Array(values) => work_queue.extend(values),
-                    Value::Object(object) => object.values_mut().for_each(|v| work_queue.push(v)),
-                    Value::String(v) if v.contains(credential.as_str()) => {
-                        *v = v.replace(credential.as_str(), "[redacted]");
-                    }
-                    Value::String(_) => {}
-                    // For now credentials can only be strings, although we should reconsider it
-                    Value::Null => {}
-                    Value::Number(_) => {}
-                    Value::Bool(_) => {}
-                }
-            }
+        if redactor.is_empty() {
+            return Ok(());
         }
+
+        let mut value = serde_json::to_value(&result).map_err(ExecutionError::JSONSerialize)?;
+        redactor.redact_value(&mut value);
         let mut filtered_result: LangServerResult<LangServerSuccess> =
             serde_json::from_value(value).map_err(ExecutionError::JSONDeserialize)?;
         std::mem::swap(result, &mut filtered_result);
@@ -477,3 +458,150 @@ struct LangServerFailureError {
     kind: String,
     message: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose, Engine};
+    use cyclone_core::{
+        ActionRunResultSuccess, ReconciliationResultSuccess, ResolverFunctionResultSuccess,
+        SchemaVariantDefinitionResultSuccess, ValidationResultSuccess,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        redact::REDACTED,
+        result::{
+            LangServerActionRunResultSuccess, LangServerReconciliationResultSuccess,
+            LangServerResolverFunctionResultSuccess, LangServerValidationResultSuccess,
+        },
+    };
+
+    const SECRET: &str = "Varginha's UFO";
+
+    fn redactor() -> Redactor {
+        Redactor::new(&[SECRET.to_owned().into()])
+    }
+
+    fn filter<LangServerSuccess, Success>(result: Value) -> Value
+    where
+        Success: Serialize + Unpin + fmt::Debug,
+        LangServerSuccess: Serialize + DeserializeOwned + Unpin + fmt::Debug + Into<Success>,
+        SymmetricalJson<SiMessage<LangServerSuccess>>: Deserializer<SiMessage<LangServerSuccess>>,
+        SiDecoderError: From<SiJsonError<LangServerSuccess>>,
+    {
+        let mut result: LangServerResult<LangServerSuccess> =
+            serde_json::from_value(result).expect("failed to deserialize result");
+        ExecutionStarted::<LangServerSuccess, Success>::filter_result(&mut result, &redactor())
+            .expect("failed to filter result");
+        serde_json::to_value(result).expect("failed to serialize result")
+    }
+
+    fn assert_redacted(value: Value) {
+        let serialized = value.to_string();
+        assert!(!serialized.contains(SECRET), "{serialized}");
+        assert!(
+            !serialized.contains(&general_purpose::STANDARD.encode(SECRET)),
+            "{serialized}"
+        );
+        assert!(serialized.contains(REDACTED), "{serialized}");
+    }
+
+    #[test]
+    fn redacts_output() {
+        let mut output: LangServerOutput = serde_json::from_value(json!({
+            "executionId": "1",
+            "stream": "stdout",
+            "level": "info",
+            "group": SECRET,
+            "message": format!("creds: {}", general_purpose::STANDARD.encode(SECRET)),
+        }))
+        .expect("failed to deserialize output");
+
+        ExecutionStarted::<LangServerResolverFunctionResultSuccess, ResolverFunctionResultSuccess>::filter_output(
+            &mut output,
+            &redactor(),
+        )
+        .expect("failed to filter output");
+
+        assert_eq!(Some(REDACTED), output.group.as_deref());
+        assert_eq!(format!("creds: {REDACTED}"), output.message);
+    }
+
+    #[test]
+    fn redacts_failure() {
+        assert_redacted(filter::<
+            LangServerResolverFunctionResultSuccess,
+            ResolverFunctionResultSuccess,
+        >(json!({
+            "status": "failure",
+            "executionId": "1",
+            "error": { "kind": "UserCodeException", "message": format!("bad token {SECRET}") },
+        })));
+    }
+
+    #[test]
+    fn redacts_resolver_function_result() {
+        assert_redacted(filter::<
+            LangServerResolverFunctionResultSuccess,
+            ResolverFunctionResultSuccess,
+        >(json!({
+            "status": "success",
+            "executionId": "1",
+            "data": { "nested": [SECRET] },
+            "unset": false,
+        })));
+    }
+
+    #[test]
+    fn redacts_validation_result() {
+        assert_redacted(filter::<
+            LangServerValidationResultSuccess,
+            ValidationResultSuccess,
+        >(json!({
+            "status": "success",
+            "executionId": "1",
+            "valid": false,
+            "message": format!("{SECRET} is not valid"),
+        })));
+    }
+
+    #[test]
+    fn redacts_action_run_result() {
+        assert_redacted(filter::<
+            LangServerActionRunResultSuccess,
+            ActionRunResultSuccess,
+        >(json!({
+            "status": "success",
+            "executionId": "1",
+            "payload": { "token": general_purpose::STANDARD.encode(SECRET) },
+            "health": "ok",
+            "message": SECRET,
+        })));
+    }
+
+    #[test]
+    fn redacts_reconciliation_result() {
+        assert_redacted(filter::<
+            LangServerReconciliationResultSuccess,
+            ReconciliationResultSuccess,
+        >(json!({
+            "status": "success",
+            "executionId": "1",
+            "updates": { "/root/domain/token": SECRET },
+            "actions": [],
+        })));
+    }
+
+    #[test]
+    fn redacts_schema_variant_definition_result() {
+        assert_redacted(filter::<
+            SchemaVariantDefinitionResultSuccess,
+            SchemaVariantDefinitionResultSuccess,
+        >(json!({
+            "status": "success",
+            "executionId": "1",
+            "definition": { "props": [{ "name": SECRET }] },
+        })));
+    }
+}
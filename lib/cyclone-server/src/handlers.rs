@@ -30,7 +30,7 @@ use crate::{
         LangServerActionRunResultSuccess, LangServerReconciliationResultSuccess,
         LangServerResolverFunctionResultSuccess, LangServerValidationResultSuccess,
     },
-    state::{DecryptionKey, LangServerPath, TelemetryLevel, WatchKeepalive},
+    state::{DecryptionKey, LangServerPath, SecretBackends, TelemetryLevel, WatchKeepalive},
     watch,
 };
 
@@ -85,6 +85,7 @@ pub async fn ws_execute_resolver(
     wsu: WebSocketUpgrade,
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
+    State(secret_backends): State<SecretBackends>,
     State(telemetry_level): State<TelemetryLevel>,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
@@ -98,6 +99,7 @@ pub async fn ws_execute_resolver(
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
             key.into(),
+            secret_backends.into(),
             limit_request_guard,
             "resolverfunction".to_owned(),
             request,
@@ -112,6 +114,7 @@ pub async fn ws_execute_validation(
     wsu: WebSocketUpgrade,
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
+    State(secret_backends): State<SecretBackends>,
     State(telemetry_level): State<TelemetryLevel>,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
@@ -125,6 +128,7 @@ pub async fn ws_execute_validation(
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
             key.into(),
+            secret_backends.into(),
             limit_request_guard,
             "validation".to_owned(),
             request,
@@ -139,6 +143,7 @@ pub async fn ws_execute_action_run(
     wsu: WebSocketUpgrade,
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
+    State(secret_backends): State<SecretBackends>,
     State(telemetry_level): State<TelemetryLevel>,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
@@ -152,6 +157,7 @@ pub async fn ws_execute_action_run(
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
             key.into(),
+            secret_backends.into(),
             limit_request_guard,
             "actionRun".to_owned(),
             request,
@@ -166,6 +172,7 @@ pub async fn ws_execute_reconciliation(
     wsu: WebSocketUpgrade,
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
+    State(secret_backends): State<SecretBackends>,
     State(telemetry_level): State<TelemetryLevel>,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
@@ -179,6 +186,7 @@ pub async fn ws_execute_reconciliation(
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
             key.into(),
+            secret_backends.into(),
             limit_request_guard,
             "reconciliation".to_owned(),
             request,
@@ -193,6 +201,7 @@ pub async fn ws_execute_schema_variant_definition(
     wsu: WebSocketUpgrade,
     State(lang_server_path): State<LangServerPath>,
     State(key): State<DecryptionKey>,
+    State(secret_backends): State<SecretBackends>,
     State(telemetry_level): State<TelemetryLevel>,
     limit_request_guard: LimitRequestGuard,
 ) -> impl IntoResponse {
@@ -206,6 +215,7 @@ pub async fn ws_execute_schema_variant_definition(
             lang_server_path,
             telemetry_level.is_debug_or_lower(),
             key.into(),
+            secret_backends.into(),
             limit_request_guard,
             "schemaVariantDefinition".to_owned(),
             request,
@@ -221,6 +231,7 @@ async fn handle_socket<Request, LangServerSuccess, Success>(
     lang_server_path: PathBuf,
     lang_server_debugging: bool,
     key: Arc<crate::DecryptionKey>,
+    secret_backends: Arc<crate::SecretBackends>,
     _limit_request_guard: LimitRequestGuard,
     sub_command: String,
     _request_marker: PhantomData<Request>,
@@ -232,8 +243,13 @@ async fn handle_socket<Request, LangServerSuccess, Success>(
     LangServerSuccess: Serialize + DeserializeOwned + Unpin + fmt::Debug + Into<Success>,
 {
     let proto = {
-        let execution: Execution<Request, LangServerSuccess, Success> =
-            execution::new(lang_server_path, lang_server_debugging, key, sub_command);
+        let execution: Execution<Request, LangServerSuccess, Success> = execution::new(
+            lang_server_path,
+            lang_server_debugging,
+            key,
+            secret_backends,
+            sub_command,
+        );
         match execution.start(&mut socket).await {
             Ok(started) => started,
             Err(err) => {
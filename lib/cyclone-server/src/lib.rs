@@ -3,9 +3,11 @@ mod decryption_key;
 mod execution;
 mod extract;
 mod handlers;
+mod redact;
 mod request;
 mod result;
 mod routes;
+mod secret_backend;
 mod server;
 mod state;
 mod timestamp;
@@ -16,6 +18,10 @@ mod watch;
 pub use axum::extract::ws::Message as WebSocketMessage;
 pub use config::{Config, ConfigBuilder, ConfigError, IncomingStream};
 pub use decryption_key::{DecryptionKey, DecryptionKeyError};
+pub use secret_backend::{
+    FileSecretBackend, SecretBackend, SecretBackendError, SecretBackendResult, SecretBackends,
+    SecretBackendsConfig, VaultSecretBackend, VaultSecretBackendConfig,
+};
 pub use server::{Server, ShutdownSource};
 pub use timestamp::timestamp;
 pub use uds::{UdsIncomingStream, UdsIncomingStreamError};
@@ -0,0 +1,234 @@
+//! Scrubbing of decrypted secret values from everything a function execution sends back.
+//!
+//! A function that logs or returns a credential must never leak it into output streams or
+//! results. Besides the plaintext value, the [`Redactor`] also matches the common encodings a
+//! function is likely to produce: JSON string escaping, hex and every base64 alphabet, including
+//! the case where the secret is a fragment of a larger base64 payload (for example the password
+//! in an HTTP basic authorization header).
+
+use std::collections::BTreeSet;
+
+use base64::{engine::general_purpose, Engine};
+use cyclone_core::SensitiveString;
+use serde_json::Value;
+
+/// The text every match of a secret is replaced with.
+pub const REDACTED: &str = "[redacted]";
+
+/// Encoded fragments shorter than this are too likely to match unrelated text.
+const MIN_FRAGMENT_LEN: usize = 4;
+
+/// Replaces every known secret value, plain or encoded, with [`REDACTED`].
+#[derive(Clone, Debug, Default)]
+pub struct Redactor {
+    patterns: Vec<SensitiveString>,
+}
+
+impl Redactor {
+    pub fn new(credentials: &[SensitiveString]) -> Self {
+        let mut patterns = BTreeSet::new();
+        for credential in credentials {
+            let credential = credential.as_str();
+            // An empty credential would match everywhere
+            if credential.is_empty() {
+                continue;
+            }
+            patterns.insert(credential.to_owned());
+            patterns.extend(encodings(credential.as_bytes()));
+        }
+
+        // Replace the longest patterns first so that a pattern that is a substring of another one
+        // never leaves part of the longer one behind
+        let mut patterns: Vec<String> = patterns.into_iter().collect();
+        patterns.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        Self {
+            patterns: patterns.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Redacts the string in place, returning whether anything was replaced.
+    //
+    // Note: This brings a possibility of random substrings being matched out of context, exposing
+    // that we have a secret by censoring it. But trying to infer word boundary might leak the
+    // plaintext credential which is arguably worse
+    pub fn redact(&self, text: &mut String) -> bool {
+        let mut redacted = false;
+        for pattern in &self.patterns {
+            if text.contains(pattern.as_str()) {
+                *text = text.replace(pattern.as_str(), REDACTED);
+                redacted = true;
+            }
+        }
+        redacted
+    }
+
+    /// Redacts every string and object key of the JSON value in place.
+    pub fn redact_value(&self, value: &mut Value) {
+        if self.is_empty() {
+            return;
+        }
+
+        let mut work_queue = vec![value];
+        while let Some(work) = work_queue.pop() {
+            match work {
+                Value::Array(values) => work_queue.extend(values),
+                Value::Object(object) => {
+                    let keys: Vec<String> = object.keys().cloned().collect();
+                    for key in keys {
+                        let mut redacted_key = key.clone();
+                        if self.redact(&mut redacted_key) {
+                            if let Some(value) = object.remove(&key) {
+                                object.insert(redacted_key, value);
+                            }
+                        }
+                    }
+                    object.values_mut().for_each(|v| work_queue.push(v));
+                }
+                Value::String(v) => {
+                    self.redact(v);
+                }
+                // For now credentials can only be strings, although we should reconsider it
+                Value::Null => {}
+                Value::Number(_) => {}
+                Value::Bool(_) => {}
+            }
+        }
+    }
+}
+
+fn encodings(secret: &[u8]) -> Vec<String> {
+    let mut encodings = Vec::new();
+
+    // As it appears inside of a serialized JSON string
+    if let Ok(json) = serde_json::to_string(&String::from_utf8_lossy(secret)) {
+        encodings.push(json[1..json.len() - 1].to_owned());
+    }
+
+    let hex: String = secret.iter().map(|byte| format!("{byte:02x}")).collect();
+    encodings.push(hex.to_uppercase());
+    encodings.push(hex);
+
+    for engine in [
+        &general_purpose::STANDARD,
+        &general_purpose::STANDARD_NO_PAD,
+        &general_purpose::URL_SAFE,
+        &general_purpose::URL_SAFE_NO_PAD,
+    ] {
+        encodings.push(engine.encode(secret));
+    }
+
+    // A secret embedded in a larger payload starts at any of the 3 byte offsets of a base64
+    // quantum, so only the characters made exclusively of the secret's bits are stable
+    for engine in [
+        &general_purpose::STANDARD_NO_PAD,
+        &general_purpose::URL_SAFE_NO_PAD,
+    ] {
+        for offset in 0..3 {
+            let mut shifted = vec![0; offset];
+            shifted.extend_from_slice(secret);
+            let encoded = engine.encode(&shifted);
+
+            let start = (offset * 8 + 5) / 6;
+            let end = (shifted.len() * 8) / 6;
+            if end > start && end - start >= MIN_FRAGMENT_LEN {
+                encodings.push(encoded[start..end].to_owned());
+            }
+        }
+    }
+
+    encodings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(secret: &str) -> Redactor {
+        Redactor::new(&[secret.to_owned().into()])
+    }
+
+    #[test]
+    fn redacts_plaintext() {
+        let mut text = "token=hunter2hunter2;".to_owned();
+        assert!(redactor("hunter2hunter2").redact(&mut text));
+        assert_eq!("token=[redacted];", text);
+    }
+
+    #[test]
+    fn ignores_empty_secrets() {
+        let redactor = redactor("");
+        assert!(redactor.is_empty());
+
+        let mut text = "nothing to see here".to_owned();
+        assert!(!redactor.redact(&mut text));
+        assert_eq!("nothing to see here", text);
+    }
+
+    #[test]
+    fn redacts_base64() {
+        let secret = "Varginha's UFO?>";
+        let redactor = redactor(secret);
+
+        for engine in [
+            &general_purpose::STANDARD,
+            &general_purpose::STANDARD_NO_PAD,
+            &general_purpose::URL_SAFE,
+            &general_purpose::URL_SAFE_NO_PAD,
+        ] {
+            let mut text = format!("encoded: {}", engine.encode(secret));
+            assert!(redactor.redact(&mut text));
+            assert!(!text.contains(&engine.encode(secret)));
+        }
+    }
+
+    #[test]
+    fn redacts_base64_fragment() {
+        let secret = "s3cr3t-p4ssw0rd";
+        let redactor = redactor(secret);
+
+        for user in ["a", "ab", "abc"] {
+            let header = general_purpose::STANDARD.encode(format!("{user}:{secret}"));
+            let mut text = format!("Authorization: Basic {header}");
+            assert!(redactor.redact(&mut text), "{user} was not redacted");
+            assert!(text.len() < header.len() + "Authorization: Basic ".len());
+        }
+    }
+
+    #[test]
+    fn redacts_hex_and_json_escaped() {
+        let secret = "quote\"d";
+        let redactor = redactor(secret);
+
+        let mut hex: String = secret.bytes().map(|byte| format!("{byte:02x}")).collect();
+        assert!(redactor.redact(&mut hex));
+        assert_eq!(REDACTED, hex);
+
+        let mut json = serde_json::json!({ "secret": secret }).to_string();
+        assert!(redactor.redact(&mut json));
+        assert_eq!(r#"{"secret":"[redacted]"}"#, json);
+    }
+
+    #[test]
+    fn redacts_value_strings_and_keys() {
+        let secret = "Varginha's UFO";
+        let mut value = serde_json::json!({
+            "nested": [{ "a": format!("x{secret}x") }, 42, null, true],
+            secret: general_purpose::STANDARD.encode(secret),
+        });
+
+        redactor(secret).redact_value(&mut value);
+
+        assert_eq!(
+            serde_json::json!({
+                "nested": [{ "a": "x[redacted]x" }, 42, null, true],
+                "[redacted]": "[redacted]",
+            }),
+            value
+        );
+    }
+}
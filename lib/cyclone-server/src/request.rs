@@ -15,6 +15,62 @@ pub trait DecryptRequest {
     fn decrypt_request(self, key: &DecryptionKey) -> Result<serde_json::Value, DecryptionKeyError>;
 }
 
+/// Lists the values of every encrypted secret found in the JSON value.
+fn list_secrets_in(
+    value: Value,
+    key: &DecryptionKey,
+) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
+    let mut credentials: Vec<SensitiveString> = vec![];
+
+    // We need to first parse the tree for the secrets and then list them
+    let mut secret_objects = vec![];
+    let mut is_inside_secret_object = false;
+
+    let mut work_queue = vec![value];
+
+    while let Some(work) = work_queue.pop() {
+        match work {
+            Value::Array(values) => work_queue.extend(values),
+            Value::Object(object) => {
+                let is_decrypted_secret = object
+                    .get("cycloneEncryptedDataMarker")
+                    .map_or(false, |v| v.as_bool() == Some(true))
+                    && object
+                        .get("encryptedSecret")
+                        .map_or(false, |v| v.is_string());
+
+                if !is_inside_secret_object && is_decrypted_secret {
+                    let encoded = object["encryptedSecret"]
+                        .as_str()
+                        .ok_or(DecryptionKeyError::EncryptedSecretNotFound)?;
+                    let decrypted = key.decode_and_decrypt(encoded)?;
+                    secret_objects.push(serde_json::de::from_slice::<Value>(&decrypted)?);
+                } else {
+                    object.into_iter().for_each(|(_, v)| work_queue.push(v));
+                }
+            }
+
+            Value::String(value) if is_inside_secret_object => credentials.push(value.into()),
+            // We don't care for scalar values outside of a secret's message JSON object
+            Value::String(_) => {}
+
+            // For now credentials can only be strings, although we should reconsider it
+            Value::Null => {}
+            Value::Bool(_) => {}
+            Value::Number(_) => {}
+        }
+
+        // We should only process secrets at the end, as they behave differently
+        if work_queue.is_empty() {
+            if let Some(obj) = secret_objects.pop() {
+                is_inside_secret_object = true;
+                work_queue.push(obj);
+            }
+        }
+    }
+    Ok(credentials)
+}
+
 impl ListSecrets for ComponentView {
     fn list_secrets(
         &self,
@@ -24,57 +80,7 @@ impl ListSecrets for ComponentView {
             return Ok(vec![]);
         }
 
-        let mut credentials: Vec<SensitiveString> = vec![];
-
-        // We need to first parse the tree for the secrets and then list them
-        let mut secret_objects = vec![];
-        let mut is_inside_secret_object = false;
-
-        let mut work_queue = vec![self.properties.clone()];
-
-        while let Some(work) = work_queue.pop() {
-            match work {
-                Value::Array(values) => work_queue.extend(values),
-                Value::Object(object) => {
-                    let is_decrypted_secret = object
-                        .get("cycloneEncryptedDataMarker")
-                        .map_or(false, |v| v.as_bool() == Some(true))
-                        && object
-                            .get("encryptedSecret")
-                            .map_or(false, |v| v.is_string());
-
-                    if !is_inside_secret_object && is_decrypted_secret {
-                        let encoded = object["encryptedSecret"]
-                            .as_str()
-                            .ok_or(DecryptionKeyError::EncryptedSecretNotFound)?;
-                        let decrypted = key.decode_and_decrypt(encoded)?;
-                        secret_objects.push(serde_json::de::from_slice::<Value>(&decrypted)?);
-                    } else {
-                        object.into_iter().for_each(|(_, v)| work_queue.push(v));
-                    }
-                }
-
-                Value::String(value) if is_inside_secret_object => {
-                    credentials.push(value.clone().into())
-                }
-                // We don't care for scalar values outside of a secret's message JSON object
-                Value::String(_) => {}
-
-                // For now credentials can only be strings, although we should reconsider it
-                Value::Null => {}
-                Value::Bool(_) => {}
-                Value::Number(_) => {}
-            }
-
-            // We should only process secrets at the end, as they behave differently
-            if work_queue.is_empty() {
-                if let Some(obj) = secret_objects.pop() {
-                    is_inside_secret_object = true;
-                    work_queue.push(obj);
-                }
-            }
-        }
-        Ok(credentials)
+        list_secrets_in(self.properties.clone(), key)
     }
 }
 
@@ -181,10 +187,9 @@ impl DecryptRequest for ResolverFunctionRequest {
 impl ListSecrets for ActionRunRequest {
     fn list_secrets(
         &self,
-        _key: &DecryptionKey,
+        key: &DecryptionKey,
     ) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
-        // TODO(fnichol): we'll need to populate/consume secrets here shortly
-        Ok(vec![])
+        list_secrets_in(self.args.clone(), key)
     }
 }
 
@@ -202,10 +207,9 @@ impl DecryptRequest for ActionRunRequest {
 impl ListSecrets for ReconciliationRequest {
     fn list_secrets(
         &self,
-        _key: &DecryptionKey,
+        key: &DecryptionKey,
     ) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
-        // TODO(fnichol): we'll need to populate/consume secrets here shortly
-        Ok(vec![])
+        list_secrets_in(self.args.clone(), key)
     }
 }
 
@@ -223,10 +227,9 @@ impl DecryptRequest for ReconciliationRequest {
 impl ListSecrets for ValidationRequest {
     fn list_secrets(
         &self,
-        _key: &DecryptionKey,
+        key: &DecryptionKey,
     ) -> Result<Vec<SensitiveString>, DecryptionKeyError> {
-        // TODO(fnichol): we'll need to populate/consume secrets here shortly
-        Ok(vec![])
+        list_secrets_in(self.value.clone(), key)
     }
 }
 
@@ -0,0 +1,614 @@
+//! This module contains [`SecretBackends`], which resolve the secrets referenced by a function
+//! request that are stored outside of SI's database.
+//!
+//! A secret stored in a backend other than [`SecretBackendKind::Database`] reaches cyclone as an
+//! encrypted [`WorkspaceSecretReference`], marked with the kind of its backend. References are
+//! resolved into the actual secret messages before the request is decrypted and handed to the
+//! lang server, so that the secret messages never leave cyclone.
+//!
+//! Every reference carries the workspace of its secret and is only resolved below that
+//! workspace's prefix of the backend: `<file_root>/<workspace_pk>/<path>` for files and
+//! `<mount>/data/<workspace_pk>/<path>` for Vault.
+
+use std::{fmt, path::PathBuf};
+
+use async_trait::async_trait;
+use cyclone_core::{
+    SecretBackendKind, SecretReference, SensitiveContainer, WorkspaceSecretReference,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::{DecryptionKey, DecryptionKeyError};
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum SecretBackendError {
+    #[error("secret backend not configured: {0}")]
+    BackendNotConfigured(SecretBackendKind),
+    #[error("decryption key error: {0}")]
+    DecryptionKey(#[from] DecryptionKeyError),
+    #[error("encrypted secret not found")]
+    EncryptedSecretNotFound,
+    #[error("invalid secret reference path: {0}")]
+    InvalidPath(String),
+    #[error("invalid secret reference workspace: {0}")]
+    InvalidWorkspace(String),
+    #[error("key {1} not found in secret at {0}")]
+    KeyNotFound(String, String),
+    #[error("secret not found at {0}")]
+    NotFound(String),
+    #[error("reqwest error: {0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("error reading secret file: {0}")]
+    SecretFile(#[from] std::io::Error),
+    #[error("error serializing/deserializing json: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("unexpected response from vault for {0}: {1}")]
+    UnexpectedVaultResponse(String, reqwest::StatusCode),
+    #[error("url parse error: {0}")]
+    UrlParse(#[from] url::ParseError),
+}
+
+pub type SecretBackendResult<T> = Result<T, SecretBackendError>;
+
+/// Selects the field of the secret the reference points at, if any.
+fn select(reference: &SecretReference, mut secret: Value) -> SecretBackendResult<Value> {
+    match &reference.key {
+        Some(key) => secret
+            .get_mut(key)
+            .map(Value::take)
+            .ok_or_else(|| SecretBackendError::KeyNotFound(reference.path.clone(), key.clone())),
+        None => Ok(secret),
+    }
+}
+
+/// The path of the reference below the prefix of its workspace. The workspace and every segment
+/// of the path have to be plain names, so that a reference can't escape the prefix.
+fn scoped_path(reference: &WorkspaceSecretReference) -> SecretBackendResult<String> {
+    let workspace_pk = &reference.workspace_pk;
+    if workspace_pk.is_empty() || !workspace_pk.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(SecretBackendError::InvalidWorkspace(workspace_pk.clone()));
+    }
+    let path = &reference.reference.path;
+    if path.is_empty()
+        || path.split('/').any(|segment| {
+            segment.is_empty()
+                || segment == "."
+                || segment == ".."
+                || segment.contains(['\\', '?', '#'])
+        })
+    {
+        return Err(SecretBackendError::InvalidPath(path.clone()));
+    }
+    Ok(format!("{workspace_pk}/{path}"))
+}
+
+/// Resolves [`WorkspaceSecretReferences`](WorkspaceSecretReference) into secret messages.
+#[async_trait]
+pub trait SecretBackend: fmt::Debug + Send + Sync {
+    fn kind(&self) -> SecretBackendKind;
+
+    async fn resolve(&self, reference: &WorkspaceSecretReference) -> SecretBackendResult<Value>;
+}
+
+/// A [`SecretBackend`] reading JSON files from a local directory.
+#[derive(Clone, Debug)]
+pub struct FileSecretBackend {
+    root: PathBuf,
+}
+
+impl FileSecretBackend {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// References may only point below the directory of their workspace.
+    fn file_path(&self, reference: &WorkspaceSecretReference) -> SecretBackendResult<PathBuf> {
+        Ok(self.root.join(scoped_path(reference)?))
+    }
+}
+
+#[async_trait]
+impl SecretBackend for FileSecretBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::File
+    }
+
+    async fn resolve(&self, reference: &WorkspaceSecretReference) -> SecretBackendResult<Value> {
+        let file_path = self.file_path(reference)?;
+        let reference = &reference.reference;
+        let contents = match tokio::fs::read(&file_path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(SecretBackendError::NotFound(reference.path.clone()));
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        select(reference, serde_json::from_slice(&contents)?)
+    }
+}
+
+/// A [`SecretBackend`] reading from the KV (version 2) secrets engine of a Vault-compatible HTTP
+/// API.
+#[derive(Clone, Debug)]
+pub struct VaultSecretBackend {
+    address: url::Url,
+    mount: String,
+    token: SensitiveContainer<String>,
+    client: reqwest::Client,
+}
+
+impl VaultSecretBackend {
+    pub fn new(
+        address: url::Url,
+        mount: impl Into<String>,
+        token: impl Into<SensitiveContainer<String>>,
+    ) -> Self {
+        Self {
+            address,
+            mount: mount.into(),
+            token: token.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// References may only point below the path of their workspace.
+    fn secret_url(&self, reference: &WorkspaceSecretReference) -> SecretBackendResult<url::Url> {
+        Ok(self.address.join(&format!(
+            "v1/{}/data/{}",
+            self.mount.trim_matches('/'),
+            scoped_path(reference)?
+        ))?)
+    }
+}
+
+#[derive(Deserialize)]
+struct VaultKvResponse {
+    data: VaultKvData,
+}
+
+#[derive(Deserialize)]
+struct VaultKvData {
+    data: Value,
+}
+
+#[async_trait]
+impl SecretBackend for VaultSecretBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::Vault
+    }
+
+    async fn resolve(&self, reference: &WorkspaceSecretReference) -> SecretBackendResult<Value> {
+        let url = self.secret_url(reference)?;
+        let reference = &reference.reference;
+        debug!(%url, "resolving secret from vault");
+
+        let response = self
+            .client
+            .get(url)
+            .header("X-Vault-Token", self.token.as_str())
+            .send()
+            .await?;
+        match response.status() {
+            reqwest::StatusCode::OK => {}
+            reqwest::StatusCode::NOT_FOUND => {
+                return Err(SecretBackendError::NotFound(reference.path.clone()));
+            }
+            status => {
+                return Err(SecretBackendError::UnexpectedVaultResponse(
+                    reference.path.clone(),
+                    status,
+                ));
+            }
+        }
+
+        let response: VaultKvResponse = response.json().await?;
+        select(reference, response.data.data)
+    }
+}
+
+/// Configuration of the Vault-compatible [`SecretBackend`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VaultSecretBackendConfig {
+    pub address: String,
+    #[serde(default = "default_vault_mount")]
+    pub mount: String,
+    pub token: SensitiveContainer<String>,
+}
+
+fn default_vault_mount() -> String {
+    "secret".to_owned()
+}
+
+/// Configuration of the [`SecretBackends`] available to a service. Backends that are not
+/// configured cannot resolve secrets.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SecretBackendsConfig {
+    pub file_root: Option<PathBuf>,
+    pub vault: Option<VaultSecretBackendConfig>,
+}
+
+/// The [`SecretBackends`](SecretBackend) available to resolve
+/// [`WorkspaceSecretReferences`](WorkspaceSecretReference).
+#[derive(Debug, Default)]
+pub struct SecretBackends {
+    backends: Vec<Box<dyn SecretBackend>>,
+}
+
+impl SecretBackends {
+    pub fn new(backends: Vec<Box<dyn SecretBackend>>) -> Self {
+        Self { backends }
+    }
+
+    pub fn from_config(config: &SecretBackendsConfig) -> SecretBackendResult<Self> {
+        let mut backends: Vec<Box<dyn SecretBackend>> = Vec::new();
+        if let Some(file_root) = &config.file_root {
+            backends.push(Box::new(FileSecretBackend::new(file_root)));
+        }
+        if let Some(vault) = &config.vault {
+            backends.push(Box::new(VaultSecretBackend::new(
+                vault.address.parse()?,
+                vault.mount.clone(),
+                vault.token.clone(),
+            )));
+        }
+        Ok(Self::new(backends))
+    }
+
+    pub fn is_configured(&self, kind: SecretBackendKind) -> bool {
+        self.backends.iter().any(|backend| backend.kind() == kind)
+    }
+
+    pub async fn resolve(
+        &self,
+        kind: SecretBackendKind,
+        reference: &WorkspaceSecretReference,
+    ) -> SecretBackendResult<Value> {
+        let backend = self
+            .backends
+            .iter()
+            .find(|backend| backend.kind() == kind)
+            .ok_or(SecretBackendError::BackendNotConfigured(kind))?;
+        backend.resolve(reference).await
+    }
+
+    /// Resolves every secret reference found in a function request. Each reference is replaced
+    /// by the secret message it points at, encrypted with the key like the secrets stored in the
+    /// database are, so the rest of the request handling treats both alike.
+    pub async fn resolve_references(
+        &self,
+        key: &DecryptionKey,
+        mut request: Value,
+    ) -> SecretBackendResult<Value> {
+        for pointer in reference_pointers(&request) {
+            let (kind, encoded) = match request.pointer(&pointer) {
+                Some(marker) => (
+                    serde_json::from_value::<SecretBackendKind>(marker["secretBackend"].clone())?,
+                    marker["encryptedSecret"]
+                        .as_str()
+                        .ok_or(SecretBackendError::EncryptedSecretNotFound)?,
+                ),
+                None => {
+                    return Err(DecryptionKeyError::JSONPointerNotFound(request, pointer).into());
+                }
+            };
+            if kind == SecretBackendKind::Database {
+                continue;
+            }
+
+            let reference: WorkspaceSecretReference =
+                serde_json::from_slice(&key.decode_and_decrypt(encoded)?)?;
+            debug!(
+                %kind,
+                workspace_pk = %reference.workspace_pk,
+                path = %reference.reference.path,
+                "resolving secret reference",
+            );
+            let message = self.resolve(kind, &reference).await?;
+            let encoded = key.encrypt_and_encode(serde_json::to_vec(&message)?);
+
+            if let Some(marker) = request.pointer_mut(&pointer) {
+                *marker = serde_json::json!({
+                    "cycloneEncryptedDataMarker": true,
+                    "encryptedSecret": encoded,
+                });
+            }
+        }
+        Ok(request)
+    }
+}
+
+/// Lists the JSON pointers of the encrypted secrets in the value which are marked with the kind
+/// of their backend.
+fn reference_pointers(value: &Value) -> Vec<String> {
+    let mut pointers = Vec::new();
+
+    let mut work_queue = vec![("".to_owned(), value)];
+    while let Some((pointer, work)) = work_queue.pop() {
+        match work {
+            Value::Array(values) => work_queue.extend(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| (format!("{pointer}/{index}"), value)),
+            ),
+            Value::Object(object) => {
+                let is_reference = object
+                    .get("cycloneEncryptedDataMarker")
+                    .map_or(false, |v| v.as_bool() == Some(true))
+                    && object.contains_key("secretBackend");
+
+                if is_reference {
+                    pointers.push(pointer);
+                } else {
+                    work_queue.extend(object.iter().map(|(key, value)| {
+                        let key = key.replace('~', "~0").replace('/', "~1");
+                        (format!("{pointer}/{key}"), value)
+                    }));
+                }
+            }
+            // Scalar values are never secret references
+            Value::String(_) | Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+    pointers
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    const WORKSPACE_PK: &str = "01H4WWE0AY3SEKHN1MTBRSWXKS";
+
+    fn reference(path: &str, key: Option<&str>) -> WorkspaceSecretReference {
+        WorkspaceSecretReference {
+            workspace_pk: WORKSPACE_PK.to_owned(),
+            reference: SecretReference {
+                path: path.to_owned(),
+                key: key.map(ToOwned::to_owned),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn file_backend_resolves() {
+        let root = tempfile::tempdir().expect("failed to create tempdir");
+        std::fs::create_dir_all(root.path().join(WORKSPACE_PK).join("aws"))
+            .expect("failed to create dir");
+        std::fs::write(
+            root.path().join(WORKSPACE_PK).join("aws/prod.json"),
+            r#"{"accessKeyId":"AKIA","secretAccessKey":"shh"}"#,
+        )
+        .expect("failed to write secret");
+        let backends = SecretBackends::from_config(&SecretBackendsConfig {
+            file_root: Some(root.path().to_path_buf()),
+            vault: None,
+        })
+        .expect("failed to create backends");
+
+        let secret = backends
+            .resolve(SecretBackendKind::File, &reference("aws/prod.json", None))
+            .await
+            .expect("failed to resolve secret");
+        assert_eq!(
+            serde_json::json!({"accessKeyId": "AKIA", "secretAccessKey": "shh"}),
+            secret
+        );
+
+        let secret = backends
+            .resolve(
+                SecretBackendKind::File,
+                &reference("aws/prod.json", Some("secretAccessKey")),
+            )
+            .await
+            .expect("failed to resolve secret");
+        assert_eq!(serde_json::json!("shh"), secret);
+
+        assert!(matches!(
+            backends
+                .resolve(SecretBackendKind::File, &reference("../prod.json", None))
+                .await,
+            Err(SecretBackendError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            backends
+                .resolve(SecretBackendKind::File, &reference("aws/dev.json", None))
+                .await,
+            Err(SecretBackendError::NotFound(_))
+        ));
+        assert!(matches!(
+            backends
+                .resolve(SecretBackendKind::Vault, &reference("aws/prod", None))
+                .await,
+            Err(SecretBackendError::BackendNotConfigured(
+                SecretBackendKind::Vault
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn references_are_confined_to_their_workspace() {
+        let other_workspace_pk = "01H4WWE9QK6Z0M5Y6ZC1A9N2TD";
+        let root = tempfile::tempdir().expect("failed to create tempdir");
+        std::fs::create_dir(root.path().join(other_workspace_pk)).expect("failed to create dir");
+        std::fs::write(
+            root.path().join(other_workspace_pk).join("prod.json"),
+            r#"{"token":"not yours"}"#,
+        )
+        .expect("failed to write secret");
+        std::fs::write(root.path().join("shared.json"), r#"{"token":"not yours"}"#)
+            .expect("failed to write secret");
+        let backends = SecretBackends::from_config(&SecretBackendsConfig {
+            file_root: Some(root.path().to_path_buf()),
+            vault: Some(VaultSecretBackendConfig {
+                address: "http://127.0.0.1:1/".to_owned(),
+                mount: "secret".to_owned(),
+                token: "s.root".to_owned().into(),
+            }),
+        })
+        .expect("failed to create backends");
+
+        for kind in [SecretBackendKind::File, SecretBackendKind::Vault] {
+            for path in [
+                format!("../{other_workspace_pk}/prod.json"),
+                "../shared.json".to_owned(),
+                format!("/{other_workspace_pk}/prod.json"),
+                "./../shared.json".to_owned(),
+                "prod.json?version=1".to_owned(),
+            ] {
+                assert!(
+                    matches!(
+                        backends.resolve(kind, &reference(&path, None)).await,
+                        Err(SecretBackendError::InvalidPath(_))
+                    ),
+                    "{kind} resolved {path}"
+                );
+            }
+
+            for workspace_pk in ["", "..", "../01H4WWE9QK6Z0M5Y6ZC1A9N2TD", "a/b"] {
+                let reference = WorkspaceSecretReference {
+                    workspace_pk: workspace_pk.to_owned(),
+                    ..reference("prod.json", None)
+                };
+                assert!(
+                    matches!(
+                        backends.resolve(kind, &reference).await,
+                        Err(SecretBackendError::InvalidWorkspace(_))
+                    ),
+                    "{kind} resolved {workspace_pk}"
+                );
+            }
+        }
+
+        // The same path only resolves within the workspace of the reference
+        assert!(matches!(
+            backends
+                .resolve(SecretBackendKind::File, &reference("prod.json", None))
+                .await,
+            Err(SecretBackendError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolve_references_in_request() {
+        let root = tempfile::tempdir().expect("failed to create tempdir");
+        std::fs::create_dir(root.path().join(WORKSPACE_PK)).expect("failed to create dir");
+        std::fs::write(
+            root.path().join(WORKSPACE_PK).join("docker.json"),
+            r#"{"password":"hunter2"}"#,
+        )
+        .expect("failed to write secret");
+        let backends = SecretBackends::from_config(&SecretBackendsConfig {
+            file_root: Some(root.path().to_path_buf()),
+            vault: None,
+        })
+        .expect("failed to create backends");
+        let (_, secret_key) = sodiumoxide::crypto::box_::gen_keypair();
+        let key = DecryptionKey::from(secret_key);
+
+        let crypted_reference = serde_json::to_vec(&reference("docker.json", None))
+            .expect("failed to serialize reference");
+        let stored = key.encrypt_and_encode(br#"{"password":"in the database"}"#);
+        let request = serde_json::json!({
+            "kind": "credential",
+            "properties": {
+                "referenced": {
+                    "message": {
+                        "cycloneEncryptedDataMarker": true,
+                        "encryptedSecret": key.encrypt_and_encode(crypted_reference),
+                        "secretBackend": "file",
+                    },
+                },
+                "stored": {
+                    "message": { "cycloneEncryptedDataMarker": true, "encryptedSecret": stored },
+                },
+            },
+        });
+
+        let request = backends
+            .resolve_references(&key, request)
+            .await
+            .expect("failed to resolve references");
+
+        let referenced = &request["properties"]["referenced"]["message"];
+        assert!(referenced.get("secretBackend").is_none());
+        let message = key
+            .decode_and_decrypt(
+                referenced["encryptedSecret"]
+                    .as_str()
+                    .expect("no encrypted secret"),
+            )
+            .expect("failed to decrypt");
+        assert_eq!(
+            serde_json::json!({"password": "hunter2"}),
+            serde_json::from_slice::<Value>(&message).expect("failed to deserialize")
+        );
+        assert_eq!(
+            serde_json::json!(stored),
+            request["properties"]["stored"]["message"]["encryptedSecret"]
+        );
+    }
+
+    /// Serves a single KV version 2 read, returning the request that was received.
+    async fn vault_stub(listener: TcpListener, body: &'static str) -> String {
+        let (mut stream, _) = listener.accept().await.expect("failed to accept");
+        let mut request = vec![0; 4096];
+        let read = stream.read(&mut request).await.expect("failed to read");
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream
+            .write_all(response.as_bytes())
+            .await
+            .expect("failed to write");
+        String::from_utf8_lossy(&request[..read]).into_owned()
+    }
+
+    #[tokio::test]
+    async fn vault_backend_resolves() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind");
+        let address = format!("http://{}/", listener.local_addr().expect("no addr"));
+        let stub = tokio::spawn(vault_stub(
+            listener,
+            r#"{"data":{"data":{"token":"hunter2"},"metadata":{"version":3}}}"#,
+        ));
+
+        let backends = SecretBackends::from_config(&SecretBackendsConfig {
+            file_root: None,
+            vault: Some(VaultSecretBackendConfig {
+                address,
+                mount: "kv".to_owned(),
+                token: "s.root".to_owned().into(),
+            }),
+        })
+        .expect("failed to create backends");
+
+        let secret = backends
+            .resolve(
+                SecretBackendKind::Vault,
+                &reference("team/github", Some("token")),
+            )
+            .await
+            .expect("failed to resolve secret");
+        assert_eq!(serde_json::json!("hunter2"), secret);
+
+        let request = stub.await.expect("stub failed").to_lowercase();
+        assert!(request.starts_with(&format!(
+            "get /v1/kv/data/{}/team/github ",
+            WORKSPACE_PK.to_lowercase()
+        )));
+        assert!(request.contains("x-vault-token: s.root"));
+    }
+}
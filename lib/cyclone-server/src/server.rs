@@ -18,7 +18,7 @@ use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 
 use crate::{
     routes::routes, state::AppState, Config, DecryptionKey, DecryptionKeyError, IncomingStream,
-    UdsIncomingStream, UdsIncomingStreamError,
+    SecretBackendError, SecretBackends, UdsIncomingStream, UdsIncomingStreamError,
 };
 
 #[remain::sorted]
@@ -30,6 +30,8 @@ pub enum ServerError {
     DecryptionKey(#[from] DecryptionKeyError),
     #[error("hyper server error")]
     Hyper(#[from] hyper::Error),
+    #[error(transparent)]
+    SecretBackend(#[from] SecretBackendError),
     #[error("failed to setup signal handler")]
     Signal(#[source] io::Error),
     #[error("UDS incoming stream error")]
@@ -149,7 +151,14 @@ fn build_service(
 ) -> Result<(IntoMakeService<Router>, oneshot::Receiver<()>)> {
     let (shutdown_tx, shutdown_rx) = mpsc::channel(4);
 
-    let state = AppState::new(config.lang_server_path(), decryption_key, telemetry_level);
+    let secret_backends = SecretBackends::from_config(config.secret_backends())?;
+
+    let state = AppState::new(
+        config.lang_server_path(),
+        decryption_key,
+        secret_backends,
+        telemetry_level,
+    );
 
     let routes = routes(config, state, shutdown_tx)
         // TODO(fnichol): customize http tracing further, using:
@@ -12,6 +12,7 @@ use tokio::sync::mpsc;
 pub struct AppState {
     lang_server_path: LangServerPath,
     decryption_key: DecryptionKey,
+    secret_backends: SecretBackends,
     telemetry_level: TelemetryLevel,
 }
 
@@ -19,11 +20,13 @@ impl AppState {
     pub fn new(
         lang_server_path: impl Into<PathBuf>,
         decryption_key: crate::DecryptionKey,
+        secret_backends: crate::SecretBackends,
         telemetry_level: Box<dyn telemetry::TelemetryLevel>,
     ) -> Self {
         Self {
             lang_server_path: LangServerPath(Arc::new(lang_server_path.into())),
             decryption_key: DecryptionKey(Arc::new(decryption_key)),
+            secret_backends: SecretBackends(Arc::new(secret_backends)),
             telemetry_level: TelemetryLevel(Arc::new(telemetry_level)),
         }
     }
@@ -55,6 +58,15 @@ impl From<DecryptionKey> for Arc<crate::DecryptionKey> {
     }
 }
 
+#[derive(Clone, Debug, FromRef)]
+pub struct SecretBackends(Arc<crate::SecretBackends>);
+
+impl From<SecretBackends> for Arc<crate::SecretBackends> {
+    fn from(value: SecretBackends) -> Self {
+        value.0
+    }
+}
+
 #[derive(Clone, FromRef)]
 pub struct TelemetryLevel(Arc<Box<dyn telemetry::TelemetryLevel>>);
 
@@ -19,6 +19,7 @@ use jwt_simple::prelude::RS256KeyPair;
 use lazy_static::lazy_static;
 use si_crypto::{
     SymmetricCryptoService, SymmetricCryptoServiceConfig, SymmetricCryptoServiceConfigFile,
+    SymmetricKey,
 };
 use si_data_nats::{NatsClient, NatsConfig};
 use si_data_pg::{PgPool, PgPoolConfig};
@@ -393,6 +394,33 @@ pub async fn jwt_public_signing_key() -> Result<JwtPublicSigningKey> {
     Ok(key)
 }
 
+/// Returns a [`ServicesContext`] sharing the connections of the given one, whose
+/// [`SymmetricCryptoService`] encrypts with a newly generated key while still being able to decrypt
+/// with the test key, as is the case after rotating the symmetric key.
+pub async fn services_context_with_rotated_symmetric_key(
+    services_context: &ServicesContext,
+) -> Result<ServicesContext> {
+    let active_key_path = {
+        let context_builder = TEST_CONTEXT_BUILDER.lock().await;
+        let config = context_builder.config()?;
+        config.symmetric_crypto_service_config.active_key.clone()
+    };
+    let previous_key = SymmetricKey::load(&active_key_path).await?;
+    let symmetric_crypto_service =
+        SymmetricCryptoService::new(SymmetricCryptoService::generate_key(), vec![previous_key]);
+
+    Ok(ServicesContext::new(
+        services_context.pg_pool().clone(),
+        services_context.nats_conn().clone(),
+        services_context.job_processor(),
+        services_context.veritech().clone(),
+        services_context.encryption_key(),
+        None,
+        services_context.module_index_url(),
+        symmetric_crypto_service,
+    ))
+}
+
 // Returns a JWT private signing key, used to sign claims
 pub async fn jwt_private_signing_key() -> Result<RS256KeyPair> {
     let key_path = {
@@ -0,0 +1,330 @@
+//! Personal API tokens let a [`User`](crate::User) authenticate to a workspace without going
+//! through the auth portal, such as from a CI pipeline or a script.
+//!
+//! A token is only ever shown once, when it is created. Only a hash of its secret is stored, so a
+//! lost token can't be recovered and has to be revoked and replaced instead.
+
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use si_data_nats::NatsError;
+use si_data_pg::PgError;
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    pk, DalContext, HistoryEvent, HistoryEventError, Timestamp, TransactionsError, UserClaim,
+    UserPk, WorkspacePk, WorkspaceRole,
+};
+
+const API_TOKEN_GET_BY_PK: &str = include_str!("queries/api_token/get_by_pk.sql");
+const API_TOKEN_LIST_FOR_USER: &str = include_str!("queries/api_token/list_for_user.sql");
+
+/// Every API token starts with this prefix, which tells them apart from the JWTs issued by the
+/// auth portal.
+pub const API_TOKEN_PREFIX: &str = "si_pat_";
+
+const SECRET_LEN: usize = 32;
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum ApiTokenError {
+    #[error("history event error: {0}")]
+    HistoryEvent(#[from] HistoryEventError),
+    #[error("nats txn error: {0}")]
+    Nats(#[from] NatsError),
+    #[error("api token not found: {0}")]
+    NotFound(ApiTokenPk),
+    #[error("pg error: {0}")]
+    Pg(#[from] PgError),
+    #[error("error serializing/deserializing json: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("strum parse error: {0}")]
+    StrumParse(#[from] strum::ParseError),
+    #[error(transparent)]
+    Transactions(#[from] TransactionsError),
+}
+
+pub type ApiTokenResult<T> = Result<T, ApiTokenError>;
+
+pk!(ApiTokenPk);
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    pk: ApiTokenPk,
+    user_pk: UserPk,
+    workspace_pk: WorkspacePk,
+    name: String,
+    /// The most the token may do, whatever the role of its user is.
+    scope: WorkspaceRole,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    timestamp: Timestamp,
+}
+
+/// The columns of a row, which are snake cased and also hold the hash of the token's secret.
+#[derive(Deserialize, Debug)]
+struct ApiTokenRow {
+    pk: ApiTokenPk,
+    user_pk: UserPk,
+    workspace_pk: WorkspacePk,
+    name: String,
+    scope: String,
+    token_hash: String,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<ApiTokenRow> for ApiToken {
+    type Error = ApiTokenError;
+
+    fn try_from(row: ApiTokenRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            pk: row.pk,
+            user_pk: row.user_pk,
+            workspace_pk: row.workspace_pk,
+            name: row.name,
+            scope: row.scope.parse()?,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+            timestamp: Timestamp {
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            },
+        })
+    }
+}
+
+impl ApiToken {
+    pub fn pk(&self) -> ApiTokenPk {
+        self.pk
+    }
+
+    pub fn user_pk(&self) -> UserPk {
+        self.user_pk
+    }
+
+    pub fn workspace_pk(&self) -> WorkspacePk {
+        self.workspace_pk
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scope(&self) -> WorkspaceRole {
+        self.scope
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    pub fn last_used_at(&self) -> Option<DateTime<Utc>> {
+        self.last_used_at
+    }
+
+    pub fn revoked_at(&self) -> Option<DateTime<Utc>> {
+        self.revoked_at
+    }
+
+    /// The claim the token authenticates as.
+    pub fn claim(&self) -> UserClaim {
+        UserClaim::new(self.user_pk, self.workspace_pk)
+    }
+
+    /// Whether the token can still be used to authenticate.
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+            && self
+                .expires_at
+                .map_or(true, |expires_at| expires_at > Utc::now())
+    }
+
+    /// Whether the bearer token is an API token rather than a JWT.
+    pub fn is_api_token(bearer_token: &str) -> bool {
+        bearer_token
+            .strip_prefix("Bearer ")
+            .unwrap_or(bearer_token)
+            .starts_with(API_TOKEN_PREFIX)
+    }
+
+    /// Creates a token for a user in a workspace, returning it along with its secret value, which
+    /// can't be retrieved again afterwards.
+    pub async fn new(
+        ctx: &DalContext,
+        user_pk: UserPk,
+        workspace_pk: WorkspacePk,
+        name: impl AsRef<str>,
+        scope: WorkspaceRole,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> ApiTokenResult<(Self, String)> {
+        let name = name.as_ref();
+        let pk = ApiTokenPk::generate();
+
+        let mut secret = [0u8; SECRET_LEN];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let secret = hex::encode(secret);
+        let token = format!("{API_TOKEN_PREFIX}{pk}_{secret}");
+
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM api_token_create_v1($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &pk,
+                    &user_pk,
+                    &workspace_pk,
+                    &name,
+                    &scope.as_ref(),
+                    &hash_secret(&secret).to_hex().as_str(),
+                    &expires_at,
+                ],
+            )
+            .await?;
+        let json: serde_json::Value = row.try_get("object")?;
+        let object: Self = serde_json::from_value::<ApiTokenRow>(json)?.try_into()?;
+
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "api_token.create".to_owned(),
+            "API token created".to_owned(),
+            &serde_json::json![{
+                "pk": pk,
+                "user_pk": user_pk,
+                "scope": scope,
+                "expires_at": expires_at,
+            }],
+        )
+        .await?;
+
+        Ok((object, token))
+    }
+
+    pub async fn get_by_pk(ctx: &DalContext, pk: ApiTokenPk) -> ApiTokenResult<Option<Self>> {
+        Ok(match Self::get_row_by_pk(ctx, pk).await? {
+            Some(row) => Some(row.try_into()?),
+            None => None,
+        })
+    }
+
+    /// Lists the tokens of a user in a workspace, including revoked and expired ones.
+    pub async fn list_for_user(
+        ctx: &DalContext,
+        user_pk: UserPk,
+        workspace_pk: WorkspacePk,
+    ) -> ApiTokenResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(API_TOKEN_LIST_FOR_USER, &[&user_pk, &workspace_pk])
+            .await?;
+
+        let mut tokens = Vec::with_capacity(rows.len());
+        for row in rows {
+            let json: serde_json::Value = row.try_get("object")?;
+            tokens.push(serde_json::from_value::<ApiTokenRow>(json)?.try_into()?);
+        }
+        Ok(tokens)
+    }
+
+    /// Finds the active token matching a bearer token. Tokens that don't exist, don't match their
+    /// secret, are revoked or are expired are all treated alike and return [`None`].
+    ///
+    /// This doesn't record that the token was used, which is up to the caller once the request it
+    /// authenticates is authorized, with [`ApiToken::touch`].
+    pub async fn find_active(ctx: &DalContext, bearer_token: &str) -> ApiTokenResult<Option<Self>> {
+        let bearer_token = bearer_token.strip_prefix("Bearer ").unwrap_or(bearer_token);
+        let (pk, secret) = match bearer_token
+            .strip_prefix(API_TOKEN_PREFIX)
+            .and_then(|token| token.split_once('_'))
+        {
+            Some((pk, secret)) => match pk.parse::<ulid::Ulid>() {
+                Ok(pk) => (ApiTokenPk::from(pk), secret),
+                Err(_) => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+
+        let row = match Self::get_row_by_pk(ctx, pk).await? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        // Comparing `blake3::Hash`es is constant time
+        let matches = blake3::Hash::from_hex(&row.token_hash)
+            .map_or(false, |token_hash| token_hash == hash_secret(secret));
+        let token: Self = row.try_into()?;
+        if !matches || !token.is_active() {
+            debug!(api_token.pk = %token.pk, "rejected inactive or mismatched api token");
+            return Ok(None);
+        }
+
+        Ok(Some(token))
+    }
+
+    /// Records that the token was used now.
+    pub async fn touch(&self, ctx: &DalContext) -> ApiTokenResult<()> {
+        ctx.txns()
+            .await?
+            .pg()
+            .execute("SELECT api_token_touch_v1($1)", &[&self.pk])
+            .await?;
+        Ok(())
+    }
+
+    /// Revokes the token so that it can't be used anymore. Revoking a revoked token keeps the
+    /// time it was first revoked.
+    pub async fn revoke(&mut self, ctx: &DalContext) -> ApiTokenResult<()> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one("SELECT object FROM api_token_revoke_v1($1)", &[&self.pk])
+            .await?;
+        let json: serde_json::Value = row.try_get("object")?;
+        *self = serde_json::from_value::<ApiTokenRow>(json)?.try_into()?;
+
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "api_token.revoke".to_owned(),
+            "API token revoked".to_owned(),
+            &serde_json::json![{ "pk": self.pk, "user_pk": self.user_pk }],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_row_by_pk(
+        ctx: &DalContext,
+        pk: ApiTokenPk,
+    ) -> ApiTokenResult<Option<ApiTokenRow>> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(API_TOKEN_GET_BY_PK, &[&pk])
+            .await?;
+        match row {
+            Some(row) => {
+                let json: serde_json::Value = row.try_get("object")?;
+                Ok(Some(serde_json::from_value(json)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+fn hash_secret(secret: &str) -> blake3::Hash {
+    blake3::hash(secret.as_bytes())
+}
@@ -69,6 +69,9 @@ use crate::{
     Tenancy, Timestamp, TransactionsError, Visibility, WsEventError,
 };
 
+pub mod bulk_update;
+pub mod impact;
+pub mod provenance;
 pub mod view;
 
 const CHILD_ATTRIBUTE_VALUES_FOR_CONTEXT: &str =
@@ -0,0 +1,295 @@
+//! This module contains [`BulkAttributeUpdate`], which sets the same [`Prop`](crate::Prop) path
+//! to a value across many [`Components`](crate::Component) of a
+//! [`SchemaVariant`](crate::SchemaVariant) at once.
+//!
+//! Every [`AttributeValue`] is updated without propagating dependent values and, once all updates
+//! have been made, a single [`DependentValuesUpdate`] job is enqueued for all of them. This means
+//! that changing a tag across 40 [`Components`](crate::Component) results in one consolidated
+//! propagation run (and one status update) rather than 40.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::attribute::context::AttributeContextBuilderError;
+use crate::job::definition::DependentValuesUpdate;
+use crate::prop::PropPath;
+use crate::{
+    AttributeContext, AttributeReadContext, AttributeValue, AttributeValueError, AttributeValueId,
+    Component, ComponentError, ComponentId, DalContext, Prop, PropError, PropId, PropKind,
+    SchemaVariantId, StandardModel, TransactionsError,
+};
+
+/// The placeholder replaced with the [`Component`] name when rendering a
+/// [`BulkAttributeValue::Template`].
+pub const TEMPLATE_COMPONENT_NAME: &str = "{{component.name}}";
+/// The placeholder replaced with the [`ComponentId`] when rendering a
+/// [`BulkAttributeValue::Template`].
+pub const TEMPLATE_COMPONENT_ID: &str = "{{component.id}}";
+/// The placeholder replaced with the current (string) value when rendering a
+/// [`BulkAttributeValue::Template`].
+pub const TEMPLATE_CURRENT_VALUE: &str = "{{value}}";
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum BulkAttributeUpdateError {
+    #[error("attribute context builder error: {0}")]
+    AttributeContextBuilder(#[from] AttributeContextBuilderError),
+    #[error("attribute value error: {0}")]
+    AttributeValue(#[from] AttributeValueError),
+    #[error("attribute value not found for context: {0:?}")]
+    AttributeValueNotFoundForContext(AttributeReadContext),
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("component {0} is not an instance of schema variant {1}")]
+    ComponentNotInSchemaVariant(ComponentId, SchemaVariantId),
+    #[error("parent attribute value not found for attribute value: {0}")]
+    ParentAttributeValueNotFound(AttributeValueId),
+    #[error("prop error: {0}")]
+    Prop(#[from] PropError),
+    #[error("prop {0} must be a child of an object prop, but its parent is a {1}")]
+    PropParentNotObject(PropId, PropKind),
+    #[error("prop {0} has no parent and cannot be bulk updated")]
+    PropWithoutParent(PropId),
+    #[error("templated values can only be set on string props, but prop {0} is a {1}")]
+    TemplateRequiresStringProp(PropId, PropKind),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] TransactionsError),
+}
+
+pub type BulkAttributeUpdateResult<T> = Result<T, BulkAttributeUpdateError>;
+
+/// The value to set for every [`AttributeValue`] in a [`BulkAttributeUpdate`].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "kind", content = "value")]
+pub enum BulkAttributeValue {
+    /// Use the same value for every [`Component`]. Passing [`None`] unsets the value.
+    Literal(Option<serde_json::Value>),
+    /// Render a string for every [`Component`]. The following placeholders are supported:
+    /// [`TEMPLATE_COMPONENT_NAME`], [`TEMPLATE_COMPONENT_ID`] and [`TEMPLATE_CURRENT_VALUE`].
+    Template(String),
+}
+
+impl BulkAttributeValue {
+    /// Render the value for a given [`Component`] name, [`ComponentId`] and its current value.
+    pub fn render(
+        &self,
+        component_name: &str,
+        component_id: ComponentId,
+        current_value: Option<&serde_json::Value>,
+    ) -> Option<serde_json::Value> {
+        match self {
+            Self::Literal(value) => value.clone(),
+            Self::Template(template) => {
+                let current_value = match current_value {
+                    Some(serde_json::Value::String(current)) => current.clone(),
+                    Some(serde_json::Value::Null) | None => "".to_string(),
+                    Some(other) => other.to_string(),
+                };
+                Some(serde_json::Value::String(
+                    template
+                        .replace(TEMPLATE_COMPONENT_NAME, component_name)
+                        .replace(TEMPLATE_COMPONENT_ID, &component_id.to_string())
+                        .replace(TEMPLATE_CURRENT_VALUE, &current_value),
+                ))
+            }
+        }
+    }
+}
+
+/// Describes a single edit applied to many [`Components`](crate::Component) of the same
+/// [`SchemaVariant`](crate::SchemaVariant).
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkAttributeUpdate {
+    pub schema_variant_id: SchemaVariantId,
+    /// The path to the [`Prop`], starting with "root" (e.g. `["root", "domain", "region"]`).
+    pub prop_path: Vec<String>,
+    /// If provided, only these [`Components`](crate::Component) will be updated. Otherwise, every
+    /// [`Component`] of the [`SchemaVariant`](crate::SchemaVariant) will be updated.
+    pub component_ids: Option<Vec<ComponentId>>,
+    pub value: BulkAttributeValue,
+}
+
+/// The outcome of a [`BulkAttributeUpdate`] for a single [`Component`].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkAttributeUpdateEntry {
+    pub component_id: ComponentId,
+    pub attribute_value_id: AttributeValueId,
+    pub value: Option<serde_json::Value>,
+}
+
+impl BulkAttributeUpdate {
+    pub fn new(
+        schema_variant_id: SchemaVariantId,
+        prop_path: Vec<String>,
+        component_ids: Option<Vec<ComponentId>>,
+        value: BulkAttributeValue,
+    ) -> Self {
+        Self {
+            schema_variant_id,
+            prop_path,
+            component_ids,
+            value,
+        }
+    }
+
+    /// Apply the update to every matching [`Component`] within the current transaction and
+    /// enqueue a single [`DependentValuesUpdate`] job for all updated
+    /// [`AttributeValues`](AttributeValue).
+    pub async fn apply(
+        &self,
+        ctx: &DalContext,
+    ) -> BulkAttributeUpdateResult<Vec<BulkAttributeUpdateEntry>> {
+        let prop =
+            Prop::find_prop_by_path(ctx, self.schema_variant_id, &PropPath::new(&self.prop_path))
+                .await?;
+        let parent_prop = prop
+            .parent_prop(ctx)
+            .await?
+            .ok_or(BulkAttributeUpdateError::PropWithoutParent(*prop.id()))?;
+        if *parent_prop.kind() != PropKind::Object {
+            return Err(BulkAttributeUpdateError::PropParentNotObject(
+                *prop.id(),
+                *parent_prop.kind(),
+            ));
+        }
+        if matches!(self.value, BulkAttributeValue::Template(_)) && *prop.kind() != PropKind::String
+        {
+            return Err(BulkAttributeUpdateError::TemplateRequiresStringProp(
+                *prop.id(),
+                *prop.kind(),
+            ));
+        }
+
+        let components = self.components(ctx).await?;
+
+        let mut entries = Vec::with_capacity(components.len());
+        for component in components {
+            let read_context = AttributeReadContext {
+                prop_id: Some(*prop.id()),
+                component_id: Some(*component.id()),
+                ..AttributeReadContext::default()
+            };
+            let attribute_value = AttributeValue::find_for_context(ctx, read_context)
+                .await?
+                .ok_or(BulkAttributeUpdateError::AttributeValueNotFoundForContext(
+                    read_context,
+                ))?;
+            let parent_attribute_value = attribute_value.parent_attribute_value(ctx).await?.ok_or(
+                BulkAttributeUpdateError::ParentAttributeValueNotFound(*attribute_value.id()),
+            )?;
+
+            let value = match &self.value {
+                BulkAttributeValue::Literal(value) => value.clone(),
+                template @ BulkAttributeValue::Template(_) => {
+                    let current_value = attribute_value.get_value(ctx).await?;
+                    template.render(
+                        &component.name(ctx).await?,
+                        *component.id(),
+                        current_value.as_ref(),
+                    )
+                }
+            };
+
+            let attribute_context = AttributeContext::builder()
+                .set_prop_id(*prop.id())
+                .set_component_id(*component.id())
+                .to_context()?;
+            let (value, attribute_value_id) =
+                AttributeValue::update_for_context_without_propagating_dependent_values(
+                    ctx,
+                    *attribute_value.id(),
+                    Some(*parent_attribute_value.id()),
+                    attribute_context,
+                    value,
+                    None,
+                )
+                .await?;
+
+            entries.push(BulkAttributeUpdateEntry {
+                component_id: *component.id(),
+                attribute_value_id,
+                value,
+            });
+        }
+
+        if !entries.is_empty() && !ctx.no_dependent_values() {
+            ctx.enqueue_job(DependentValuesUpdate::new(
+                ctx.access_builder(),
+                *ctx.visibility(),
+                entries
+                    .iter()
+                    .map(|entry| entry.attribute_value_id)
+                    .collect(),
+            ))
+            .await?;
+        }
+
+        Ok(entries)
+    }
+
+    /// Find the [`Components`](crate::Component) that this update applies to, ensuring that any
+    /// explicitly requested [`Component`] belongs to the [`SchemaVariant`](crate::SchemaVariant).
+    async fn components(&self, ctx: &DalContext) -> BulkAttributeUpdateResult<Vec<Component>> {
+        let components = Component::list_for_schema_variant(ctx, self.schema_variant_id).await?;
+
+        match &self.component_ids {
+            None => Ok(components),
+            Some(component_ids) => {
+                let mut filtered = Vec::with_capacity(component_ids.len());
+                for component_id in component_ids {
+                    let component = components
+                        .iter()
+                        .find(|component| component.id() == component_id)
+                        .ok_or(BulkAttributeUpdateError::ComponentNotInSchemaVariant(
+                            *component_id,
+                            self.schema_variant_id,
+                        ))?;
+                    filtered.push(component.clone());
+                }
+                Ok(filtered)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_literal() {
+        let value = BulkAttributeValue::Literal(Some(serde_json::json!("us-east-1")));
+        assert_eq!(
+            Some(serde_json::json!("us-east-1")),
+            value.render(
+                "poop",
+                ComponentId::NONE,
+                Some(&serde_json::json!("us-west-2"))
+            )
+        );
+        assert_eq!(
+            None,
+            BulkAttributeValue::Literal(None).render("poop", ComponentId::NONE, None)
+        );
+    }
+
+    #[test]
+    fn render_template() {
+        let value = BulkAttributeValue::Template(format!(
+            "{TEMPLATE_COMPONENT_NAME}-{TEMPLATE_CURRENT_VALUE}-{TEMPLATE_COMPONENT_ID}"
+        ));
+        assert_eq!(
+            Some(serde_json::json!(format!(
+                "canoe-old-{}",
+                ComponentId::NONE
+            ))),
+            value.render("canoe", ComponentId::NONE, Some(&serde_json::json!("old")))
+        );
+        assert_eq!(
+            Some(serde_json::json!(format!("canoe--{}", ComponentId::NONE))),
+            value.render("canoe", ComponentId::NONE, None)
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! This module contains [`AttributeValueImpact`], which answers the question "what will be
+//! recomputed if I change this value?" _before_ the value is changed.
+//!
+//! The analysis uses the same [`AttributeValue::dependent_value_graph()`] that
+//! [`DependentValuesUpdate`](crate::job::definition::DependentValuesUpdate) walks when
+//! propagating, but does not execute any [`Funcs`](crate::Func). Every dependent value is
+//! described and then grouped by [`Component`], code generation entry and qualification entry.
+//! [`Actions`](Action) already queued in the current change set for any of the affected
+//! [`Components`](Component) are reported too, since they will run against the new values.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use thiserror::Error;
+
+use crate::attribute::value::provenance::{
+    AttributeValueProvenance, AttributeValueProvenanceError, AttributeValueProvenanceSource,
+};
+use crate::{
+    Action, ActionError, ActionId, ActionKind, AttributeValue, AttributeValueError,
+    AttributeValueId, Component, ComponentError, ComponentId, DalContext, LeafKind, StandardModel,
+    StandardModelError, TransactionsError,
+};
+
+/// How far up the tree we look for the entry of a code generation or qualification map.
+const MAX_LEAF_ENTRY_DEPTH: usize = 4;
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum AttributeValueImpactError {
+    #[error("action error: {0}")]
+    Action(#[from] ActionError),
+    #[error("attribute value error: {0}")]
+    AttributeValue(#[from] AttributeValueError),
+    #[error("attribute value not found: {0}")]
+    AttributeValueNotFound(AttributeValueId),
+    #[error("attribute value provenance error: {0}")]
+    AttributeValueProvenance(#[from] AttributeValueProvenanceError),
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("standard model error: {0}")]
+    StandardModel(#[from] StandardModelError),
+    #[error("transactions error: {0}")]
+    Transactions(#[from] TransactionsError),
+}
+
+pub type AttributeValueImpactResult<T> = Result<T, AttributeValueImpactError>;
+
+/// An [`AttributeValue`] that will be recomputed.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueImpactValue {
+    pub attribute_value_id: AttributeValueId,
+    pub component_id: ComponentId,
+    pub source: AttributeValueProvenanceSource,
+}
+
+/// A [`Component`] with at least one [`AttributeValue`] that will be recomputed.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueImpactComponent {
+    pub component_id: ComponentId,
+    pub name: String,
+    pub value_count: usize,
+}
+
+/// A code generation or qualification entry (the key of the "/root/code" or
+/// "/root/qualification" map) that will be recomputed.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueImpactLeaf {
+    pub component_id: ComponentId,
+    pub name: String,
+}
+
+/// An [`Action`] queued in the current change set for an affected [`Component`].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueImpactAction {
+    pub action_id: ActionId,
+    pub component_id: ComponentId,
+    pub kind: ActionKind,
+    pub name: Option<String>,
+}
+
+/// Everything that will be recomputed when the provided [`AttributeValues`](AttributeValue)
+/// change.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueImpact {
+    pub attribute_value_ids: Vec<AttributeValueId>,
+    pub values: Vec<AttributeValueImpactValue>,
+    pub components: Vec<AttributeValueImpactComponent>,
+    pub code_generation: Vec<AttributeValueImpactLeaf>,
+    pub qualifications: Vec<AttributeValueImpactLeaf>,
+    pub actions: Vec<AttributeValueImpactAction>,
+}
+
+impl AttributeValueImpact {
+    /// Compute the impact of changing the provided [`AttributeValueIds`](AttributeValue). Nothing
+    /// is written and no [`Funcs`](crate::Func) are executed.
+    pub async fn new(
+        ctx: &DalContext,
+        attribute_value_ids: &[AttributeValueId],
+    ) -> AttributeValueImpactResult<Self> {
+        let edited: HashSet<AttributeValueId> = attribute_value_ids.iter().copied().collect();
+        let dependency_graph =
+            AttributeValue::dependent_value_graph(ctx, attribute_value_ids).await?;
+
+        let mut impacted: Vec<AttributeValueId> = dependency_graph
+            .keys()
+            .filter(|id| !edited.contains(id))
+            .copied()
+            .collect();
+        impacted.sort();
+
+        let (code_map_prop_name, code_item_prop_name) = LeafKind::CodeGeneration.prop_names();
+        let code_item_path = format!("/root/{code_map_prop_name}/{code_item_prop_name}");
+        let (qualification_map_prop_name, qualification_item_prop_name) =
+            LeafKind::Qualification.prop_names();
+        let qualification_item_path =
+            format!("/root/{qualification_map_prop_name}/{qualification_item_prop_name}");
+
+        let mut values = Vec::with_capacity(impacted.len());
+        let mut value_counts: BTreeMap<ComponentId, usize> = BTreeMap::new();
+        let mut code_generation = BTreeSet::new();
+        let mut qualifications = BTreeSet::new();
+
+        for attribute_value_id in impacted {
+            let attribute_value = AttributeValue::get_by_id(ctx, &attribute_value_id)
+                .await?
+                .ok_or(AttributeValueImpactError::AttributeValueNotFound(
+                    attribute_value_id,
+                ))?;
+            let component_id = attribute_value.context.component_id();
+            let (source, _) =
+                AttributeValueProvenance::source(ctx, attribute_value.context).await?;
+
+            if let AttributeValueProvenanceSource::Prop { path, .. } = &source {
+                let leaves = if path.starts_with(&code_item_path) {
+                    Some(&mut code_generation)
+                } else if path.starts_with(&qualification_item_path) {
+                    Some(&mut qualifications)
+                } else {
+                    None
+                };
+                if let Some(leaves) = leaves {
+                    if let Some(name) = Self::leaf_entry_name(ctx, attribute_value).await? {
+                        leaves.insert(AttributeValueImpactLeaf { component_id, name });
+                    }
+                }
+            }
+
+            if component_id.is_some() {
+                *value_counts.entry(component_id).or_default() += 1;
+            }
+            values.push(AttributeValueImpactValue {
+                attribute_value_id,
+                component_id,
+                source,
+            });
+        }
+
+        let mut components = Vec::with_capacity(value_counts.len());
+        for (component_id, value_count) in &value_counts {
+            if let Some(component) = Component::get_by_id(ctx, component_id).await? {
+                components.push(AttributeValueImpactComponent {
+                    component_id: *component_id,
+                    name: component.name(ctx).await?,
+                    value_count: *value_count,
+                });
+            }
+        }
+
+        let mut actions = Vec::new();
+        if !ctx.visibility().is_head() {
+            for action in Action::find_for_change_set(ctx).await? {
+                if !value_counts.contains_key(action.component_id()) {
+                    continue;
+                }
+                let prototype = action.prototype(ctx).await?;
+                actions.push(AttributeValueImpactAction {
+                    action_id: *action.id(),
+                    component_id: *action.component_id(),
+                    kind: *prototype.kind(),
+                    name: prototype.name().map(ToOwned::to_owned),
+                });
+            }
+        }
+
+        Ok(Self {
+            attribute_value_ids: attribute_value_ids.to_vec(),
+            values,
+            components,
+            code_generation: code_generation.into_iter().collect(),
+            qualifications: qualifications.into_iter().collect(),
+            actions,
+        })
+    }
+
+    /// Find the key of the map entry that the [`AttributeValue`] lives under. Entries of the
+    /// "/root/code" and "/root/qualification" maps are keyed by the name of their
+    /// [`Func`](crate::Func).
+    async fn leaf_entry_name(
+        ctx: &DalContext,
+        mut attribute_value: AttributeValue,
+    ) -> AttributeValueImpactResult<Option<String>> {
+        for _ in 0..MAX_LEAF_ENTRY_DEPTH {
+            if let Some(key) = attribute_value.key() {
+                return Ok(Some(key.to_owned()));
+            }
+            attribute_value = match attribute_value.parent_attribute_value(ctx).await? {
+                Some(parent_attribute_value) => parent_attribute_value,
+                None => return Ok(None),
+            };
+        }
+        Ok(None)
+    }
+}
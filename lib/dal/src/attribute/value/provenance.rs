@@ -0,0 +1,400 @@
+//! This module contains [`AttributeValueProvenance`], which answers the question "why is this
+//! value what it is?" for a given [`AttributeValue`].
+//!
+//! Starting from an [`AttributeValue`], we walk upstream through its
+//! [`AttributePrototype`](crate::AttributePrototype), the
+//! [`AttributePrototypeArguments`](crate::AttributePrototypeArgument) that fed the
+//! [`Func`](crate::Func) and the [`InternalProviders`](crate::InternalProvider) and
+//! [`ExternalProviders`](crate::ExternalProvider) that those arguments are sourced from, across
+//! [`Components`](crate::Component) when the value crossed an [`Edge`](crate::Edge).
+
+use async_recursion::async_recursion;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use thiserror::Error;
+use veritech_client::OutputStream;
+
+use crate::func::argument::{FuncArgument, FuncArgumentError, FuncArgumentId};
+use crate::func::binding_return_value::FuncBindingReturnValueError;
+use crate::{
+    AttributeContext, AttributePrototypeArgument, AttributePrototypeArgumentError,
+    AttributePrototypeId, AttributeReadContext, AttributeValue, AttributeValueError,
+    AttributeValueId, ComponentId, DalContext, ExternalProvider, ExternalProviderError,
+    ExternalProviderId, Func, FuncBackendKind, FuncBinding, FuncBindingError,
+    FuncBindingReturnValue, FuncError, FuncId, InternalProvider, InternalProviderError,
+    InternalProviderId, Prop, PropError, PropId, StandardModel, StandardModelError,
+};
+
+/// How far upstream [`AttributeValueProvenance::new()`] will walk before giving up.
+pub const MAX_PROVENANCE_DEPTH: usize = 64;
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum AttributeValueProvenanceError {
+    #[error("attribute prototype argument error: {0}")]
+    AttributePrototypeArgument(#[from] AttributePrototypeArgumentError),
+    #[error("attribute prototype not found for attribute value: {0}")]
+    AttributePrototypeNotFound(AttributeValueId),
+    #[error("attribute value error: {0}")]
+    AttributeValue(#[from] AttributeValueError),
+    #[error("attribute value not found: {0}")]
+    AttributeValueNotFound(AttributeValueId),
+    #[error("external provider error: {0}")]
+    ExternalProvider(#[from] ExternalProviderError),
+    #[error("external provider not found: {0}")]
+    ExternalProviderNotFound(ExternalProviderId),
+    #[error("func error: {0}")]
+    Func(#[from] FuncError),
+    #[error("func argument error: {0}")]
+    FuncArgument(#[from] FuncArgumentError),
+    #[error("func argument not found: {0}")]
+    FuncArgumentNotFound(FuncArgumentId),
+    #[error("func binding error: {0}")]
+    FuncBinding(#[from] FuncBindingError),
+    #[error("func binding return value error: {0}")]
+    FuncBindingReturnValue(#[from] FuncBindingReturnValueError),
+    #[error("func not found: {0}")]
+    FuncNotFound(FuncId),
+    #[error("internal provider error: {0}")]
+    InternalProvider(#[from] InternalProviderError),
+    #[error("internal provider not found: {0}")]
+    InternalProviderNotFound(InternalProviderId),
+    #[error("prop error: {0}")]
+    Prop(#[from] PropError),
+    #[error("prop not found: {0}")]
+    PropNotFound(PropId),
+    #[error("standard model error: {0}")]
+    StandardModel(#[from] StandardModelError),
+}
+
+pub type AttributeValueProvenanceResult<T> = Result<T, AttributeValueProvenanceError>;
+
+/// What an [`AttributeValue`] in the provenance tree is for.
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum AttributeValueProvenanceSource {
+    /// The value for an input socket (an explicit [`InternalProvider`]).
+    #[serde(rename_all = "camelCase")]
+    InputSocket {
+        internal_provider_id: InternalProviderId,
+        name: String,
+    },
+    /// The value for an output socket (an [`ExternalProvider`]).
+    #[serde(rename_all = "camelCase")]
+    OutputSocket {
+        external_provider_id: ExternalProviderId,
+        name: String,
+    },
+    /// The value for a [`Prop`].
+    #[serde(rename_all = "camelCase")]
+    Prop { prop_id: PropId, path: String },
+    /// The value of a [`Prop`] as seen by other values in the same
+    /// [`Component`](crate::Component) (an implicit [`InternalProvider`]).
+    #[serde(rename_all = "camelCase")]
+    PropProvider {
+        internal_provider_id: InternalProviderId,
+        prop_id: PropId,
+        path: String,
+    },
+}
+
+/// The [`Func`] that was executed to produce a value, alongside the arguments it received and the
+/// output it logged.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueProvenanceFunc {
+    pub id: FuncId,
+    pub name: String,
+    pub backend_kind: FuncBackendKind,
+    pub args: serde_json::Value,
+    pub output: Vec<OutputStream>,
+}
+
+/// An argument to the [`Func`] that produced a value, and the upstream values it came from.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueProvenanceArgument {
+    pub name: String,
+    pub value: Option<serde_json::Value>,
+    pub sources: Vec<AttributeValueProvenance>,
+}
+
+/// The full upstream derivation tree for an [`AttributeValue`].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueProvenance {
+    pub attribute_value_id: AttributeValueId,
+    pub component_id: ComponentId,
+    pub context: AttributeContext,
+    pub source: AttributeValueProvenanceSource,
+    pub value: Option<serde_json::Value>,
+    pub proxy_for_attribute_value_id: Option<AttributeValueId>,
+    pub attribute_prototype_id: AttributePrototypeId,
+    pub func: AttributeValueProvenanceFunc,
+    pub arguments: Vec<AttributeValueProvenanceArgument>,
+    /// Set when the walk stopped here because this value is already on the path from the root
+    /// (i.e. there is a cycle) or [`MAX_PROVENANCE_DEPTH`] was reached. No arguments are populated
+    /// when truncated.
+    pub truncated: bool,
+    /// Set when this value is shared with another branch of the tree, where its arguments were
+    /// already walked. No arguments are populated here, so that shared values are walked once.
+    pub expanded_elsewhere: bool,
+}
+
+/// The values seen while building an [`AttributeValueProvenance`].
+#[derive(Debug, Default)]
+struct ProvenanceWalk {
+    /// The values between the root and the value being walked, to detect cycles.
+    path: HashSet<AttributeValueId>,
+    /// The values whose arguments have been walked.
+    expanded: HashSet<AttributeValueId>,
+}
+
+impl AttributeValueProvenance {
+    /// Build the derivation tree for the provided [`AttributeValueId`].
+    pub async fn new(
+        ctx: &DalContext,
+        attribute_value_id: AttributeValueId,
+    ) -> AttributeValueProvenanceResult<Self> {
+        let mut walk = ProvenanceWalk::default();
+        Self::build(ctx, attribute_value_id, &mut walk, 0).await
+    }
+
+    #[async_recursion]
+    async fn build(
+        ctx: &DalContext,
+        attribute_value_id: AttributeValueId,
+        walk: &mut ProvenanceWalk,
+        depth: usize,
+    ) -> AttributeValueProvenanceResult<Self> {
+        let attribute_value = AttributeValue::get_by_id(ctx, &attribute_value_id)
+            .await?
+            .ok_or(AttributeValueProvenanceError::AttributeValueNotFound(
+                attribute_value_id,
+            ))?;
+        let context = attribute_value.context;
+        let component_id = context.component_id();
+
+        let attribute_prototype = attribute_value.attribute_prototype(ctx).await?.ok_or(
+            AttributeValueProvenanceError::AttributePrototypeNotFound(attribute_value_id),
+        )?;
+        let func_id = attribute_prototype.func_id();
+        let func = Func::get_by_id(ctx, &func_id)
+            .await?
+            .ok_or(AttributeValueProvenanceError::FuncNotFound(func_id))?;
+        let args = match FuncBinding::get_by_id(ctx, &attribute_value.func_binding_id()).await? {
+            Some(func_binding) => func_binding.args().clone(),
+            None => serde_json::Value::Null,
+        };
+        let output = match FuncBindingReturnValue::get_by_id(
+            ctx,
+            &attribute_value.func_binding_return_value_id(),
+        )
+        .await?
+        {
+            Some(func_binding_return_value) => func_binding_return_value
+                .get_output_stream(ctx)
+                .await?
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        let (source, implicit_prop_id) = Self::source(ctx, context).await?;
+
+        // A value on the path from the root means there is a cycle, while a value that was already
+        // walked is shared with another branch (i.e. a diamond) and only referenced here.
+        let truncated = depth >= MAX_PROVENANCE_DEPTH || walk.path.contains(&attribute_value_id);
+        let expanded_elsewhere = !truncated && walk.expanded.contains(&attribute_value_id);
+        let expand = !truncated && !expanded_elsewhere;
+        if expand {
+            walk.path.insert(attribute_value_id);
+        }
+        let arguments = if !expand {
+            Vec::new()
+        } else if let Some(prop_id) = implicit_prop_id {
+            // Implicit internal providers do not execute a meaningful function with arguments:
+            // they emit the current value of their prop, so that is where the value comes from.
+            let read_context = AttributeReadContext {
+                prop_id: Some(prop_id),
+                component_id: Some(component_id),
+                ..AttributeReadContext::default()
+            };
+            let mut sources = Vec::new();
+            if let Some(prop_value) = AttributeValue::find_for_context(ctx, read_context).await? {
+                sources.push(Self::build(ctx, *prop_value.id(), walk, depth + 1).await?);
+            }
+            vec![AttributeValueProvenanceArgument {
+                name: "value".to_owned(),
+                value: attribute_value.get_value(ctx).await?,
+                sources,
+            }]
+        } else {
+            Self::arguments(
+                ctx,
+                *attribute_prototype.id(),
+                component_id,
+                &args,
+                walk,
+                depth,
+            )
+            .await?
+        };
+        if expand {
+            walk.path.remove(&attribute_value_id);
+            walk.expanded.insert(attribute_value_id);
+        }
+
+        Ok(Self {
+            attribute_value_id,
+            component_id,
+            context,
+            source,
+            value: attribute_value.get_value(ctx).await?,
+            proxy_for_attribute_value_id: attribute_value.proxy_for_attribute_value_id().copied(),
+            attribute_prototype_id: *attribute_prototype.id(),
+            func: AttributeValueProvenanceFunc {
+                id: func_id,
+                name: func.name().to_owned(),
+                backend_kind: *func.backend_kind(),
+                args,
+                output,
+            },
+            arguments,
+            truncated,
+            expanded_elsewhere,
+        })
+    }
+
+    /// Describe what the value is for. If the value is for an implicit [`InternalProvider`], the
+    /// [`PropId`] that it emits is also returned.
+    pub(crate) async fn source(
+        ctx: &DalContext,
+        context: AttributeContext,
+    ) -> AttributeValueProvenanceResult<(AttributeValueProvenanceSource, Option<PropId>)> {
+        let internal_provider_id = context.internal_provider_id();
+        let external_provider_id = context.external_provider_id();
+
+        if internal_provider_id != InternalProviderId::NONE {
+            let internal_provider = InternalProvider::get_by_id(ctx, &internal_provider_id)
+                .await?
+                .ok_or(AttributeValueProvenanceError::InternalProviderNotFound(
+                    internal_provider_id,
+                ))?;
+            if internal_provider.is_internal_consumer() {
+                let prop_id = *internal_provider.prop_id();
+                Ok((
+                    AttributeValueProvenanceSource::PropProvider {
+                        internal_provider_id,
+                        prop_id,
+                        path: Self::prop_path(ctx, prop_id).await?,
+                    },
+                    Some(prop_id),
+                ))
+            } else {
+                Ok((
+                    AttributeValueProvenanceSource::InputSocket {
+                        internal_provider_id,
+                        name: internal_provider.name().to_owned(),
+                    },
+                    None,
+                ))
+            }
+        } else if external_provider_id != ExternalProviderId::NONE {
+            let external_provider = ExternalProvider::get_by_id(ctx, &external_provider_id)
+                .await?
+                .ok_or(AttributeValueProvenanceError::ExternalProviderNotFound(
+                    external_provider_id,
+                ))?;
+            Ok((
+                AttributeValueProvenanceSource::OutputSocket {
+                    external_provider_id,
+                    name: external_provider.name().to_owned(),
+                },
+                None,
+            ))
+        } else {
+            let prop_id = context.prop_id();
+            Ok((
+                AttributeValueProvenanceSource::Prop {
+                    prop_id,
+                    path: Self::prop_path(ctx, prop_id).await?,
+                },
+                None,
+            ))
+        }
+    }
+
+    async fn prop_path(
+        ctx: &DalContext,
+        prop_id: PropId,
+    ) -> AttributeValueProvenanceResult<String> {
+        let prop = Prop::get_by_id(ctx, &prop_id)
+            .await?
+            .ok_or(AttributeValueProvenanceError::PropNotFound(prop_id))?;
+        Ok(prop.json_pointer(ctx).await?)
+    }
+
+    /// Find the upstream values for each argument of the [`AttributePrototype`](crate::AttributePrototype),
+    /// grouped by argument name.
+    async fn arguments(
+        ctx: &DalContext,
+        attribute_prototype_id: AttributePrototypeId,
+        component_id: ComponentId,
+        args: &serde_json::Value,
+        walk: &mut ProvenanceWalk,
+        depth: usize,
+    ) -> AttributeValueProvenanceResult<Vec<AttributeValueProvenanceArgument>> {
+        let mut arguments: BTreeMap<String, AttributeValueProvenanceArgument> = BTreeMap::new();
+
+        for argument in
+            AttributePrototypeArgument::list_for_attribute_prototype(ctx, attribute_prototype_id)
+                .await?
+        {
+            // Inter component arguments exist for every connected component, so we only care
+            // about those pointing at the component we are walking.
+            let head_component_id = argument.head_component_id();
+            if head_component_id != ComponentId::NONE && head_component_id != component_id {
+                continue;
+            }
+
+            let func_argument_id = argument.func_argument_id();
+            let func_argument = FuncArgument::get_by_id(ctx, &func_argument_id)
+                .await?
+                .ok_or(AttributeValueProvenanceError::FuncArgumentNotFound(
+                    func_argument_id,
+                ))?;
+            let name = func_argument.name().to_owned();
+
+            let read_context = if argument.is_internal_provider_unset() {
+                AttributeReadContext {
+                    external_provider_id: Some(argument.external_provider_id()),
+                    component_id: Some(argument.tail_component_id()),
+                    ..AttributeReadContext::default()
+                }
+            } else {
+                AttributeReadContext {
+                    internal_provider_id: Some(argument.internal_provider_id()),
+                    component_id: Some(component_id),
+                    ..AttributeReadContext::default()
+                }
+            };
+
+            let entry =
+                arguments
+                    .entry(name.clone())
+                    .or_insert_with(|| AttributeValueProvenanceArgument {
+                        value: args.get(&name).cloned(),
+                        name,
+                        sources: Vec::new(),
+                    });
+            if let Some(upstream) = AttributeValue::find_for_context(ctx, read_context).await? {
+                entry
+                    .sources
+                    .push(Self::build(ctx, *upstream.id(), walk, depth + 1).await?);
+            }
+        }
+
+        Ok(arguments.into_values().collect())
+    }
+}
@@ -43,6 +43,7 @@ use crate::{Edge, FixResolverError, NodeKind};
 pub mod code;
 pub mod diff;
 pub mod qualification;
+pub mod query;
 pub mod resource;
 pub mod status;
 pub mod validation;
@@ -11,9 +11,15 @@ use crate::qualification::{
 use crate::schema::SchemaVariant;
 use crate::validation::ValidationError;
 use crate::ws_event::WsEvent;
-use crate::{AttributeReadContext, DalContext, RootPropChild, StandardModel, ValidationResolver};
+use crate::{
+    AttributeReadContext, AttributeValueId, DalContext, RootPropChild, StandardModel,
+    ValidationResolver,
+};
 use crate::{Component, ComponentError, ComponentId};
 
+const LIST_IDS_FOR_ATTRIBUTE_VALUES: &str =
+    include_str!("../queries/component/list_ids_for_attribute_values.sql");
+
 // FIXME(nick): use the formal types from the new version of function authoring instead of this
 // struct. This struct is a temporary stopgap until that's implemented.
 #[derive(Deserialize, Debug)]
@@ -203,4 +209,56 @@ impl Component {
             qualification_name: name.to_string(),
         })
     }
+
+    /// Whether any qualification of the [`Component`] is failing, including the
+    /// [`"all fields valid"`](Self::all_fields_valid_qualification) one.
+    pub async fn qualifications_failing(
+        ctx: &DalContext,
+        component_id: ComponentId,
+    ) -> ComponentResult<bool> {
+        Ok(Self::list_qualifications(ctx, component_id)
+            .await?
+            .iter()
+            .any(|view| {
+                view.result.as_ref().map(|result| result.status)
+                    == Some(QualificationSubCheckStatus::Failure)
+            }))
+    }
+
+    /// Returns the [`AttributeValueIds`](crate::AttributeValue) of the "qualification"
+    /// [`leaves`](crate::schema::variant::leaves) of the [`Components`](Component) that any of the
+    /// given values belong to, along with the [`ComponentId`] each of them belongs to.
+    pub async fn qualification_attribute_values_for_values(
+        ctx: &DalContext,
+        attribute_value_ids: &[AttributeValueId],
+    ) -> ComponentResult<HashMap<AttributeValueId, ComponentId>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                LIST_IDS_FOR_ATTRIBUTE_VALUES,
+                &[ctx.tenancy(), ctx.visibility(), &attribute_value_ids],
+            )
+            .await?;
+
+        let mut values = HashMap::new();
+        for row in rows {
+            let component_id: ComponentId = row.try_get("component_id")?;
+            let qualification_map_attribute_value =
+                Self::root_prop_child_attribute_value_for_component(
+                    ctx,
+                    component_id,
+                    RootPropChild::Qualification,
+                )
+                .await?;
+            for child in qualification_map_attribute_value
+                .child_attribute_values(ctx)
+                .await?
+            {
+                values.insert(*child.id(), component_id);
+            }
+        }
+        Ok(values)
+    }
 }
@@ -0,0 +1,918 @@
+//! This module contains [`ComponentQuery`], a small query language for finding
+//! [`Components`](crate::Component) by their attribute values, schema, qualifications, resources
+//! and connections.
+//!
+//! ## Syntax
+//!
+//! ```text
+//! query     := or
+//! or        := and ("or" and)*
+//! and       := unary ("and" unary)*
+//! unary     := "not" unary | "(" or ")" | predicate
+//! predicate := "schema" op string
+//!            | "variant" op string
+//!            | "name" op string
+//!            | "qualification" op ("success" | "warning" | "failure" | "unknown")
+//!            | "resource" op ("ok" | "warning" | "error" | "none")
+//!            | ("upstream" | "downstream") "(" or ")"
+//!            | json_pointer op json_literal
+//! op        := "=" | "!=" | "~"
+//! ```
+//!
+//! A json pointer must start with "/root" (e.g. `/root/domain/region`). The `~` operator performs
+//! a case-sensitive "contains" check on strings. For example:
+//!
+//! ```text
+//! schema = "Docker Image" and /root/domain/image ~ "nginx" and qualification = failure
+//! ```
+//!
+//! The `upstream` predicate matches [`Components`](crate::Component) that receive data from a
+//! [`Component`](crate::Component) matching the inner query, while `downstream` matches those
+//! that send data to one.
+
+use async_recursion::async_recursion;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+use veritech_client::ResourceStatus;
+
+use crate::component::view::ComponentViewError;
+use crate::qualification::QualificationSubCheckStatus;
+use crate::{
+    Component, ComponentError, ComponentId, ComponentView, DalContext, Edge, EdgeError,
+    SchemaVariantId, StandardModel, StandardModelError,
+};
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum ComponentQueryError {
+    #[error("component error: {0}")]
+    Component(#[from] ComponentError),
+    #[error("component view error: {0}")]
+    ComponentView(#[from] ComponentViewError),
+    #[error("edge error: {0}")]
+    Edge(#[from] EdgeError),
+    #[error("invalid json pointer (must start with \"/root\"): {0}")]
+    InvalidJsonPointer(String),
+    #[error("invalid literal at position {0}: {1}")]
+    InvalidLiteral(usize, String),
+    #[error("operator {0} is not supported for {1}")]
+    InvalidOperator(ComponentQueryOperator, String),
+    #[error("invalid qualification status: {0}")]
+    InvalidQualificationStatus(String),
+    #[error("invalid resource status: {0}")]
+    InvalidResourceStatus(String),
+    #[error(transparent)]
+    StandardModel(#[from] StandardModelError),
+    #[error("unexpected end of query")]
+    UnexpectedEnd,
+    #[error("unexpected token at position {0}: {1}")]
+    UnexpectedToken(usize, String),
+    #[error("unterminated string starting at position {0}")]
+    UnterminatedString(usize),
+}
+
+pub type ComponentQueryResult<T> = Result<T, ComponentQueryError>;
+
+/// The comparison operators supported by [`ComponentQuery`] predicates.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[serde(rename_all = "camelCase")]
+pub enum ComponentQueryOperator {
+    #[strum(serialize = "~")]
+    Contains,
+    #[strum(serialize = "=")]
+    Equals,
+    #[strum(serialize = "!=")]
+    NotEquals,
+}
+
+impl ComponentQueryOperator {
+    fn compare_str(&self, actual: &str, expected: &str) -> bool {
+        match self {
+            Self::Contains => actual.contains(expected),
+            Self::Equals => actual == expected,
+            Self::NotEquals => actual != expected,
+        }
+    }
+}
+
+/// The resource status of a [`Component`], including whether or not it has a resource at all.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ComponentQueryResourceStatus {
+    Error,
+    None,
+    Ok,
+    Warning,
+}
+
+/// A parsed predicate or boolean combination of predicates.
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum ComponentQuery {
+    And {
+        left: Box<ComponentQuery>,
+        right: Box<ComponentQuery>,
+    },
+    Downstream {
+        query: Box<ComponentQuery>,
+    },
+    Name {
+        operator: ComponentQueryOperator,
+        value: String,
+    },
+    Not {
+        query: Box<ComponentQuery>,
+    },
+    Or {
+        left: Box<ComponentQuery>,
+        right: Box<ComponentQuery>,
+    },
+    Prop {
+        /// The json pointer relative to "/root" (e.g. "/domain/region").
+        pointer: String,
+        operator: ComponentQueryOperator,
+        value: serde_json::Value,
+    },
+    Qualification {
+        operator: ComponentQueryOperator,
+        status: QualificationSubCheckStatus,
+    },
+    Resource {
+        operator: ComponentQueryOperator,
+        status: ComponentQueryResourceStatus,
+    },
+    Schema {
+        operator: ComponentQueryOperator,
+        value: String,
+    },
+    Upstream {
+        query: Box<ComponentQuery>,
+    },
+    Variant {
+        operator: ComponentQueryOperator,
+        value: String,
+    },
+}
+
+/// A [`Component`] that matched a [`ComponentQuery`].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentQueryMatch {
+    pub component_id: ComponentId,
+    pub component_name: String,
+    pub schema_variant_id: SchemaVariantId,
+}
+
+impl std::str::FromStr for ComponentQuery {
+    type Err = ComponentQueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl ComponentQuery {
+    /// Parse a query string into a [`ComponentQuery`].
+    pub fn parse(input: &str) -> ComponentQueryResult<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let query = parser.parse_or()?;
+        match parser.peek() {
+            Some((position, token)) => Err(ComponentQueryError::UnexpectedToken(
+                *position,
+                token.to_string(),
+            )),
+            None => Ok(query),
+        }
+    }
+
+    /// Evaluate the query against every [`Component`] visible in the current
+    /// [`DalContext`](crate::DalContext).
+    pub async fn evaluate(
+        &self,
+        ctx: &DalContext,
+    ) -> ComponentQueryResult<Vec<ComponentQueryMatch>> {
+        let mut evaluator = Evaluator::new(ctx).await?;
+        let matched = evaluator.evaluate(self).await?;
+
+        // Preserve the order in which components were listed.
+        let component_ids: Vec<ComponentId> = evaluator
+            .components
+            .iter()
+            .map(|component| *component.id())
+            .filter(|component_id| matched.contains(component_id))
+            .collect();
+
+        let mut matches = Vec::with_capacity(component_ids.len());
+        for component_id in component_ids {
+            matches.push(ComponentQueryMatch {
+                component_id,
+                component_name: evaluator.name(component_id).await?,
+                schema_variant_id: evaluator.schema_variant_id(component_id).await?,
+            });
+        }
+        Ok(matches)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Bang,
+    Equals,
+    Ident(String),
+    LeftParen,
+    Literal(serde_json::Value),
+    Pointer(String),
+    RightParen,
+    Tilde,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bang => write!(f, "!="),
+            Self::Equals => write!(f, "="),
+            Self::Ident(ident) => write!(f, "{ident}"),
+            Self::LeftParen => write!(f, "("),
+            Self::Literal(literal) => write!(f, "{literal}"),
+            Self::Pointer(pointer) => write!(f, "{pointer}"),
+            Self::RightParen => write!(f, ")"),
+            Self::Tilde => write!(f, "~"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> ComponentQueryResult<Vec<(usize, Token)>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < chars.len() {
+        let start = position;
+        match chars[position] {
+            c if c.is_whitespace() => position += 1,
+            '(' => {
+                tokens.push((start, Token::LeftParen));
+                position += 1;
+            }
+            ')' => {
+                tokens.push((start, Token::RightParen));
+                position += 1;
+            }
+            '=' => {
+                tokens.push((start, Token::Equals));
+                position += 1;
+            }
+            '~' => {
+                tokens.push((start, Token::Tilde));
+                position += 1;
+            }
+            '!' if chars.get(position + 1) == Some(&'=') => {
+                tokens.push((start, Token::Bang));
+                position += 2;
+            }
+            '!' => {
+                return Err(ComponentQueryError::UnexpectedToken(start, "!".to_owned()));
+            }
+            '"' => {
+                position += 1;
+                let mut escaped = false;
+                loop {
+                    match chars.get(position) {
+                        None => return Err(ComponentQueryError::UnterminatedString(start)),
+                        Some('\\') if !escaped => escaped = true,
+                        Some('"') if !escaped => break,
+                        Some(_) => escaped = false,
+                    }
+                    position += 1;
+                }
+                position += 1;
+                let raw: String = chars[start..position].iter().collect();
+                let literal = serde_json::from_str(&raw)
+                    .map_err(|_| ComponentQueryError::InvalidLiteral(start, raw))?;
+                tokens.push((start, Token::Literal(literal)));
+            }
+            '/' => {
+                while position < chars.len() && !is_delimiter(chars[position]) {
+                    position += 1;
+                }
+                tokens.push((
+                    start,
+                    Token::Pointer(chars[start..position].iter().collect()),
+                ));
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                while position < chars.len() && !is_delimiter(chars[position]) {
+                    position += 1;
+                }
+                let raw: String = chars[start..position].iter().collect();
+                let literal = serde_json::from_str(&raw)
+                    .map_err(|_| ComponentQueryError::InvalidLiteral(start, raw))?;
+                tokens.push((start, Token::Literal(literal)));
+            }
+            _ => {
+                while position < chars.len() && !is_delimiter(chars[position]) {
+                    position += 1;
+                }
+                // Every other arm consumes at least one character, but guard against looping
+                // forever should a delimiter ever be left unhandled above
+                if position == start {
+                    return Err(ComponentQueryError::UnexpectedToken(
+                        start,
+                        chars[start].to_string(),
+                    ));
+                }
+                let ident: String = chars[start..position].iter().collect();
+                let token = match ident.as_str() {
+                    "true" => Token::Literal(serde_json::Value::Bool(true)),
+                    "false" => Token::Literal(serde_json::Value::Bool(false)),
+                    "null" => Token::Literal(serde_json::Value::Null),
+                    _ => Token::Ident(ident),
+                };
+                tokens.push((start, token));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '=' | '~' | '!' | '"')
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a (usize, Token)> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> ComponentQueryResult<&'a (usize, Token)> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or(ComponentQueryError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some((_, Token::Ident(ident))) if ident.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> ComponentQueryResult<()> {
+        let (position, token) = self.next()?;
+        if *token != expected {
+            return Err(ComponentQueryError::UnexpectedToken(
+                *position,
+                token.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> ComponentQueryResult<ComponentQuery> {
+        let mut left = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.position += 1;
+            let right = self.parse_and()?;
+            left = ComponentQuery::Or {
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> ComponentQueryResult<ComponentQuery> {
+        let mut left = self.parse_unary()?;
+        while self.peek_keyword("and") {
+            self.position += 1;
+            let right = self.parse_unary()?;
+            left = ComponentQuery::And {
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> ComponentQueryResult<ComponentQuery> {
+        if self.peek_keyword("not") {
+            self.position += 1;
+            return Ok(ComponentQuery::Not {
+                query: Box::new(self.parse_unary()?),
+            });
+        }
+        if let Some((_, Token::LeftParen)) = self.peek() {
+            self.position += 1;
+            let query = self.parse_or()?;
+            self.expect(Token::RightParen)?;
+            return Ok(query);
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_operator(&mut self) -> ComponentQueryResult<ComponentQueryOperator> {
+        match self.next()? {
+            (_, Token::Equals) => Ok(ComponentQueryOperator::Equals),
+            (_, Token::Bang) => Ok(ComponentQueryOperator::NotEquals),
+            (_, Token::Tilde) => Ok(ComponentQueryOperator::Contains),
+            (position, token) => Err(ComponentQueryError::UnexpectedToken(
+                *position,
+                token.to_string(),
+            )),
+        }
+    }
+
+    fn parse_string(&mut self) -> ComponentQueryResult<String> {
+        match self.next()? {
+            (_, Token::Literal(serde_json::Value::String(value))) => Ok(value.clone()),
+            (_, Token::Ident(ident)) => Ok(ident.clone()),
+            (position, token) => Err(ComponentQueryError::UnexpectedToken(
+                *position,
+                token.to_string(),
+            )),
+        }
+    }
+
+    fn parse_predicate(&mut self) -> ComponentQueryResult<ComponentQuery> {
+        let (position, token) = self.next()?;
+        match token {
+            Token::Pointer(pointer) => {
+                let pointer = pointer
+                    .strip_prefix("/root")
+                    .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+                    .ok_or_else(|| ComponentQueryError::InvalidJsonPointer(pointer.clone()))?
+                    .to_owned();
+                let operator = self.parse_operator()?;
+                let value = match self.next()? {
+                    (_, Token::Literal(value)) => value.clone(),
+                    (_, Token::Ident(ident)) => serde_json::Value::String(ident.clone()),
+                    (position, token) => {
+                        return Err(ComponentQueryError::UnexpectedToken(
+                            *position,
+                            token.to_string(),
+                        ))
+                    }
+                };
+                Ok(ComponentQuery::Prop {
+                    pointer,
+                    operator,
+                    value,
+                })
+            }
+            Token::Ident(ident) => match ident.to_lowercase().as_str() {
+                "schema" => Ok(ComponentQuery::Schema {
+                    operator: self.parse_operator()?,
+                    value: self.parse_string()?,
+                }),
+                "variant" => Ok(ComponentQuery::Variant {
+                    operator: self.parse_operator()?,
+                    value: self.parse_string()?,
+                }),
+                "name" => Ok(ComponentQuery::Name {
+                    operator: self.parse_operator()?,
+                    value: self.parse_string()?,
+                }),
+                "qualification" => {
+                    let operator = self.parse_operator()?;
+                    if operator == ComponentQueryOperator::Contains {
+                        return Err(ComponentQueryError::InvalidOperator(
+                            operator,
+                            "qualification".to_owned(),
+                        ));
+                    }
+                    let status = self.parse_string()?;
+                    let status = match status.to_lowercase().as_str() {
+                        "success" => QualificationSubCheckStatus::Success,
+                        "warning" => QualificationSubCheckStatus::Warning,
+                        "failure" => QualificationSubCheckStatus::Failure,
+                        "unknown" => QualificationSubCheckStatus::Unknown,
+                        _ => return Err(ComponentQueryError::InvalidQualificationStatus(status)),
+                    };
+                    Ok(ComponentQuery::Qualification { operator, status })
+                }
+                "resource" => {
+                    let operator = self.parse_operator()?;
+                    if operator == ComponentQueryOperator::Contains {
+                        return Err(ComponentQueryError::InvalidOperator(
+                            operator,
+                            "resource".to_owned(),
+                        ));
+                    }
+                    let status = self.parse_string()?;
+                    let status = match status.to_lowercase().as_str() {
+                        "ok" => ComponentQueryResourceStatus::Ok,
+                        "warning" => ComponentQueryResourceStatus::Warning,
+                        "error" => ComponentQueryResourceStatus::Error,
+                        "none" => ComponentQueryResourceStatus::None,
+                        _ => return Err(ComponentQueryError::InvalidResourceStatus(status)),
+                    };
+                    Ok(ComponentQuery::Resource { operator, status })
+                }
+                "upstream" | "downstream" => {
+                    self.expect(Token::LeftParen)?;
+                    let query = Box::new(self.parse_or()?);
+                    self.expect(Token::RightParen)?;
+                    if ident.eq_ignore_ascii_case("upstream") {
+                        Ok(ComponentQuery::Upstream { query })
+                    } else {
+                        Ok(ComponentQuery::Downstream { query })
+                    }
+                }
+                _ => Err(ComponentQueryError::UnexpectedToken(
+                    *position,
+                    ident.clone(),
+                )),
+            },
+            token => Err(ComponentQueryError::UnexpectedToken(
+                *position,
+                token.to_string(),
+            )),
+        }
+    }
+}
+
+/// Evaluates [`ComponentQueries`](ComponentQuery) as sets of [`ComponentIds`](ComponentId),
+/// caching everything it has to fetch about each [`Component`].
+struct Evaluator<'a> {
+    ctx: &'a DalContext,
+    components: Vec<Component>,
+    names: HashMap<ComponentId, String>,
+    schema_names: HashMap<ComponentId, String>,
+    schema_variant_ids: HashMap<ComponentId, SchemaVariantId>,
+    properties: HashMap<ComponentId, serde_json::Value>,
+    qualifications: HashMap<ComponentId, QualificationSubCheckStatus>,
+    resources: HashMap<ComponentId, ComponentQueryResourceStatus>,
+    edges: Option<Vec<(ComponentId, ComponentId)>>,
+}
+
+impl<'a> Evaluator<'a> {
+    async fn new(ctx: &'a DalContext) -> ComponentQueryResult<Evaluator<'a>> {
+        Ok(Self {
+            ctx,
+            components: Component::list(ctx).await?,
+            names: HashMap::new(),
+            schema_names: HashMap::new(),
+            schema_variant_ids: HashMap::new(),
+            properties: HashMap::new(),
+            qualifications: HashMap::new(),
+            resources: HashMap::new(),
+            edges: None,
+        })
+    }
+
+    fn universe(&self) -> HashSet<ComponentId> {
+        self.components.iter().map(|c| *c.id()).collect()
+    }
+
+    #[async_recursion]
+    async fn evaluate(
+        &mut self,
+        query: &ComponentQuery,
+    ) -> ComponentQueryResult<HashSet<ComponentId>> {
+        let matched = match query {
+            ComponentQuery::And { left, right } => {
+                let left = self.evaluate(left).await?;
+                let right = self.evaluate(right).await?;
+                left.intersection(&right).copied().collect()
+            }
+            ComponentQuery::Or { left, right } => {
+                let left = self.evaluate(left).await?;
+                let right = self.evaluate(right).await?;
+                left.union(&right).copied().collect()
+            }
+            ComponentQuery::Not { query } => {
+                let matched = self.evaluate(query).await?;
+                self.universe().difference(&matched).copied().collect()
+            }
+            ComponentQuery::Upstream { query } => {
+                // Match components whose inputs come from a matching component.
+                let matched = self.evaluate(query).await?;
+                self.edges()
+                    .await?
+                    .iter()
+                    .filter(|(tail, _)| matched.contains(tail))
+                    .map(|(_, head)| *head)
+                    .collect()
+            }
+            ComponentQuery::Downstream { query } => {
+                // Match components whose outputs go to a matching component.
+                let matched = self.evaluate(query).await?;
+                self.edges()
+                    .await?
+                    .iter()
+                    .filter(|(_, head)| matched.contains(head))
+                    .map(|(tail, _)| *tail)
+                    .collect()
+            }
+            predicate => {
+                let mut matched = HashSet::new();
+                for component_id in self.universe() {
+                    if self.matches(component_id, predicate).await? {
+                        matched.insert(component_id);
+                    }
+                }
+                matched
+            }
+        };
+        Ok(matched)
+    }
+
+    async fn matches(
+        &mut self,
+        component_id: ComponentId,
+        predicate: &ComponentQuery,
+    ) -> ComponentQueryResult<bool> {
+        Ok(match predicate {
+            ComponentQuery::Name { operator, value } => {
+                operator.compare_str(&self.name(component_id).await?, value)
+            }
+            ComponentQuery::Schema { operator, value } => {
+                operator.compare_str(&self.schema_name(component_id).await?, value)
+            }
+            ComponentQuery::Variant { operator, value } => operator.compare_str(
+                &self.schema_variant_id(component_id).await?.to_string(),
+                value,
+            ),
+            ComponentQuery::Prop {
+                pointer,
+                operator,
+                value,
+            } => {
+                let properties = self.properties(component_id).await?;
+                let actual = properties
+                    .pointer(pointer)
+                    .unwrap_or(&serde_json::Value::Null);
+                match operator {
+                    ComponentQueryOperator::Equals => actual == value,
+                    ComponentQueryOperator::NotEquals => actual != value,
+                    ComponentQueryOperator::Contains => match (actual, value) {
+                        (serde_json::Value::String(actual), serde_json::Value::String(value)) => {
+                            actual.contains(value.as_str())
+                        }
+                        (serde_json::Value::Array(actual), value) => actual.contains(value),
+                        _ => false,
+                    },
+                }
+            }
+            ComponentQuery::Qualification { operator, status } => {
+                let actual = self.qualification(component_id).await?;
+                match operator {
+                    ComponentQueryOperator::NotEquals => actual != *status,
+                    _ => actual == *status,
+                }
+            }
+            ComponentQuery::Resource { operator, status } => {
+                let actual = self.resource(component_id).await?;
+                match operator {
+                    ComponentQueryOperator::NotEquals => actual != *status,
+                    _ => actual == *status,
+                }
+            }
+            ComponentQuery::And { .. }
+            | ComponentQuery::Or { .. }
+            | ComponentQuery::Not { .. }
+            | ComponentQuery::Upstream { .. }
+            | ComponentQuery::Downstream { .. } => {
+                self.evaluate(predicate).await?.contains(&component_id)
+            }
+        })
+    }
+
+    async fn name(&mut self, component_id: ComponentId) -> ComponentQueryResult<String> {
+        if let Some(name) = self.names.get(&component_id) {
+            return Ok(name.clone());
+        }
+        let name = Component::find_name(self.ctx, component_id).await?;
+        self.names.insert(component_id, name.clone());
+        Ok(name)
+    }
+
+    async fn schema_name(&mut self, component_id: ComponentId) -> ComponentQueryResult<String> {
+        if let Some(name) = self.schema_names.get(&component_id) {
+            return Ok(name.clone());
+        }
+        let component = self
+            .components
+            .iter()
+            .find(|c| *c.id() == component_id)
+            .ok_or(ComponentError::NotFound(component_id))?;
+        let schema = component
+            .schema(self.ctx)
+            .await?
+            .ok_or(ComponentError::NoSchema(component_id))?;
+        let name = schema.name().to_owned();
+        self.schema_names.insert(component_id, name.clone());
+        Ok(name)
+    }
+
+    async fn schema_variant_id(
+        &mut self,
+        component_id: ComponentId,
+    ) -> ComponentQueryResult<SchemaVariantId> {
+        if let Some(schema_variant_id) = self.schema_variant_ids.get(&component_id) {
+            return Ok(*schema_variant_id);
+        }
+        let schema_variant_id = Component::schema_variant_id(self.ctx, component_id).await?;
+        self.schema_variant_ids
+            .insert(component_id, schema_variant_id);
+        Ok(schema_variant_id)
+    }
+
+    async fn properties(
+        &mut self,
+        component_id: ComponentId,
+    ) -> ComponentQueryResult<&serde_json::Value> {
+        if !self.properties.contains_key(&component_id) {
+            let view = ComponentView::new(self.ctx, component_id).await?;
+            self.properties.insert(component_id, view.properties);
+        }
+        Ok(&self.properties[&component_id])
+    }
+
+    async fn qualification(
+        &mut self,
+        component_id: ComponentId,
+    ) -> ComponentQueryResult<QualificationSubCheckStatus> {
+        if let Some(status) = self.qualifications.get(&component_id) {
+            return Ok(*status);
+        }
+
+        // Mirror the rollup used by the qualification summary: any failure fails the component,
+        // then any warning warns it. Components without a single result are unknown.
+        let mut status = QualificationSubCheckStatus::Unknown;
+        for qualification in Component::list_qualifications(self.ctx, component_id).await? {
+            status = match (status, qualification.result.map(|result| result.status)) {
+                (QualificationSubCheckStatus::Failure, _)
+                | (_, Some(QualificationSubCheckStatus::Failure)) => {
+                    QualificationSubCheckStatus::Failure
+                }
+                (QualificationSubCheckStatus::Warning, _)
+                | (_, Some(QualificationSubCheckStatus::Warning)) => {
+                    QualificationSubCheckStatus::Warning
+                }
+                (_, Some(QualificationSubCheckStatus::Success)) => {
+                    QualificationSubCheckStatus::Success
+                }
+                (status, _) => status,
+            };
+        }
+        self.qualifications.insert(component_id, status);
+        Ok(status)
+    }
+
+    async fn resource(
+        &mut self,
+        component_id: ComponentId,
+    ) -> ComponentQueryResult<ComponentQueryResourceStatus> {
+        if let Some(status) = self.resources.get(&component_id) {
+            return Ok(*status);
+        }
+        let resource = Component::resource_by_id(self.ctx, component_id).await?;
+        let status = match (resource.payload, resource.status) {
+            (None, _) => ComponentQueryResourceStatus::None,
+            (Some(_), ResourceStatus::Ok) => ComponentQueryResourceStatus::Ok,
+            (Some(_), ResourceStatus::Warning) => ComponentQueryResourceStatus::Warning,
+            (Some(_), ResourceStatus::Error) => ComponentQueryResourceStatus::Error,
+        };
+        self.resources.insert(component_id, status);
+        Ok(status)
+    }
+
+    /// Returns every (tail, head) pair of connected [`Components`](Component), where data flows
+    /// from the tail to the head.
+    async fn edges(&mut self) -> ComponentQueryResult<&[(ComponentId, ComponentId)]> {
+        if self.edges.is_none() {
+            let edges = Edge::list(self.ctx)
+                .await?
+                .into_iter()
+                .map(|edge| {
+                    (
+                        ComponentId::from(edge.tail_object_id()),
+                        ComponentId::from(edge.head_object_id()),
+                    )
+                })
+                .collect();
+            self.edges = Some(edges);
+        }
+        Ok(self.edges.as_deref().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_predicates() {
+        let query = ComponentQuery::parse(
+            r#"schema = "Docker Image" and /root/domain/region = "us-east-1" and qualification = failure"#,
+        )
+        .expect("could not parse query");
+
+        assert_eq!(
+            ComponentQuery::And {
+                left: Box::new(ComponentQuery::And {
+                    left: Box::new(ComponentQuery::Schema {
+                        operator: ComponentQueryOperator::Equals,
+                        value: "Docker Image".to_owned(),
+                    }),
+                    right: Box::new(ComponentQuery::Prop {
+                        pointer: "/domain/region".to_owned(),
+                        operator: ComponentQueryOperator::Equals,
+                        value: serde_json::json!("us-east-1"),
+                    }),
+                }),
+                right: Box::new(ComponentQuery::Qualification {
+                    operator: ComponentQueryOperator::Equals,
+                    status: QualificationSubCheckStatus::Failure,
+                }),
+            },
+            query
+        );
+    }
+
+    #[test]
+    fn parse_precedence_and_traversal() {
+        let query = ComponentQuery::parse(
+            "not resource = none or upstream(/root/domain/port != 80 and name ~ web)",
+        )
+        .expect("could not parse query");
+
+        assert_eq!(
+            ComponentQuery::Or {
+                left: Box::new(ComponentQuery::Not {
+                    query: Box::new(ComponentQuery::Resource {
+                        operator: ComponentQueryOperator::Equals,
+                        status: ComponentQueryResourceStatus::None,
+                    }),
+                }),
+                right: Box::new(ComponentQuery::Upstream {
+                    query: Box::new(ComponentQuery::And {
+                        left: Box::new(ComponentQuery::Prop {
+                            pointer: "/domain/port".to_owned(),
+                            operator: ComponentQueryOperator::NotEquals,
+                            value: serde_json::json!(80),
+                        }),
+                        right: Box::new(ComponentQuery::Name {
+                            operator: ComponentQueryOperator::Contains,
+                            value: "web".to_owned(),
+                        }),
+                    }),
+                }),
+            },
+            query
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!(
+            ComponentQuery::parse("/domain/region = foo"),
+            Err(ComponentQueryError::InvalidJsonPointer(_))
+        ));
+        assert!(matches!(
+            ComponentQuery::parse(r#"schema = "unterminated"#),
+            Err(ComponentQueryError::UnterminatedString(9))
+        ));
+        assert!(matches!(
+            ComponentQuery::parse("qualification ~ failure"),
+            Err(ComponentQueryError::InvalidOperator(_, _))
+        ));
+        assert!(matches!(
+            ComponentQuery::parse("schema ="),
+            Err(ComponentQueryError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            ComponentQuery::parse("(name = a"),
+            Err(ComponentQueryError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            ComponentQuery::parse("name = a b"),
+            Err(ComponentQueryError::UnexpectedToken(9, _))
+        ));
+        assert!(matches!(
+            ComponentQuery::parse("!"),
+            Err(ComponentQueryError::UnexpectedToken(0, _))
+        ));
+        assert!(matches!(
+            ComponentQuery::parse("a!b"),
+            Err(ComponentQueryError::UnexpectedToken(1, _))
+        ));
+        assert!(matches!(
+            ComponentQuery::parse("name = a !"),
+            Err(ComponentQueryError::UnexpectedToken(9, _))
+        ));
+    }
+}
@@ -7,8 +7,10 @@ use crate::{
     component::ComponentKind, func::binding_return_value::FuncBindingReturnValueId,
     AttributeReadContext, AttributeValue, AttributeValueError, Component, ComponentId, DalContext,
     EncryptedSecret, FuncBindingReturnValue, InternalProvider, InternalProviderError, PropError,
-    PropId, SchemaVariantId, SecretError, SecretId, StandardModel, StandardModelError,
+    PropId, SchemaVariantId, SecretBackendKind, SecretError, SecretId, SecretReference,
+    StandardModel, StandardModelError,
 };
+use veritech_client::WorkspaceSecretReference;
 
 pub mod debug;
 pub mod properties;
@@ -142,14 +144,24 @@ impl ComponentView {
             for (_key, value) in object {
                 if let Some(raw_id) = value.as_str() {
                     let id = SecretId::from_str(raw_id)?;
-                    let decrypted_secret = EncryptedSecret::get_by_id(ctx, &id)
+                    let encrypted_secret = EncryptedSecret::get_by_id(ctx, &id)
                         .await?
-                        .ok_or(ComponentViewError::SecretNotFound(id))?
-                        .decrypt(ctx)
-                        .await?;
-                    let encoded = ctx
-                        .encryption_key()
-                        .encrypt_and_encode(serde_json::to_string(&decrypted_secret.message())?);
+                        .ok_or(ComponentViewError::SecretNotFound(id))?;
+                    let backend = *encrypted_secret.backend();
+                    let workspace_pk = encrypted_secret.tenancy().workspace_pk();
+                    let decrypted_secret = encrypted_secret.decrypt(ctx).await?;
+                    let message = if backend == SecretBackendKind::Database {
+                        serde_json::to_string(&decrypted_secret.message())?
+                    } else {
+                        // Cyclone only resolves a reference below the prefix of the workspace
+                        // owning the secret
+                        let workspace_pk = workspace_pk.ok_or(SecretError::NoWorkspaceInTenancy)?;
+                        serde_json::to_string(&WorkspaceSecretReference {
+                            workspace_pk: workspace_pk.to_string(),
+                            reference: SecretReference::deserialize(&*decrypted_secret.message())?,
+                        })?
+                    };
+                    let encoded = ctx.encryption_key().encrypt_and_encode(message);
 
                     *value = serde_json::to_value(&decrypted_secret)?;
                     match value.pointer_mut("/message") {
@@ -157,7 +169,12 @@ impl ComponentView {
                             *v = serde_json::json!({
                                 "cycloneEncryptedDataMarker": true,
                                 "encryptedSecret": encoded
-                            })
+                            });
+                            // The message of a secret stored in an external backend is only a
+                            // reference, which cyclone resolves before executing the function
+                            if backend != SecretBackendKind::Database {
+                                v["secretBackend"] = serde_json::json!(backend);
+                            }
                         }
                         None => {
                             return Err(ComponentViewError::JSONPointerNotFound(
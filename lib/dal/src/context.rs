@@ -1,4 +1,8 @@
-use std::{mem, path::PathBuf, sync::Arc};
+use std::{
+    mem,
+    path::PathBuf,
+    sync::{Arc, PoisonError, RwLock},
+};
 
 use futures::Future;
 use serde::{Deserialize, Serialize};
@@ -36,8 +40,8 @@ pub struct ServicesContext {
     encryption_key: Arc<EncryptionKey>,
     /// The path where available packages can be found
     pkgs_path: Option<PathBuf>,
-    /// The URL of the module index
-    module_index_url: Option<String>,
+    /// The URL of the module index, which can be changed while the services are running
+    module_index_url: Arc<RwLock<Option<String>>>,
     /// A service that can encrypt and decrypt values with a set of symmetric keys
     symmetric_crypto_service: SymmetricCryptoService,
 }
@@ -62,7 +66,7 @@ impl ServicesContext {
             veritech,
             encryption_key,
             pkgs_path,
-            module_index_url,
+            module_index_url: Arc::new(RwLock::new(module_index_url)),
             symmetric_crypto_service,
         }
     }
@@ -100,9 +104,20 @@ impl ServicesContext {
         self.encryption_key.clone()
     }
 
-    /// Get a reference to the module index url
-    pub fn module_index_url(&self) -> &Option<String> {
-        &self.module_index_url
+    /// Gets the module index url
+    pub fn module_index_url(&self) -> Option<String> {
+        self.module_index_url
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Changes the module index url for every clone of this services context
+    pub fn set_module_index_url(&self, module_index_url: Option<String>) {
+        *self
+            .module_index_url
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = module_index_url;
     }
 
     /// Get a reference to the symmetric encryption service
@@ -482,9 +497,9 @@ impl DalContext {
         self.services_context.pkgs_path.as_ref()
     }
 
-    /// Gets an optional reference to the module index service's url
-    pub fn module_index_url(&self) -> Option<&str> {
-        self.services_context.module_index_url.as_deref()
+    /// Gets the module index service's url, if one is configured
+    pub fn module_index_url(&self) -> Option<String> {
+        self.services_context.module_index_url()
     }
 
     /// Determines if a standard model object matches the tenancy of the current context and
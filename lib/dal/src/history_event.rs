@@ -1,4 +1,6 @@
 use crate::{Tenancy, TransactionsError};
+use chrono::{DateTime, Utc};
+use futures::{stream, Stream};
 use serde::{Deserialize, Serialize};
 use strum::Display as StrumDisplay;
 use thiserror::Error;
@@ -7,7 +9,14 @@ use si_data_nats::NatsError;
 use si_data_pg::PgError;
 use telemetry::prelude::*;
 
-use crate::{pk, DalContext, Timestamp, UserPk};
+use crate::{pk, ChangeSetPk, ComponentId, DalContext, FuncId, Timestamp, UserPk};
+
+const HISTORY_EVENT_LIST: &str = include_str!("queries/history_event/list.sql");
+
+/// The number of events in a page when [`HistoryEventFilter::limit`] isn't set.
+pub const DEFAULT_HISTORY_EVENT_PAGE_SIZE: u32 = 100;
+/// The most events a single page can hold.
+pub const MAX_HISTORY_EVENT_PAGE_SIZE: u32 = 1000;
 
 #[remain::sorted]
 #[derive(Error, Debug)]
@@ -58,6 +67,14 @@ pub struct HistoryEvent {
     pub actor: HistoryActor,
     pub message: String,
     pub data: serde_json::Value,
+    /// The change set the event happened in.
+    #[serde(rename = "visibility_change_set_pk")]
+    pub change_set_pk: Option<ChangeSetPk>,
+    /// The id of the object the event is about, when the event is about a single object.
+    pub entity_id: Option<String>,
+    /// The component the event is about, for events about a component or one of its attribute
+    /// values.
+    pub component_id: Option<ComponentId>,
     #[serde(flatten)]
     pub tenancy: Tenancy,
     #[serde(flatten)]
@@ -75,12 +92,21 @@ impl HistoryEvent {
         let label = label.as_ref();
         let message = message.as_ref();
         let actor = serde_json::to_value(ctx.history_actor())?;
+        let entity_id = data.get("id").and_then(serde_json::Value::as_str);
         let txns = ctx.txns().await?;
         let row = txns
             .pg()
             .query_one(
-                "SELECT object FROM history_event_create_v1($1, $2, $3, $4, $5)",
-                &[&label.to_string(), &actor, &message, &data, ctx.tenancy()],
+                "SELECT object FROM history_event_create_v2($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &label.to_string(),
+                    &actor,
+                    &message,
+                    &data,
+                    ctx.tenancy(),
+                    &ctx.visibility().change_set_pk,
+                    &entity_id,
+                ],
             )
             .await?;
         let json: serde_json::Value = row.try_get("object")?;
@@ -89,4 +115,133 @@ impl HistoryEvent {
         let object: HistoryEvent = serde_json::from_value(json)?;
         Ok(object)
     }
+
+    /// Lists the events of the workspace in the context's tenancy matching the filter, newest
+    /// first. Events that don't belong to a workspace are never listed.
+    #[instrument(skip(ctx))]
+    pub async fn list(
+        ctx: &DalContext,
+        filter: &HistoryEventFilter,
+    ) -> HistoryEventResult<HistoryEventPage> {
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_EVENT_PAGE_SIZE)
+            .clamp(1, MAX_HISTORY_EVENT_PAGE_SIZE);
+        let actor = filter.actor.map(serde_json::to_value).transpose()?;
+        let label_prefix = filter.label_prefix.as_deref().map(escape_like_pattern);
+        let func_id = filter.func_id.map(|id| id.to_string());
+
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                HISTORY_EVENT_LIST,
+                &[
+                    &ctx.tenancy().workspace_pk(),
+                    &actor,
+                    &label_prefix,
+                    &filter.change_set_pk,
+                    &filter.component_id,
+                    &func_id,
+                    &filter.since,
+                    &filter.until,
+                    &filter.cursor,
+                    // Fetch one more than asked for to know whether there is a next page
+                    &(i64::from(limit) + 1),
+                ],
+            )
+            .await?;
+
+        let mut events = Vec::with_capacity(rows.len());
+        for row in rows {
+            let json: serde_json::Value = row.try_get("object")?;
+            events.push(serde_json::from_value::<Self>(json)?);
+        }
+
+        let next_cursor = if events.len() > limit as usize {
+            events.truncate(limit as usize);
+            events.last().map(|event| event.pk)
+        } else {
+            None
+        };
+
+        Ok(HistoryEventPage {
+            events,
+            next_cursor,
+        })
+    }
+
+    /// Streams every event matching the filter as JSON lines, newest first, ignoring the filter's
+    /// cursor. Each item holds the lines of one page of events, so that the export is never held
+    /// in memory as a whole.
+    pub fn export_json_lines(
+        ctx: DalContext,
+        filter: &HistoryEventFilter,
+    ) -> impl Stream<Item = HistoryEventResult<Vec<u8>>> + Send + 'static {
+        let filter = HistoryEventFilter {
+            cursor: None,
+            limit: Some(MAX_HISTORY_EVENT_PAGE_SIZE),
+            ..filter.clone()
+        };
+
+        stream::try_unfold(Some(filter), move |filter| {
+            let ctx = ctx.clone();
+            async move {
+                let mut filter = match filter {
+                    Some(filter) => filter,
+                    None => return Ok(None),
+                };
+
+                let page = Self::list(&ctx, &filter).await?;
+                let mut lines = Vec::new();
+                for event in &page.events {
+                    serde_json::to_writer(&mut lines, event)?;
+                    lines.push(b'\n');
+                }
+
+                let next_filter = page.next_cursor.map(|cursor| {
+                    filter.cursor = Some(cursor);
+                    filter
+                });
+                Ok(Some((lines, next_filter)))
+            }
+        })
+    }
+}
+
+/// Narrows down the events returned by [`HistoryEvent::list`]. Every filter that is set must
+/// match.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEventFilter {
+    pub actor: Option<HistoryActor>,
+    /// Matches labels starting with the prefix, such as `component.` for every component event.
+    pub label_prefix: Option<String>,
+    pub change_set_pk: Option<ChangeSetPk>,
+    pub component_id: Option<ComponentId>,
+    pub func_id: Option<FuncId>,
+    /// Matches events created at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Matches events created before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Matches events older than this one, which is the `next_cursor` of the previous page.
+    pub cursor: Option<HistoryEventPk>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEventPage {
+    pub events: Vec<HistoryEvent>,
+    /// The cursor for the next page, if there are more events.
+    pub next_cursor: Option<HistoryEventPk>,
+}
+
+/// Escapes the wildcards of a `LIKE` pattern so that they match literally.
+fn escape_like_pattern(pattern: &str) -> String {
+    pattern
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
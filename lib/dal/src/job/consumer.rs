@@ -11,8 +11,8 @@ use crate::{
     fix::FixError, func::binding_return_value::FuncBindingReturnValueError,
     job::producer::BlockingJobError, job::producer::JobProducerError, status::StatusUpdaterError,
     AccessBuilder, ActionPrototypeError, ActionPrototypeId, AttributeValueError, ComponentError,
-    ComponentId, DalContext, DalContextBuilder, FixBatchId, FixResolverError, StandardModelError,
-    TransactionsError, Visibility, WsEventError,
+    ComponentId, DalContext, DalContextBuilder, FixBatchId, FixResolverError, SecretError,
+    StandardModelError, TransactionsError, Visibility, WebhookError, WsEventError,
 };
 
 #[remain::sorted]
@@ -65,6 +65,8 @@ pub enum JobConsumerError {
     #[error(transparent)]
     PgPool(#[from] PgPoolError),
     #[error(transparent)]
+    Secret(#[from] SecretError),
+    #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
     #[error(transparent)]
     StandardModel(#[from] StandardModelError),
@@ -77,6 +79,8 @@ pub enum JobConsumerError {
     #[error(transparent)]
     UlidDecode(#[from] ulid::DecodeError),
     #[error(transparent)]
+    Webhook(#[from] WebhookError),
+    #[error(transparent)]
     WsEvent(#[from] WsEventError),
 }
 
@@ -1,7 +1,15 @@
 mod dependent_values_update;
 mod fix;
+mod health_check;
 mod refresh;
+mod symmetric_key_rotation;
+mod webhook_delivery;
 
 pub use dependent_values_update::DependentValuesUpdate;
 pub use fix::{FixItem, FixesJob};
+pub use health_check::HealthCheckJob;
 pub use refresh::RefreshJob;
+pub use symmetric_key_rotation::{
+    SymmetricKeyRotationJob, DEFAULT_SYMMETRIC_KEY_ROTATION_BATCH_SIZE,
+};
+pub use webhook_delivery::{WebhookDeliveryJob, WEBHOOK_DELIVERY_MAX_ATTEMPTS};
@@ -165,6 +165,10 @@ impl DependentValuesUpdate {
         // Save printed output to a file and execute the following: "dot <file> -Tsvg -o <newfile>.svg"
         // println!("{}", dependency_graph_to_dot(ctx, &dependency_graph).await?);
 
+        // Keep the complete set of dependencies around for early cutoff: a value only needs to be
+        // recomputed if at least one of the values it depends on actually changed.
+        let dependencies = dependency_graph.clone();
+
         // Remove the `AttributeValueIds` from the list of values that are in the dependencies,
         // as we consider that one to have already been updated. This lets us check for
         // `AttributeValuesId`s where the list of *unsatisfied* dependencies is empty.
@@ -210,14 +214,35 @@ impl DependentValuesUpdate {
 
         let mut update_tasks = JoinSet::new();
 
+        // The values that this job has either recomputed or pruned, and whose value did not
+        // change as a result. The initial `AttributeValueIds` are never in here, since they are
+        // the values that changed in the first place.
+        let mut unchanged: HashSet<AttributeValueId> = HashSet::new();
+
         while !dependency_graph.is_empty() {
             match council.fetch_response().await? {
                 Some(response) => match response {
-                    council_server::Response::OkToProcess { node_ids } => {
+                    council_server::Response::OkToProcess { node_ids, shared_node_ids } => {
                         debug!(?node_ids, job_id = ?self.job_id(), "Ok to start processing nodes");
                         for node_id in node_ids {
                             let id = AttributeValueId::from(node_id);
 
+                            // Early cutoff: if nothing this value depends on changed, neither
+                            // will it, so we can skip running its function. Nodes that other
+                            // jobs are waiting on may have dependencies we don't know about.
+                            let dependencies_unchanged =
+                                dependencies.get(&id).map_or(false, |dependencies| {
+                                    dependencies
+                                        .iter()
+                                        .all(|dependency| unchanged.contains(dependency))
+                                });
+                            if dependencies_unchanged && !shared_node_ids.contains(&node_id) {
+                                debug!(?id, job_id = ?self.job_id(), "Pruning node whose dependencies are unchanged");
+                                unchanged.insert(id);
+                                pub_council.processed_value(node_id).await?;
+                                continue;
+                            }
+
                             status_updater.values_running(ctx, vec![id]).await;
                             // Status updater reads from the database and uses its own connection
                             // from the pg_pool to do writes
@@ -269,6 +294,8 @@ impl DependentValuesUpdate {
                     // we have told it that we've finished doing so. This should never be able to happen normally,
                     // as it breaks the protocol contract we have with council.
                     council_server::Response::OkToCreate => return Err(JobConsumerError::CouncilProtocol("Told to create values again after we've finished creating values. Multiple instances of council running?".to_string())),
+                    // Pongs only ever answer pings, which go through their own inbox.
+                    council_server::Response::Pong => return Err(JobConsumerError::CouncilProtocol("Received a pong without having pinged council".to_string())),
                     council_server::Response::Shutdown => break,
                 },
                 // FIXME: reconnect
@@ -298,7 +325,11 @@ impl DependentValuesUpdate {
                 // anything went wrong in joining the task.
                 match future_result {
                     // We have successfully updated a value
-                    Ok(Ok(())) => {}
+                    Ok(Ok((id, changed))) => {
+                        if !changed {
+                            unchanged.insert(id);
+                        }
+                    }
                     // There was an error (with our code) when updating the value
                     Ok(Err(err)) => {
                         warn!(error = ?err, "error updating value");
@@ -345,6 +376,8 @@ impl DependentValuesUpdate {
 
 /// Wrapper around `AttributeValue.update_from_prototype_function(&ctx)` to get it to
 /// play more nicely with being spawned into a `JoinSet`.
+///
+/// Returns whether or not the value changed as a result of the update.
 #[instrument(
     name = "dependent_values_update.update_value",
     skip_all,
@@ -357,7 +390,9 @@ async fn update_value(
     ctx: DalContext,
     mut attribute_value: AttributeValue,
     council: council_server::PubClient,
-) -> JobConsumerResult<()> {
+) -> JobConsumerResult<(AttributeValueId, bool)> {
+    let id = *attribute_value.id();
+    let previous_value = current_value(&ctx, id).await;
     let update_result = attribute_value.update_from_prototype_function(&ctx).await;
     // We don't propagate the error up, because we want the rest of the nodes in the graph to make progress
     // if they are able to.
@@ -369,13 +404,38 @@ async fn update_value(
         ctx.rollback().await?;
     }
 
+    let changed = match &update_result {
+        Ok(()) => {
+            let new_value = current_value(&ctx, id).await;
+            previous_value.is_none() || new_value.is_none() || previous_value != new_value
+        }
+        Err(_) => true,
+    };
+
     ctx.commit().await?;
 
     if update_result.is_ok() {
         council.processed_value(attribute_value.id().into()).await?;
     }
 
-    Ok(())
+    Ok((id, changed))
+}
+
+/// Fetch both the processed and unprocessed value for an [`AttributeValue`]. The unprocessed
+/// value is what the function returned, so it captures changes in child values of objects, maps
+/// and arrays that the processed value does not. Returns [`None`] if the value cannot be
+/// determined, in which case it should be considered changed.
+async fn current_value(
+    ctx: &DalContext,
+    attribute_value_id: AttributeValueId,
+) -> Option<(Option<serde_json::Value>, Option<serde_json::Value>)> {
+    let attribute_value = AttributeValue::get_by_id(ctx, &attribute_value_id)
+        .await
+        .ok()
+        .flatten()?;
+    let unprocessed_value = attribute_value.get_unprocessed_value(ctx).await.ok()?;
+    let value = attribute_value.get_value(ctx).await.ok()?;
+    Some((unprocessed_value, value))
 }
 
 impl TryFrom<JobInfo> for DependentValuesUpdate {
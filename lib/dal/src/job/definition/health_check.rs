@@ -0,0 +1,75 @@
+use std::convert::TryFrom;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+
+use crate::{
+    job::{
+        consumer::{
+            JobConsumer, JobConsumerError, JobConsumerMetadata, JobConsumerResult, JobInfo,
+        },
+        producer::{JobProducer, JobProducerResult},
+    },
+    AccessBuilder, DalContext, HistoryActor, Tenancy, Visibility,
+};
+
+/// A job that does nothing, used to check that pinga is consuming jobs. Blocking on it checks
+/// the whole round trip through NATS and back.
+#[derive(Clone, Debug, Serialize)]
+pub struct HealthCheckJob {
+    access_builder: AccessBuilder,
+    visibility: Visibility,
+    job: Option<JobInfo>,
+}
+
+impl HealthCheckJob {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {
+            access_builder: AccessBuilder::new(Tenancy::new_empty(), HistoryActor::SystemInit),
+            visibility: Visibility::new_head(false),
+            job: None,
+        })
+    }
+}
+
+impl JobProducer for HealthCheckJob {
+    fn arg(&self) -> JobProducerResult<serde_json::Value> {
+        Ok(serde_json::Value::Null)
+    }
+}
+
+impl JobConsumerMetadata for HealthCheckJob {
+    fn type_name(&self) -> String {
+        "HealthCheckJob".to_string()
+    }
+
+    fn access_builder(&self) -> AccessBuilder {
+        self.access_builder
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+}
+
+#[async_trait]
+impl JobConsumer for HealthCheckJob {
+    #[instrument(name = "health_check_job.run", skip_all, level = "debug")]
+    async fn run(&self, _ctx: &mut DalContext) -> JobConsumerResult<()> {
+        debug!("health check job consumed");
+        Ok(())
+    }
+}
+
+impl TryFrom<JobInfo> for HealthCheckJob {
+    type Error = JobConsumerError;
+
+    fn try_from(job: JobInfo) -> Result<Self, Self::Error> {
+        Ok(Self {
+            access_builder: job.access_builder,
+            visibility: job.visibility,
+            job: Some(job),
+        })
+    }
+}
@@ -0,0 +1,173 @@
+use std::convert::TryFrom;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+
+use crate::{
+    job::{
+        consumer::{
+            JobConsumer, JobConsumerError, JobConsumerMetadata, JobConsumerResult, JobInfo,
+        },
+        producer::{JobProducer, JobProducerResult},
+    },
+    AccessBuilder, DalContext, EncryptedSecret, HistoryActor, Tenancy, Visibility, WebhookEndpoint,
+};
+
+/// The default number of secrets re-encrypted per transaction.
+pub const DEFAULT_SYMMETRIC_KEY_ROTATION_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct SymmetricKeyRotationJobArgs {
+    batch_size: i64,
+}
+
+impl From<SymmetricKeyRotationJob> for SymmetricKeyRotationJobArgs {
+    fn from(value: SymmetricKeyRotationJob) -> Self {
+        Self {
+            batch_size: value.batch_size,
+        }
+    }
+}
+
+/// Re-encrypts every [`EncryptedSecret`] and [`WebhookEndpoint`] secret that is not encrypted with
+/// the active symmetric key, across all workspaces, in batches of `batch_size` secrets per
+/// transaction.
+///
+/// To rotate, make the new key the active key and move the old key to the extra keys of every
+/// service, then run this job. Once [`EncryptedSecret::key_hash_usage()`] no longer reports the
+/// old key, it can be removed from the extra keys.
+#[derive(Clone, Debug, Serialize)]
+pub struct SymmetricKeyRotationJob {
+    batch_size: i64,
+    access_builder: AccessBuilder,
+    visibility: Visibility,
+    job: Option<JobInfo>,
+}
+
+impl SymmetricKeyRotationJob {
+    pub fn new(batch_size: i64) -> Box<Self> {
+        Box::new(Self {
+            batch_size,
+            access_builder: AccessBuilder::new(Tenancy::new_empty(), HistoryActor::SystemInit),
+            visibility: Visibility::new_head(false),
+            job: None,
+        })
+    }
+}
+
+impl JobProducer for SymmetricKeyRotationJob {
+    fn arg(&self) -> JobProducerResult<serde_json::Value> {
+        Ok(serde_json::to_value(SymmetricKeyRotationJobArgs::from(
+            self.clone(),
+        ))?)
+    }
+}
+
+impl JobConsumerMetadata for SymmetricKeyRotationJob {
+    fn type_name(&self) -> String {
+        "SymmetricKeyRotationJob".to_string()
+    }
+
+    fn access_builder(&self) -> AccessBuilder {
+        self.access_builder
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+}
+
+#[async_trait]
+impl JobConsumer for SymmetricKeyRotationJob {
+    #[instrument(
+        name = "symmetric_key_rotation_job.run",
+        skip_all,
+        level = "info",
+        fields(
+            batch_size = %self.batch_size,
+        )
+    )]
+    async fn run(&self, ctx: &mut DalContext) -> JobConsumerResult<()> {
+        let active_key_hash = *ctx.symmetric_crypto_service().active_key_hash();
+        let total: i64 = EncryptedSecret::key_hash_usage(ctx)
+            .await?
+            .iter()
+            .filter(|usage| !usage.active)
+            .map(|usage| usage.count)
+            .sum();
+        info!(%active_key_hash, %total, "rotating secrets to the active symmetric key");
+
+        let mut after = None;
+        let mut rotated = 0;
+        let mut failed = Vec::new();
+        loop {
+            let batch = EncryptedSecret::reencrypt_batch(ctx, after, self.batch_size).await?;
+            // Commit every batch so that progress is kept if the job is interrupted
+            ctx.commit().await?;
+
+            rotated += batch.rotated;
+            failed.extend(batch.failed.into_iter().map(|pk| pk.to_string()));
+            info!(%rotated, failed = %failed.len(), %total, "symmetric key rotation progress");
+
+            match batch.last_pk {
+                Some(last_pk) => after = Some(last_pk),
+                None => break,
+            }
+        }
+
+        let mut after = None;
+        loop {
+            let batch =
+                WebhookEndpoint::reencrypt_secrets_batch(ctx, after, self.batch_size).await?;
+            ctx.commit().await?;
+
+            rotated += batch.rotated;
+            failed.extend(batch.failed.into_iter().map(|pk| pk.to_string()));
+            info!(%rotated, failed = %failed.len(), %total, "symmetric key rotation progress");
+
+            match batch.last_pk {
+                Some(last_pk) => after = Some(last_pk),
+                None => break,
+            }
+        }
+
+        if !failed.is_empty() {
+            warn!(
+                ?failed,
+                "some secrets could not be re-encrypted, are all of the old keys loaded?"
+            );
+        }
+
+        for usage in EncryptedSecret::key_hash_usage(ctx).await? {
+            if !usage.active {
+                warn!(
+                    key_hash = %usage.key_hash,
+                    count = %usage.count,
+                    loaded = %usage.loaded,
+                    "symmetric key is still referenced and cannot be retired yet",
+                );
+            }
+        }
+        ctx.rollback().await?;
+
+        info!(%rotated, failed = %failed.len(), "finished symmetric key rotation");
+
+        Ok(())
+    }
+}
+
+impl TryFrom<JobInfo> for SymmetricKeyRotationJob {
+    type Error = JobConsumerError;
+
+    fn try_from(job: JobInfo) -> Result<Self, Self::Error> {
+        let args = SymmetricKeyRotationJobArgs::deserialize(&job.arg)?;
+
+        Ok(Self {
+            batch_size: args.batch_size,
+            access_builder: job.access_builder,
+            visibility: job.visibility,
+            job: Some(job),
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use std::{convert::TryFrom, time::Duration};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+
+use crate::{
+    job::{
+        consumer::{
+            JobConsumer, JobConsumerError, JobConsumerMetadata, JobConsumerResult, JobInfo,
+        },
+        producer::{JobProducer, JobProducerResult},
+    },
+    webhook::{WebhookClient, WebhookDelivery, WebhookDeliveryPk, WebhookError},
+    AccessBuilder, DalContext, Visibility,
+};
+
+/// How many times a delivery is attempted before it is given up on.
+pub const WEBHOOK_DELIVERY_MAX_ATTEMPTS: u32 = 5;
+
+/// How long to wait before the first retry, which doubles with every retry after it.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize, Serialize)]
+struct WebhookDeliveryJobArgs {
+    delivery_pk: WebhookDeliveryPk,
+}
+
+impl From<WebhookDeliveryJob> for WebhookDeliveryJobArgs {
+    fn from(value: WebhookDeliveryJob) -> Self {
+        Self {
+            delivery_pk: value.delivery_pk,
+        }
+    }
+}
+
+/// Sends a [`WebhookDelivery`] to its endpoint, retrying with an exponential backoff until the
+/// endpoint accepts it or [`WEBHOOK_DELIVERY_MAX_ATTEMPTS`] attempts failed.
+#[derive(Clone, Debug, Serialize)]
+pub struct WebhookDeliveryJob {
+    delivery_pk: WebhookDeliveryPk,
+    access_builder: AccessBuilder,
+    visibility: Visibility,
+    job: Option<JobInfo>,
+}
+
+impl WebhookDeliveryJob {
+    pub fn new(
+        access_builder: AccessBuilder,
+        visibility: Visibility,
+        delivery_pk: WebhookDeliveryPk,
+    ) -> Box<Self> {
+        Box::new(Self {
+            delivery_pk,
+            access_builder,
+            visibility,
+            job: None,
+        })
+    }
+}
+
+impl JobProducer for WebhookDeliveryJob {
+    fn arg(&self) -> JobProducerResult<serde_json::Value> {
+        Ok(serde_json::to_value(WebhookDeliveryJobArgs::from(
+            self.clone(),
+        ))?)
+    }
+}
+
+impl JobConsumerMetadata for WebhookDeliveryJob {
+    fn type_name(&self) -> String {
+        "WebhookDeliveryJob".to_string()
+    }
+
+    fn access_builder(&self) -> AccessBuilder {
+        self.access_builder
+    }
+
+    fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+}
+
+#[async_trait]
+impl JobConsumer for WebhookDeliveryJob {
+    #[instrument(
+        name = "webhook_delivery_job.run",
+        skip_all,
+        level = "info",
+        fields(
+            delivery_pk = %self.delivery_pk,
+        )
+    )]
+    async fn run(&self, ctx: &mut DalContext) -> JobConsumerResult<()> {
+        let mut delivery = WebhookDelivery::get_by_pk(ctx, self.delivery_pk)
+            .await?
+            .ok_or(WebhookError::NotFound(self.delivery_pk))?;
+        let client = WebhookClient::new();
+
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 0;
+        while delivery.is_pending() {
+            attempt += 1;
+            let last_attempt = attempt >= WEBHOOK_DELIVERY_MAX_ATTEMPTS;
+            let succeeded = delivery.attempt(ctx, &client, last_attempt).await?;
+            // Commit every attempt so that the delivery log shows them as they happen
+            ctx.commit().await?;
+
+            if succeeded {
+                debug!(%attempt, "delivered webhook");
+            } else if delivery.is_pending() {
+                debug!(%attempt, ?backoff, error = ?delivery.last_error(), "webhook delivery failed, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            } else {
+                warn!(%attempt, error = ?delivery.last_error(), "giving up on webhook delivery");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<JobInfo> for WebhookDeliveryJob {
+    type Error = JobConsumerError;
+
+    fn try_from(job: JobInfo) -> Result<Self, Self::Error> {
+        let args = WebhookDeliveryJobArgs::deserialize(&job.arg)?;
+
+        Ok(Self {
+            delivery_pk: args.delivery_pk,
+            access_builder: job.access_builder,
+            visibility: job.visibility,
+            job: Some(job),
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use si_crypto::{SymmetricCryptoError, SymmetricCryptoService, SymmetricNonce};
 use si_data_nats::NatsError;
@@ -12,13 +13,14 @@ use crate::{
     pk,
     serde_impls::{base64_bytes_serde, nonce_serde},
     standard_model_accessor_ro, DalContext, HistoryEvent, HistoryEventError, Timestamp,
-    TransactionsError, Workspace, WorkspaceError, WorkspacePk,
+    TransactionsError, Workspace, WorkspaceError, WorkspacePk, WsEvent, WsEventResult, WsPayload,
 };
 
 mod key_pair_box_public_key_serde;
 
 const PUBLIC_KEY_GET_CURRENT: &str = include_str!("./queries/public_key_get_current.sql");
 const KEY_PAIR_GET_BY_PK: &str = include_str!("queries/key_pair_get_by_pk.sql");
+const KEY_PAIR_DEACTIVATE_UNUSED: &str = include_str!("queries/key_pair_deactivate_unused.sql");
 
 #[remain::sorted]
 #[derive(Error, Debug)]
@@ -33,6 +35,8 @@ pub enum KeyPairError {
     Nats(#[from] NatsError),
     #[error("no current key pair found when one was expected")]
     NoCurrentKeyPair,
+    #[error("no workspace in tenancy")]
+    NoWorkspaceInTenancy,
     #[error("pg error: {0}")]
     Pg(#[from] PgError),
     #[error("error serializing/deserializing json: {0}")]
@@ -57,6 +61,7 @@ pub struct KeyPair {
     public_key: BoxPublicKey,
     secret_key: BoxSecretKey,
     created_lamport_clock: u64,
+    deactivated_at: Option<DateTime<Utc>>,
     #[serde(flatten)]
     timestamp: Timestamp,
 }
@@ -138,6 +143,85 @@ impl KeyPair {
     standard_model_accessor_ro!(public_key, BoxPublicKey);
     standard_model_accessor_ro!(secret_key, BoxSecretKey);
     standard_model_accessor_ro!(created_lamport_clock, u64);
+    standard_model_accessor_ro!(deactivated_at, Option<DateTime<Utc>>);
+
+    /// Whether the key pair has been retired. Deactivated key pairs are never returned as the
+    /// current key pair and cannot be used to create new [`Secrets`](crate::Secret), but can
+    /// still decrypt any secret that references them.
+    pub fn is_deactivated(&self) -> bool {
+        self.deactivated_at.is_some()
+    }
+
+    /// Generates a new key pair for the workspace in the [`Tenancy`](crate::Tenancy), which
+    /// becomes the current key pair: it is the one returned by [`PublicKey::get_current()`] and
+    /// thus the one new secrets will be sealed with.
+    ///
+    /// Existing secrets keep referencing their previous key pair until they are re-wrapped with
+    /// [`EncryptedSecret::rewrap_for_key_pair()`](crate::EncryptedSecret::rewrap_for_key_pair),
+    /// after which [`Self::deactivate_unused()`] can retire the previous key pairs.
+    pub async fn rotate(ctx: &DalContext) -> KeyPairResult<Self> {
+        let previous_key_pair_pk: Option<KeyPairPk> = match ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(PUBLIC_KEY_GET_CURRENT, &[&ctx.tenancy().workspace_pk()])
+            .await?
+        {
+            Some(row) => {
+                let json: serde_json::Value = row.try_get("object")?;
+                Some(serde_json::from_value::<PublicKey>(json)?.pk)
+            }
+            None => None,
+        };
+
+        let key_pair = Self::new(ctx, "default").await?;
+
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "key_pair.rotate".to_owned(),
+            "Key Pair rotated".to_owned(),
+            &serde_json::json![{
+                "previous_key_pair_pk": previous_key_pair_pk,
+                "key_pair_pk": key_pair.pk,
+            }],
+        )
+        .await?;
+
+        Ok(key_pair)
+    }
+
+    /// Deactivates every key pair of the workspace in the [`Tenancy`](crate::Tenancy) that is not
+    /// the current key pair and is no longer referenced by any [`Secret`](crate::Secret), in any
+    /// change set. Returns the [`KeyPairPks`](KeyPairPk) that were deactivated.
+    pub async fn deactivate_unused(ctx: &DalContext) -> KeyPairResult<Vec<KeyPairPk>> {
+        let workspace_pk = ctx
+            .tenancy()
+            .workspace_pk()
+            .ok_or(KeyPairError::NoWorkspaceInTenancy)?;
+        let current = Self::get_current(ctx).await?;
+
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(KEY_PAIR_DEACTIVATE_UNUSED, &[&workspace_pk, &current.pk])
+            .await?;
+
+        let mut deactivated = Vec::with_capacity(rows.len());
+        for row in rows {
+            let pk: KeyPairPk = row.try_get("pk")?;
+            let _history_event = HistoryEvent::new(
+                ctx,
+                "key_pair.deactivate".to_owned(),
+                "Key Pair deactivated".to_owned(),
+                &serde_json::json![{ "key_pair_pk": pk }],
+            )
+            .await?;
+            deactivated.push(pk);
+        }
+
+        Ok(deactivated)
+    }
 
     pub async fn workspace(&self, ctx: &DalContext) -> KeyPairResult<Workspace> {
         Workspace::get_by_pk(ctx, &self.workspace_pk)
@@ -221,6 +305,7 @@ struct KeyPairRow {
     #[serde(with = "base64_bytes_serde")]
     secret_key_crypted: Vec<u8>,
     created_lamport_clock: u64,
+    deactivated_at: Option<DateTime<Utc>>,
     #[serde(flatten)]
     timestamp: Timestamp,
 }
@@ -245,11 +330,18 @@ impl KeyPairRow {
             public_key: self.public_key,
             secret_key,
             created_lamport_clock: self.created_lamport_clock,
+            deactivated_at: self.deactivated_at,
             timestamp: self.timestamp,
         })
     }
 }
 
+impl WsEvent {
+    pub async fn key_pair_rotated(ctx: &DalContext, key_pair_pk: KeyPairPk) -> WsEventResult<Self> {
+        WsEvent::new(ctx, WsPayload::KeyPairRotated(key_pair_pk)).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use sodiumoxide::crypto::sealedbox;
@@ -273,6 +365,7 @@ mod tests {
             secret_key_key_hash: *secret_key_key_hash,
             secret_key_crypted,
             created_lamport_clock: 0,
+            deactivated_at: None,
             timestamp: Timestamp::now(),
         }
     }
@@ -18,6 +18,7 @@ pub use action_prototype::{
     ActionPrototypeView,
 };
 pub use actor_view::ActorView;
+pub use api_token::{ApiToken, ApiTokenError, ApiTokenPk, ApiTokenResult};
 pub use attribute::value::view::AttributeView;
 pub use attribute::{
     context::{
@@ -32,6 +33,10 @@ pub use attribute::{
         AttributePrototype, AttributePrototypeError, AttributePrototypeId, AttributePrototypeResult,
     },
     value::{
+        bulk_update::{
+            BulkAttributeUpdate, BulkAttributeUpdateEntry, BulkAttributeUpdateError,
+            BulkAttributeUpdateResult, BulkAttributeValue,
+        },
         AttributeValue, AttributeValueError, AttributeValueId, AttributeValuePayload,
         AttributeValueResult,
     },
@@ -62,7 +67,10 @@ pub use func::{
     binding::{FuncBinding, FuncBindingError, FuncBindingId},
     Func, FuncError, FuncId, FuncResult,
 };
-pub use history_event::{HistoryActor, HistoryEvent, HistoryEventError};
+pub use history_event::{
+    HistoryActor, HistoryEvent, HistoryEventError, HistoryEventFilter, HistoryEventPage,
+    HistoryEventPk,
+};
 pub use index_map::IndexMap;
 pub use job::definition::DependentValuesUpdate;
 pub use job::processor::{JobQueueProcessor, NatsProcessor};
@@ -94,8 +102,9 @@ pub use schema::variant::root_prop::RootPropChild;
 pub use schema::variant::SchemaVariantError;
 pub use schema::{Schema, SchemaError, SchemaId, SchemaPk, SchemaVariant, SchemaVariantId};
 pub use secret::{
-    DecryptedSecret, EncryptedSecret, Secret, SecretAlgorithm, SecretError, SecretId, SecretPk,
-    SecretResult, SecretVersion,
+    DecryptedSecret, EncryptedSecret, Secret, SecretAlgorithm, SecretBackendKind, SecretError,
+    SecretId, SecretKeyHashUsage, SecretKeyRotationBatch, SecretPk, SecretReference, SecretResult,
+    SecretUsage, SecretVersion,
 };
 use si_data_nats::{NatsClient, NatsError};
 use si_data_pg::{PgError, PgPool, PgPoolError};
@@ -107,7 +116,7 @@ pub use status::{
 use telemetry::prelude::*;
 pub use tenancy::{Tenancy, TenancyError};
 pub use timestamp::{Timestamp, TimestampError};
-pub use user::{User, UserClaim, UserError, UserPk, UserResult};
+pub use user::{User, UserClaim, UserError, UserPk, UserResult, WorkspaceMember, WorkspaceRole};
 pub use validation::prototype::{
     context::ValidationPrototypeContext, ValidationPrototype, ValidationPrototypeError,
     ValidationPrototypeId,
@@ -117,14 +126,19 @@ pub use validation::resolver::{
 };
 use veritech_client::EncryptionKey;
 pub use visibility::{Visibility, VisibilityError};
+pub use webhook::{
+    WebhookClient, WebhookDelivery, WebhookDeliveryPk, WebhookDeliveryStatus, WebhookEndpoint,
+    WebhookEndpointPk, WebhookError, WebhookEventKind, WebhookResult,
+};
 pub use workspace::{Workspace, WorkspaceError, WorkspacePk, WorkspaceResult, WorkspaceSignup};
-pub use ws_event::{WsEvent, WsEventError, WsEventResult, WsPayload};
+pub use ws_event::{ResyncRequiredPayload, WsEvent, WsEventError, WsEventResult, WsPayload};
 
 use crate::builtins::SelectedTestBuiltinSchemas;
 
 pub mod action;
 pub mod action_prototype;
 pub mod actor_view;
+pub mod api_token;
 pub mod attribute;
 pub mod builtins;
 pub mod change_set;
@@ -170,6 +184,7 @@ pub mod timestamp;
 pub mod user;
 pub mod validation;
 pub mod visibility;
+pub mod webhook;
 pub mod workspace;
 pub mod ws_event;
 
@@ -0,0 +1,3 @@
+ALTER TABLE key_pairs ADD COLUMN deactivated_at timestamp with time zone;
+CREATE INDEX ON key_pairs (workspace_pk, deactivated_at NULLS FIRST);
+CREATE INDEX ON encrypted_secrets (key_pair_pk);
@@ -0,0 +1,18 @@
+ALTER TABLE encrypted_secrets ADD COLUMN backend text NOT NULL DEFAULT 'database';
+
+CREATE OR REPLACE VIEW secrets AS
+SELECT pk,
+       id,
+       tenancy_workspace_pk,
+       visibility_change_set_pk,
+       visibility_deleted_at,
+       key_pair_pk,
+       created_at,
+       created_by,
+       updated_at,
+       updated_by,
+       name,
+       definition,
+       description,
+       backend
+FROM encrypted_secrets;
@@ -0,0 +1,66 @@
+CREATE TABLE api_tokens
+(
+    pk                          ident primary key default ident_create_v1(),
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    user_pk                     ident                    NOT NULL,
+    workspace_pk                ident                    NOT NULL,
+    name                        text                     NOT NULL,
+    scope                       text                     NOT NULL,
+    token_hash                  text                     NOT NULL,
+    expires_at                  timestamp with time zone,
+    last_used_at                timestamp with time zone,
+    revoked_at                  timestamp with time zone
+);
+CREATE UNIQUE INDEX ON api_tokens (pk);
+CREATE INDEX ON api_tokens (user_pk, workspace_pk);
+
+CREATE OR REPLACE FUNCTION api_token_create_v1(
+    this_pk ident,
+    this_user_pk ident,
+    this_workspace_pk ident,
+    this_name text,
+    this_scope text,
+    this_token_hash text,
+    this_expires_at timestamp with time zone,
+    OUT object json) AS
+$$
+DECLARE
+    this_new_row           api_tokens%ROWTYPE;
+BEGIN
+    INSERT INTO api_tokens (pk, user_pk, workspace_pk, name, scope, token_hash, expires_at)
+    VALUES (this_pk, this_user_pk, this_workspace_pk, this_name, this_scope, this_token_hash,
+            this_expires_at)
+    RETURNING * INTO this_new_row;
+
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION api_token_revoke_v1(
+    this_pk ident,
+    OUT object json) AS
+$$
+DECLARE
+    this_updated_row       api_tokens%ROWTYPE;
+BEGIN
+    UPDATE api_tokens
+    SET revoked_at = COALESCE(revoked_at, CLOCK_TIMESTAMP()),
+        updated_at = CLOCK_TIMESTAMP()
+    WHERE pk = this_pk
+    RETURNING * INTO this_updated_row;
+
+    object := row_to_json(this_updated_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION api_token_touch_v1(
+    this_pk ident
+    ) RETURNS void AS
+$$
+BEGIN
+    UPDATE api_tokens
+    SET last_used_at = CLOCK_TIMESTAMP()
+    WHERE pk = this_pk;
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
@@ -0,0 +1,39 @@
+ALTER TABLE history_events
+    ADD COLUMN visibility_change_set_pk ident,
+    ADD COLUMN entity_id                text;
+
+UPDATE history_events
+SET visibility_change_set_pk = data -> 'visibility' ->> 'visibility_change_set_pk',
+    entity_id                = data ->> 'id'
+WHERE data ? 'visibility'
+   OR data ? 'id';
+
+CREATE INDEX ON history_events (tenancy_workspace_pk, created_at DESC, pk DESC);
+CREATE INDEX ON history_events (tenancy_workspace_pk, label text_pattern_ops);
+CREATE INDEX ON history_events (tenancy_workspace_pk, visibility_change_set_pk);
+CREATE INDEX ON history_events (tenancy_workspace_pk, entity_id);
+
+CREATE OR REPLACE FUNCTION history_event_create_v2(this_label text,
+                                                   this_actor jsonb,
+                                                   this_message text,
+                                                   this_data jsonb,
+                                                   this_tenancy jsonb,
+                                                   this_visibility_change_set_pk ident,
+                                                   this_entity_id text,
+                                                   OUT object json) AS
+$$
+DECLARE
+    this_tenancy_record tenancy_record_v1;
+    this_new_row        history_events%ROWTYPE;
+BEGIN
+    SELECT * FROM tenancy_json_to_columns_v1(this_tenancy) INTO this_tenancy_record;
+
+    INSERT INTO history_events (label, actor, message, data, tenancy_workspace_pk,
+                                visibility_change_set_pk, entity_id)
+    VALUES (this_label, this_actor, this_message, this_data, this_tenancy_record.tenancy_workspace_pk,
+            this_visibility_change_set_pk, this_entity_id)
+    RETURNING * INTO this_new_row;
+
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
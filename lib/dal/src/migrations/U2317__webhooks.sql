@@ -0,0 +1,163 @@
+CREATE TABLE webhook_endpoints
+(
+    pk                          ident primary key default ident_create_v1(),
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    workspace_pk                ident                    NOT NULL,
+    url                         text                     NOT NULL,
+    event_kinds                 jsonb                    NOT NULL,
+    enabled                     bool                     NOT NULL DEFAULT TRUE,
+    secret_crypted              text                     NOT NULL,
+    secret_nonce                text                     NOT NULL,
+    secret_key_hash             text                     NOT NULL
+);
+CREATE INDEX ON webhook_endpoints (workspace_pk);
+
+CREATE TABLE webhook_deliveries
+(
+    pk                          ident primary key default ident_create_v1(),
+    created_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    endpoint_pk                 ident                    NOT NULL REFERENCES webhook_endpoints (pk) ON DELETE CASCADE,
+    event_kind                  text                     NOT NULL,
+    payload                     jsonb                    NOT NULL,
+    status                      text                     NOT NULL DEFAULT 'pending',
+    attempts                    integer                  NOT NULL DEFAULT 0,
+    response_status             integer,
+    last_error                  text,
+    delivered_at                timestamp with time zone
+);
+CREATE INDEX ON webhook_deliveries (endpoint_pk, created_at DESC);
+
+-- Whether the qualifications of a component were failing when last checked, so that webhooks are
+-- only sent when they start failing
+CREATE TABLE webhook_qualification_states
+(
+    workspace_pk                ident                    NOT NULL,
+    change_set_pk               ident                    NOT NULL,
+    component_id                ident                    NOT NULL,
+    failing                     bool                     NOT NULL,
+    updated_at                  timestamp with time zone NOT NULL DEFAULT CLOCK_TIMESTAMP(),
+    PRIMARY KEY (workspace_pk, change_set_pk, component_id)
+);
+
+CREATE OR REPLACE FUNCTION webhook_endpoint_create_v1(
+    this_workspace_pk ident,
+    this_url text,
+    this_event_kinds jsonb,
+    this_secret_crypted text,
+    this_secret_nonce text,
+    this_secret_key_hash text,
+    OUT object json) AS
+$$
+DECLARE
+    this_new_row           webhook_endpoints%ROWTYPE;
+BEGIN
+    INSERT INTO webhook_endpoints (workspace_pk, url, event_kinds, secret_crypted, secret_nonce,
+                                   secret_key_hash)
+    VALUES (this_workspace_pk, this_url, this_event_kinds, this_secret_crypted, this_secret_nonce,
+            this_secret_key_hash)
+    RETURNING * INTO this_new_row;
+
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION webhook_endpoint_update_v1(
+    this_pk ident,
+    this_event_kinds jsonb,
+    this_enabled bool,
+    OUT object json) AS
+$$
+DECLARE
+    this_updated_row       webhook_endpoints%ROWTYPE;
+BEGIN
+    UPDATE webhook_endpoints
+    SET event_kinds = this_event_kinds,
+        enabled     = this_enabled,
+        updated_at  = CLOCK_TIMESTAMP()
+    WHERE pk = this_pk
+    RETURNING * INTO this_updated_row;
+
+    object := row_to_json(this_updated_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION webhook_endpoint_delete_v1(
+    this_pk ident
+    ) RETURNS void AS
+$$
+BEGIN
+    DELETE FROM webhook_endpoints WHERE pk = this_pk;
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION webhook_delivery_create_v1(
+    this_endpoint_pk ident,
+    this_event_kind text,
+    this_payload jsonb,
+    OUT object json) AS
+$$
+DECLARE
+    this_new_row           webhook_deliveries%ROWTYPE;
+BEGIN
+    INSERT INTO webhook_deliveries (endpoint_pk, event_kind, payload)
+    VALUES (this_endpoint_pk, this_event_kind, this_payload)
+    RETURNING * INTO this_new_row;
+
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+CREATE OR REPLACE FUNCTION webhook_delivery_record_attempt_v1(
+    this_pk ident,
+    this_status text,
+    this_response_status integer,
+    this_last_error text,
+    OUT object json) AS
+$$
+DECLARE
+    this_updated_row       webhook_deliveries%ROWTYPE;
+BEGIN
+    UPDATE webhook_deliveries
+    SET status          = this_status,
+        attempts        = attempts + 1,
+        response_status = this_response_status,
+        last_error      = this_last_error,
+        delivered_at    = CASE WHEN this_status = 'succeeded' THEN CLOCK_TIMESTAMP() END,
+        updated_at      = CLOCK_TIMESTAMP()
+    WHERE pk = this_pk
+    RETURNING * INTO this_updated_row;
+
+    object := row_to_json(this_updated_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
+
+-- Records whether the qualifications of a component are failing, returning whether they were not
+-- failing before
+CREATE OR REPLACE FUNCTION webhook_qualification_state_set_v1(
+    this_workspace_pk ident,
+    this_change_set_pk ident,
+    this_component_id ident,
+    this_failing bool,
+    OUT started_failing bool) AS
+$$
+DECLARE
+    was_failing bool;
+BEGIN
+    SELECT failing
+    INTO was_failing
+    FROM webhook_qualification_states
+    WHERE workspace_pk = this_workspace_pk
+      AND change_set_pk = this_change_set_pk
+      AND component_id = this_component_id
+        FOR UPDATE;
+
+    INSERT INTO webhook_qualification_states (workspace_pk, change_set_pk, component_id, failing)
+    VALUES (this_workspace_pk, this_change_set_pk, this_component_id, this_failing)
+    ON CONFLICT (workspace_pk, change_set_pk, component_id)
+        DO UPDATE SET failing = this_failing, updated_at = CLOCK_TIMESTAMP();
+
+    started_failing := this_failing AND NOT COALESCE(was_failing, FALSE);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
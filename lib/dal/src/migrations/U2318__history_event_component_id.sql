@@ -0,0 +1,54 @@
+ALTER TABLE history_events
+    ADD COLUMN component_id ident;
+
+UPDATE history_events
+SET component_id = entity_id
+WHERE label LIKE 'component.%';
+
+UPDATE history_events
+SET component_id = NULLIF(COALESCE(history_events.data ->> 'attribute_context_component_id',
+                                   (SELECT attribute_values.attribute_context_component_id
+                                    FROM attribute_values
+                                    WHERE attribute_values.id = history_events.entity_id
+                                    LIMIT 1)), ident_nil_v1())
+WHERE label LIKE 'attribute_value.%';
+
+CREATE INDEX ON history_events (tenancy_workspace_pk, component_id);
+
+CREATE OR REPLACE FUNCTION history_event_create_v2(this_label text,
+                                                   this_actor jsonb,
+                                                   this_message text,
+                                                   this_data jsonb,
+                                                   this_tenancy jsonb,
+                                                   this_visibility_change_set_pk ident,
+                                                   this_entity_id text,
+                                                   OUT object json) AS
+$$
+DECLARE
+    this_tenancy_record tenancy_record_v1;
+    this_component_id   ident;
+    this_new_row        history_events%ROWTYPE;
+BEGIN
+    SELECT * FROM tenancy_json_to_columns_v1(this_tenancy) INTO this_tenancy_record;
+
+    -- Events about attribute values are about the component the value belongs to, if any. Hard
+    -- deleted values only live on in the event's data.
+    IF this_label LIKE 'component.%' THEN
+        this_component_id := this_entity_id;
+    ELSIF this_label LIKE 'attribute_value.%' THEN
+        this_component_id := NULLIF(COALESCE(this_data ->> 'attribute_context_component_id',
+                                             (SELECT attribute_values.attribute_context_component_id
+                                              FROM attribute_values
+                                              WHERE attribute_values.id = this_entity_id
+                                              LIMIT 1)), ident_nil_v1());
+    END IF;
+
+    INSERT INTO history_events (label, actor, message, data, tenancy_workspace_pk,
+                                visibility_change_set_pk, entity_id, component_id)
+    VALUES (this_label, this_actor, this_message, this_data, this_tenancy_record.tenancy_workspace_pk,
+            this_visibility_change_set_pk, this_entity_id, this_component_id)
+    RETURNING * INTO this_new_row;
+
+    object := row_to_json(this_new_row);
+END;
+$$ LANGUAGE PLPGSQL VOLATILE;
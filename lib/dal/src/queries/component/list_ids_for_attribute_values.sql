@@ -0,0 +1,4 @@
+SELECT DISTINCT av.attribute_context_component_id AS component_id
+FROM attribute_values_v1($1, $2) AS av
+WHERE av.id = ANY ($3)
+  AND av.attribute_context_component_id != ident_nil_v1()
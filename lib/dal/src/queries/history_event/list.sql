@@ -0,0 +1,17 @@
+SELECT row_to_json(history_events.*) AS object
+FROM history_events
+WHERE history_events.tenancy_workspace_pk = $1
+  AND ($2::jsonb IS NULL OR history_events.actor = $2::jsonb)
+  AND ($3::text IS NULL OR history_events.label LIKE $3::text || '%')
+  AND ($4::ident IS NULL OR history_events.visibility_change_set_pk = $4::ident)
+  AND ($5::ident IS NULL OR history_events.component_id = $5::ident)
+  AND ($6::text IS NULL OR (history_events.entity_id = $6::text
+                            AND history_events.label LIKE 'function.%'))
+  AND ($7::timestamptz IS NULL OR history_events.created_at >= $7::timestamptz)
+  AND ($8::timestamptz IS NULL OR history_events.created_at < $8::timestamptz)
+  AND ($9::ident IS NULL OR (history_events.created_at, history_events.pk) <
+                            (SELECT cursor.created_at, cursor.pk
+                             FROM history_events AS cursor
+                             WHERE cursor.pk = $9::ident))
+ORDER BY history_events.created_at DESC, history_events.pk DESC
+LIMIT $10
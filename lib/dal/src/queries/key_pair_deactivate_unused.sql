@@ -0,0 +1,10 @@
+UPDATE key_pairs
+SET deactivated_at = CLOCK_TIMESTAMP(),
+    updated_at     = CLOCK_TIMESTAMP()
+WHERE key_pairs.workspace_pk = $1
+  AND key_pairs.pk != $2
+  AND key_pairs.deactivated_at IS NULL
+  AND NOT EXISTS(SELECT 1
+                 FROM encrypted_secrets
+                 WHERE encrypted_secrets.key_pair_pk = key_pairs.pk)
+RETURNING key_pairs.pk;
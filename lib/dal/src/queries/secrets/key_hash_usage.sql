@@ -0,0 +1,11 @@
+SELECT key_hash,
+       sum(count)::bigint AS count
+FROM (SELECT key_hash, count(*) AS count
+      FROM encrypted_secrets
+      GROUP BY key_hash
+      UNION ALL
+      SELECT secret_key_hash AS key_hash, count(*) AS count
+      FROM webhook_endpoints
+      GROUP BY secret_key_hash) AS usage
+GROUP BY key_hash
+ORDER BY key_hash
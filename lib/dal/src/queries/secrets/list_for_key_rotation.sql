@@ -0,0 +1,7 @@
+SELECT row_to_json(encrypted_secrets.*) AS object
+FROM encrypted_secrets
+WHERE key_hash != $1
+  AND ($2::ident IS NULL OR pk > $2)
+ORDER BY pk
+LIMIT $3
+FOR UPDATE SKIP LOCKED
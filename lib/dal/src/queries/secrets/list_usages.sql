@@ -0,0 +1,16 @@
+SELECT av.id                             AS attribute_value_id,
+       av.attribute_context_component_id AS component_id,
+       props.id                          AS prop_id,
+       props.path                        AS prop_path,
+       fbrv.value #>> '{}'               AS secret_id
+FROM attribute_values_v1($1, $2) AS av
+         INNER JOIN props_v1($1, $2) AS props
+                    ON props.id = av.attribute_context_prop_id
+                        AND props.widget_kind = 'secret'
+         INNER JOIN components_v1($1, $2) AS components
+                    ON components.id = av.attribute_context_component_id
+         INNER JOIN func_binding_return_values_v1($1, $2) AS fbrv
+                    ON fbrv.id = av.func_binding_return_value_id
+WHERE jsonb_typeof(fbrv.value) = 'string'
+  AND ($3::text IS NULL OR fbrv.value #>> '{}' = $3::text)
+ORDER BY secret_id, component_id, prop_path, av.id
@@ -0,0 +1,7 @@
+SELECT user_belongs_to_workspaces.role AS role
+FROM user_belongs_to_workspaces
+INNER JOIN users ON users.pk = user_belongs_to_workspaces.user_pk
+    AND users.visibility_deleted_at IS NULL
+WHERE user_belongs_to_workspaces.user_pk = $1
+  AND user_belongs_to_workspaces.workspace_pk = $2
+  AND user_belongs_to_workspaces.visibility_deleted_at IS NULL
@@ -0,0 +1,3 @@
+SELECT row_to_json(webhook_deliveries.*) AS object
+FROM webhook_deliveries
+WHERE webhook_deliveries.pk = $1
@@ -0,0 +1,3 @@
+SELECT row_to_json(webhook_endpoints.*) AS object
+FROM webhook_endpoints
+WHERE webhook_endpoints.pk = $1
@@ -0,0 +1,6 @@
+SELECT row_to_json(webhook_endpoints.*) AS object
+FROM webhook_endpoints
+WHERE webhook_endpoints.workspace_pk = $1
+  AND webhook_endpoints.enabled
+  AND webhook_endpoints.event_kinds ? $2
+ORDER BY webhook_endpoints.created_at
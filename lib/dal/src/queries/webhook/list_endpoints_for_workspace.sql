@@ -0,0 +1,4 @@
+SELECT row_to_json(webhook_endpoints.*) AS object
+FROM webhook_endpoints
+WHERE webhook_endpoints.workspace_pk = $1
+ORDER BY webhook_endpoints.created_at
@@ -1,10 +1,11 @@
+use std::collections::{hash_map::Entry, HashMap};
 use std::fmt;
 
 use base64::{engine::general_purpose, Engine};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use si_crypto::{SymmetricCryptoError, SymmetricCryptoService, SymmetricNonce};
-use si_hash::Hash;
+use si_hash::{Hash, HashParseError};
 use sodiumoxide::crypto::{
     box_::{PublicKey, SecretKey},
     sealedbox,
@@ -21,31 +22,57 @@ use crate::{
     impl_standard_model,
     key_pair::KeyPairPk,
     pk,
+    prop::PropPath,
     serde_impls::{base64_bytes_serde, nonce_serde},
     standard_model::{self, objects_from_rows, TypeHint},
-    standard_model_accessor, standard_model_accessor_ro, ActorView, DalContext, HistoryActor,
-    HistoryEvent, HistoryEventError, KeyPair, KeyPairError, StandardModel, StandardModelError,
-    Tenancy, Timestamp, TransactionsError, UserPk, Visibility,
+    standard_model_accessor, standard_model_accessor_ro, ActorView, AttributeValueId, Component,
+    ComponentError, ComponentId, DalContext, HistoryActor, HistoryEvent, HistoryEventError,
+    KeyPair, KeyPairError, PropId, PublicKey as KeyPairPublicKey, StandardModel,
+    StandardModelError, Tenancy, Timestamp, TransactionsError, UserPk, Visibility,
 };
 
+pub use veritech_client::{SecretBackendKind, SecretReference};
+
+const KEY_HASH_USAGE: &str = include_str!("queries/secrets/key_hash_usage.sql");
+const LIST_FOR_KEY_PAIR_REWRAP: &str = include_str!("queries/secrets/list_for_key_pair_rewrap.sql");
+const LIST_FOR_KEY_ROTATION: &str = include_str!("queries/secrets/list_for_key_rotation.sql");
 const LIST_SECRET_DEFINITIONS: &str = include_str!("queries/secrets/list_secret_definitions.sql");
+const LIST_USAGES: &str = include_str!("queries/secrets/list_usages.sql");
 
 /// Error type for Secrets.
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum SecretError {
+    #[error("component error: {0}")]
+    Component(#[from] Box<ComponentError>),
+    #[error("database secrets are not references, use EncryptedSecret::new instead")]
+    DatabaseBackendReference,
     #[error("error when decrypting crypted secret")]
     DecryptionFailed,
     #[error("error deserializing message: {0}")]
     DeserializeMessage(#[source] serde_json::Error),
     #[error("history event error: {0}")]
     HistoryEvent(#[from] HistoryEventError),
+    #[error("secret {0} is used by {1} attribute value(s) and cannot be deleted")]
+    InUse(SecretId, usize),
+    #[error("invalid secret reference path: {0}")]
+    InvalidReferencePath(String),
+    #[error("key hash parse error: {0}")]
+    KeyHashParse(#[from] HashParseError),
     #[error("key pair error: {0}")]
     KeyPair(#[from] KeyPairError),
+    #[error("key pair is deactivated, fetch the current public key and try again: {0}")]
+    KeyPairDeactivated(KeyPairPk),
     #[error("key pair not found for secret")]
     KeyPairNotFound,
+    #[error("no workspace in tenancy")]
+    NoWorkspaceInTenancy,
+    #[error("secret not found: {0}")]
+    NotFound(SecretId),
     #[error("pg error: {0}")]
     Pg(#[from] PgError),
+    #[error("error serializing message: {0}")]
+    SerializeMessage(#[source] serde_json::Error),
     #[error("standard model error: {0}")]
     StandardModelError(#[from] StandardModelError),
     #[error("symmetric crypto error: {0}")]
@@ -72,6 +99,8 @@ pub struct Secret {
     key_pair_pk: KeyPairPk,
     definition: String,
     description: Option<String>,
+    #[serde(default)]
+    backend: SecretBackendKind,
     #[serde(flatten)]
     tenancy: Tenancy,
     #[serde(flatten)]
@@ -127,10 +156,125 @@ impl Secret {
     // Once created, these object fields are to be considered immutable
     standard_model_accessor_ro!(definition, String);
     standard_model_accessor_ro!(description, Option<String>);
+    standard_model_accessor_ro!(backend, SecretBackendKind);
 
     pub async fn key_pair(&self, ctx: &DalContext) -> SecretResult<KeyPair> {
         Ok(KeyPair::get_by_pk(ctx, self.key_pair_pk).await?)
     }
+
+    /// Returns the [`Component`] attribute values that reference this [`Secret`].
+    pub async fn usages(&self, ctx: &DalContext) -> SecretResult<Vec<SecretUsage>> {
+        SecretUsage::list(ctx, Some(self.id)).await
+    }
+
+    /// Deletes the [`Secret`] (through its underlying [`EncryptedSecret`]), unless it is still
+    /// referenced by a [`Component`].
+    pub async fn delete(self, ctx: &DalContext) -> SecretResult<()> {
+        let usages = self.usages(ctx).await?;
+        if !usages.is_empty() {
+            return Err(SecretError::InUse(self.id, usages.len()));
+        }
+
+        let mut encrypted_secret = EncryptedSecret::get_by_id(ctx, &self.id)
+            .await?
+            .ok_or(SecretError::NotFound(self.id))?;
+        encrypted_secret.delete_by_id(ctx).await?;
+
+        Ok(())
+    }
+}
+
+/// A [`Component`] attribute value whose [`Prop`](crate::Prop) uses the secret widget and
+/// references a [`Secret`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretUsage {
+    pub secret_id: SecretId,
+    pub attribute_value_id: AttributeValueId,
+    pub component_id: ComponentId,
+    pub component_name: String,
+    pub prop_id: PropId,
+    pub prop_path: String,
+}
+
+impl SecretUsage {
+    /// Lists the usages of the [`Secret`] with the provided [`SecretId`], or of every
+    /// [`Secret`] if [`None`] is provided.
+    pub async fn list(ctx: &DalContext, secret_id: Option<SecretId>) -> SecretResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                LIST_USAGES,
+                &[
+                    ctx.tenancy(),
+                    ctx.visibility(),
+                    &secret_id.map(|secret_id| secret_id.to_string()),
+                ],
+            )
+            .await?;
+
+        let mut component_names: HashMap<ComponentId, String> = HashMap::new();
+        let mut usages = Vec::with_capacity(rows.len());
+        for row in rows {
+            // Values that are not a valid id cannot reference a secret
+            let raw_secret_id: String = row.try_get("secret_id")?;
+            let secret_id: SecretId = match raw_secret_id.parse() {
+                Ok(secret_id) => secret_id,
+                Err(_) => continue,
+            };
+            let component_id: ComponentId = row.try_get("component_id")?;
+            let component_name = match component_names.entry(component_id) {
+                Entry::Occupied(entry) => entry.get().clone(),
+                Entry::Vacant(entry) => entry
+                    .insert(
+                        Component::find_name(ctx, component_id)
+                            .await
+                            .map_err(Box::new)?,
+                    )
+                    .clone(),
+            };
+            let prop_path: String = row.try_get("prop_path")?;
+
+            usages.push(Self {
+                secret_id,
+                attribute_value_id: row.try_get("attribute_value_id")?,
+                component_id,
+                component_name,
+                prop_id: row.try_get("prop_id")?,
+                prop_path: format!("/{}", PropPath::from(&prop_path).with_replaced_sep("/")),
+            });
+        }
+
+        Ok(usages)
+    }
+}
+
+/// The number of [`EncryptedSecrets`](EncryptedSecret) and
+/// [`WebhookEndpoint`](crate::WebhookEndpoint) secrets encrypted with a given symmetric key.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretKeyHashUsage {
+    pub key_hash: Hash,
+    pub count: i64,
+    /// Whether this is the key used for all new encryption.
+    pub active: bool,
+    /// Whether the key is loaded and can therefore decrypt these secrets.
+    pub loaded: bool,
+}
+
+/// The outcome of [`EncryptedSecret::reencrypt_batch()`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretKeyRotationBatch {
+    /// How many secrets were re-encrypted with the active key.
+    pub rotated: usize,
+    /// The secrets that could not be re-encrypted.
+    pub failed: Vec<SecretPk>,
+    /// The last secret looked at, to be passed to the next batch. [`None`] once there is nothing
+    /// left to rotate.
+    pub last_pk: Option<SecretPk>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -140,6 +284,7 @@ pub struct SecretView {
     pub name: String,
     pub definition: String,
     pub description: Option<String>,
+    pub backend: SecretBackendKind,
     pub created_info: HistoryEventMetadata,
     pub updated_info: Option<HistoryEventMetadata>,
 }
@@ -183,6 +328,7 @@ impl SecretView {
             name: secret.name,
             definition: secret.definition,
             description: secret.description,
+            backend: secret.backend,
             created_info,
             updated_info,
         })
@@ -211,6 +357,8 @@ pub struct EncryptedSecret {
     crypted: Vec<u8>,
     version: SecretVersion,
     algorithm: SecretAlgorithm,
+    #[serde(default)]
+    backend: SecretBackendKind,
     #[serde(flatten)]
     tenancy: Tenancy,
     #[serde(flatten)]
@@ -231,6 +379,7 @@ impl fmt::Debug for EncryptedSecret {
             .field("description", &self.description)
             .field("version", &self.version)
             .field("algorithm", &self.algorithm)
+            .field("backend", &self.backend)
             .field("key_hash", &self.key_hash)
             .field("tenancy", &self.tenancy)
             .field("timestamp", &self.timestamp)
@@ -263,6 +412,12 @@ impl EncryptedSecret {
     ) -> SecretResult<Secret> {
         let name = name.as_ref();
 
+        // Clients seal secrets with the public key they fetched last, which may have been
+        // rotated out since
+        if KeyPair::get_by_pk(ctx, key_pair_pk).await?.is_deactivated() {
+            return Err(SecretError::KeyPairDeactivated(key_pair_pk));
+        }
+
         let maybe_actor = match ctx.history_actor() {
             HistoryActor::SystemInit => None,
             HistoryActor::User(user_pk) => Some(user_pk),
@@ -304,12 +459,89 @@ impl EncryptedSecret {
     standard_model_accessor_ro!(definition, String);
     standard_model_accessor_ro!(version, SecretVersion);
     standard_model_accessor_ro!(algorithm, SecretAlgorithm);
+    standard_model_accessor_ro!(backend, SecretBackendKind);
+
+    /// Creates a new secret whose message lives in an external backend and returns a
+    /// corresponding [`Secret`] representation. Only the [`SecretReference`] is stored, sealed
+    /// server-side with the current [`KeyPair`] like any other secret message. Cyclone resolves
+    /// the reference when the secret is handed to a function.
+    ///
+    /// The path of the reference is relative to the prefix of the secret's workspace in the
+    /// backend (`<workspace_pk>/<path>`), which cyclone enforces when resolving it. It must be made
+    /// of plain, non-empty segments, so that it can't point outside of that prefix.
+    pub async fn new_reference(
+        ctx: &DalContext,
+        name: impl AsRef<str>,
+        definition: String,
+        description: Option<String>,
+        backend: SecretBackendKind,
+        reference: &SecretReference,
+    ) -> SecretResult<Secret> {
+        if backend == SecretBackendKind::Database {
+            return Err(SecretError::DatabaseBackendReference);
+        }
+        if reference.path.is_empty()
+            || reference.path.split('/').any(|segment| {
+                segment.is_empty()
+                    || segment == "."
+                    || segment == ".."
+                    || segment.contains(['\\', '?', '#'])
+            })
+        {
+            return Err(SecretError::InvalidReferencePath(reference.path.clone()));
+        }
+
+        let public_key = KeyPairPublicKey::get_current(ctx).await?;
+        let crypted = sealedbox::seal(
+            &serde_json::to_vec(reference).map_err(SecretError::SerializeMessage)?,
+            public_key.public_key(),
+        );
+
+        let mut secret = Self::new(
+            ctx,
+            name,
+            definition,
+            description,
+            &crypted,
+            *public_key.pk(),
+            SecretVersion::default(),
+            SecretAlgorithm::default(),
+        )
+        .await?;
+
+        ctx.txns()
+            .await?
+            .pg()
+            .execute(
+                "UPDATE encrypted_secrets SET backend = $2 WHERE pk = $1",
+                &[&secret.pk, &backend.as_ref()],
+            )
+            .await?;
+        secret.backend = backend;
+
+        Ok(secret)
+    }
 
     /// Decrypts the encrypted secret with its associated [`KeyPair`] and returns a
-    /// [`DecryptedSecret`].
+    /// [`DecryptedSecret`]. The message of a secret stored in an external backend is its
+    /// [`SecretReference`], which only cyclone resolves. Every call writes a [`HistoryEvent`].
     pub async fn decrypt(self, ctx: &DalContext) -> SecretResult<DecryptedSecret> {
         let key_pair = self.key_pair(ctx).await?;
 
+        // Every decryption is recorded so that it is known which secrets were handed to function
+        // executions and by whom
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "secret.decrypt".to_owned(),
+            "Secret decrypted".to_owned(),
+            &serde_json::json![{
+                "pk": self.pk,
+                "id": self.id,
+                "backend": self.backend,
+            }],
+        )
+        .await?;
+
         self.into_decrypted(
             key_pair.public_key(),
             key_pair.secret_key(),
@@ -348,6 +580,164 @@ impl EncryptedSecret {
     pub async fn key_pair(&self, ctx: &DalContext) -> SecretResult<KeyPair> {
         Ok(KeyPair::get_by_pk(ctx, self.key_pair_pk).await?)
     }
+
+    /// Re-wraps every [`EncryptedSecret`](Self) of the workspace in the [`Tenancy`] that is not
+    /// sealed for the provided [`KeyPair`], across all change sets. Each secret is opened with
+    /// the private key of its current [`KeyPair`] and sealed again for the provided one, so the
+    /// secret message never leaves the server. Returns how many secrets were re-wrapped.
+    pub async fn rewrap_for_key_pair(ctx: &DalContext, key_pair: &KeyPair) -> SecretResult<usize> {
+        let workspace_pk = ctx
+            .tenancy()
+            .workspace_pk()
+            .ok_or(SecretError::NoWorkspaceInTenancy)?;
+        let symmetric_crypto_service = ctx.symmetric_crypto_service();
+
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(LIST_FOR_KEY_PAIR_REWRAP, &[&workspace_pk, &key_pair.pk()])
+            .await?;
+        let encrypted_secrets: Vec<Self> = objects_from_rows(rows)?;
+
+        let mut previous_key_pairs: HashMap<KeyPairPk, KeyPair> = HashMap::new();
+        let mut rewrapped = 0;
+        for encrypted_secret in encrypted_secrets {
+            let previous_key_pair = match previous_key_pairs.entry(encrypted_secret.key_pair_pk) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(encrypted_secret.key_pair(ctx).await?),
+            };
+
+            let secret_pk = encrypted_secret.pk;
+            let decrypted = encrypted_secret.into_decrypted(
+                previous_key_pair.public_key(),
+                previous_key_pair.secret_key(),
+                symmetric_crypto_service,
+            )?;
+            let crypted = sealedbox::seal(
+                &serde_json::to_vec(&decrypted.message).map_err(SecretError::SerializeMessage)?,
+                key_pair.public_key(),
+            );
+            let (double_crypted, nonce, key_hash) = symmetric_crypto_service.encrypt(&crypted);
+
+            ctx.txns()
+                .await?
+                .pg()
+                .execute(
+                    "UPDATE encrypted_secrets SET crypted = $2, nonce = $3, key_hash = $4, key_pair_pk = $5 WHERE pk = $1",
+                    &[
+                        &secret_pk,
+                        &base64_encode_bytes(double_crypted.as_slice()),
+                        &base64_encode_bytes(nonce.as_ref()),
+                        &key_hash.to_string(),
+                        &key_pair.pk(),
+                    ],
+                )
+                .await?;
+            rewrapped += 1;
+        }
+
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "encrypted_secret.rewrap".to_owned(),
+            "Encrypted Secrets re-wrapped".to_owned(),
+            &serde_json::json![{
+                "key_pair_pk": key_pair.pk(),
+                "count": rewrapped,
+            }],
+        )
+        .await?;
+
+        Ok(rewrapped)
+    }
+
+    /// Returns how many [`EncryptedSecrets`](Self) and [`WebhookEndpoint`](crate::WebhookEndpoint)
+    /// secrets are encrypted with each symmetric key, across all workspaces and change sets. A key
+    /// can be retired once it is no longer referenced.
+    pub async fn key_hash_usage(ctx: &DalContext) -> SecretResult<Vec<SecretKeyHashUsage>> {
+        let symmetric_crypto_service = ctx.symmetric_crypto_service();
+        let rows = ctx.txns().await?.pg().query(KEY_HASH_USAGE, &[]).await?;
+
+        let mut usage = Vec::with_capacity(rows.len());
+        for row in rows {
+            let key_hash: String = row.try_get("key_hash")?;
+            let key_hash: Hash = key_hash.parse()?;
+            usage.push(SecretKeyHashUsage {
+                active: key_hash == *symmetric_crypto_service.active_key_hash(),
+                loaded: symmetric_crypto_service.has_key(&key_hash),
+                count: row.try_get("count")?,
+                key_hash,
+            });
+        }
+
+        Ok(usage)
+    }
+
+    /// Re-encrypts up to `batch_size` [`EncryptedSecrets`](Self) that are not encrypted with the
+    /// active symmetric key, across all workspaces and change sets. Only the outer symmetric
+    /// encryption layer changes: the [`KeyPair`] sealed payload is left untouched.
+    ///
+    /// Secrets are walked in primary key order starting after `after`, so that secrets which
+    /// cannot be re-encrypted (i.e. their key is not loaded) do not stall the rotation.
+    pub async fn reencrypt_batch(
+        ctx: &DalContext,
+        after: Option<SecretPk>,
+        batch_size: i64,
+    ) -> SecretResult<SecretKeyRotationBatch> {
+        let symmetric_crypto_service = ctx.symmetric_crypto_service();
+        let active_key_hash = symmetric_crypto_service.active_key_hash().to_string();
+
+        let txns = ctx.txns().await?;
+        let rows = txns
+            .pg()
+            .query(
+                LIST_FOR_KEY_ROTATION,
+                &[&active_key_hash, &after, &batch_size],
+            )
+            .await?;
+        let encrypted_secrets: Vec<Self> = objects_from_rows(rows)?;
+
+        let mut batch = SecretKeyRotationBatch {
+            last_pk: encrypted_secrets
+                .last()
+                .map(|encrypted_secret| encrypted_secret.pk),
+            ..Default::default()
+        };
+        for encrypted_secret in encrypted_secrets {
+            let (crypted, nonce, key_hash) = match symmetric_crypto_service.reencrypt(
+                &encrypted_secret.crypted,
+                &encrypted_secret.nonce,
+                &encrypted_secret.key_hash,
+            ) {
+                Ok(reencrypted) => reencrypted,
+                Err(err) => {
+                    warn!(
+                        error = ?err,
+                        secret_pk = %encrypted_secret.pk,
+                        key_hash = %encrypted_secret.key_hash,
+                        "could not re-encrypt secret",
+                    );
+                    batch.failed.push(encrypted_secret.pk);
+                    continue;
+                }
+            };
+
+            txns.pg()
+                .execute(
+                    "UPDATE encrypted_secrets SET crypted = $2, nonce = $3, key_hash = $4 WHERE pk = $1",
+                    &[
+                        &encrypted_secret.pk,
+                        &base64_encode_bytes(crypted.as_slice()),
+                        &base64_encode_bytes(nonce.as_ref()),
+                        &key_hash.to_string(),
+                    ],
+                )
+                .await?;
+            batch.rotated += 1;
+        }
+
+        Ok(batch)
+    }
 }
 
 /// A secret that has been decrypted.
@@ -496,6 +886,7 @@ mod tests {
                 crypted: double_crypted,
                 version: Default::default(),
                 algorithm: Default::default(),
+                backend: Default::default(),
                 tenancy: Tenancy::new(wid),
                 timestamp: Timestamp::now(),
                 created_by: None,
@@ -240,7 +240,7 @@ macro_rules! standard_model_belongs_to {
                 ctx,
                 &Self::history_event_label(vec![stringify!($set_fn)]),
                 &Self::history_event_message(format!("set {}", stringify!($returns))),
-                &serde_json::json![{ "pk": self.pk, "belongs_to_id": &belongs_to_id }],
+                &serde_json::json![{ "pk": self.pk, "id": self.id(), "belongs_to_id": &belongs_to_id }],
             )
             .await?;
             Ok(())
@@ -303,6 +303,7 @@ macro_rules! standard_model_accessor {
                     &Self::history_event_message("updated"),
                     &serde_json::json![{
                         "pk": self.pk,
+                        "id": self.id(),
                         "field": stringify!($column),
                         "value": &value,
                     }],
@@ -339,6 +340,7 @@ macro_rules! standard_model_accessor {
                     &Self::history_event_message("updated"),
                     &serde_json::json![{
                         "pk": self.pk,
+                        "id": self.id(),
                         "field": stringify!($column),
                         "value": &value,
                     }],
@@ -375,6 +377,7 @@ macro_rules! standard_model_accessor {
                     &Self::history_event_message("updated"),
                     &serde_json::json![{
                         "pk": self.pk,
+                        "id": self.id(),
                         "field": stringify!($column),
                         "value": &value,
                     }],
@@ -410,6 +413,7 @@ macro_rules! standard_model_accessor {
                     &Self::history_event_message("updated"),
                     &serde_json::json![{
                         "pk": self.pk,
+                        "id": self.id(),
                         "field": stringify!($column),
                         "value": &value,
                     }],
@@ -445,6 +449,7 @@ macro_rules! standard_model_accessor {
                     &Self::history_event_message("updated"),
                     &serde_json::json![{
                         "pk": self.pk,
+                        "id": self.id(),
                         "field": stringify!($column),
                         "value": &value,
                     }],
@@ -611,7 +611,7 @@ pub async fn finish_create_from_row<Object: Send + Sync + DeserializeOwned + Sta
         ctx,
         Object::history_event_label(vec!["create"]),
         Object::history_event_message("created"),
-        &serde_json::json![{ "visibility": ctx.visibility() }],
+        &serde_json::json![{ "id": json.get("id"), "visibility": ctx.visibility() }],
     )
     .await?;
     let object: Object = serde_json::from_value(json)?;
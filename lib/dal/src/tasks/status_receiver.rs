@@ -17,7 +17,7 @@ use tokio::sync::broadcast;
 use crate::{
     AttributeValue, AttributeValueError, AttributeValueId, Component, ComponentId, DalContext,
     DalContextBuilder, ServicesContext, StandardModel, StandardModelError, Tenancy,
-    TransactionsError, Visibility, WsEvent,
+    TransactionsError, Visibility, WebhookDelivery, WebhookEventKind, WsEvent,
 };
 
 pub mod client;
@@ -171,9 +171,20 @@ impl StatusReceiver {
             request.payload.dependent_graph.keys().collect();
         flattened_dependent_graph.extend(request.payload.dependent_graph.values().flatten());
 
+        // Only the components with values in the graph can have had their qualifications change
+        let graph_attribute_value_ids: Vec<AttributeValueId> =
+            flattened_dependent_graph.iter().map(|id| **id).collect();
+        let qualification_attribute_values: HashMap<AttributeValueId, ComponentId> =
+            Component::qualification_attribute_values_for_values(&ctx, &graph_attribute_value_ids)
+                .await?;
+
         // Send events according to every value in the dependency graph.
         let mut seen_code_generation_components: HashSet<ComponentId> = HashSet::new();
+        let mut qualified_components: HashSet<ComponentId> = HashSet::new();
         for dependent_value in flattened_dependent_graph {
+            if let Some(component_id) = qualification_attribute_values.get(dependent_value) {
+                qualified_components.insert(*component_id);
+            }
             if code_generation_attribute_values.contains(dependent_value) {
                 let attribute_value = AttributeValue::get_by_id(&ctx, dependent_value)
                     .await?
@@ -196,6 +207,22 @@ impl StatusReceiver {
             }
         }
 
+        // Send the webhooks of the components whose qualifications started failing. This only
+        // writes to the webhook tables, which the job doesn't touch.
+        for component_id in qualified_components {
+            let failing = Component::qualifications_failing(&ctx, component_id).await?;
+            if WebhookDelivery::record_qualifications_failing(&ctx, component_id, failing).await? {
+                let event = WsEvent::checked_qualifications(&ctx, component_id).await?;
+                WebhookDelivery::enqueue_for_event(
+                    &ctx,
+                    WebhookEventKind::QualificationsFailing,
+                    &event,
+                )
+                .await?;
+            }
+        }
+        ctx.commit().await?;
+
         Ok(())
     }
 
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use si_data_nats::NatsError;
 use si_data_pg::PgError;
+use strum::{AsRefStr, Display, EnumString};
 use telemetry::prelude::*;
 use thiserror::Error;
 use tokio::task::JoinError;
@@ -11,6 +12,8 @@ use crate::{
 };
 
 const USER_GET_BY_PK: &str = include_str!("queries/user/get_by_pk.sql");
+const USER_GET_WORKSPACE_ROLE: &str = include_str!("queries/user/get_workspace_role.sql");
+const USER_LIST_FOR_WORKSPACE: &str = include_str!("queries/user/list_for_workspace.sql");
 
 #[remain::sorted]
 #[derive(Error, Debug)]
@@ -21,8 +24,12 @@ pub enum UserError {
     Join(#[from] JoinError),
     #[error(transparent)]
     JwtKey(#[from] JwtKeyError),
+    #[error("workspace {0} must keep at least one owner")]
+    LastOwner(WorkspacePk),
     #[error("nats txn error: {0}")]
     Nats(#[from] NatsError),
+    #[error("user {0} is not a member of workspace {1}")]
+    NotAWorkspaceMember(UserPk, WorkspacePk),
     #[error("user not found in tenancy: {0} {1:?}")]
     NotFoundInTenancy(UserPk, Tenancy),
     #[error("no workspace in tenancy")]
@@ -31,6 +38,8 @@ pub enum UserError {
     Pg(#[from] PgError),
     #[error("error serializing/deserializing json: {0}")]
     SerdeJson(#[from] serde_json::Error),
+    #[error("strum parse error: {0}")]
+    StrumParse(#[from] strum::ParseError),
     #[error(transparent)]
     Transactions(#[from] TransactionsError),
 }
@@ -39,6 +48,50 @@ pub type UserResult<T> = Result<T, UserError>;
 
 pk!(UserPk);
 
+/// What a [`User`] is allowed to do in a [`Workspace`](crate::Workspace). From least to most
+/// capable, the roles are viewer, editor, applier and owner, and each one can do everything the
+/// ones before it can.
+#[remain::sorted]
+#[derive(
+    AsRefStr, Deserialize, Display, EnumString, Serialize, Debug, Eq, PartialEq, Clone, Copy, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum WorkspaceRole {
+    /// Can apply change sets and run fixes, which changes real infrastructure.
+    Applier,
+    /// Can make changes to the workspace, but can't apply them.
+    Editor,
+    /// Can manage the roles of the other members of the workspace.
+    Owner,
+    /// Can only look at the workspace.
+    Viewer,
+}
+
+impl WorkspaceRole {
+    fn rank(self) -> u8 {
+        match self {
+            Self::Viewer => 0,
+            Self::Editor => 1,
+            Self::Applier => 2,
+            Self::Owner => 3,
+        }
+    }
+
+    /// Whether this role is allowed to do everything the `required` role is.
+    pub fn permits(self, required: WorkspaceRole) -> bool {
+        self.rank() >= required.rank()
+    }
+}
+
+/// A [`User`] along with their role in a [`Workspace`](crate::Workspace).
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMember {
+    pub user: User,
+    pub role: WorkspaceRole,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct User {
     pk: UserPk,
@@ -117,26 +170,137 @@ impl User {
         }
     }
 
-    pub async fn authorize(_ctx: &DalContext, _user_pk: &UserPk) -> UserResult<bool> {
-        // TODO(paulo,theo): implement capabilities through auth0
-        Ok(true)
+    /// Whether the user is allowed to act as the `required_role` in the workspace of the
+    /// context's tenancy. Users who aren't members of the workspace are never authorized.
+    pub async fn authorize(
+        ctx: &DalContext,
+        user_pk: &UserPk,
+        required_role: WorkspaceRole,
+    ) -> UserResult<bool> {
+        let workspace_pk = ctx
+            .tenancy()
+            .workspace_pk()
+            .ok_or(UserError::NoWorkspaceInTenancy)?;
+
+        Ok(Self::workspace_role(ctx, *user_pk, workspace_pk)
+            .await?
+            .map_or(false, |role| role.permits(required_role)))
+    }
+
+    /// The role of the user in a workspace, if they are a member of it.
+    pub async fn workspace_role(
+        ctx: &DalContext,
+        user_pk: UserPk,
+        workspace_pk: WorkspacePk,
+    ) -> UserResult<Option<WorkspaceRole>> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(USER_GET_WORKSPACE_ROLE, &[&user_pk, &workspace_pk])
+            .await?;
+        match row {
+            Some(row) => {
+                let role: String = row.try_get("role")?;
+                Ok(Some(role.parse()?))
+            }
+            None => Ok(None),
+        }
     }
 
+    /// Makes the user a member of a workspace with the given role. Users who already are members
+    /// keep the role they have.
     pub async fn associate_workspace(
         &self,
         ctx: &DalContext,
         workspace_pk: WorkspacePk,
+        role: WorkspaceRole,
     ) -> UserResult<()> {
         ctx.txns()
             .await?
             .pg()
             .execute(
-                "SELECT user_associate_workspace_v1($1, $2)",
-                &[&self.pk, &workspace_pk],
+                "SELECT user_associate_workspace_v2($1, $2, $3)",
+                &[&self.pk, &workspace_pk, &role.as_ref()],
             )
             .await?;
         Ok(())
     }
+
+    /// Changes the role of a member of a workspace. The last owner of a workspace can't be given
+    /// another role, so that there is always someone able to manage its members.
+    pub async fn set_workspace_role(
+        ctx: &DalContext,
+        user_pk: UserPk,
+        workspace_pk: WorkspacePk,
+        role: WorkspaceRole,
+    ) -> UserResult<()> {
+        let current_role = Self::workspace_role(ctx, user_pk, workspace_pk)
+            .await?
+            .ok_or(UserError::NotAWorkspaceMember(user_pk, workspace_pk))?;
+        if current_role == role {
+            return Ok(());
+        }
+
+        if current_role == WorkspaceRole::Owner {
+            let owners = Self::list_for_workspace(ctx, workspace_pk)
+                .await?
+                .into_iter()
+                .filter(|member| member.role == WorkspaceRole::Owner)
+                .count();
+            if owners <= 1 {
+                return Err(UserError::LastOwner(workspace_pk));
+            }
+        }
+
+        ctx.txns()
+            .await?
+            .pg()
+            .execute(
+                "SELECT user_set_workspace_role_v1($1, $2, $3)",
+                &[&user_pk, &workspace_pk, &role.as_ref()],
+            )
+            .await?;
+
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "user.workspace_role.update".to_owned(),
+            "User workspace role updated".to_owned(),
+            &serde_json::json![{
+                "user_pk": user_pk,
+                "workspace_pk": workspace_pk,
+                "before": current_role,
+                "after": role,
+            }],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lists the members of a workspace along with their roles.
+    pub async fn list_for_workspace(
+        ctx: &DalContext,
+        workspace_pk: WorkspacePk,
+    ) -> UserResult<Vec<WorkspaceMember>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(USER_LIST_FOR_WORKSPACE, &[&workspace_pk])
+            .await?;
+
+        let mut members = Vec::with_capacity(rows.len());
+        for row in rows {
+            let json: serde_json::Value = row.try_get("object")?;
+            let role: String = row.try_get("role")?;
+            members.push(WorkspaceMember {
+                user: serde_json::from_value(json)?,
+                role: role.parse()?,
+            });
+        }
+        Ok(members)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
@@ -0,0 +1,950 @@
+//! Webhooks notify services outside of SI, such as chat or CI, when something happens in a
+//! workspace.
+//!
+//! A [`WebhookEndpoint`] is a URL that is sent the [`WebhookEventKinds`](WebhookEventKind) it
+//! subscribes to. Every event sent to an endpoint is recorded as a [`WebhookDelivery`] and sent
+//! by a [`WebhookDeliveryJob`], which retries failed attempts.
+//!
+//! Each request is signed with the secret of its endpoint, which is only shown once when the
+//! endpoint is created. The `X-SI-Webhook-Signature` header holds the hex encoded HMAC-SHA256 of
+//! the `X-SI-Webhook-Timestamp` header, a `.` and the body, as computed by [`sign()`].
+//!
+//! Requests are only sent to public addresses, see [`WebhookClient`].
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use base64::{engine::general_purpose, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use si_crypto::{SymmetricCryptoError, SymmetricNonce};
+use si_data_nats::NatsError;
+use si_data_pg::PgError;
+use si_hash::Hash;
+use strum::{AsRefStr, Display, EnumString};
+use telemetry::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    job::definition::WebhookDeliveryJob,
+    pk,
+    serde_impls::{base64_bytes_serde, nonce_serde},
+    ComponentId, DalContext, HistoryEvent, HistoryEventError, Timestamp, TransactionsError,
+    WorkspacePk, WsEvent, WsPayload,
+};
+
+const WEBHOOK_ENDPOINT_GET_BY_PK: &str = include_str!("queries/webhook/get_endpoint_by_pk.sql");
+const WEBHOOK_ENDPOINT_LIST_FOR_WORKSPACE: &str =
+    include_str!("queries/webhook/list_endpoints_for_workspace.sql");
+const WEBHOOK_ENDPOINT_LIST_FOR_EVENT_KIND: &str =
+    include_str!("queries/webhook/list_endpoints_for_event_kind.sql");
+const WEBHOOK_ENDPOINT_LIST_FOR_KEY_ROTATION: &str =
+    include_str!("queries/webhook/list_endpoints_for_key_rotation.sql");
+const WEBHOOK_DELIVERY_GET_BY_PK: &str = include_str!("queries/webhook/get_delivery_by_pk.sql");
+const WEBHOOK_DELIVERY_LIST_FOR_ENDPOINT: &str =
+    include_str!("queries/webhook/list_deliveries_for_endpoint.sql");
+
+/// Every endpoint secret starts with this prefix, which makes them easy to spot.
+pub const WEBHOOK_SECRET_PREFIX: &str = "si_whsec_";
+
+pub const WEBHOOK_ID_HEADER: &str = "X-SI-Webhook-Id";
+pub const WEBHOOK_EVENT_HEADER: &str = "X-SI-Webhook-Event";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-SI-Webhook-Timestamp";
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-SI-Webhook-Signature";
+
+/// How long an endpoint has to respond before an attempt fails.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How much of a failed response body is kept in the delivery log.
+const MAX_ERROR_LEN: usize = 1024;
+
+const SECRET_LEN: usize = 32;
+
+#[remain::sorted]
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    #[error("webhook endpoint not found: {0}")]
+    EndpointNotFound(WebhookEndpointPk),
+    #[error("webhook url {0} resolves to {1}, which is not a public address")]
+    ForbiddenDestination(String, IpAddr),
+    #[error("history event error: {0}")]
+    HistoryEvent(#[from] HistoryEventError),
+    #[error("invalid webhook url {0}: {1}")]
+    InvalidUrl(String, String),
+    #[error("nats txn error: {0}")]
+    Nats(#[from] NatsError),
+    #[error("webhook delivery not found: {0}")]
+    NotFound(WebhookDeliveryPk),
+    #[error("pg error: {0}")]
+    Pg(#[from] PgError),
+    #[error("reqwest error: {0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("cannot resolve host of webhook url {0}: {1}")]
+    Resolve(String, #[source] std::io::Error),
+    #[error("error serializing/deserializing json: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("strum parse error: {0}")]
+    StrumParse(#[from] strum::ParseError),
+    #[error("symmetric crypto error: {0}")]
+    SymmetricCrypto(#[from] SymmetricCryptoError),
+    #[error(transparent)]
+    Transactions(#[from] TransactionsError),
+    #[error("webhook secret is not valid utf8")]
+    Utf8(#[from] std::string::FromUtf8Error),
+}
+
+pub type WebhookResult<T> = Result<T, WebhookError>;
+
+pk!(WebhookEndpointPk);
+pk!(WebhookDeliveryPk);
+
+/// What a [`WebhookEndpoint`] can be sent.
+#[remain::sorted]
+#[derive(
+    AsRefStr, Deserialize, Display, EnumString, Serialize, Debug, Eq, PartialEq, Clone, Copy, Hash,
+)]
+pub enum WebhookEventKind {
+    /// A change set was applied to head, sent with the `ChangeSetApplied` payload.
+    ChangeSetApplied,
+    /// A fix batch finished, sent with the `FixBatchReturn` payload.
+    FixBatchReturn,
+    /// The qualifications of a component started failing, sent with the `CheckedQualifications`
+    /// payload.
+    QualificationsFailing,
+}
+
+impl WebhookEventKind {
+    /// The kind of webhook event a [`WsEvent`] is sent as, if any. Qualifications failing isn't
+    /// known from the payload alone, see
+    /// [`WebhookDelivery::record_qualifications_failing()`].
+    pub fn for_payload(payload: &WsPayload) -> Option<Self> {
+        match payload {
+            WsPayload::ChangeSetApplied(_) => Some(Self::ChangeSetApplied),
+            WsPayload::FixBatchReturn(_) => Some(Self::FixBatchReturn),
+            _ => None,
+        }
+    }
+}
+
+#[remain::sorted]
+#[derive(
+    AsRefStr, Deserialize, Display, EnumString, Serialize, Debug, Eq, PartialEq, Clone, Copy,
+)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum WebhookDeliveryStatus {
+    /// Every attempt failed and no more will be made.
+    Failed,
+    /// Not sent yet, or an attempt failed and another one will be made.
+    Pending,
+    /// The endpoint responded with a success status.
+    Succeeded,
+}
+
+/// The outcome of [`WebhookEndpoint::reencrypt_secrets_batch()`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookKeyRotationBatch {
+    /// How many endpoint secrets were re-encrypted with the active key.
+    pub rotated: usize,
+    /// The endpoints whose secret could not be re-encrypted.
+    pub failed: Vec<WebhookEndpointPk>,
+    /// The last endpoint looked at, to be passed to the next batch. [`None`] once there is
+    /// nothing left to rotate.
+    pub last_pk: Option<WebhookEndpointPk>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEndpoint {
+    pk: WebhookEndpointPk,
+    workspace_pk: WorkspacePk,
+    url: String,
+    event_kinds: Vec<WebhookEventKind>,
+    enabled: bool,
+    #[serde(flatten)]
+    timestamp: Timestamp,
+}
+
+/// The columns of a row, which are snake cased and also hold the encrypted secret.
+#[derive(Deserialize)]
+struct WebhookEndpointRow {
+    pk: WebhookEndpointPk,
+    workspace_pk: WorkspacePk,
+    url: String,
+    event_kinds: Vec<WebhookEventKind>,
+    enabled: bool,
+    #[serde(with = "base64_bytes_serde")]
+    secret_crypted: Vec<u8>,
+    #[serde(with = "nonce_serde")]
+    secret_nonce: SymmetricNonce,
+    secret_key_hash: Hash,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl WebhookEndpointRow {
+    fn decrypt_secret(&self, ctx: &DalContext) -> WebhookResult<String> {
+        let secret = ctx.symmetric_crypto_service().decrypt(
+            &self.secret_crypted,
+            &self.secret_nonce,
+            &self.secret_key_hash,
+        )?;
+        Ok(String::from_utf8(secret)?)
+    }
+}
+
+impl From<WebhookEndpointRow> for WebhookEndpoint {
+    fn from(row: WebhookEndpointRow) -> Self {
+        Self {
+            pk: row.pk,
+            workspace_pk: row.workspace_pk,
+            url: row.url,
+            event_kinds: row.event_kinds,
+            enabled: row.enabled,
+            timestamp: Timestamp {
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            },
+        }
+    }
+}
+
+impl WebhookEndpoint {
+    pub fn pk(&self) -> WebhookEndpointPk {
+        self.pk
+    }
+
+    pub fn workspace_pk(&self) -> WorkspacePk {
+        self.workspace_pk
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn event_kinds(&self) -> &[WebhookEventKind] {
+        &self.event_kinds
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Creates an endpoint in a workspace, returning it along with the secret its requests are
+    /// signed with, which can't be retrieved again afterwards.
+    pub async fn new(
+        ctx: &DalContext,
+        workspace_pk: WorkspacePk,
+        url: impl AsRef<str>,
+        event_kinds: Vec<WebhookEventKind>,
+    ) -> WebhookResult<(Self, String)> {
+        let url = url.as_ref();
+        validate_url(url)?;
+
+        let mut secret = [0u8; SECRET_LEN];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let secret = format!("{WEBHOOK_SECRET_PREFIX}{}", hex::encode(secret));
+        let (crypted, nonce, key_hash) = ctx.symmetric_crypto_service().encrypt(secret.as_bytes());
+
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM webhook_endpoint_create_v1($1, $2, $3, $4, $5, $6)",
+                &[
+                    &workspace_pk,
+                    &url,
+                    &serde_json::to_value(&event_kinds)?,
+                    &general_purpose::STANDARD_NO_PAD.encode(crypted),
+                    &general_purpose::STANDARD_NO_PAD.encode(nonce.as_ref()),
+                    &key_hash.to_string(),
+                ],
+            )
+            .await?;
+        let json: serde_json::Value = row.try_get("object")?;
+        let object: Self = serde_json::from_value::<WebhookEndpointRow>(json)?.into();
+
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "webhook_endpoint.create".to_owned(),
+            "Webhook endpoint created".to_owned(),
+            &serde_json::json![{
+                "pk": object.pk,
+                "url": object.url,
+                "event_kinds": object.event_kinds,
+            }],
+        )
+        .await?;
+
+        Ok((object, secret))
+    }
+
+    pub async fn get_by_pk(ctx: &DalContext, pk: WebhookEndpointPk) -> WebhookResult<Option<Self>> {
+        Ok(Self::get_row_by_pk(ctx, pk).await?.map(Into::into))
+    }
+
+    /// Lists the endpoints of a workspace, including disabled ones.
+    pub async fn list_for_workspace(
+        ctx: &DalContext,
+        workspace_pk: WorkspacePk,
+    ) -> WebhookResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(WEBHOOK_ENDPOINT_LIST_FOR_WORKSPACE, &[&workspace_pk])
+            .await?;
+
+        let mut endpoints = Vec::with_capacity(rows.len());
+        for row in rows {
+            let json: serde_json::Value = row.try_get("object")?;
+            endpoints.push(serde_json::from_value::<WebhookEndpointRow>(json)?.into());
+        }
+        Ok(endpoints)
+    }
+
+    /// Changes which events the endpoint is sent and whether it is sent any at all.
+    pub async fn update(
+        &mut self,
+        ctx: &DalContext,
+        event_kinds: Vec<WebhookEventKind>,
+        enabled: bool,
+    ) -> WebhookResult<()> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM webhook_endpoint_update_v1($1, $2, $3)",
+                &[&self.pk, &serde_json::to_value(&event_kinds)?, &enabled],
+            )
+            .await?;
+        let json: serde_json::Value = row.try_get("object")?;
+        *self = serde_json::from_value::<WebhookEndpointRow>(json)?.into();
+
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "webhook_endpoint.update".to_owned(),
+            "Webhook endpoint updated".to_owned(),
+            &serde_json::json![{
+                "pk": self.pk,
+                "event_kinds": self.event_kinds,
+                "enabled": self.enabled,
+            }],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the endpoint along with its delivery log.
+    pub async fn delete(self, ctx: &DalContext) -> WebhookResult<()> {
+        ctx.txns()
+            .await?
+            .pg()
+            .execute("SELECT webhook_endpoint_delete_v1($1)", &[&self.pk])
+            .await?;
+
+        let _history_event = HistoryEvent::new(
+            ctx,
+            "webhook_endpoint.delete".to_owned(),
+            "Webhook endpoint deleted".to_owned(),
+            &serde_json::json![{ "pk": self.pk, "url": self.url }],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Re-encrypts the secrets of up to `batch_size` endpoints that are not encrypted with the
+    /// active symmetric key, across all workspaces, like
+    /// [`EncryptedSecret::reencrypt_batch()`](crate::EncryptedSecret::reencrypt_batch) does for
+    /// secrets.
+    pub async fn reencrypt_secrets_batch(
+        ctx: &DalContext,
+        after: Option<WebhookEndpointPk>,
+        batch_size: i64,
+    ) -> WebhookResult<WebhookKeyRotationBatch> {
+        let symmetric_crypto_service = ctx.symmetric_crypto_service();
+        let active_key_hash = symmetric_crypto_service.active_key_hash().to_string();
+
+        let txns = ctx.txns().await?;
+        let rows = txns
+            .pg()
+            .query(
+                WEBHOOK_ENDPOINT_LIST_FOR_KEY_ROTATION,
+                &[&active_key_hash, &after, &batch_size],
+            )
+            .await?;
+        let mut endpoints = Vec::with_capacity(rows.len());
+        for row in rows {
+            let json: serde_json::Value = row.try_get("object")?;
+            endpoints.push(serde_json::from_value::<WebhookEndpointRow>(json)?);
+        }
+
+        let mut batch = WebhookKeyRotationBatch {
+            last_pk: endpoints.last().map(|endpoint| endpoint.pk),
+            ..Default::default()
+        };
+        for endpoint in endpoints {
+            let (crypted, nonce, key_hash) = match symmetric_crypto_service.reencrypt(
+                &endpoint.secret_crypted,
+                &endpoint.secret_nonce,
+                &endpoint.secret_key_hash,
+            ) {
+                Ok(reencrypted) => reencrypted,
+                Err(err) => {
+                    warn!(
+                        error = ?err,
+                        endpoint_pk = %endpoint.pk,
+                        key_hash = %endpoint.secret_key_hash,
+                        "could not re-encrypt webhook endpoint secret",
+                    );
+                    batch.failed.push(endpoint.pk);
+                    continue;
+                }
+            };
+
+            txns.pg()
+                .execute(
+                    "UPDATE webhook_endpoints SET secret_crypted = $2, secret_nonce = $3, secret_key_hash = $4 WHERE pk = $1",
+                    &[
+                        &endpoint.pk,
+                        &general_purpose::STANDARD_NO_PAD.encode(crypted),
+                        &general_purpose::STANDARD_NO_PAD.encode(nonce.as_ref()),
+                        &key_hash.to_string(),
+                    ],
+                )
+                .await?;
+            batch.rotated += 1;
+        }
+
+        Ok(batch)
+    }
+
+    async fn get_row_by_pk(
+        ctx: &DalContext,
+        pk: WebhookEndpointPk,
+    ) -> WebhookResult<Option<WebhookEndpointRow>> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(WEBHOOK_ENDPOINT_GET_BY_PK, &[&pk])
+            .await?;
+        match row {
+            Some(row) => {
+                let json: serde_json::Value = row.try_get("object")?;
+                Ok(Some(serde_json::from_value(json)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pk: WebhookDeliveryPk,
+    endpoint_pk: WebhookEndpointPk,
+    event_kind: WebhookEventKind,
+    /// The body of the request.
+    payload: serde_json::Value,
+    status: WebhookDeliveryStatus,
+    attempts: i32,
+    /// The status the endpoint responded to the latest attempt with, if it responded at all.
+    response_status: Option<i32>,
+    last_error: Option<String>,
+    delivered_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    timestamp: Timestamp,
+}
+
+/// The columns of a row, which are snake cased.
+#[derive(Deserialize, Debug)]
+struct WebhookDeliveryRow {
+    pk: WebhookDeliveryPk,
+    endpoint_pk: WebhookEndpointPk,
+    event_kind: String,
+    payload: serde_json::Value,
+    status: String,
+    attempts: i32,
+    response_status: Option<i32>,
+    last_error: Option<String>,
+    delivered_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<WebhookDeliveryRow> for WebhookDelivery {
+    type Error = WebhookError;
+
+    fn try_from(row: WebhookDeliveryRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            pk: row.pk,
+            endpoint_pk: row.endpoint_pk,
+            event_kind: row.event_kind.parse()?,
+            payload: row.payload,
+            status: row.status.parse()?,
+            attempts: row.attempts,
+            response_status: row.response_status,
+            last_error: row.last_error,
+            delivered_at: row.delivered_at,
+            timestamp: Timestamp {
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            },
+        })
+    }
+}
+
+impl WebhookDelivery {
+    pub fn pk(&self) -> WebhookDeliveryPk {
+        self.pk
+    }
+
+    pub fn endpoint_pk(&self) -> WebhookEndpointPk {
+        self.endpoint_pk
+    }
+
+    pub fn event_kind(&self) -> WebhookEventKind {
+        self.event_kind
+    }
+
+    pub fn payload(&self) -> &serde_json::Value {
+        &self.payload
+    }
+
+    pub fn status(&self) -> WebhookDeliveryStatus {
+        self.status
+    }
+
+    pub fn attempts(&self) -> i32 {
+        self.attempts
+    }
+
+    pub fn response_status(&self) -> Option<i32> {
+        self.response_status
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    pub fn delivered_at(&self) -> Option<DateTime<Utc>> {
+        self.delivered_at
+    }
+
+    /// Records a delivery of the event to every enabled endpoint of its workspace that subscribes
+    /// to its kind, and enqueues the jobs sending them.
+    pub async fn enqueue_for_event(
+        ctx: &DalContext,
+        event_kind: WebhookEventKind,
+        event: &WsEvent,
+    ) -> WebhookResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(
+                WEBHOOK_ENDPOINT_LIST_FOR_EVENT_KIND,
+                &[&event.workspace_pk(), &event_kind.as_ref()],
+            )
+            .await?;
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let payload = serde_json::json![{
+            "event": event_kind,
+            "workspacePk": event.workspace_pk(),
+            "changeSetPk": event.change_set_pk(),
+            "payload": event.payload(),
+        }];
+
+        let mut deliveries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let json: serde_json::Value = row.try_get("object")?;
+            let endpoint: WebhookEndpointRow = serde_json::from_value(json)?;
+
+            let row = ctx
+                .txns()
+                .await?
+                .pg()
+                .query_one(
+                    "SELECT object FROM webhook_delivery_create_v1($1, $2, $3)",
+                    &[&endpoint.pk, &event_kind.as_ref(), &payload],
+                )
+                .await?;
+            let json: serde_json::Value = row.try_get("object")?;
+            let delivery: Self = serde_json::from_value::<WebhookDeliveryRow>(json)?.try_into()?;
+
+            ctx.enqueue_job(WebhookDeliveryJob::new(
+                ctx.access_builder(),
+                *ctx.visibility(),
+                delivery.pk,
+            ))
+            .await?;
+            deliveries.push(delivery);
+        }
+
+        Ok(deliveries)
+    }
+
+    /// Records whether the qualifications of a component are failing, returning whether they
+    /// started failing, in which case the
+    /// [`QualificationsFailing`](WebhookEventKind::QualificationsFailing) event should be sent.
+    /// Components that are failing when first checked count as having started to fail.
+    pub async fn record_qualifications_failing(
+        ctx: &DalContext,
+        component_id: ComponentId,
+        failing: bool,
+    ) -> WebhookResult<bool> {
+        let workspace_pk = match ctx.tenancy().workspace_pk() {
+            Some(workspace_pk) => workspace_pk,
+            None => return Ok(false),
+        };
+
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT started_failing FROM webhook_qualification_state_set_v1($1, $2, $3, $4)",
+                &[
+                    &workspace_pk,
+                    &ctx.visibility().change_set_pk,
+                    &component_id,
+                    &failing,
+                ],
+            )
+            .await?;
+        Ok(row.try_get("started_failing")?)
+    }
+
+    pub async fn get_by_pk(ctx: &DalContext, pk: WebhookDeliveryPk) -> WebhookResult<Option<Self>> {
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_opt(WEBHOOK_DELIVERY_GET_BY_PK, &[&pk])
+            .await?;
+        match row {
+            Some(row) => {
+                let json: serde_json::Value = row.try_get("object")?;
+                Ok(Some(
+                    serde_json::from_value::<WebhookDeliveryRow>(json)?.try_into()?,
+                ))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Lists the latest deliveries of an endpoint, newest first.
+    pub async fn list_for_endpoint(
+        ctx: &DalContext,
+        endpoint_pk: WebhookEndpointPk,
+        limit: i64,
+    ) -> WebhookResult<Vec<Self>> {
+        let rows = ctx
+            .txns()
+            .await?
+            .pg()
+            .query(WEBHOOK_DELIVERY_LIST_FOR_ENDPOINT, &[&endpoint_pk, &limit])
+            .await?;
+
+        let mut deliveries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let json: serde_json::Value = row.try_get("object")?;
+            deliveries.push(serde_json::from_value::<WebhookDeliveryRow>(json)?.try_into()?);
+        }
+        Ok(deliveries)
+    }
+
+    /// Sends the delivery to its endpoint once and records the attempt, returning whether the
+    /// endpoint accepted it. A failed attempt leaves the delivery
+    /// [`Pending`](WebhookDeliveryStatus::Pending) unless it is the last one.
+    ///
+    /// Deliveries to endpoints that were disabled since are failed without being sent.
+    pub async fn attempt(
+        &mut self,
+        ctx: &DalContext,
+        client: &WebhookClient,
+        last_attempt: bool,
+    ) -> WebhookResult<bool> {
+        let endpoint = WebhookEndpoint::get_row_by_pk(ctx, self.endpoint_pk)
+            .await?
+            .ok_or(WebhookError::EndpointNotFound(self.endpoint_pk))?;
+
+        let (succeeded, response_status, error) = if endpoint.enabled {
+            let secret = endpoint.decrypt_secret(ctx)?;
+            self.send(client, &endpoint.url, &secret).await
+        } else {
+            (false, None, Some("webhook endpoint is disabled".to_owned()))
+        };
+
+        let status = if succeeded {
+            WebhookDeliveryStatus::Succeeded
+        } else if last_attempt || !endpoint.enabled {
+            WebhookDeliveryStatus::Failed
+        } else {
+            WebhookDeliveryStatus::Pending
+        };
+
+        let row = ctx
+            .txns()
+            .await?
+            .pg()
+            .query_one(
+                "SELECT object FROM webhook_delivery_record_attempt_v1($1, $2, $3, $4)",
+                &[&self.pk, &status.as_ref(), &response_status, &error],
+            )
+            .await?;
+        let json: serde_json::Value = row.try_get("object")?;
+        *self = serde_json::from_value::<WebhookDeliveryRow>(json)?.try_into()?;
+
+        Ok(succeeded)
+    }
+
+    /// Whether the delivery will be attempted again.
+    pub fn is_pending(&self) -> bool {
+        self.status == WebhookDeliveryStatus::Pending
+    }
+
+    /// Posts the payload, returning whether the endpoint accepted it, the status it responded
+    /// with and what went wrong otherwise.
+    async fn send(
+        &self,
+        client: &WebhookClient,
+        url: &str,
+        secret: &str,
+    ) -> (bool, Option<i32>, Option<String>) {
+        let body = match serde_json::to_string(&self.payload) {
+            Ok(body) => body,
+            Err(err) => return (false, None, Some(err.to_string())),
+        };
+        let client = match client.client_for(url).await {
+            Ok(client) => client,
+            Err(err) => return (false, None, Some(err.to_string())),
+        };
+        let timestamp = Utc::now().timestamp().to_string();
+        let signature = sign(secret, &timestamp, &body);
+
+        let response = client
+            .post(url)
+            .timeout(REQUEST_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_ID_HEADER, self.pk.to_string())
+            .header(WEBHOOK_EVENT_HEADER, self.event_kind.as_ref())
+            .header(WEBHOOK_TIMESTAMP_HEADER, &timestamp)
+            .header(WEBHOOK_SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_success() {
+                    (true, Some(status.as_u16().into()), None)
+                } else {
+                    let mut error = read_error(response).await;
+                    if error.is_empty() {
+                        error = status.to_string();
+                    }
+                    (
+                        false,
+                        Some(status.as_u16().into()),
+                        Some(truncate(error, MAX_ERROR_LEN)),
+                    )
+                }
+            }
+            Err(err) => (false, None, Some(err.to_string())),
+        }
+    }
+}
+
+/// Reads at most [`MAX_ERROR_LEN`] bytes of a failed response, so that an endpoint can't make
+/// deliveries buffer an arbitrarily large body.
+async fn read_error(mut response: reqwest::Response) -> String {
+    let mut body = Vec::new();
+    while body.len() < MAX_ERROR_LEN {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) | Err(_) => break,
+        }
+    }
+    body.truncate(MAX_ERROR_LEN);
+    String::from_utf8_lossy(&body).into_owned()
+}
+
+/// Sends the requests of [`WebhookDeliveries`](WebhookDelivery).
+///
+/// The host of an endpoint is resolved before every attempt and the request is refused unless
+/// every address is public, so that endpoints can't be used to reach services running next to SI,
+/// such as cloud metadata services. The request then only connects to the checked addresses, and
+/// redirects are not followed, so that the destination can't change after the check.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WebhookClient {
+    allow_private_destinations: bool,
+}
+
+impl WebhookClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A client which also sends to loopback and private addresses, for tests whose endpoints
+    /// run locally.
+    pub fn allowing_private_destinations() -> Self {
+        Self {
+            allow_private_destinations: true,
+        }
+    }
+
+    async fn client_for(&self, url: &str) -> WebhookResult<reqwest::Client> {
+        let parsed = url::Url::parse(url)
+            .map_err(|err| WebhookError::InvalidUrl(url.to_owned(), err.to_string()))?;
+        let port = parsed
+            .port_or_known_default()
+            .ok_or_else(|| WebhookError::InvalidUrl(url.to_owned(), "unknown port".to_owned()))?;
+
+        let builder = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+        let (builder, addrs) = match parsed.host() {
+            Some(url::Host::Domain(domain)) => {
+                let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain, port))
+                    .await
+                    .map_err(|err| WebhookError::Resolve(url.to_owned(), err))?
+                    .collect();
+                (builder.resolve_to_addrs(domain, &addrs), addrs)
+            }
+            Some(url::Host::Ipv4(ip)) => (builder, vec![SocketAddr::new(ip.into(), port)]),
+            Some(url::Host::Ipv6(ip)) => (builder, vec![SocketAddr::new(ip.into(), port)]),
+            None => {
+                return Err(WebhookError::InvalidUrl(
+                    url.to_owned(),
+                    "no host".to_owned(),
+                ))
+            }
+        };
+        if addrs.is_empty() {
+            return Err(WebhookError::InvalidUrl(
+                url.to_owned(),
+                "host has no addresses".to_owned(),
+            ));
+        }
+        if !self.allow_private_destinations {
+            if let Some(addr) = addrs.iter().find(|addr| !is_public_address(addr.ip())) {
+                return Err(WebhookError::ForbiddenDestination(
+                    url.to_owned(),
+                    addr.ip(),
+                ));
+            }
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+/// Whether an address is reachable from the internet, as opposed to loopback, private, link-local
+/// and other special purpose addresses.
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            // 100.64.0.0/10 is shared by carrier-grade NATs
+            let shared = octets[0] == 100 && octets[1] & 0xc0 == 64;
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || shared)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                let first_segment = ip.segments()[0];
+                // fc00::/7 is unique local and fe80::/10 is link-local
+                let unique_local = first_segment & 0xfe00 == 0xfc00;
+                let link_local = first_segment & 0xffc0 == 0xfe80;
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || unique_local
+                    || link_local)
+            }
+        },
+    }
+}
+
+/// Computes the signature of a request, which endpoints can compare with the
+/// `X-SI-Webhook-Signature` header to check that it was sent by SI.
+pub fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn validate_url(url: &str) -> WebhookResult<()> {
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        Ok(parsed) => Err(WebhookError::InvalidUrl(
+            url.to_owned(),
+            format!("unsupported scheme {}", parsed.scheme()),
+        )),
+        Err(err) => Err(WebhookError::InvalidUrl(url.to_owned(), err.to_string())),
+    }
+}
+
+fn truncate(mut s: String, max_len: usize) -> String {
+    if s.len() > max_len {
+        let mut end = max_len;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_public_addresses_are_allowed() {
+        for ip in ["93.184.216.34", "2606:2800:220:1:248:1893:25c8:1946"] {
+            assert!(is_public_address(ip.parse().expect("invalid ip")), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public_address(ip.parse().expect("invalid ip")), "{ip}");
+        }
+    }
+}
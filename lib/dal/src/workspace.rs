@@ -7,7 +7,7 @@ use thiserror::Error;
 use crate::{
     pk, standard_model, standard_model_accessor_ro, DalContext, HistoryActor, HistoryEvent,
     HistoryEventError, KeyPair, KeyPairError, StandardModelError, Tenancy, Timestamp,
-    TransactionsError, User, UserError, UserPk,
+    TransactionsError, User, UserError, UserPk, WorkspaceRole,
 };
 
 const WORKSPACE_GET_BY_PK: &str = include_str!("queries/workspace/get_by_pk.sql");
@@ -163,6 +163,8 @@ impl Workspace {
             None::<&str>,
         )
         .await?;
+        user.associate_workspace(ctx, *workspace.pk(), WorkspaceRole::Owner)
+            .await?;
         ctx.update_history_actor(HistoryActor::User(user.pk()));
 
         ctx.import_builtins().await?;
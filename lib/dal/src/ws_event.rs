@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use si_data_nats::NatsError;
 use si_data_pg::PgError;
+use strum::AsRefStr;
 use thiserror::Error;
 
 use crate::component::ComponentCreatedPayload;
@@ -9,8 +10,10 @@ use crate::{
     component::{code::CodeGeneratedPayload, resource::ResourceRefreshedPayload},
     fix::{batch::FixBatchReturn, FixReturn},
     func::binding::LogLinePayload,
+    key_pair::KeyPairPk,
     qualification::QualificationCheckPayload,
     status::StatusMessage,
+    webhook::{WebhookDelivery, WebhookError, WebhookEventKind},
     AttributeValueId, ChangeSetPk, ComponentId, DalContext, PropId, SchemaPk, SocketId,
     StandardModelError, TransactionsError, WorkspacePk,
 };
@@ -30,12 +33,15 @@ pub enum WsEventError {
     StandardModel(#[from] StandardModelError),
     #[error(transparent)]
     Transactions(#[from] TransactionsError),
+    #[error("webhook error: {0}")]
+    Webhook(#[from] WebhookError),
 }
 
 pub type WsEventResult<T> = Result<T, WsEventError>;
 
+/// The payload of a [`WsEvent`], whose variant name is its `kind`.
 #[remain::sorted]
-#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[derive(AsRefStr, Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
 #[serde(tag = "kind", content = "data")]
 #[allow(clippy::large_enum_variant)]
 pub enum WsPayload {
@@ -48,9 +54,11 @@ pub enum WsPayload {
     ComponentCreated(ComponentCreatedPayload),
     FixBatchReturn(FixBatchReturn),
     FixReturn(FixReturn),
+    KeyPairRotated(KeyPairPk),
     LogLine(LogLinePayload),
     ModuleImported(ModuleImported),
     ResourceRefreshed(ResourceRefreshedPayload),
+    ResyncRequired(ResyncRequiredPayload),
     SchemaCreated(SchemaPk),
     StatusUpdate(StatusMessage),
 }
@@ -89,11 +97,27 @@ impl AttributeValueStatusUpdate {
     }
 }
 
+/// Tells a websocket client that it missed events which can't be replayed, so it has to refetch
+/// everything and resume from the latest event of the stream.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncRequiredPayload {
+    pub stream_id: String,
+    pub latest_sequence: Option<u64>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
 pub struct WsEvent {
     version: i64,
     workspace_pk: WorkspacePk,
     change_set_pk: ChangeSetPk,
+    /// The position of the event in the stream of the workspace's events, which sdf sets when
+    /// relaying the event to websocket clients. It goes up by one with every event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sequence: Option<u64>,
+    /// The stream the sequence belongs to, which changes whenever the sequence starts over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stream_id: Option<String>,
     payload: WsPayload,
 }
 
@@ -111,19 +135,75 @@ impl WsEvent {
             version: 1,
             workspace_pk,
             change_set_pk,
+            sequence: None,
+            stream_id: None,
             payload,
         })
     }
 
+    /// Creates the event telling a websocket client to resync, which isn't part of any change set
+    /// and is never published.
+    pub fn resync_required(
+        workspace_pk: WorkspacePk,
+        stream_id: impl Into<String>,
+        latest_sequence: Option<u64>,
+    ) -> Self {
+        let stream_id = stream_id.into();
+        WsEvent {
+            version: 1,
+            workspace_pk,
+            change_set_pk: ChangeSetPk::NONE,
+            sequence: None,
+            stream_id: Some(stream_id.clone()),
+            payload: WsPayload::ResyncRequired(ResyncRequiredPayload {
+                stream_id,
+                latest_sequence,
+            }),
+        }
+    }
+
     pub fn workspace_pk(&self) -> WorkspacePk {
         self.workspace_pk
     }
 
+    pub fn change_set_pk(&self) -> ChangeSetPk {
+        self.change_set_pk
+    }
+
+    pub fn payload(&self) -> &WsPayload {
+        &self.payload
+    }
+
+    /// The `kind` of the payload, such as `StatusUpdate`.
+    pub fn payload_kind(&self) -> &str {
+        self.payload.as_ref()
+    }
+
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    pub fn stream_id(&self) -> Option<&str> {
+        self.stream_id.as_deref()
+    }
+
+    /// Places the event in a stream of the workspace's events.
+    pub fn set_sequence(&mut self, stream_id: impl Into<String>, sequence: u64) {
+        self.stream_id = Some(stream_id.into());
+        self.sequence = Some(sequence);
+    }
+
     /// Publishes the [`event`](Self) to the [`NatsTxn`](si_data_nats::NatsTxn). When the
     /// transaction is committed, the [`event`](Self) will be published for external use.
+    ///
+    /// Events that webhooks can be sent for are also delivered to the subscribed
+    /// [`WebhookEndpoints`](crate::WebhookEndpoint) once the transaction is committed.
     pub async fn publish_on_commit(&self, ctx: &DalContext) -> WsEventResult<()> {
         let subject = format!("si.workspace_pk.{}.event", self.workspace_pk);
         ctx.txns().await?.nats().publish(subject, &self).await?;
+        if let Some(event_kind) = WebhookEventKind::for_payload(&self.payload) {
+            WebhookDelivery::enqueue_for_event(ctx, event_kind, self).await?;
+        }
         Ok(())
     }
 }
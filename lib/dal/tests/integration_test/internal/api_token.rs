@@ -0,0 +1,72 @@
+use dal::{ApiToken, DalContext, WorkspaceRole, WorkspaceSignup};
+use dal_test::test;
+
+#[test]
+async fn find_active_and_touch(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let (api_token, token) = ApiToken::new(
+        ctx,
+        nw.user.pk(),
+        *nw.workspace.pk(),
+        "ci",
+        WorkspaceRole::Editor,
+        None,
+    )
+    .await
+    .expect("cannot create api token");
+    assert!(ApiToken::is_api_token(&token));
+    assert!(api_token.last_used_at().is_none());
+
+    let found = ApiToken::find_active(ctx, &format!("Bearer {token}"))
+        .await
+        .expect("cannot find api token")
+        .expect("api token not found");
+    assert_eq!(api_token.pk(), found.pk());
+    assert_eq!(nw.user.pk(), found.claim().user_pk);
+
+    let tampered = format!("{token}0");
+    assert!(ApiToken::find_active(ctx, &tampered)
+        .await
+        .expect("cannot find api token")
+        .is_none());
+
+    // Finding the token doesn't record its use, touching it does
+    let tokens = ApiToken::list_for_user(ctx, nw.user.pk(), *nw.workspace.pk())
+        .await
+        .expect("cannot list api tokens");
+    assert!(tokens[0].last_used_at().is_none());
+
+    found.touch(ctx).await.expect("cannot touch api token");
+    let tokens = ApiToken::list_for_user(ctx, nw.user.pk(), *nw.workspace.pk())
+        .await
+        .expect("cannot list api tokens");
+    assert!(tokens[0].last_used_at().is_some());
+}
+
+#[test]
+async fn revoke(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let (mut api_token, token) = ApiToken::new(
+        ctx,
+        nw.user.pk(),
+        *nw.workspace.pk(),
+        "ci",
+        WorkspaceRole::Viewer,
+        None,
+    )
+    .await
+    .expect("cannot create api token");
+
+    api_token
+        .revoke(ctx)
+        .await
+        .expect("cannot revoke api token");
+    assert!(!api_token.is_active());
+    assert!(ApiToken::find_active(ctx, &token)
+        .await
+        .expect("cannot find api token")
+        .is_none());
+
+    let tokens = ApiToken::list_for_user(ctx, nw.user.pk(), *nw.workspace.pk())
+        .await
+        .expect("cannot list api tokens");
+    assert_eq!(vec![api_token], tokens);
+}
@@ -1,4 +1,8 @@
+pub mod bulk_update;
+pub mod dependent_values;
+pub mod impact;
 pub mod prototype;
 pub mod prototype_argument;
+pub mod provenance;
 pub mod value;
 pub mod view;
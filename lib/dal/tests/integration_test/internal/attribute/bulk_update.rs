@@ -0,0 +1,153 @@
+use dal::{
+    component::view::ComponentView, BulkAttributeUpdate, BulkAttributeUpdateError,
+    BulkAttributeValue, Component, ComponentId, DalContext, Prop, PropKind, StandardModel,
+};
+use dal_test::{
+    test,
+    test_harness::{create_schema, create_schema_variant_with_root},
+};
+use pretty_assertions_sorted::assert_eq;
+
+#[test]
+async fn bulk_update_literal_and_template(ctx: &DalContext) {
+    let mut schema = create_schema(ctx).await;
+    let (mut schema_variant, root) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    schema
+        .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+
+    Prop::new(
+        ctx,
+        "region",
+        PropKind::String,
+        None,
+        *schema_variant.id(),
+        Some(root.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    Prop::new(
+        ctx,
+        "tag",
+        PropKind::String,
+        None,
+        *schema_variant.id(),
+        Some(root.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    schema_variant
+        .finalize(ctx, None)
+        .await
+        .expect("cannot finalize SchemaVariant");
+
+    let (canoe, _) = Component::new_for_default_variant_from_schema(ctx, "canoe", *schema.id())
+        .await
+        .expect("unable to create component");
+    let (kayak, _) = Component::new_for_default_variant_from_schema(ctx, "kayak", *schema.id())
+        .await
+        .expect("unable to create component");
+
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    let entries = BulkAttributeUpdate::new(
+        *schema_variant.id(),
+        vec!["root".into(), "domain".into(), "region".into()],
+        None,
+        BulkAttributeValue::Literal(Some(serde_json::json!("us-east-1"))),
+    )
+    .apply(ctx)
+    .await
+    .expect("could not apply bulk update");
+    assert_eq!(2, entries.len());
+
+    let entries = BulkAttributeUpdate::new(
+        *schema_variant.id(),
+        vec!["root".into(), "domain".into(), "tag".into()],
+        Some(vec![*kayak.id()]),
+        BulkAttributeValue::Template("team-{{component.name}}".into()),
+    )
+    .apply(ctx)
+    .await
+    .expect("could not apply bulk update");
+    assert_eq!(1, entries.len());
+
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    assert_eq!(
+        serde_json::json![{
+            "si": {
+                "name": "canoe",
+                "type": "component",
+                "protected": false,
+            },
+            "domain": {
+                "region": "us-east-1",
+            },
+        }],
+        ComponentView::new(ctx, *canoe.id())
+            .await
+            .expect("cannot get component view")
+            .properties,
+    );
+    assert_eq!(
+        serde_json::json![{
+            "si": {
+                "name": "kayak",
+                "type": "component",
+                "protected": false,
+            },
+            "domain": {
+                "region": "us-east-1",
+                "tag": "team-kayak",
+            },
+        }],
+        ComponentView::new(ctx, *kayak.id())
+            .await
+            .expect("cannot get component view")
+            .properties,
+    );
+}
+
+#[test]
+async fn bulk_update_rejects_foreign_component(ctx: &DalContext) {
+    let mut schema = create_schema(ctx).await;
+    let (mut schema_variant, root) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    schema
+        .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+    Prop::new(
+        ctx,
+        "region",
+        PropKind::String,
+        None,
+        *schema_variant.id(),
+        Some(root.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    schema_variant
+        .finalize(ctx, None)
+        .await
+        .expect("cannot finalize SchemaVariant");
+
+    let result = BulkAttributeUpdate::new(
+        *schema_variant.id(),
+        vec!["root".into(), "domain".into(), "region".into()],
+        Some(vec![ComponentId::generate()]),
+        BulkAttributeValue::Literal(Some(serde_json::json!("us-east-1"))),
+    )
+    .apply(ctx)
+    .await;
+
+    assert!(matches!(
+        result,
+        Err(BulkAttributeUpdateError::ComponentNotInSchemaVariant(_, _))
+    ));
+}
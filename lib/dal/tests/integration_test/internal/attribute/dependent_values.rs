@@ -0,0 +1,240 @@
+use dal::{
+    func::argument::{FuncArgument, FuncArgumentKind},
+    AttributeContext, AttributePrototypeArgument, AttributeReadContext, AttributeValue, Component,
+    ComponentId, DalContext, Func, FuncBackendKind, FuncBackendResponseType, InternalProvider,
+    Prop, PropId, PropKind, StandardModel,
+};
+use dal_test::{
+    test,
+    test_harness::{create_schema, create_schema_variant_with_root},
+};
+use pretty_assertions_sorted::assert_eq;
+
+/// The props of a component whose "joined" value is computed from its "first" and "second"
+/// values.
+struct Joined {
+    component_id: ComponentId,
+    domain_prop_id: PropId,
+    first_prop_id: PropId,
+    second_prop_id: PropId,
+    joined_prop_id: PropId,
+}
+
+impl Joined {
+    async fn new(ctx: &DalContext) -> Self {
+        let mut schema = create_schema(ctx).await;
+        let (mut schema_variant, root_prop) =
+            create_schema_variant_with_root(ctx, *schema.id()).await;
+        schema
+            .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+            .await
+            .expect("cannot set default schema variant");
+        let schema_variant_id = *schema_variant.id();
+
+        // domain: Object
+        // ├─ first: String
+        // ├─ second: String
+        // └─ joined: String
+        let mut prop_ids = Vec::new();
+        for name in ["first", "second", "joined"] {
+            let prop = Prop::new(
+                ctx,
+                name,
+                PropKind::String,
+                None,
+                schema_variant_id,
+                Some(root_prop.domain_prop_id),
+            )
+            .await
+            .expect("could not create prop");
+            prop_ids.push(*prop.id());
+        }
+        schema_variant
+            .finalize(ctx, None)
+            .await
+            .expect("cannot finalize SchemaVariant");
+
+        let mut join_func = Func::new(
+            ctx,
+            "test:join",
+            FuncBackendKind::JsAttribute,
+            FuncBackendResponseType::String,
+        )
+        .await
+        .expect("could not create func");
+        join_func
+            .set_code_plaintext(
+                ctx,
+                Some("function join(input) { return `${input.first}-${input.second}`; }"),
+            )
+            .await
+            .expect("set code");
+        join_func
+            .set_handler(ctx, Some("join"))
+            .await
+            .expect("set handler");
+
+        let mut joined_attribute_prototype = AttributeValue::find_for_context(
+            ctx,
+            AttributeReadContext {
+                prop_id: Some(prop_ids[2]),
+                ..AttributeReadContext::default()
+            },
+        )
+        .await
+        .expect("cannot get attribute value")
+        .expect("attribute value not found")
+        .attribute_prototype(ctx)
+        .await
+        .expect("cannot find attribute prototype")
+        .expect("attribute prototype not found");
+        joined_attribute_prototype
+            .set_func_id(ctx, *join_func.id())
+            .await
+            .expect("could not set func id on attribute prototype");
+        for (name, prop_id) in [("first", prop_ids[0]), ("second", prop_ids[1])] {
+            let func_argument =
+                FuncArgument::new(ctx, name, FuncArgumentKind::String, None, *join_func.id())
+                    .await
+                    .expect("could not create func argument");
+            let internal_provider = InternalProvider::find_for_prop(ctx, prop_id)
+                .await
+                .expect("could not get internal provider")
+                .expect("internal provider not found");
+            AttributePrototypeArgument::new_for_intra_component(
+                ctx,
+                *joined_attribute_prototype.id(),
+                *func_argument.id(),
+                *internal_provider.id(),
+            )
+            .await
+            .expect("could not create attribute prototype argument");
+        }
+
+        let (component, _) =
+            Component::new_for_default_variant_from_schema(ctx, "starfield", *schema.id())
+                .await
+                .expect("unable to create component");
+        ctx.blocking_commit()
+            .await
+            .expect("could not commit & run jobs");
+
+        Self {
+            component_id: *component.id(),
+            domain_prop_id: root_prop.domain_prop_id,
+            first_prop_id: prop_ids[0],
+            second_prop_id: prop_ids[1],
+            joined_prop_id: prop_ids[2],
+        }
+    }
+
+    async fn attribute_value(&self, ctx: &DalContext, prop_id: PropId) -> AttributeValue {
+        AttributeValue::find_for_context(
+            ctx,
+            AttributeReadContext {
+                prop_id: Some(prop_id),
+                component_id: Some(self.component_id),
+                ..AttributeReadContext::default()
+            },
+        )
+        .await
+        .expect("cannot get attribute value")
+        .expect("attribute value not found")
+    }
+
+    /// Sets a value, leaving the dependent values update to the next commit.
+    async fn set(&self, ctx: &DalContext, prop_id: PropId, value: &str) {
+        let domain_attribute_value = self.attribute_value(ctx, self.domain_prop_id).await;
+        let attribute_value = self.attribute_value(ctx, prop_id).await;
+        let context = AttributeContext::builder()
+            .set_prop_id(prop_id)
+            .set_component_id(self.component_id)
+            .to_context()
+            .expect("could not build attribute context");
+        AttributeValue::update_for_context(
+            ctx,
+            *attribute_value.id(),
+            Some(*domain_attribute_value.id()),
+            context,
+            Some(serde_json::json!(value)),
+            None,
+        )
+        .await
+        .expect("could not update attribute value");
+    }
+
+    async fn joined(&self, ctx: &DalContext) -> AttributeValue {
+        self.attribute_value(ctx, self.joined_prop_id).await
+    }
+}
+
+#[test]
+async fn values_with_unchanged_inputs_are_not_recomputed(ctx: &DalContext) {
+    let joined = Joined::new(ctx).await;
+    joined.set(ctx, joined.first_prop_id, "poop").await;
+    joined.set(ctx, joined.second_prop_id, "canoe").await;
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+    let before = joined.joined(ctx).await;
+    assert_eq!(
+        Some(serde_json::json!["poop-canoe"]),
+        before.get_value(ctx).await.expect("cannot get value")
+    );
+
+    // Setting "first" to the value it already has leaves "joined" with unchanged inputs. The job
+    // only finishes once council was told that every value in its graph was processed, pruned
+    // ones included.
+    joined.set(ctx, joined.first_prop_id, "poop").await;
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+    let after = joined.joined(ctx).await;
+    assert_eq!(
+        Some(serde_json::json!["poop-canoe"]),
+        after.get_value(ctx).await.expect("cannot get value")
+    );
+    assert_eq!(before.timestamp().updated_at, after.timestamp().updated_at);
+
+    // Whereas a change is propagated
+    joined.set(ctx, joined.first_prop_id, "paddle").await;
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+    let changed = joined.joined(ctx).await;
+    assert_eq!(
+        Some(serde_json::json!["paddle-canoe"]),
+        changed.get_value(ctx).await.expect("cannot get value")
+    );
+    assert!(changed.timestamp().updated_at > after.timestamp().updated_at);
+}
+
+#[test]
+async fn shared_values_are_never_pruned(ctx: &DalContext) {
+    let joined = Joined::new(ctx).await;
+    joined.set(ctx, joined.first_prop_id, "poop").await;
+    joined.set(ctx, joined.second_prop_id, "canoe").await;
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    // Both updates get a job of their own, which run at the same time and both wait on "joined".
+    // From the point of view of the job for "second", nothing "joined" depends on changed, but
+    // council may hand "joined" to that job rather than to the job for "first", so it must not
+    // be pruned.
+    joined.set(ctx, joined.first_prop_id, "paddle").await;
+    joined.set(ctx, joined.second_prop_id, "canoe").await;
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    assert_eq!(
+        Some(serde_json::json!["paddle-canoe"]),
+        joined
+            .joined(ctx)
+            .await
+            .get_value(ctx)
+            .await
+            .expect("cannot get value")
+    );
+}
@@ -0,0 +1,148 @@
+use dal::{
+    attribute::value::impact::AttributeValueImpact,
+    attribute::value::provenance::AttributeValueProvenanceSource, AttributePrototypeArgument,
+    AttributeReadContext, AttributeValue, Component, DalContext, InternalProvider, Prop, PropKind,
+    StandardModel,
+};
+use dal_test::{
+    helpers::setup_identity_func,
+    test,
+    test_harness::{create_schema, create_schema_variant_with_root},
+};
+use pretty_assertions_sorted::assert_eq;
+
+#[test]
+async fn impact_through_intra_component_identity(ctx: &DalContext) {
+    let mut schema = create_schema(ctx).await;
+    let (mut schema_variant, root_prop) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    schema
+        .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+    let schema_variant_id = *schema_variant.id();
+
+    // domain: Object
+    // ├─ source: String
+    // └─ destination: String
+    let source_prop = Prop::new(
+        ctx,
+        "source",
+        PropKind::String,
+        None,
+        schema_variant_id,
+        Some(root_prop.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    let destination_prop = Prop::new(
+        ctx,
+        "destination",
+        PropKind::String,
+        None,
+        schema_variant_id,
+        Some(root_prop.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    schema_variant
+        .finalize(ctx, None)
+        .await
+        .expect("cannot finalize SchemaVariant");
+
+    // Make "destination" an identity of "source" on the schema variant.
+    let (identity_func_id, _, _, identity_func_identity_argument_id) =
+        setup_identity_func(ctx).await;
+    let mut destination_attribute_prototype = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(*destination_prop.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found")
+    .attribute_prototype(ctx)
+    .await
+    .expect("cannot find attribute prototype")
+    .expect("attribute prototype not found");
+    destination_attribute_prototype
+        .set_func_id(ctx, identity_func_id)
+        .await
+        .expect("could not set func id on attribute prototype");
+    let source_internal_provider = InternalProvider::find_for_prop(ctx, *source_prop.id())
+        .await
+        .expect("could not get internal provider")
+        .expect("internal provider not found");
+    AttributePrototypeArgument::new_for_intra_component(
+        ctx,
+        *destination_attribute_prototype.id(),
+        identity_func_identity_argument_id,
+        *source_internal_provider.id(),
+    )
+    .await
+    .expect("could not create attribute prototype argument");
+
+    let (component, _) =
+        Component::new_for_default_variant_from_schema(ctx, "starfield", *schema.id())
+            .await
+            .expect("unable to create component");
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    let source_attribute_value = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(*source_prop.id()),
+            component_id: Some(*component.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found");
+    let destination_attribute_value = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(*destination_prop.id()),
+            component_id: Some(*component.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found");
+
+    let impact = AttributeValueImpact::new(ctx, &[*source_attribute_value.id()])
+        .await
+        .expect("could not compute impact");
+
+    assert_eq!(
+        vec![*source_attribute_value.id()],
+        impact.attribute_value_ids
+    );
+    assert!(impact
+        .values
+        .iter()
+        .all(|value| value.attribute_value_id != *source_attribute_value.id()));
+    let destination = impact
+        .values
+        .iter()
+        .find(|value| value.attribute_value_id == *destination_attribute_value.id())
+        .expect("destination value is not impacted");
+    assert_eq!(*component.id(), destination.component_id);
+    assert_eq!(
+        AttributeValueProvenanceSource::Prop {
+            prop_id: *destination_prop.id(),
+            path: "/root/domain/destination".to_owned(),
+        },
+        destination.source
+    );
+
+    assert_eq!(1, impact.components.len());
+    assert_eq!(*component.id(), impact.components[0].component_id);
+    assert_eq!("starfield", impact.components[0].name);
+    assert_eq!(impact.values.len(), impact.components[0].value_count);
+    assert!(impact.actions.is_empty());
+}
@@ -0,0 +1,302 @@
+use dal::{
+    attribute::value::provenance::{AttributeValueProvenance, AttributeValueProvenanceSource},
+    AttributeContext, AttributePrototypeArgument, AttributeReadContext, AttributeValue, Component,
+    DalContext, InternalProvider, Prop, PropKind, StandardModel,
+};
+use dal_test::{
+    helpers::setup_identity_func,
+    test,
+    test_harness::{create_schema, create_schema_variant_with_root},
+};
+use pretty_assertions_sorted::assert_eq;
+
+#[test]
+async fn provenance_through_intra_component_identity(ctx: &DalContext) {
+    let mut schema = create_schema(ctx).await;
+    let (mut schema_variant, root_prop) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    schema
+        .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+    let schema_variant_id = *schema_variant.id();
+
+    // domain: Object
+    // ├─ source: String
+    // └─ destination: String
+    let source_prop = Prop::new(
+        ctx,
+        "source",
+        PropKind::String,
+        None,
+        schema_variant_id,
+        Some(root_prop.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    let destination_prop = Prop::new(
+        ctx,
+        "destination",
+        PropKind::String,
+        None,
+        schema_variant_id,
+        Some(root_prop.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    schema_variant
+        .finalize(ctx, None)
+        .await
+        .expect("cannot finalize SchemaVariant");
+
+    // Make "destination" an identity of "source" on the schema variant.
+    let (identity_func_id, _, _, identity_func_identity_argument_id) =
+        setup_identity_func(ctx).await;
+    let mut destination_attribute_prototype = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(*destination_prop.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found")
+    .attribute_prototype(ctx)
+    .await
+    .expect("cannot find attribute prototype")
+    .expect("attribute prototype not found");
+    destination_attribute_prototype
+        .set_func_id(ctx, identity_func_id)
+        .await
+        .expect("could not set func id on attribute prototype");
+    let source_internal_provider = InternalProvider::find_for_prop(ctx, *source_prop.id())
+        .await
+        .expect("could not get internal provider")
+        .expect("internal provider not found");
+    AttributePrototypeArgument::new_for_intra_component(
+        ctx,
+        *destination_attribute_prototype.id(),
+        identity_func_identity_argument_id,
+        *source_internal_provider.id(),
+    )
+    .await
+    .expect("could not create attribute prototype argument");
+
+    let (component, _) =
+        Component::new_for_default_variant_from_schema(ctx, "starfield", *schema.id())
+            .await
+            .expect("unable to create component");
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    // Set "source" on the component and let the value flow into "destination".
+    let source_attribute_value = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(*source_prop.id()),
+            component_id: Some(*component.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found");
+    let domain_attribute_value = source_attribute_value
+        .parent_attribute_value(ctx)
+        .await
+        .expect("cannot get parent attribute value")
+        .expect("parent attribute value not found");
+    let source_attribute_context = AttributeContext::builder()
+        .set_prop_id(*source_prop.id())
+        .set_component_id(*component.id())
+        .to_context()
+        .expect("could not build attribute context");
+    AttributeValue::update_for_context(
+        ctx,
+        *source_attribute_value.id(),
+        Some(*domain_attribute_value.id()),
+        source_attribute_context,
+        Some(serde_json::json!["updateme"]),
+        None,
+    )
+    .await
+    .expect("cannot update value for context");
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    let destination_attribute_value = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(*destination_prop.id()),
+            component_id: Some(*component.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found");
+
+    let provenance = AttributeValueProvenance::new(ctx, *destination_attribute_value.id())
+        .await
+        .expect("could not build provenance");
+
+    assert_eq!(
+        AttributeValueProvenanceSource::Prop {
+            prop_id: *destination_prop.id(),
+            path: "/root/domain/destination".to_owned(),
+        },
+        provenance.source
+    );
+    assert_eq!(Some(serde_json::json!["updateme"]), provenance.value);
+    assert_eq!(identity_func_id, provenance.func.id);
+    assert!(!provenance.truncated);
+    assert!(!provenance.expanded_elsewhere);
+
+    // destination <- identity(source provider)
+    assert_eq!(1, provenance.arguments.len());
+    let argument = &provenance.arguments[0];
+    assert_eq!("identity", argument.name);
+    assert_eq!(1, argument.sources.len());
+    let provider = &argument.sources[0];
+    assert_eq!(
+        AttributeValueProvenanceSource::PropProvider {
+            internal_provider_id: *source_internal_provider.id(),
+            prop_id: *source_prop.id(),
+            path: "/root/domain/source".to_owned(),
+        },
+        provider.source
+    );
+
+    // source provider <- source prop (set directly on the component)
+    assert_eq!(1, provider.arguments.len());
+    assert_eq!(1, provider.arguments[0].sources.len());
+    let source = &provider.arguments[0].sources[0];
+    assert_eq!(
+        AttributeValueProvenanceSource::Prop {
+            prop_id: *source_prop.id(),
+            path: "/root/domain/source".to_owned(),
+        },
+        source.source
+    );
+    assert_eq!(Some(serde_json::json!["updateme"]), source.value);
+    assert_eq!(*component.id(), source.component_id);
+}
+
+#[test]
+async fn provenance_walks_shared_upstream_value_once(ctx: &DalContext) {
+    let mut schema = create_schema(ctx).await;
+    let (mut schema_variant, root_prop) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    schema
+        .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+    let schema_variant_id = *schema_variant.id();
+
+    // domain: Object
+    // ├─ source: String
+    // └─ destination: String
+    let source_prop = Prop::new(
+        ctx,
+        "source",
+        PropKind::String,
+        None,
+        schema_variant_id,
+        Some(root_prop.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    let destination_prop = Prop::new(
+        ctx,
+        "destination",
+        PropKind::String,
+        None,
+        schema_variant_id,
+        Some(root_prop.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    schema_variant
+        .finalize(ctx, None)
+        .await
+        .expect("cannot finalize SchemaVariant");
+
+    // Feed "source" into the identity of "destination" twice, so both arguments share the same
+    // upstream value (a diamond rather than a cycle).
+    let (identity_func_id, _, _, identity_func_identity_argument_id) =
+        setup_identity_func(ctx).await;
+    let mut destination_attribute_prototype = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(*destination_prop.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found")
+    .attribute_prototype(ctx)
+    .await
+    .expect("cannot find attribute prototype")
+    .expect("attribute prototype not found");
+    destination_attribute_prototype
+        .set_func_id(ctx, identity_func_id)
+        .await
+        .expect("could not set func id on attribute prototype");
+    let source_internal_provider = InternalProvider::find_for_prop(ctx, *source_prop.id())
+        .await
+        .expect("could not get internal provider")
+        .expect("internal provider not found");
+    for _ in 0..2 {
+        AttributePrototypeArgument::new_for_intra_component(
+            ctx,
+            *destination_attribute_prototype.id(),
+            identity_func_identity_argument_id,
+            *source_internal_provider.id(),
+        )
+        .await
+        .expect("could not create attribute prototype argument");
+    }
+
+    let (component, _) =
+        Component::new_for_default_variant_from_schema(ctx, "starfield", *schema.id())
+            .await
+            .expect("unable to create component");
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    let destination_attribute_value = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(*destination_prop.id()),
+            component_id: Some(*component.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found");
+
+    let provenance = AttributeValueProvenance::new(ctx, *destination_attribute_value.id())
+        .await
+        .expect("could not build provenance");
+
+    assert!(!provenance.truncated);
+    assert_eq!(1, provenance.arguments.len());
+    let sources = &provenance.arguments[0].sources;
+    assert_eq!(2, sources.len());
+    assert_eq!(sources[0].attribute_value_id, sources[1].attribute_value_id);
+
+    // The shared value is walked in the first branch only, and referenced from the second
+    let (walked, referenced) = (&sources[0], &sources[1]);
+    assert!(!walked.truncated);
+    assert!(!walked.expanded_elsewhere);
+    assert_eq!(1, walked.arguments.len());
+    assert_eq!(1, walked.arguments[0].sources.len());
+    assert!(!walked.arguments[0].sources[0].truncated);
+    assert!(!referenced.truncated);
+    assert!(referenced.expanded_elsewhere);
+    assert!(referenced.arguments.is_empty());
+}
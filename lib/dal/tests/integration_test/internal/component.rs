@@ -21,6 +21,7 @@ use veritech_client::ResourceStatus;
 
 mod code;
 mod qualification;
+mod query;
 mod resource;
 mod validation;
 mod view;
@@ -0,0 +1,88 @@
+use dal::{
+    component::query::ComponentQuery, BulkAttributeUpdate, BulkAttributeValue, Component,
+    DalContext, Prop, PropKind, StandardModel,
+};
+use dal_test::{
+    test,
+    test_harness::{create_schema, create_schema_variant_with_root},
+};
+use pretty_assertions_sorted::assert_eq;
+
+#[test]
+async fn query_by_prop_schema_and_name(ctx: &DalContext) {
+    let mut schema = create_schema(ctx).await;
+    let (mut schema_variant, root) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    schema
+        .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+    Prop::new(
+        ctx,
+        "region",
+        PropKind::String,
+        None,
+        *schema_variant.id(),
+        Some(root.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    schema_variant
+        .finalize(ctx, None)
+        .await
+        .expect("cannot finalize SchemaVariant");
+
+    let (east, _) = Component::new_for_default_variant_from_schema(ctx, "east", *schema.id())
+        .await
+        .expect("unable to create component");
+    let (west, _) = Component::new_for_default_variant_from_schema(ctx, "west", *schema.id())
+        .await
+        .expect("unable to create component");
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    for (component, region) in [(&east, "us-east-1"), (&west, "us-west-2")] {
+        BulkAttributeUpdate::new(
+            *schema_variant.id(),
+            vec!["root".into(), "domain".into(), "region".into()],
+            Some(vec![*component.id()]),
+            BulkAttributeValue::Literal(Some(serde_json::json!(region))),
+        )
+        .apply(ctx)
+        .await
+        .expect("could not apply bulk update");
+    }
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    let query = ComponentQuery::parse(&format!(
+        r#"schema = "{}" and /root/domain/region = "us-east-1""#,
+        schema.name()
+    ))
+    .expect("could not parse query");
+    let matches = query.evaluate(ctx).await.expect("could not evaluate query");
+    assert_eq!(
+        vec![*east.id()],
+        matches
+            .iter()
+            .map(|matched| matched.component_id)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!("east", matches[0].component_name);
+    assert_eq!(*schema_variant.id(), matches[0].schema_variant_id);
+
+    let query = ComponentQuery::parse(&format!(
+        r#"schema = "{}" and not (name = east or /root/domain/region ~ "east")"#,
+        schema.name()
+    ))
+    .expect("could not parse query");
+    let matches = query.evaluate(ctx).await.expect("could not evaluate query");
+    assert_eq!(
+        vec![*west.id()],
+        matches
+            .iter()
+            .map(|matched| matched.component_id)
+            .collect::<Vec<_>>()
+    );
+}
@@ -1,5 +1,13 @@
-use dal::{DalContext, HistoryActor, HistoryEvent};
-use dal_test::test;
+use dal::{
+    AttributeReadContext, AttributeValue, AttributeValueId, ChangeSetPk, Component, ComponentId,
+    DalContext, HistoryActor, HistoryEvent, HistoryEventFilter, HistoryEventPage, HistoryEventPk,
+    StandardModel, UserPk,
+};
+use dal_test::{
+    test,
+    test_harness::{create_schema, create_schema_variant_with_root},
+};
+use futures::TryStreamExt;
 
 #[test]
 async fn new(ctx: &DalContext) {
@@ -17,3 +25,197 @@ async fn new(ctx: &DalContext) {
     assert_eq!(&history_event.data, &serde_json::json!({}));
     assert_eq!(&history_event.tenancy, ctx.tenancy());
 }
+
+#[test]
+async fn list(ctx: &DalContext) {
+    let component_id = ComponentId::generate();
+    let component_event = HistoryEvent::new(
+        ctx,
+        "component.updated",
+        "component updated",
+        &serde_json::json!({ "id": component_id }),
+    )
+    .await
+    .expect("cannot create a new history event");
+    let func_event = HistoryEvent::new(
+        ctx,
+        "function.updated",
+        "function updated",
+        &serde_json::json!({ "id": component_id }),
+    )
+    .await
+    .expect("cannot create a new history event");
+    assert_eq!(
+        Some(ctx.visibility().change_set_pk),
+        component_event.change_set_pk
+    );
+    assert_eq!(Some(component_id.to_string()), component_event.entity_id);
+
+    let page = HistoryEvent::list(
+        ctx,
+        &HistoryEventFilter {
+            component_id: Some(component_id),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("cannot list history events");
+    assert_eq!(vec![component_event.pk], pks(&page));
+    assert_eq!(None, page.next_cursor);
+
+    let page = HistoryEvent::list(
+        ctx,
+        &HistoryEventFilter {
+            label_prefix: Some("function.".to_owned()),
+            change_set_pk: Some(ctx.visibility().change_set_pk),
+            actor: Some(HistoryActor::SystemInit),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("cannot list history events");
+    assert!(pks(&page).contains(&func_event.pk));
+    assert!(!pks(&page).contains(&component_event.pk));
+
+    let page = HistoryEvent::list(
+        ctx,
+        &HistoryEventFilter {
+            change_set_pk: Some(ChangeSetPk::generate()),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("cannot list history events");
+    assert!(page.events.is_empty());
+
+    let page = HistoryEvent::list(
+        ctx,
+        &HistoryEventFilter {
+            actor: Some(HistoryActor::User(UserPk::generate())),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("cannot list history events");
+    assert!(page.events.is_empty());
+}
+
+#[test]
+async fn list_attribute_value_events_by_component(ctx: &DalContext) {
+    let mut schema = create_schema(ctx).await;
+    let (mut schema_variant, root_prop) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    schema
+        .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+    schema_variant
+        .finalize(ctx, None)
+        .await
+        .expect("cannot finalize SchemaVariant");
+    let (component, _) =
+        Component::new_for_default_variant_from_schema(ctx, "starfield", *schema.id())
+            .await
+            .expect("unable to create component");
+
+    let component_domain_value = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(root_prop.domain_prop_id),
+            component_id: Some(*component.id()),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found");
+    let schema_variant_domain_value = AttributeValue::find_for_context(
+        ctx,
+        AttributeReadContext {
+            prop_id: Some(root_prop.domain_prop_id),
+            ..AttributeReadContext::default()
+        },
+    )
+    .await
+    .expect("cannot get attribute value")
+    .expect("attribute value not found");
+
+    let component_value_event = HistoryEvent::new(
+        ctx,
+        "attribute_value.updated",
+        "attribute value updated",
+        &serde_json::json!({ "id": component_domain_value.id() }),
+    )
+    .await
+    .expect("cannot create a new history event");
+    // Hard deleted values are gone from the table, so their component comes from the event
+    let hard_deleted_value_event = HistoryEvent::new(
+        ctx,
+        "attribute_value.hard_deleted",
+        "attribute value hard_deleted",
+        &serde_json::json!({
+            "id": AttributeValueId::generate(),
+            "attribute_context_component_id": component.id(),
+        }),
+    )
+    .await
+    .expect("cannot create a new history event");
+    let schema_variant_value_event = HistoryEvent::new(
+        ctx,
+        "attribute_value.updated",
+        "attribute value updated",
+        &serde_json::json!({ "id": schema_variant_domain_value.id() }),
+    )
+    .await
+    .expect("cannot create a new history event");
+    assert_eq!(Some(*component.id()), component_value_event.component_id);
+    assert_eq!(Some(*component.id()), hard_deleted_value_event.component_id);
+    assert_eq!(None, schema_variant_value_event.component_id);
+
+    let page = HistoryEvent::list(
+        ctx,
+        &HistoryEventFilter {
+            label_prefix: Some("attribute_value.".to_owned()),
+            component_id: Some(*component.id()),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("cannot list history events");
+    assert!(pks(&page).contains(&component_value_event.pk));
+    assert!(pks(&page).contains(&hard_deleted_value_event.pk));
+    assert!(!pks(&page).contains(&schema_variant_value_event.pk));
+}
+
+#[test]
+async fn export_json_lines(ctx: &DalContext) {
+    for message in ["first", "second", "third"] {
+        HistoryEvent::new(ctx, "export.test", message, &serde_json::json!({}))
+            .await
+            .expect("cannot create a new history event");
+    }
+
+    let filter = HistoryEventFilter {
+        label_prefix: Some("export.".to_owned()),
+        limit: Some(1),
+        ..Default::default()
+    };
+    let output = HistoryEvent::export_json_lines(ctx.clone(), &filter)
+        .try_concat()
+        .await
+        .expect("cannot export history events");
+
+    let messages: Vec<String> = String::from_utf8(output)
+        .expect("output is not utf-8")
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<HistoryEvent>(line)
+                .expect("line is not a history event")
+                .message
+        })
+        .collect();
+    assert_eq!(vec!["third", "second", "first"], messages);
+}
+
+fn pks(page: &HistoryEventPage) -> Vec<HistoryEventPk> {
+    page.events.iter().map(|event| event.pk).collect()
+}
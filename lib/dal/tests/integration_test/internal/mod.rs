@@ -1,4 +1,5 @@
 mod action_prototype;
+mod api_token;
 mod attribute;
 mod change_set;
 mod component;
@@ -26,4 +27,5 @@ mod user;
 mod validation_prototype;
 mod validation_resolver;
 mod visibility;
+mod webhook;
 mod workspace;
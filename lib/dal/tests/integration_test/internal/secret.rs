@@ -1,10 +1,15 @@
 use dal::{
-    DalContext, EncryptedSecret, Secret, SecretAlgorithm, SecretVersion, StandardModel,
-    WorkspaceSignup,
+    property_editor::schema::WidgetKind, AttributeContext, AttributeReadContext, AttributeValue,
+    Component, DalContext, EncryptedSecret, HistoryEvent, HistoryEventFilter, KeyPair, Prop,
+    PropKind, PublicKey, Secret, SecretAlgorithm, SecretBackendKind, SecretError, SecretId,
+    SecretReference, SecretUsage, SecretVersion, StandardModel, WorkspaceSignup,
 };
 use dal_test::{
     test,
-    test_harness::{create_secret, generate_fake_name},
+    test_harness::{
+        create_schema, create_schema_variant_with_root, create_secret, create_secret_with_message,
+        generate_fake_name,
+    },
 };
 
 #[test]
@@ -116,3 +121,375 @@ async fn encrypt_decrypt_round_trip(ctx: &DalContext, nw: &WorkspaceSignup) {
         serde_json::to_value(&decrypted).expect("failed to serial decrypted into Value");
     assert_eq!(decrypted_value["message"], message);
 }
+
+#[test]
+async fn rotate_key_pair_rewraps_secrets(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let message = serde_json::json!({"song": "Cumbersome"});
+    let secret = create_secret_with_message(ctx, nw.key_pair.pk(), &message).await;
+
+    let key_pair = KeyPair::rotate(ctx)
+        .await
+        .expect("failed to rotate key pair");
+    assert_ne!(key_pair.pk(), nw.key_pair.pk());
+    let public_key = PublicKey::get_current(ctx)
+        .await
+        .expect("cannot get public key");
+    assert_eq!(key_pair.pk(), *public_key.pk());
+
+    let rewrapped = EncryptedSecret::rewrap_for_key_pair(ctx, &key_pair)
+        .await
+        .expect("failed to rewrap secrets");
+    assert_eq!(1, rewrapped);
+
+    let encrypted_secret = EncryptedSecret::get_by_id(ctx, secret.id())
+        .await
+        .expect("failed to fetch encrypted secret")
+        .expect("failed to find encrypted secret for tenancy and/or visibility");
+    assert_eq!(
+        key_pair.pk(),
+        encrypted_secret
+            .key_pair(ctx)
+            .await
+            .expect("failed to fetch key pair")
+            .pk()
+    );
+    let decrypted = encrypted_secret
+        .decrypt(ctx)
+        .await
+        .expect("failed to decrypt encrypted secret");
+    let decrypted_value =
+        serde_json::to_value(&decrypted).expect("failed to serial decrypted into Value");
+    assert_eq!(decrypted_value["message"], message);
+
+    let deactivated = KeyPair::deactivate_unused(ctx)
+        .await
+        .expect("failed to deactivate unused key pairs");
+    assert_eq!(vec![nw.key_pair.pk()], deactivated);
+    assert!(KeyPair::get_by_pk(ctx, nw.key_pair.pk())
+        .await
+        .expect("failed to fetch key pair")
+        .is_deactivated());
+
+    // Secrets sealed for the previous key pair are now rejected
+    let result = EncryptedSecret::new(
+        ctx,
+        generate_fake_name(),
+        "Mock".to_owned(),
+        None,
+        "im-crypted-bytes-maybe".as_bytes(),
+        nw.key_pair.pk(),
+        Default::default(),
+        Default::default(),
+    )
+    .await;
+    assert!(matches!(result, Err(SecretError::KeyPairDeactivated(pk)) if pk == nw.key_pair.pk()));
+}
+
+#[test]
+async fn new_reference(ctx: &DalContext) {
+    let reference = SecretReference {
+        path: "team/github".to_owned(),
+        key: Some("token".to_owned()),
+    };
+
+    let secret = EncryptedSecret::new_reference(
+        ctx,
+        generate_fake_name(),
+        "Mock".to_owned(),
+        None,
+        SecretBackendKind::Vault,
+        &reference,
+    )
+    .await
+    .expect("failed to create secret reference");
+    assert_eq!(&SecretBackendKind::Vault, secret.backend());
+
+    // Only cyclone resolves references, so decrypting the secret returns the reference itself
+    let decrypted = EncryptedSecret::get_by_id(ctx, secret.id())
+        .await
+        .expect("failed to fetch encrypted secret")
+        .expect("failed to find encrypted secret for tenancy and/or visibility")
+        .decrypt(ctx)
+        .await
+        .expect("failed to decrypt encrypted secret");
+    assert_eq!(
+        serde_json::to_value(&reference).expect("failed to serialize reference"),
+        *decrypted.message()
+    );
+
+    let result = EncryptedSecret::new_reference(
+        ctx,
+        generate_fake_name(),
+        "Mock".to_owned(),
+        None,
+        SecretBackendKind::Database,
+        &reference,
+    )
+    .await;
+    assert!(matches!(result, Err(SecretError::DatabaseBackendReference)));
+
+    // References are relative to the prefix of the workspace and can't leave it
+    for path in [
+        "",
+        "../01H4WWE9QK6Z0M5Y6ZC1A9N2TD/team/github",
+        "/team/github",
+        "team//github",
+    ] {
+        let result = EncryptedSecret::new_reference(
+            ctx,
+            generate_fake_name(),
+            "Mock".to_owned(),
+            None,
+            SecretBackendKind::Vault,
+            &SecretReference {
+                path: path.to_owned(),
+                key: None,
+            },
+        )
+        .await;
+        assert!(
+            matches!(result, Err(SecretError::InvalidReferencePath(ref invalid)) if invalid == path)
+        );
+    }
+}
+
+#[test]
+async fn delete_unused_secret(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let secret = create_secret(ctx, nw.key_pair.pk()).await;
+    let secret_id = *secret.id();
+
+    let usages = secret
+        .usages(ctx)
+        .await
+        .expect("could not list secret usages");
+    assert!(usages.is_empty());
+
+    secret.delete(ctx).await.expect("could not delete secret");
+
+    assert!(Secret::get_by_id(ctx, &secret_id)
+        .await
+        .expect("could not get secret")
+        .is_none());
+}
+
+#[test]
+async fn reencrypt_batch_rotates_symmetric_key(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let message = serde_json::json!({"song": "Strobe"});
+    let secret = create_secret_with_message(ctx, nw.key_pair.pk(), &message).await;
+    ctx.blocking_commit().await.expect("failed to commit");
+
+    // The rotated context is never committed so that other tests keep using the test key
+    let services_context =
+        dal_test::services_context_with_rotated_symmetric_key(&ctx.services_context())
+            .await
+            .expect("cannot build services context with rotated key");
+    let rotated_ctx = services_context
+        .into_builder(true)
+        .build(ctx.access_builder().build(*ctx.visibility()))
+        .await
+        .expect("cannot build rotated dal context");
+
+    let mut after = None;
+    loop {
+        let batch = EncryptedSecret::reencrypt_batch(&rotated_ctx, after, 100)
+            .await
+            .expect("failed to re-encrypt secrets");
+        assert!(batch.failed.is_empty());
+        after = batch.last_pk;
+        if after.is_none() {
+            break;
+        }
+    }
+
+    let usage = EncryptedSecret::key_hash_usage(&rotated_ctx)
+        .await
+        .expect("failed to fetch key hash usage");
+    // Secrets locked by concurrently running tests are skipped, so only the new key is checked
+    let active_key_hash = *rotated_ctx.symmetric_crypto_service().active_key_hash();
+    let active_usage = usage
+        .iter()
+        .find(|usage| usage.key_hash == active_key_hash)
+        .expect("no secrets are encrypted with the active key");
+    assert!(active_usage.active && active_usage.loaded);
+    assert!(active_usage.count >= 1);
+
+    let decrypted = EncryptedSecret::get_by_id(&rotated_ctx, secret.id())
+        .await
+        .expect("failed to fetch encrypted secret")
+        .expect("failed to find encrypted secret for tenancy and/or visibility")
+        .decrypt(&rotated_ctx)
+        .await
+        .expect("failed to decrypt encrypted secret with the new key");
+    let decrypted_value =
+        serde_json::to_value(&decrypted).expect("failed to serial decrypted into Value");
+    assert_eq!(decrypted_value["message"], message);
+
+    rotated_ctx
+        .rollback()
+        .await
+        .expect("failed to roll back rotation");
+}
+
+/// Creates a component whose "credential" prop uses the secret widget and references the secret.
+async fn create_component_using_secret(ctx: &DalContext, secret_id: SecretId) -> Component {
+    let mut schema = create_schema(ctx).await;
+    let (mut schema_variant, root_prop) = create_schema_variant_with_root(ctx, *schema.id()).await;
+    schema
+        .set_default_schema_variant_id(ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+    let credential_prop = Prop::new(
+        ctx,
+        "credential",
+        PropKind::String,
+        Some((WidgetKind::Secret, None)),
+        *schema_variant.id(),
+        Some(root_prop.domain_prop_id),
+    )
+    .await
+    .expect("could not create prop");
+    schema_variant
+        .finalize(ctx, None)
+        .await
+        .expect("cannot finalize SchemaVariant");
+
+    let (component, _) =
+        Component::new_for_default_variant_from_schema(ctx, generate_fake_name(), *schema.id())
+            .await
+            .expect("unable to create component");
+    let read_context = |prop_id| AttributeReadContext {
+        prop_id: Some(prop_id),
+        component_id: Some(*component.id()),
+        ..AttributeReadContext::default()
+    };
+    let domain_attribute_value =
+        AttributeValue::find_for_context(ctx, read_context(root_prop.domain_prop_id))
+            .await
+            .expect("cannot get attribute value")
+            .expect("attribute value not found");
+    let credential_attribute_value =
+        AttributeValue::find_for_context(ctx, read_context(*credential_prop.id()))
+            .await
+            .expect("cannot get attribute value")
+            .expect("attribute value not found");
+    let context = AttributeContext::builder()
+        .set_prop_id(*credential_prop.id())
+        .set_component_id(*component.id())
+        .to_context()
+        .expect("could not build attribute context");
+    AttributeValue::update_for_context(
+        ctx,
+        *credential_attribute_value.id(),
+        Some(*domain_attribute_value.id()),
+        context,
+        Some(serde_json::json!(secret_id)),
+        None,
+    )
+    .await
+    .expect("could not update attribute value");
+    ctx.blocking_commit()
+        .await
+        .expect("could not commit & run jobs");
+
+    component
+}
+
+#[test]
+async fn secret_usages_list_referencing_components(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let secret = create_secret(ctx, nw.key_pair.pk()).await;
+    let unused_secret = create_secret(ctx, nw.key_pair.pk()).await;
+    let component = create_component_using_secret(ctx, *secret.id()).await;
+
+    let usages = SecretUsage::list(ctx, Some(*secret.id()))
+        .await
+        .expect("failed to list secret usages");
+    assert_eq!(1, usages.len());
+    let usage = &usages[0];
+    assert_eq!(*secret.id(), usage.secret_id);
+    assert_eq!(*component.id(), usage.component_id);
+    assert_eq!(
+        component
+            .name(ctx)
+            .await
+            .expect("cannot get component name"),
+        usage.component_name
+    );
+    assert_eq!("/root/domain/credential", usage.prop_path);
+    assert_eq!(
+        usages,
+        secret
+            .usages(ctx)
+            .await
+            .expect("failed to list secret usages")
+    );
+
+    assert!(unused_secret
+        .usages(ctx)
+        .await
+        .expect("failed to list secret usages")
+        .is_empty());
+    assert!(SecretUsage::list(ctx, None)
+        .await
+        .expect("failed to list secret usages")
+        .contains(usage));
+}
+
+#[test]
+async fn delete_in_use_secret(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let secret = create_secret(ctx, nw.key_pair.pk()).await;
+    create_component_using_secret(ctx, *secret.id()).await;
+
+    match secret.clone().delete(ctx).await {
+        Err(SecretError::InUse(secret_id, 1)) => assert_eq!(*secret.id(), secret_id),
+        other => panic!("expected the secret to be in use, got {other:?}"),
+    }
+    assert!(Secret::get_by_id(ctx, secret.id())
+        .await
+        .expect("failed to get secret")
+        .is_some());
+
+    let unused_secret = create_secret(ctx, nw.key_pair.pk()).await;
+    unused_secret
+        .clone()
+        .delete(ctx)
+        .await
+        .expect("failed to delete unused secret");
+    assert!(Secret::get_by_id(ctx, unused_secret.id())
+        .await
+        .expect("failed to get secret")
+        .is_none());
+}
+
+#[test]
+async fn decrypt_records_history_event(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let secret = create_secret(ctx, nw.key_pair.pk()).await;
+
+    EncryptedSecret::get_by_id(ctx, secret.id())
+        .await
+        .expect("failed to fetch encrypted secret")
+        .expect("failed to find encrypted secret for tenancy and/or visibility")
+        .decrypt(ctx)
+        .await
+        .expect("failed to decrypt encrypted secret");
+
+    let page = HistoryEvent::list(
+        ctx,
+        &HistoryEventFilter {
+            label_prefix: Some("secret.decrypt".to_owned()),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("cannot list history events");
+    let event = page
+        .events
+        .iter()
+        .find(|event| event.data["id"] == serde_json::json!(secret.id()))
+        .expect("no history event recorded for the decryption");
+    assert_eq!("secret.decrypt", event.label);
+    assert_eq!(serde_json::json!(secret.pk()), event.data["pk"]);
+    assert_eq!(
+        serde_json::json!(SecretBackendKind::Database),
+        event.data["backend"]
+    );
+}
@@ -1,5 +1,5 @@
-use dal::{DalContext, User, UserPk, WorkspaceSignup};
-use dal_test::test;
+use dal::{DalContext, User, UserError, UserPk, WorkspaceRole, WorkspaceSignup};
+use dal_test::{helpers::create_user, test};
 
 #[test]
 async fn new(ctx: &DalContext) {
@@ -16,26 +16,67 @@ async fn new(ctx: &DalContext) {
 
 #[test]
 async fn authorize(ctx: &DalContext, nw: &WorkspaceSignup) {
-    let worked = User::authorize(ctx, &nw.user.pk())
+    let worked = User::authorize(ctx, &nw.user.pk(), WorkspaceRole::Owner)
         .await
-        .expect("admin group user should be authorized");
-    assert!(worked, "authorized admin group user returns true");
+        .expect("cannot authorize user");
+    assert!(worked, "the user who signed up owns the workspace");
 
-    // TODO(theo,paulo): re-enable that when capabilities are back
-    /*
-    let user_no_group = User::new(
-        ctx,
-        UserPk::generate(),
-        "funky",
-        "bobotclown@systeminit.com",
-    )
-    .await
-    .expect("cannot create user");
+    let viewer = create_user(ctx).await;
+    viewer
+        .associate_workspace(ctx, *nw.workspace.pk(), WorkspaceRole::Viewer)
+        .await
+        .expect("cannot associate user to workspace");
+    assert!(User::authorize(ctx, &viewer.pk(), WorkspaceRole::Viewer)
+        .await
+        .expect("cannot authorize user"));
+    assert!(!User::authorize(ctx, &viewer.pk(), WorkspaceRole::Editor)
+        .await
+        .expect("cannot authorize user"));
+
+    let stranger = create_user(ctx).await;
+    assert!(!User::authorize(ctx, &stranger.pk(), WorkspaceRole::Viewer)
+        .await
+        .expect("cannot authorize user"));
+}
+
+#[test]
+async fn set_workspace_role(ctx: &DalContext, nw: &WorkspaceSignup) {
+    let workspace_pk = *nw.workspace.pk();
+    let user = create_user(ctx).await;
+    user.associate_workspace(ctx, workspace_pk, WorkspaceRole::Editor)
+        .await
+        .expect("cannot associate user to workspace");
+
+    // Associating a member again keeps their role
+    user.associate_workspace(ctx, workspace_pk, WorkspaceRole::Owner)
+        .await
+        .expect("cannot associate user to workspace");
+    assert_eq!(
+        Some(WorkspaceRole::Editor),
+        User::workspace_role(ctx, user.pk(), workspace_pk)
+            .await
+            .expect("cannot get workspace role")
+    );
+
+    User::set_workspace_role(ctx, user.pk(), workspace_pk, WorkspaceRole::Applier)
+        .await
+        .expect("cannot set workspace role");
+    assert_eq!(
+        Some(WorkspaceRole::Applier),
+        User::workspace_role(ctx, user.pk(), workspace_pk)
+            .await
+            .expect("cannot get workspace role")
+    );
 
-    let f = User::authorize(ctx, &user_no_group.pk()).await;
+    let result =
+        User::set_workspace_role(ctx, nw.user.pk(), workspace_pk, WorkspaceRole::Viewer).await;
     assert!(
-        f.is_err(),
-        "user that is not in the admin group should fail"
+        matches!(result, Err(UserError::LastOwner(pk)) if pk == workspace_pk),
+        "the last owner can't be demoted"
     );
-    */
+
+    let members = User::list_for_workspace(ctx, workspace_pk)
+        .await
+        .expect("cannot list workspace members");
+    assert_eq!(2, members.len());
 }
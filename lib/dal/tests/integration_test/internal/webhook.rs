@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use dal::{
+    webhook::{sign, WEBHOOK_EVENT_HEADER, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER},
+    ChangeSetPk, ComponentId, DalContext, EncryptedSecret, WebhookClient, WebhookDelivery,
+    WebhookDeliveryStatus, WebhookEndpoint, WebhookEventKind, WorkspacePk, WsEvent, WsPayload,
+};
+use dal_test::test;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    sync::mpsc,
+};
+
+/// A request received by the [`listen`] server.
+struct ReceivedRequest {
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+/// Starts a local HTTP server that responds to every request with the given status, returning
+/// its url and the requests it receives.
+async fn listen(status: u16) -> (String, mpsc::UnboundedReceiver<ReceivedRequest>) {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("cannot bind listener");
+    let url = format!(
+        "http://{}/hook",
+        listener.local_addr().expect("cannot get listener address")
+    );
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let (head_len, content_length) = loop {
+                let read = stream.read(&mut chunk).await.expect("cannot read request");
+                assert!(read > 0, "connection closed before the request was read");
+                buf.extend_from_slice(&chunk[..read]);
+                if let Some(pos) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+                    let head = String::from_utf8_lossy(&buf[..pos]).to_lowercase();
+                    let content_length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |value| value.trim().parse().expect("bad content length"));
+                    break (pos + 4, content_length);
+                }
+            };
+            while buf.len() < head_len + content_length {
+                let read = stream.read(&mut chunk).await.expect("cannot read body");
+                buf.extend_from_slice(&chunk[..read]);
+            }
+
+            let head = String::from_utf8_lossy(&buf[..head_len]).to_string();
+            let headers = head
+                .lines()
+                .skip(1)
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_owned()))
+                .collect();
+            let body =
+                String::from_utf8_lossy(&buf[head_len..head_len + content_length]).to_string();
+
+            let response = format!(
+                "HTTP/1.1 {status} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+            );
+            stream
+                .write_all(response.as_bytes())
+                .await
+                .expect("cannot write response");
+            let _ = tx.send(ReceivedRequest { headers, body });
+        }
+    });
+
+    (url, rx)
+}
+
+fn workspace_pk(ctx: &DalContext) -> WorkspacePk {
+    ctx.tenancy()
+        .workspace_pk()
+        .expect("no workspace in tenancy")
+}
+
+#[test]
+async fn enqueue_for_event_only_matches_subscribed_endpoints(ctx: &DalContext) {
+    let (subscribed, _) = WebhookEndpoint::new(
+        ctx,
+        workspace_pk(ctx),
+        "http://127.0.0.1:1/subscribed",
+        vec![WebhookEventKind::ChangeSetApplied],
+    )
+    .await
+    .expect("cannot create webhook endpoint");
+    let (_other_kind, _) = WebhookEndpoint::new(
+        ctx,
+        workspace_pk(ctx),
+        "http://127.0.0.1:1/other",
+        vec![WebhookEventKind::FixBatchReturn],
+    )
+    .await
+    .expect("cannot create webhook endpoint");
+    let (mut disabled, _) = WebhookEndpoint::new(
+        ctx,
+        workspace_pk(ctx),
+        "http://127.0.0.1:1/disabled",
+        vec![WebhookEventKind::ChangeSetApplied],
+    )
+    .await
+    .expect("cannot create webhook endpoint");
+    disabled
+        .update(ctx, vec![WebhookEventKind::ChangeSetApplied], false)
+        .await
+        .expect("cannot disable webhook endpoint");
+
+    let event = WsEvent::new(ctx, WsPayload::ChangeSetApplied(ChangeSetPk::NONE))
+        .await
+        .expect("cannot create event");
+    let deliveries =
+        WebhookDelivery::enqueue_for_event(ctx, WebhookEventKind::ChangeSetApplied, &event)
+            .await
+            .expect("cannot enqueue webhook deliveries");
+
+    assert_eq!(1, deliveries.len());
+    let delivery = &deliveries[0];
+    assert_eq!(subscribed.pk(), delivery.endpoint_pk());
+    assert_eq!(WebhookDeliveryStatus::Pending, delivery.status());
+    assert_eq!(
+        &serde_json::json!({
+            "event": "ChangeSetApplied",
+            "workspacePk": workspace_pk(ctx),
+            "changeSetPk": ctx.visibility().change_set_pk,
+            "payload": { "kind": "ChangeSetApplied", "data": ChangeSetPk::NONE },
+        }),
+        delivery.payload()
+    );
+}
+
+#[test]
+async fn attempt_sends_signed_payload(ctx: &DalContext) {
+    let (url, mut requests) = listen(200).await;
+    let (endpoint, secret) = WebhookEndpoint::new(
+        ctx,
+        workspace_pk(ctx),
+        &url,
+        vec![WebhookEventKind::ChangeSetApplied],
+    )
+    .await
+    .expect("cannot create webhook endpoint");
+
+    let event = WsEvent::new(ctx, WsPayload::ChangeSetApplied(ChangeSetPk::NONE))
+        .await
+        .expect("cannot create event");
+    let mut delivery =
+        WebhookDelivery::enqueue_for_event(ctx, WebhookEventKind::ChangeSetApplied, &event)
+            .await
+            .expect("cannot enqueue webhook deliveries")
+            .pop()
+            .expect("no webhook delivery");
+
+    let succeeded = delivery
+        .attempt(ctx, &WebhookClient::allowing_private_destinations(), false)
+        .await
+        .expect("cannot attempt webhook delivery");
+    assert!(succeeded);
+    assert_eq!(WebhookDeliveryStatus::Succeeded, delivery.status());
+    assert_eq!(1, delivery.attempts());
+    assert_eq!(Some(200), delivery.response_status());
+    assert!(delivery.delivered_at().is_some());
+
+    let request = requests.recv().await.expect("no request received");
+    let body: serde_json::Value = serde_json::from_str(&request.body).expect("body is not json");
+    assert_eq!(delivery.payload(), &body);
+    assert_eq!(
+        Some("ChangeSetApplied"),
+        request
+            .headers
+            .get(&WEBHOOK_EVENT_HEADER.to_lowercase())
+            .map(String::as_str)
+    );
+    let timestamp = request
+        .headers
+        .get(&WEBHOOK_TIMESTAMP_HEADER.to_lowercase())
+        .expect("no timestamp header");
+    assert_eq!(
+        Some(&sign(&secret, timestamp, &request.body)),
+        request
+            .headers
+            .get(&WEBHOOK_SIGNATURE_HEADER.to_lowercase())
+    );
+
+    let deliveries = WebhookDelivery::list_for_endpoint(ctx, endpoint.pk(), 10)
+        .await
+        .expect("cannot list webhook deliveries");
+    assert_eq!(vec![delivery], deliveries);
+}
+
+#[test]
+async fn attempt_records_failures(ctx: &DalContext) {
+    let (url, mut requests) = listen(500).await;
+    WebhookEndpoint::new(
+        ctx,
+        workspace_pk(ctx),
+        &url,
+        vec![WebhookEventKind::ChangeSetApplied],
+    )
+    .await
+    .expect("cannot create webhook endpoint");
+
+    let event = WsEvent::new(ctx, WsPayload::ChangeSetApplied(ChangeSetPk::NONE))
+        .await
+        .expect("cannot create event");
+    let mut delivery =
+        WebhookDelivery::enqueue_for_event(ctx, WebhookEventKind::ChangeSetApplied, &event)
+            .await
+            .expect("cannot enqueue webhook deliveries")
+            .pop()
+            .expect("no webhook delivery");
+    let client = WebhookClient::allowing_private_destinations();
+
+    assert!(!delivery
+        .attempt(ctx, &client, false)
+        .await
+        .expect("cannot attempt webhook delivery"));
+    assert_eq!(WebhookDeliveryStatus::Pending, delivery.status());
+    assert_eq!(Some(500), delivery.response_status());
+    assert!(delivery.last_error().is_some());
+
+    assert!(!delivery
+        .attempt(ctx, &client, true)
+        .await
+        .expect("cannot attempt webhook delivery"));
+    assert_eq!(WebhookDeliveryStatus::Failed, delivery.status());
+    assert_eq!(2, delivery.attempts());
+    assert!(delivery.delivered_at().is_none());
+
+    requests.recv().await.expect("no request received");
+    requests.recv().await.expect("no request received");
+}
+
+#[test]
+async fn attempt_refuses_private_destinations(ctx: &DalContext) {
+    let (url, mut requests) = listen(200).await;
+    WebhookEndpoint::new(
+        ctx,
+        workspace_pk(ctx),
+        &url,
+        vec![WebhookEventKind::ChangeSetApplied],
+    )
+    .await
+    .expect("cannot create webhook endpoint");
+
+    let event = WsEvent::new(ctx, WsPayload::ChangeSetApplied(ChangeSetPk::NONE))
+        .await
+        .expect("cannot create event");
+    let mut delivery =
+        WebhookDelivery::enqueue_for_event(ctx, WebhookEventKind::ChangeSetApplied, &event)
+            .await
+            .expect("cannot enqueue webhook deliveries")
+            .pop()
+            .expect("no webhook delivery");
+
+    assert!(!delivery
+        .attempt(ctx, &WebhookClient::new(), true)
+        .await
+        .expect("cannot attempt webhook delivery"));
+    assert_eq!(WebhookDeliveryStatus::Failed, delivery.status());
+    assert_eq!(None, delivery.response_status());
+    assert!(delivery
+        .last_error()
+        .expect("no error recorded")
+        .contains("not a public address"));
+    assert!(requests.try_recv().is_err());
+}
+
+#[test]
+async fn reencrypt_secrets_batch_rotates_symmetric_key(ctx: &DalContext) {
+    let (url, mut requests) = listen(200).await;
+    let (endpoint, secret) = WebhookEndpoint::new(
+        ctx,
+        workspace_pk(ctx),
+        &url,
+        vec![WebhookEventKind::ChangeSetApplied],
+    )
+    .await
+    .expect("cannot create webhook endpoint");
+    ctx.blocking_commit().await.expect("failed to commit");
+
+    // The rotated context is never committed so that other tests keep using the test key
+    let services_context =
+        dal_test::services_context_with_rotated_symmetric_key(&ctx.services_context())
+            .await
+            .expect("cannot build services context with rotated key");
+    let rotated_ctx = services_context
+        .into_builder(true)
+        .build(ctx.access_builder().build(*ctx.visibility()))
+        .await
+        .expect("cannot build rotated dal context");
+
+    let mut after = None;
+    loop {
+        let batch = WebhookEndpoint::reencrypt_secrets_batch(&rotated_ctx, after, 100)
+            .await
+            .expect("failed to re-encrypt webhook endpoint secrets");
+        assert!(batch.failed.is_empty());
+        after = batch.last_pk;
+        if after.is_none() {
+            break;
+        }
+    }
+
+    let active_key_hash = *rotated_ctx.symmetric_crypto_service().active_key_hash();
+    let usage = EncryptedSecret::key_hash_usage(&rotated_ctx)
+        .await
+        .expect("failed to fetch key hash usage");
+    assert!(usage
+        .iter()
+        .any(|usage| usage.key_hash == active_key_hash && usage.count >= 1));
+
+    // The endpoint's requests are still signed with its original secret
+    let event = WsEvent::new(&rotated_ctx, WsPayload::ChangeSetApplied(ChangeSetPk::NONE))
+        .await
+        .expect("cannot create event");
+    let mut delivery = WebhookDelivery::enqueue_for_event(
+        &rotated_ctx,
+        WebhookEventKind::ChangeSetApplied,
+        &event,
+    )
+    .await
+    .expect("cannot enqueue webhook deliveries")
+    .into_iter()
+    .find(|delivery| delivery.endpoint_pk() == endpoint.pk())
+    .expect("no webhook delivery");
+    assert!(delivery
+        .attempt(
+            &rotated_ctx,
+            &WebhookClient::allowing_private_destinations(),
+            true
+        )
+        .await
+        .expect("cannot attempt webhook delivery"));
+
+    let request = requests.recv().await.expect("no request received");
+    let timestamp = request
+        .headers
+        .get(&WEBHOOK_TIMESTAMP_HEADER.to_lowercase())
+        .expect("no timestamp header");
+    assert_eq!(
+        Some(&sign(&secret, timestamp, &request.body)),
+        request
+            .headers
+            .get(&WEBHOOK_SIGNATURE_HEADER.to_lowercase())
+    );
+
+    rotated_ctx
+        .rollback()
+        .await
+        .expect("failed to roll back rotation");
+}
+
+#[test]
+async fn record_qualifications_failing(ctx: &DalContext) {
+    let component_id = ComponentId::generate();
+    let record =
+        |failing| WebhookDelivery::record_qualifications_failing(ctx, component_id, failing);
+
+    assert!(!record(false).await.expect("cannot record qualifications"));
+    assert!(record(true).await.expect("cannot record qualifications"));
+    assert!(!record(true).await.expect("cannot record qualifications"));
+    assert!(!record(false).await.expect("cannot record qualifications"));
+    assert!(record(true).await.expect("cannot record qualifications"));
+}
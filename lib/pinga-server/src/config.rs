@@ -11,7 +11,7 @@ use telemetry::prelude::*;
 use thiserror::Error;
 
 pub use dal::CycloneKeyPair;
-pub use si_settings::{StandardConfig, StandardConfigFile};
+pub use si_settings::{ConfigReload, ReloadableConfigFile, StandardConfig, StandardConfigFile};
 use ulid::Ulid;
 
 const DEFAULT_CONCURRENCY_LIMIT: usize = 5;
@@ -113,6 +113,9 @@ pub struct ConfigFile {
     instance_id: String,
     #[serde(default = "default_symmetric_crypto_config")]
     symmetric_crypto_service: SymmetricCryptoServiceConfigFile,
+    /// Tracing directives replacing the ones from `SI_LOG`, such as `info,pinga_server=debug`.
+    #[serde(default)]
+    log_filter: Option<String>,
 }
 
 impl Default for ConfigFile {
@@ -124,14 +127,35 @@ impl Default for ConfigFile {
             concurrency_limit: default_concurrency_limit(),
             instance_id: random_instance_id(),
             symmetric_crypto_service: default_symmetric_crypto_config(),
+            log_filter: None,
         }
     }
 }
 
+impl ConfigFile {
+    /// Gets the config file's tracing directives, if any.
+    pub fn log_filter(&self) -> Option<&str> {
+        self.log_filter.as_deref()
+    }
+
+    pub(crate) fn concurrency_limit(&self) -> usize {
+        self.concurrency_limit
+    }
+
+    pub(crate) fn pg_pool_max_size(&self) -> usize {
+        self.pg.pool_max_size
+    }
+}
+
 impl StandardConfigFile for ConfigFile {
     type Error = ConfigError;
 }
 
+impl ReloadableConfigFile for ConfigFile {
+    const LIVE_SETTINGS: &'static [&'static str] =
+        &["concurrency_limit", "log_filter", "pg.pool_max_size"];
+}
+
 impl TryFrom<ConfigFile> for Config {
     type Error = ConfigError;
 
@@ -3,10 +3,11 @@ pub mod server;
 
 pub use crate::{
     config::{
-        detect_and_configure_development, Config, ConfigBuilder, ConfigError, ConfigFile,
-        StandardConfig, StandardConfigFile,
+        detect_and_configure_development, start_config_reload_signal_handler_task, Config,
+        ConfigBuilder, ConfigError, ConfigFile, ConfigReload, ReloadableConfigFile, StandardConfig,
+        StandardConfigFile,
     },
-    server::{Server, ServerError},
+    server::{PingaConfigReloadHandle, Server, ServerError},
 };
 
 const NATS_JOBS_DEFAULT_SUBJECT: &str = "pinga-jobs";
@@ -1,9 +1,15 @@
-use std::{io, path::Path, sync::Arc};
+use std::{
+    io,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
 
 use dal::{
     job::{
         consumer::{JobConsumer, JobConsumerError, JobInfo},
-        definition::{FixesJob, RefreshJob},
+        definition::{
+            FixesJob, HealthCheckJob, RefreshJob, SymmetricKeyRotationJob, WebhookDeliveryJob,
+        },
         producer::BlockingJobError,
     },
     DalContext, DalContextBuilder, DependentValuesUpdate, InitializationError, JobFailure,
@@ -21,14 +27,14 @@ use tokio::{
     signal::unix,
     sync::{
         mpsc::{self, UnboundedReceiver, UnboundedSender},
-        oneshot, watch,
+        oneshot, watch, OwnedSemaphorePermit, Semaphore,
     },
     task,
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use veritech_client::{Client as VeritechClient, EncryptionKey, EncryptionKeyError};
 
-use crate::{nats_jobs_subject, Config, NATS_JOBS_DEFAULT_QUEUE};
+use crate::{nats_jobs_subject, Config, ConfigFile, ConfigReload, NATS_JOBS_DEFAULT_QUEUE};
 
 #[remain::sorted]
 #[derive(Debug, Error)]
@@ -80,7 +86,7 @@ impl From<TransactionsError> for ServerError {
 type Result<T> = std::result::Result<T, ServerError>;
 
 pub struct Server {
-    concurrency_limit: usize,
+    concurrency_limit: ConcurrencyLimit,
     services_context: ServicesContext,
     /// An internal shutdown watch receiver handle which can be provided to internal tasks which
     /// want to be notified when a shutdown event is in progress.
@@ -151,7 +157,7 @@ impl Server {
             prepare_graceful_shutdown(external_shutdown_rx, shutdown_watch_tx)?;
 
         Ok(Server {
-            concurrency_limit,
+            concurrency_limit: ConcurrencyLimit::new(concurrency_limit),
             services_context,
             shutdown_watch_rx,
             external_shutdown_tx,
@@ -185,6 +191,15 @@ impl Server {
         Ok(())
     }
 
+    /// Gets a [`PingaConfigReloadHandle`] that applies the live settings of a reloaded config to
+    /// the running server.
+    pub fn config_reload_handle(&self) -> PingaConfigReloadHandle {
+        PingaConfigReloadHandle {
+            concurrency_limit: self.concurrency_limit.clone(),
+            pg_pool: self.services_context.pg_pool().clone(),
+        }
+    }
+
     /// Gets a [`ShutdownHandle`](PingaShutdownHandle) that can externally or on demand trigger the server's shutdown
     /// process.
     pub fn shutdown_handle(&self) -> PingaShutdownHandle {
@@ -250,6 +265,67 @@ impl PingaShutdownHandle {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct PingaConfigReloadHandle {
+    concurrency_limit: ConcurrencyLimit,
+    pg_pool: PgPool,
+}
+
+impl PingaConfigReloadHandle {
+    /// Applies the concurrency limit and database pool size of a reloaded config.
+    pub fn apply(&self, reload: &ConfigReload<ConfigFile>) {
+        if reload.has_changed("concurrency_limit") {
+            self.concurrency_limit
+                .set(reload.config_file.concurrency_limit());
+        }
+        if reload.has_changed("pg.pool_max_size") {
+            self.pg_pool.resize(reload.config_file.pg_pool_max_size());
+        }
+    }
+}
+
+/// The number of jobs processed at once, which can be changed while the server is running.
+#[derive(Clone, Debug)]
+struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+    limit: Arc<Mutex<usize>>,
+}
+
+impl ConcurrencyLimit {
+    fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            limit: Arc::new(Mutex::new(limit)),
+        }
+    }
+
+    fn set(&self, limit: usize) {
+        let limit = limit.max(1);
+        let mut current = self.limit.lock().unwrap_or_else(PoisonError::into_inner);
+        if limit > *current {
+            self.semaphore.add_permits(limit - *current);
+        } else if limit < *current {
+            // Permits held by running jobs can't be taken back, so the extra permits are retired
+            // as those jobs finish
+            let retired = u32::try_from(*current - limit).unwrap_or(u32::MAX);
+            let semaphore = self.semaphore.clone();
+            drop(task::spawn(async move {
+                if let Ok(permits) = semaphore.acquire_many_owned(retired).await {
+                    permits.forget();
+                }
+            }));
+        }
+        info!(from = *current, to = limit, "changed job concurrency limit");
+        *current = limit;
+    }
+
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        // The semaphore is never closed
+        self.semaphore.clone().acquire_owned().await.ok()
+    }
+}
+
 #[remain::sorted]
 #[derive(Debug, Eq, PartialEq)]
 pub enum ShutdownSource {
@@ -336,35 +412,47 @@ async fn receive_job_requests(
     Ok(())
 }
 
-async fn process_job_requests_task(rx: UnboundedReceiver<JobItem>, concurrency_limit: usize) {
+async fn process_job_requests_task(
+    rx: UnboundedReceiver<JobItem>,
+    concurrency_limit: ConcurrencyLimit,
+) {
+    // The limit is enforced by the semaphore rather than the stream so that it can be changed
+    // while jobs are being processed
     UnboundedReceiverStream::new(rx)
-        .for_each_concurrent(concurrency_limit, |job| async move {
-            // Got the next message from the subscriber
-            trace!("pulled request into an available concurrent task");
-
-            match job.request {
-                Ok(request) => {
-                    // Spawn a task and process the request
-                    let join_handle = task::spawn(execute_job_task(
-                        job.metadata,
-                        job.messaging_destination,
-                        job.ctx_builder,
-                        request,
-                    ));
-                    if let Err(err) = join_handle.await {
-                        // NOTE(fnichol): This likely happens when there is contention or
-                        // an error in the Tokio runtime so we will be loud and log an
-                        // error under the assumptions that 1) this event rarely
-                        // happens and 2) the task code did not contribute to trigger
-                        // the `JoinError`.
-                        error!(
+        .for_each_concurrent(None, |job| {
+            let concurrency_limit = concurrency_limit.clone();
+            async move {
+                let _permit = concurrency_limit.acquire().await;
+                // Got the next message from the subscriber
+                trace!("pulled request into an available concurrent task");
+
+                match job.request {
+                    Ok(request) => {
+                        // Spawn a task and process the request
+                        let join_handle = task::spawn(execute_job_task(
+                            job.metadata,
+                            job.messaging_destination,
+                            job.ctx_builder,
+                            request,
+                        ));
+                        if let Err(err) = join_handle.await {
+                            // NOTE(fnichol): This likely happens when there is contention or
+                            // an error in the Tokio runtime so we will be loud and log an
+                            // error under the assumptions that 1) this event rarely
+                            // happens and 2) the task code did not contribute to trigger
+                            // the `JoinError`.
+                            error!(
+                                error = ?err,
+                                "execute-job-task failed to execute to completion"
+                            );
+                        };
+                    }
+                    Err(err) => {
+                        warn!(
                             error = ?err,
-                            "execute-job-task failed to execute to completion"
+                            "next job request had an error, job will not be executed"
                         );
-                    };
-                }
-                Err(err) => {
-                    warn!(error = ?err, "next job request had an error, job will not be executed");
+                    }
                 }
             }
         })
@@ -477,18 +565,27 @@ async fn execute_job(
         tracing::Span::current().record("job_info.blocking", job_info.blocking);
     }
 
-    let job =
-        match job_info.kind.as_str() {
-            stringify!(DependentValuesUpdate) => {
-                Box::new(DependentValuesUpdate::try_from(job_info.clone())?)
-                    as Box<dyn JobConsumer + Send + Sync>
-            }
-            stringify!(FixesJob) => Box::new(FixesJob::try_from(job_info.clone())?)
-                as Box<dyn JobConsumer + Send + Sync>,
-            stringify!(RefreshJob) => Box::new(RefreshJob::try_from(job_info.clone())?)
-                as Box<dyn JobConsumer + Send + Sync>,
-            kind => return Err(ServerError::UnknownJobKind(kind.to_owned())),
-        };
+    let job = match job_info.kind.as_str() {
+        stringify!(DependentValuesUpdate) => {
+            Box::new(DependentValuesUpdate::try_from(job_info.clone())?)
+                as Box<dyn JobConsumer + Send + Sync>
+        }
+        stringify!(FixesJob) => {
+            Box::new(FixesJob::try_from(job_info.clone())?) as Box<dyn JobConsumer + Send + Sync>
+        }
+        stringify!(HealthCheckJob) => Box::new(HealthCheckJob::try_from(job_info.clone())?)
+            as Box<dyn JobConsumer + Send + Sync>,
+        stringify!(RefreshJob) => {
+            Box::new(RefreshJob::try_from(job_info.clone())?) as Box<dyn JobConsumer + Send + Sync>
+        }
+        stringify!(SymmetricKeyRotationJob) => {
+            Box::new(SymmetricKeyRotationJob::try_from(job_info.clone())?)
+                as Box<dyn JobConsumer + Send + Sync>
+        }
+        stringify!(WebhookDeliveryJob) => Box::new(WebhookDeliveryJob::try_from(job_info.clone())?)
+            as Box<dyn JobConsumer + Send + Sync>,
+        kind => return Err(ServerError::UnknownJobKind(kind.to_owned())),
+    };
 
     info!("Processing job");
 
@@ -570,3 +667,43 @@ fn prepare_graceful_shutdown(
 
     Ok(graceful_shutdown_rx)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Yields until the retiring tasks spawned by [`ConcurrencyLimit::set`] have caught up.
+    async fn available_permits_settle(limit: &ConcurrencyLimit, expected: usize) {
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while limit.semaphore.available_permits() != expected {
+                task::yield_now().await;
+            }
+        })
+        .await
+        .expect("available permits never settled");
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_set_grows_and_shrinks() {
+        let limit = ConcurrencyLimit::new(2);
+        assert_eq!(2, limit.semaphore.available_permits());
+
+        limit.set(4);
+        assert_eq!(4, limit.semaphore.available_permits());
+
+        // Free permits are retired right away, while a permit held by a running job counts
+        // against the new limit
+        let permit = limit.acquire().await.expect("semaphore is never closed");
+        limit.set(1);
+        available_permits_settle(&limit, 0).await;
+        drop(permit);
+        available_permits_settle(&limit, 1).await;
+
+        limit.set(3);
+        assert_eq!(3, limit.semaphore.available_permits());
+
+        // A limit of zero would never process a job again
+        limit.set(0);
+        available_permits_settle(&limit, 1).await;
+    }
+}
@@ -0,0 +1,355 @@
+use std::time::Duration;
+
+use reqwest::{header, RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use url::Url;
+
+use crate::types::{
+    ApiErrorResponse, ApplyChangeSetRequest, ApplyChangeSetResponse, ChangeSetMutation,
+    ChangeSetPk, CreateChangeSetRequest, CreateChangeSetResponse, CreateConnectionRequest,
+    CreateConnectionResponse, CreateFuncRequest, CreateFuncResponse, CreateNodeRequest,
+    CreateNodeResponse, DeleteComponentRequest, DeleteConnectionRequest, ExecuteRequest,
+    ExecuteResponse, ExportPkgFileResponse, ExportPkgRequest, ExportPkgResponse, FixesRunRequest,
+    FixesRunResponse, GetChangeSetRequest, GetChangeSetResponse, GetDiagramRequest,
+    GetDiagramResponse, GetFuncRequest, GetFuncResponse, GetPropertyEditorSchemaRequest,
+    GetPropertyEditorSchemaResponse, GetPropertyEditorValuesRequest,
+    GetPropertyEditorValuesResponse, InsertPropertyEditorValueRequest, InstallPkgFileRequest,
+    InstallPkgRequest, InstallPkgResponse, ListFixesRequest, ListFixesResponse, ListFuncsRequest,
+    ListFuncsResponse, ListOpenChangeSetsResponse, ListSchemaVariantsRequest,
+    ListSchemaVariantsResponse, PkgListRequest, PkgListResponse, QueryComponentsRequest,
+    QueryComponentsResponse, RateLimitMetrics, Resume, SaveFuncRequest, SaveFuncResponse,
+    SdfClientError, SdfClientResult, SetNodePositionRequest, SetNodePositionResponse,
+    SetTypeRequest, UpdatePropertyEditorValueRequest,
+};
+use crate::ws::WorkspaceUpdates;
+
+/// The header sdf sets when it created a change set to make a change requested at head in.
+const FORCE_CHANGE_SET_PK_HEADER: &str = "force_changeset_pk";
+
+/// A client of sdf's api, authenticated with either a user's session token or an api token.
+#[derive(Debug, Clone)]
+pub struct SdfClient {
+    base_url: Url,
+    auth_token: String,
+    http: reqwest::Client,
+}
+
+impl SdfClient {
+    pub fn new(base_url: Url, auth_token: &str) -> Self {
+        Self {
+            base_url,
+            auth_token: auth_token
+                .strip_prefix("Bearer ")
+                .unwrap_or(auth_token)
+                .to_owned(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    pub async fn list_open_change_sets(&self) -> SdfClientResult<ListOpenChangeSetsResponse> {
+        self.get("api/change_set/list_open_change_sets", &()).await
+    }
+
+    pub async fn create_change_set(
+        &self,
+        request: &CreateChangeSetRequest,
+    ) -> SdfClientResult<CreateChangeSetResponse> {
+        self.post("api/change_set/create_change_set", request).await
+    }
+
+    pub async fn get_change_set(
+        &self,
+        request: &GetChangeSetRequest,
+    ) -> SdfClientResult<GetChangeSetResponse> {
+        self.get("api/change_set/get_change_set", request).await
+    }
+
+    pub async fn apply_change_set(
+        &self,
+        request: &ApplyChangeSetRequest,
+    ) -> SdfClientResult<ApplyChangeSetResponse> {
+        self.post("api/change_set/apply_change_set", request).await
+    }
+
+    pub async fn query_components(
+        &self,
+        request: &QueryComponentsRequest,
+    ) -> SdfClientResult<QueryComponentsResponse> {
+        self.get("api/component/query", request).await
+    }
+
+    pub async fn get_property_editor_schema(
+        &self,
+        request: &GetPropertyEditorSchemaRequest,
+    ) -> SdfClientResult<GetPropertyEditorSchemaResponse> {
+        self.get("api/component/get_property_editor_schema", request)
+            .await
+    }
+
+    pub async fn get_property_editor_values(
+        &self,
+        request: &GetPropertyEditorValuesRequest,
+    ) -> SdfClientResult<GetPropertyEditorValuesResponse> {
+        self.get("api/component/get_property_editor_values", request)
+            .await
+    }
+
+    pub async fn update_property_editor_value(
+        &self,
+        request: &UpdatePropertyEditorValueRequest,
+    ) -> SdfClientResult<ChangeSetMutation<()>> {
+        self.post_mutation("api/component/update_property_editor_value", request)
+            .await
+    }
+
+    pub async fn insert_property_editor_value(
+        &self,
+        request: &InsertPropertyEditorValueRequest,
+    ) -> SdfClientResult<ChangeSetMutation<()>> {
+        self.post_mutation("api/component/insert_property_editor_value", request)
+            .await
+    }
+
+    pub async fn set_component_type(
+        &self,
+        request: &SetTypeRequest,
+    ) -> SdfClientResult<ChangeSetMutation<()>> {
+        self.post_mutation("api/component/set_type", request).await
+    }
+
+    pub async fn get_diagram(
+        &self,
+        request: &GetDiagramRequest,
+    ) -> SdfClientResult<GetDiagramResponse> {
+        self.get("api/diagram/get_diagram", request).await
+    }
+
+    pub async fn list_schema_variants(
+        &self,
+        request: &ListSchemaVariantsRequest,
+    ) -> SdfClientResult<ListSchemaVariantsResponse> {
+        self.get("api/diagram/list_schema_variants", request).await
+    }
+
+    pub async fn create_node(
+        &self,
+        request: &CreateNodeRequest,
+    ) -> SdfClientResult<ChangeSetMutation<CreateNodeResponse>> {
+        self.post_mutation("api/diagram/create_node", request).await
+    }
+
+    pub async fn set_node_position(
+        &self,
+        request: &SetNodePositionRequest,
+    ) -> SdfClientResult<SetNodePositionResponse> {
+        self.post("api/diagram/set_node_position", request).await
+    }
+
+    pub async fn delete_component(
+        &self,
+        request: &DeleteComponentRequest,
+    ) -> SdfClientResult<ChangeSetMutation<()>> {
+        self.post_mutation("api/diagram/delete_component", request)
+            .await
+    }
+
+    pub async fn create_connection(
+        &self,
+        request: &CreateConnectionRequest,
+    ) -> SdfClientResult<ChangeSetMutation<CreateConnectionResponse>> {
+        self.post_mutation("api/diagram/create_connection", request)
+            .await
+    }
+
+    pub async fn delete_connection(
+        &self,
+        request: &DeleteConnectionRequest,
+    ) -> SdfClientResult<ChangeSetMutation<()>> {
+        self.post_mutation("api/diagram/delete_connection", request)
+            .await
+    }
+
+    pub async fn list_funcs(
+        &self,
+        request: &ListFuncsRequest,
+    ) -> SdfClientResult<ListFuncsResponse> {
+        self.get("api/func/list_funcs", request).await
+    }
+
+    pub async fn get_func(&self, request: &GetFuncRequest) -> SdfClientResult<GetFuncResponse> {
+        self.get("api/func/get_func", request).await
+    }
+
+    pub async fn create_func(
+        &self,
+        request: &CreateFuncRequest,
+    ) -> SdfClientResult<ChangeSetMutation<CreateFuncResponse>> {
+        self.post_mutation("api/func/create_func", request).await
+    }
+
+    pub async fn save_func(
+        &self,
+        request: &SaveFuncRequest,
+    ) -> SdfClientResult<ChangeSetMutation<SaveFuncResponse>> {
+        self.post_mutation("api/func/save_func", request).await
+    }
+
+    pub async fn execute_func(&self, request: &ExecuteRequest) -> SdfClientResult<ExecuteResponse> {
+        self.post("api/func/execute", request).await
+    }
+
+    pub async fn list_pkgs(&self, request: &PkgListRequest) -> SdfClientResult<PkgListResponse> {
+        self.get("api/pkg/list_pkgs", request).await
+    }
+
+    pub async fn export_pkg(
+        &self,
+        request: &ExportPkgRequest,
+    ) -> SdfClientResult<ExportPkgResponse> {
+        self.post("api/pkg/export_pkg", request).await
+    }
+
+    pub async fn install_pkg(
+        &self,
+        request: &InstallPkgRequest,
+    ) -> SdfClientResult<InstallPkgResponse> {
+        self.post("api/pkg/install_pkg", request).await
+    }
+
+    /// Packages schema variants as a module, returning the package file instead of publishing it
+    /// to the module index.
+    pub async fn export_pkg_file(
+        &self,
+        request: &ExportPkgRequest,
+    ) -> SdfClientResult<ExportPkgFileResponse> {
+        self.post("api/pkg/export_pkg_file", request).await
+    }
+
+    pub async fn install_pkg_file(
+        &self,
+        request: &InstallPkgFileRequest,
+    ) -> SdfClientResult<InstallPkgResponse> {
+        self.post("api/pkg/install_pkg_file", request).await
+    }
+
+    pub async fn list_fixes(
+        &self,
+        request: &ListFixesRequest,
+    ) -> SdfClientResult<ListFixesResponse> {
+        self.get("api/fix/list", request).await
+    }
+
+    pub async fn run_fixes(&self, request: &FixesRunRequest) -> SdfClientResult<FixesRunResponse> {
+        self.post("api/fix/run", request).await
+    }
+
+    pub async fn rate_limit_metrics(&self) -> SdfClientResult<RateLimitMetrics> {
+        self.get("api/rate_limit/metrics", &()).await
+    }
+
+    /// Connects to the websocket streaming the events of the workspace, resuming after the last
+    /// event a previous connection received when `resume` is set.
+    pub async fn workspace_updates(&self, resume: Resume) -> SdfClientResult<WorkspaceUpdates> {
+        let mut url = self.base_url.join("api/ws/workspace_updates")?;
+        let scheme = match url.scheme() {
+            "https" => "wss",
+            _ => "ws",
+        };
+        // Only fails when changing between special and non-special schemes, which these aren't
+        let _ = url.set_scheme(scheme);
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("token", &format!("Bearer {}", self.auth_token));
+            if let Some(stream_id) = &resume.stream_id {
+                query.append_pair("streamId", stream_id);
+            }
+            if let Some(resume_from) = resume.resume_from {
+                query.append_pair("resumeFrom", &resume_from.to_string());
+            }
+        }
+
+        WorkspaceUpdates::connect(url, resume).await
+    }
+
+    async fn get<Req: Serialize + ?Sized, Res: DeserializeOwned>(
+        &self,
+        path: &str,
+        request: &Req,
+    ) -> SdfClientResult<Res> {
+        let url = self.base_url.join(path)?;
+        let response = self.send(self.http.get(url).query(request)).await?;
+        Ok(response.json::<Res>().await?)
+    }
+
+    async fn post<Req: Serialize + ?Sized, Res: DeserializeOwned>(
+        &self,
+        path: &str,
+        request: &Req,
+    ) -> SdfClientResult<Res> {
+        let url = self.base_url.join(path)?;
+        let response = self.send(self.http.post(url).json(request)).await?;
+        Ok(response.json::<Res>().await?)
+    }
+
+    async fn post_mutation<Req: Serialize + ?Sized, Res: DeserializeOwned>(
+        &self,
+        path: &str,
+        request: &Req,
+    ) -> SdfClientResult<ChangeSetMutation<Res>> {
+        let url = self.base_url.join(path)?;
+        let response = self.send(self.http.post(url).json(request)).await?;
+
+        let forced_change_set_pk = match response.headers().get(FORCE_CHANGE_SET_PK_HEADER) {
+            Some(value) => {
+                let value = value
+                    .to_str()
+                    .map_err(|_| SdfClientError::InvalidForceChangeSetPk(format!("{value:?}")))?;
+                Some(
+                    value
+                        .parse::<ChangeSetPk>()
+                        .map_err(|_| SdfClientError::InvalidForceChangeSetPk(value.to_owned()))?,
+                )
+            }
+            None => None,
+        };
+
+        // Some mutations respond with an empty body
+        let body = response.bytes().await?;
+        let body: &[u8] = if body.is_empty() { b"null" } else { &body };
+
+        Ok(ChangeSetMutation {
+            response: serde_json::from_slice(body)?,
+            forced_change_set_pk,
+        })
+    }
+
+    async fn send(&self, request: RequestBuilder) -> SdfClientResult<Response> {
+        let response = request.bearer_auth(&self.auth_token).send().await?;
+        let status = response.status();
+        if status.is_client_error() || status.is_server_error() {
+            let retry_after = response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs);
+            let body = response.text().await?;
+            let message = match serde_json::from_str::<ApiErrorResponse>(&body) {
+                Ok(error_response) => error_response.error.message,
+                Err(_) => body,
+            };
+            return Err(match (status, retry_after) {
+                (StatusCode::TOO_MANY_REQUESTS, Some(retry_after)) => {
+                    SdfClientError::RateLimited(retry_after, message)
+                }
+                _ => SdfClientError::Api(status, message),
+            });
+        }
+
+        Ok(response)
+    }
+}
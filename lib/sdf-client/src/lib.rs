@@ -0,0 +1,9 @@
+pub mod client;
+pub mod types;
+pub mod ws;
+
+pub use client::SdfClient;
+pub use types::{ChangeSetMutation, SdfClientError, SdfClientResult};
+pub use ws::WorkspaceUpdates;
+
+pub const DEFAULT_URL: &str = "http://localhost:5156";
@@ -0,0 +1,65 @@
+//! The requests and responses of sdf's api, defined here rather than shared with sdf so that
+//! scripts using the client don't have to build sdf and the dal.
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+
+pub use self::{
+    change_set::*, component::*, diagram::*, fix::*, func::*, id::*, pkg::*, rate_limit::*,
+    visibility::*, ws::*,
+};
+
+mod change_set;
+mod component;
+mod diagram;
+mod fix;
+mod func;
+mod id;
+mod pkg;
+mod rate_limit;
+mod visibility;
+mod ws;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum SdfClientError {
+    #[error("sdf returned {0}: {1}")]
+    Api(StatusCode, String),
+    #[error("invalid force_changeset_pk header: {0}")]
+    InvalidForceChangeSetPk(String),
+    #[error("rate limited, retry in {0:?}: {1}")]
+    RateLimited(Duration, String),
+    #[error("Request error: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("json serialization error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("Url parse error: {0}")]
+    UrlParse(#[from] url::ParseError),
+    #[error("websocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+}
+
+pub type SdfClientResult<T> = Result<T, SdfClientError>;
+
+/// The response to a request that changes a change set. When the request was made at head, sdf
+/// creates a change set to make the change in and returns its pk, which later requests should use
+/// in their visibility.
+#[derive(Debug, Clone)]
+pub struct ChangeSetMutation<T> {
+    pub response: T,
+    pub forced_change_set_pk: Option<ChangeSetPk>,
+}
+
+/// The body sdf responds with when a request fails.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ApiErrorResponse {
+    pub error: ApiError,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ApiError {
+    pub message: String,
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{ActionId, ActionPrototypeId, ChangeSetPk, ComponentId, WorkspacePk};
+
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub enum ChangeSetStatus {
+    Abandoned,
+    Applied,
+    Closed,
+    Failed,
+    Open,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ChangeSet {
+    pub pk: ChangeSetPk,
+    pub name: String,
+    pub note: Option<String>,
+    pub status: ChangeSetStatus,
+    #[serde(rename = "tenancy_workspace_pk")]
+    pub workspace_pk: Option<WorkspacePk>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateChangeSetRequest {
+    pub change_set_name: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateChangeSetResponse {
+    pub change_set: ChangeSet,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetChangeSetRequest {
+    pub pk: ChangeSetPk,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetChangeSetResponse {
+    pub change_set: ChangeSet,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyChangeSetRequest {
+    pub change_set_pk: ChangeSetPk,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyChangeSetResponse {
+    pub change_set: ChangeSet,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionView {
+    pub id: ActionId,
+    pub action_prototype_id: ActionPrototypeId,
+    pub name: String,
+    pub component_id: ComponentId,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ChangeSetView {
+    pub pk: ChangeSetPk,
+    pub name: String,
+    pub status: ChangeSetStatus,
+    pub actions: Vec<ActionView>,
+}
+
+pub type ListOpenChangeSetsResponse = Vec<ChangeSetView>;
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{AttributeValueId, ComponentId, PropId, SchemaVariantId, Visibility};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryComponentsRequest {
+    pub query: String,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+/// A component that matched a query.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentQueryMatch {
+    pub component_id: ComponentId,
+    pub component_name: String,
+    pub schema_variant_id: SchemaVariantId,
+}
+
+pub type QueryComponentsResponse = Vec<ComponentQueryMatch>;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPropertyEditorSchemaRequest {
+    pub component_id: ComponentId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+/// The props of a component's schema variant, as a tree from the root prop.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyEditorSchema {
+    pub root_prop_id: PropId,
+    pub props: HashMap<PropId, PropertyEditorProp>,
+    pub child_props: HashMap<PropId, Vec<PropId>>,
+}
+
+pub type GetPropertyEditorSchemaResponse = PropertyEditorSchema;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyEditorProp {
+    pub id: PropId,
+    pub name: String,
+    pub kind: PropertyEditorPropKind,
+    pub widget_kind: PropertyEditorPropWidgetKind,
+    pub doc_link: Option<String>,
+}
+
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PropertyEditorPropKind {
+    Array,
+    Boolean,
+    Integer,
+    Map,
+    Object,
+    String,
+}
+
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PropertyEditorPropWidgetKind {
+    Array,
+    Checkbox,
+    Color,
+    ComboBox { options: Option<Value> },
+    Header,
+    Map,
+    Secret { options: Option<Value> },
+    Select { options: Option<Value> },
+    Text,
+    TextArea,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPropertyEditorValuesRequest {
+    pub component_id: ComponentId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+/// The values of a component, as a tree from the root value. Every value is identified by the id
+/// of its attribute value.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyEditorValues {
+    pub root_value_id: AttributeValueId,
+    pub values: HashMap<AttributeValueId, PropertyEditorValue>,
+    pub child_values: HashMap<AttributeValueId, Vec<AttributeValueId>>,
+}
+
+pub type GetPropertyEditorValuesResponse = PropertyEditorValues;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyEditorValue {
+    pub id: AttributeValueId,
+    pub prop_id: PropId,
+    pub key: Option<String>,
+    pub value: Value,
+    pub is_from_external_source: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePropertyEditorValueRequest {
+    pub attribute_value_id: AttributeValueId,
+    pub parent_attribute_value_id: Option<AttributeValueId>,
+    pub prop_id: PropId,
+    pub component_id: ComponentId,
+    pub value: Option<Value>,
+    pub key: Option<String>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertPropertyEditorValueRequest {
+    pub parent_attribute_value_id: AttributeValueId,
+    pub prop_id: PropId,
+    pub component_id: ComponentId,
+    pub value: Option<Value>,
+    pub key: Option<String>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTypeRequest {
+    pub component_id: ComponentId,
+    pub value: Option<Value>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
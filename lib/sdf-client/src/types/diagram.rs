@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    ComponentId, EdgeId, ExternalProviderId, InternalProviderId, NodeId, SchemaId, SchemaVariantId,
+    SocketId, Visibility,
+};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDiagramRequest {
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+/// The components and edges of a change set's diagram. The parts of them which are only used to
+/// render the diagram are left as JSON.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagram {
+    pub components: Vec<DiagramComponentView>,
+    pub edges: Vec<DiagramEdgeView>,
+}
+
+pub type GetDiagramResponse = Diagram;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramComponentView {
+    pub id: ComponentId,
+    pub node_id: NodeId,
+    pub display_name: Option<String>,
+    pub parent_node_id: Option<NodeId>,
+    pub child_node_ids: Vec<NodeId>,
+    pub schema_name: String,
+    pub schema_id: SchemaId,
+    pub schema_variant_id: SchemaVariantId,
+    pub schema_variant_name: String,
+    pub schema_category: Option<String>,
+    pub actions: Vec<Value>,
+    pub sockets: Option<Vec<Value>>,
+    pub position: Value,
+    pub size: Option<Value>,
+    pub color: Option<String>,
+    pub node_type: String,
+    pub change_status: String,
+    pub resource: Value,
+    pub created_info: Value,
+    pub updated_info: Value,
+    pub deleted_info: Option<Value>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramEdgeView {
+    pub id: EdgeId,
+    pub from_node_id: NodeId,
+    pub from_socket_id: SocketId,
+    pub to_node_id: NodeId,
+    pub to_socket_id: SocketId,
+    pub change_status: String,
+    pub created_info: Option<Value>,
+    pub deleted_info: Option<Value>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSchemaVariantsRequest {
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputProviderView {
+    pub id: ExternalProviderId,
+    pub ty: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputSocketView {
+    pub id: SocketId,
+    pub name: String,
+    pub diagram_kind: String,
+    pub provider: OutputProviderView,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InputProviderView {
+    pub id: InternalProviderId,
+    pub ty: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InputSocketView {
+    pub id: SocketId,
+    pub name: String,
+    pub diagram_kind: String,
+    pub provider: InputProviderView,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaVariantView {
+    pub id: SchemaVariantId,
+    pub builtin: bool,
+    pub name: String,
+    pub schema_name: String,
+    pub schema_id: SchemaId,
+    pub color: String,
+    pub input_sockets: Vec<InputSocketView>,
+    pub output_sockets: Vec<OutputSocketView>,
+}
+
+pub type ListSchemaVariantsResponse = Vec<SchemaVariantView>;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateNodeRequest {
+    pub schema_id: SchemaId,
+    pub parent_id: Option<NodeId>,
+    pub x: String,
+    pub y: String,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateNodeResponse {
+    pub component_id: ComponentId,
+    pub node_id: NodeId,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SetNodePositionRequest {
+    #[serde(flatten)]
+    pub visibility: Visibility,
+    pub node_id: NodeId,
+    pub x: String,
+    pub y: String,
+    pub width: Option<String>,
+    pub height: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SetNodePositionResponse {
+    pub node: Node,
+}
+
+/// Where a node is drawn on the diagram.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub id: NodeId,
+    pub x: String,
+    pub y: String,
+    pub width: Option<String>,
+    pub height: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteComponentRequest {
+    pub component_id: ComponentId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateConnectionRequest {
+    pub from_node_id: NodeId,
+    pub from_socket_id: SocketId,
+    pub to_node_id: NodeId,
+    pub to_socket_id: SocketId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateConnectionResponse {
+    pub connection: Connection,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Vertex {
+    pub node_id: NodeId,
+    pub socket_id: SocketId,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection {
+    pub id: EdgeId,
+    pub classification: String,
+    pub source: Vertex,
+    pub destination: Vertex,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteConnectionRequest {
+    pub edge_id: EdgeId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
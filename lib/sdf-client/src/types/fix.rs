@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ActionPrototypeId, ComponentId, FixBatchId, FixId, Visibility};
+
+/// Describes how an action affects the world.
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionKind {
+    Create,
+    Delete,
+    Other,
+    Refresh,
+}
+
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FixCompletionStatus {
+    Error,
+    Failure,
+    Success,
+    Unstarted,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFixesRequest {
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FixHistoryView {
+    pub id: FixId,
+    pub status: FixCompletionStatus,
+    pub action_kind: ActionKind,
+    pub display_name: String,
+    pub schema_name: String,
+    pub component_name: String,
+    pub component_id: ComponentId,
+    pub provider: Option<String>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub resource: Option<Value>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchHistoryView {
+    pub id: FixBatchId,
+    pub status: Option<FixCompletionStatus>,
+    pub author: String,
+    pub fixes: Vec<FixHistoryView>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+pub type ListFixesResponse = Vec<BatchHistoryView>;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FixRunRequest {
+    pub component_id: ComponentId,
+    pub action_prototype_id: ActionPrototypeId,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FixesRunRequest {
+    pub list: Vec<FixRunRequest>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FixesRunResponse {
+    pub id: FixBatchId,
+}
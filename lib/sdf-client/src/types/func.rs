@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ActionKind, ExternalProviderId, FuncId, PropId, SchemaVariantId, Visibility};
+
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuncVariant {
+    Action,
+    Attribute,
+    CodeGeneration,
+    Qualification,
+    Reconciliation,
+    Validation,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFuncsRequest {
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListedFuncView {
+    pub id: FuncId,
+    pub handler: Option<String>,
+    pub variant: FuncVariant,
+    pub name: String,
+    pub display_name: Option<String>,
+    pub is_builtin: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFuncsResponse {
+    pub funcs: Vec<ListedFuncView>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFuncRequest {
+    pub id: FuncId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+/// A func along with what it is associated with. The associations are left as JSON, to be passed
+/// back as they are or changed when saving the func.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFuncResponse {
+    pub id: FuncId,
+    pub variant: FuncVariant,
+    pub name: String,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub code: Option<String>,
+    pub types: String,
+    pub is_builtin: bool,
+    pub is_revertible: bool,
+    pub associations: Option<Value>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AttributeOutputLocation {
+    #[serde(rename_all = "camelCase")]
+    OutputSocket {
+        external_provider_id: ExternalProviderId,
+    },
+    #[serde(rename_all = "camelCase")]
+    Prop { prop_id: PropId },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CreateFuncOptions {
+    #[serde(rename_all = "camelCase")]
+    ActionOptions {
+        schema_variant_id: SchemaVariantId,
+        action_kind: ActionKind,
+    },
+    #[serde(rename_all = "camelCase")]
+    AttributeOptions {
+        schema_variant_id: SchemaVariantId,
+        output_location: AttributeOutputLocation,
+    },
+    #[serde(rename_all = "camelCase")]
+    CodeGenerationOptions { schema_variant_id: SchemaVariantId },
+    #[serde(rename_all = "camelCase")]
+    QualificationOptions { schema_variant_id: SchemaVariantId },
+    #[serde(rename_all = "camelCase")]
+    ValidationOptions {
+        schema_variant_id: SchemaVariantId,
+        prop_to_validate: PropId,
+    },
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFuncRequest {
+    pub variant: FuncVariant,
+    pub name: Option<String>,
+    pub options: Option<CreateFuncOptions>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFuncResponse {
+    pub id: FuncId,
+    pub handler: Option<String>,
+    pub variant: FuncVariant,
+    pub name: String,
+    pub code: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveFuncRequest {
+    pub id: FuncId,
+    pub display_name: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub code: Option<String>,
+    pub associations: Option<Value>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveFuncResponse {
+    pub associations: Option<Value>,
+    pub success: bool,
+    pub is_revertible: bool,
+    pub types: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteRequest {
+    pub id: FuncId,
+    pub args: Value,
+    pub execution_key: String,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+/// A line of output of a function execution.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct OutputStream {
+    pub stream: String,
+    pub execution_id: String,
+    pub level: String,
+    pub group: Option<String>,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteResponse {
+    pub id: FuncId,
+    pub args: Value,
+    pub output: Value,
+    pub execution_key: String,
+    pub logs: Vec<OutputStream>,
+}
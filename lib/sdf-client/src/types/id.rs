@@ -0,0 +1,82 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+/// Defines the id of a kind of object in sdf, which goes over the wire as the string of a ulid.
+macro_rules! id {
+    ($name:ident) => {
+        #[derive(Clone, Copy, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+        pub struct $name(Ulid);
+
+        impl $name {
+            /// An unset id value.
+            pub const NONE: Self = Self(Ulid::nil());
+
+            /// Generates a new id which is virtually guaranteed to be unique.
+            pub fn generate() -> Self {
+                Self(Ulid::new())
+            }
+
+            /// Returns `true` if is unset (i.e. value is equal to [`NONE`](Self::NONE)).
+            pub fn is_none(&self) -> bool {
+                self == &Self::NONE
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    .field(&self.0.to_string())
+                    .finish()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ulid::DecodeError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(Self(Ulid::from_string(s)?))
+            }
+        }
+
+        impl From<Ulid> for $name {
+            fn from(ulid: Ulid) -> Self {
+                Self(ulid)
+            }
+        }
+
+        impl From<$name> for Ulid {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+    };
+}
+
+id!(ActionId);
+id!(ActionPrototypeId);
+id!(AttributeValueId);
+id!(ChangeSetPk);
+id!(ComponentId);
+id!(EdgeId);
+id!(ExternalProviderId);
+id!(FixBatchId);
+id!(FixId);
+id!(FuncId);
+id!(InternalProviderId);
+id!(KeyPairPk);
+id!(NodeId);
+id!(PropId);
+id!(SchemaId);
+id!(SchemaPk);
+id!(SchemaVariantId);
+id!(SocketId);
+id!(WorkspacePk);
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use super::{SchemaVariantId, Visibility};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PkgListRequest {
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PkgView {
+    pub name: String,
+    pub hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PkgListResponse {
+    pub pkgs: Vec<PkgView>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPkgRequest {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub schema_variants: Vec<SchemaVariantId>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPkgResponse {
+    pub success: bool,
+    pub full_path: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPkgFileResponse {
+    pub name: String,
+    pub version: String,
+    /// The contents of the package file, base64 encoded.
+    pub data: String,
+}
+
+/// Installs a module from the module index.
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallPkgRequest {
+    pub id: Ulid,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallPkgFileRequest {
+    /// The contents of the package file, base64 encoded.
+    pub data: String,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallPkgResponse {
+    pub success: bool,
+    pub skipped_attributes: bool,
+    pub skipped_edges: bool,
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// What a rate limited request is counted against.
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum RateLimitBudget {
+    /// Routes running functions.
+    FuncExecution,
+    /// Every other route changing a workspace.
+    Mutation,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitMetrics {
+    pub enabled: bool,
+    pub budgets: Vec<BudgetMetrics>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetMetrics {
+    pub budget: RateLimitBudget,
+    pub allowed: u64,
+    pub limited_by_user: u64,
+    pub limited_by_workspace: u64,
+    /// How many users and workspaces have made requests recently enough for their buckets not to
+    /// have refilled yet.
+    pub active_buckets: u64,
+}
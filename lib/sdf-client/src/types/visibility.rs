@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::ChangeSetPk;
+
+/// Which change set a request reads from or writes to.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Visibility {
+    #[serde(rename = "visibility_change_set_pk")]
+    pub change_set_pk: ChangeSetPk,
+    #[serde(rename = "visibility_deleted_at")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl Visibility {
+    pub fn new(change_set_pk: ChangeSetPk) -> Self {
+        Self {
+            change_set_pk,
+            deleted_at: None,
+        }
+    }
+
+    /// The visibility of head, which requests changing something create a change set from.
+    pub fn new_head() -> Self {
+        Self::new(ChangeSetPk::NONE)
+    }
+
+    pub fn is_head(&self) -> bool {
+        self.change_set_pk.is_none()
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use super::{ChangeSetPk, KeyPairPk, SchemaPk, WorkspacePk};
+
+/// An event of a workspace, as streamed over the websocket.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct WsEvent {
+    pub version: i64,
+    pub workspace_pk: WorkspacePk,
+    pub change_set_pk: ChangeSetPk,
+    /// The position of the event in the stream of the workspace's events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+    /// The stream the sequence belongs to, which changes whenever the sequence starts over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_id: Option<String>,
+    pub payload: WsPayload,
+}
+
+/// The payload of an event. Payloads carrying more than an id are left as JSON.
+//
+// NOTE: `remote = "Self"` turns the derived implementations into inherent functions, which the
+// `Serialize` and `Deserialize` implementations below fall back to for unknown payloads.
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(remote = "Self", tag = "kind", content = "data")]
+pub enum WsPayload {
+    ChangeSetApplied(ChangeSetPk),
+    ChangeSetCanceled(ChangeSetPk),
+    ChangeSetCreated(ChangeSetPk),
+    ChangeSetWritten(ChangeSetPk),
+    CheckedQualifications(Value),
+    CodeGenerated(Value),
+    ComponentCreated(Value),
+    FixBatchReturn(Value),
+    FixReturn(Value),
+    KeyPairRotated(KeyPairPk),
+    LogLine(Value),
+    ModuleImported(Value),
+    ResourceRefreshed(Value),
+    ResyncRequired(ResyncRequiredPayload),
+    SchemaCreated(SchemaPk),
+    StatusUpdate(Value),
+    /// A payload this client can't parse, such as one of a kind added to sdf after the client was
+    /// built, kept as it was sent.
+    #[serde(skip)]
+    Unknown {
+        kind: String,
+        data: Value,
+    },
+}
+
+/// The shape of every payload, whatever its kind.
+#[derive(Deserialize)]
+struct RawWsPayload {
+    kind: String,
+    #[serde(default)]
+    data: Value,
+}
+
+impl Serialize for WsPayload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Unknown { kind, data } => {
+                let mut payload = serializer.serialize_struct("WsPayload", 2)?;
+                payload.serialize_field("kind", kind)?;
+                payload.serialize_field("data", data)?;
+                payload.end()
+            }
+            known => Self::serialize(known, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WsPayload {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let RawWsPayload { kind, data } = RawWsPayload::deserialize(deserializer)?;
+        let payload = serde_json::json!({ "kind": kind, "data": data });
+        Ok(Self::deserialize(payload).unwrap_or(Self::Unknown { kind, data }))
+    }
+}
+
+/// Tells a websocket client that it missed events which can't be replayed, so it has to refetch
+/// everything and resume from the latest event of the stream.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncRequiredPayload {
+    pub stream_id: String,
+    pub latest_sequence: Option<u64>,
+}
+
+/// Which events a websocket client receives. Leaving a field unset doesn't filter on it.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+    pub change_set_pks: Option<HashSet<ChangeSetPk>>,
+    pub payload_kinds: Option<HashSet<String>>,
+}
+
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub(crate) enum ClientMessage {
+    Subscribe(EventFilter),
+}
+
+/// Where a reconnecting client left off: the last event it received and the stream that event
+/// belongs to.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Resume {
+    pub stream_id: Option<String>,
+    pub resume_from: Option<u64>,
+}
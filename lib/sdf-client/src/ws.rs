@@ -0,0 +1,75 @@
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use url::Url;
+
+use crate::types::{ClientMessage, EventFilter, Resume, SdfClientResult, WsEvent, WsPayload};
+
+/// A connection to the websocket streaming the events of a workspace.
+///
+/// Only plain `ws` connections are supported, so an sdf served over https has to be reached
+/// through a proxy terminating tls.
+#[derive(Debug)]
+pub struct WorkspaceUpdates {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    resume: Resume,
+}
+
+impl WorkspaceUpdates {
+    pub(crate) async fn connect(url: Url, resume: Resume) -> SdfClientResult<Self> {
+        let (stream, _) = tokio_tungstenite::connect_async(url).await?;
+        Ok(Self { stream, resume })
+    }
+
+    /// Only receives the events matching the filter from now on.
+    pub async fn subscribe(&mut self, filter: EventFilter) -> SdfClientResult<()> {
+        let message = serde_json::to_string(&ClientMessage::Subscribe(filter))?;
+        self.stream.send(Message::Text(message)).await?;
+        Ok(())
+    }
+
+    /// Waits for the next event, returning `None` once the connection is closed. An event whose
+    /// payload this client can't parse still arrives, with a [`WsPayload::Unknown`] payload.
+    pub async fn next_event(&mut self) -> SdfClientResult<Option<WsEvent>> {
+        while let Some(message) = self.stream.next().await {
+            let text = match message? {
+                Message::Text(text) => text,
+                Message::Close(_) => return Ok(None),
+                _ => continue,
+            };
+            let event: WsEvent = serde_json::from_str(&text)?;
+
+            if let WsPayload::ResyncRequired(resync) = &event.payload {
+                self.resume = Resume {
+                    stream_id: Some(resync.stream_id.clone()),
+                    resume_from: resync.latest_sequence,
+                };
+            } else if let Some(sequence) = event.sequence {
+                self.resume = Resume {
+                    stream_id: event.stream_id.clone(),
+                    resume_from: Some(sequence),
+                };
+            }
+
+            return Ok(Some(event));
+        }
+
+        Ok(None)
+    }
+
+    /// Waits for the payload of the next event, returning `None` once the connection is closed.
+    pub async fn next_payload(&mut self) -> SdfClientResult<Option<WsPayload>> {
+        Ok(self.next_event().await?.map(|event| event.payload))
+    }
+
+    /// Where these updates left off, to pass to
+    /// [`SdfClient::workspace_updates`](crate::SdfClient::workspace_updates) when reconnecting.
+    pub fn resume(&self) -> &Resume {
+        &self.resume
+    }
+
+    pub async fn close(mut self) -> SdfClientResult<()> {
+        self.stream.close(None).await?;
+        Ok(())
+    }
+}
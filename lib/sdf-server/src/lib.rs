@@ -15,7 +15,8 @@
 mod server;
 pub use server::{
     build_service, build_service_for_tests, detect_and_configure_development,
-    job_processor::JobProcessorClientCloser, job_processor::JobProcessorConnector, service, Config,
-    ConfigError, ConfigFile, IncomingStream, JobQueueProcessor, MigrationMode, NatsProcessor,
-    Server, ServicesContext, StandardConfig, StandardConfigFile,
+    job_processor::JobProcessorClientCloser, job_processor::JobProcessorConnector, openapi,
+    rate_limit, service, start_config_reload_signal_handler_task, Config, ConfigError, ConfigFile,
+    ConfigReload, IncomingStream, JobQueueProcessor, MigrationMode, NatsProcessor,
+    ReloadableConfigFile, Server, ServicesContext, StandardConfig, StandardConfigFile,
 };
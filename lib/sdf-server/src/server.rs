@@ -1,6 +1,7 @@
 pub use config::{
-    detect_and_configure_development, Config, ConfigBuilder, ConfigError, ConfigFile,
-    IncomingStream, StandardConfig, StandardConfigFile,
+    detect_and_configure_development, start_config_reload_signal_handler_task, Config,
+    ConfigBuilder, ConfigError, ConfigFile, ConfigReload, IncomingStream, ReloadableConfigFile,
+    StandardConfig, StandardConfigFile,
 };
 pub use dal::{JobQueueProcessor, MigrationMode, NatsProcessor, ServicesContext};
 pub use routes::{routes, AppError};
@@ -11,6 +12,8 @@ mod config;
 pub(crate) mod extract;
 mod feature_flags;
 pub(crate) mod job_processor;
+pub mod openapi;
+pub mod rate_limit;
 mod routes;
 mod server;
 pub mod service;
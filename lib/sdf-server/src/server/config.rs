@@ -15,8 +15,13 @@ use si_std::{CanonicalFile, CanonicalFileError, SensitiveString};
 use telemetry::prelude::*;
 use thiserror::Error;
 
+use super::rate_limit::RateLimitsConfig;
+
 pub use dal::{CycloneKeyPair, MigrationMode};
-pub use si_settings::{StandardConfig, StandardConfigFile};
+pub use si_settings::{
+    start_config_reload_signal_handler_task, ConfigReload, ReloadableConfigFile, StandardConfig,
+    StandardConfigFile,
+};
 
 const DEFAULT_SIGNUP_SECRET: &str = "cool-steam";
 const DEFAULT_MODULE_INDEX_URL: &str = "https://module-index.systeminit.com";
@@ -68,6 +73,9 @@ pub struct Config {
     #[builder(default = "MigrationMode::default()")]
     migration_mode: MigrationMode,
 
+    #[builder(default = "RateLimitsConfig::default()")]
+    rate_limits: RateLimitsConfig,
+
     jwt_signing_public_key_path: CanonicalFile,
 
     cyclone_encryption_key_path: CanonicalFile,
@@ -138,6 +146,12 @@ impl Config {
         &self.symmetric_crypto_service
     }
 
+    /// Gets a reference to the config's rate limits.
+    #[must_use]
+    pub fn rate_limits(&self) -> &RateLimitsConfig {
+        &self.rate_limits
+    }
+
     /// URL to the module index service
     #[must_use]
     pub fn module_index_url(&self) -> &str {
@@ -177,6 +191,11 @@ pub struct ConfigFile {
     pub module_index_url: String,
     #[serde(default = "default_symmetric_crypto_config")]
     symmetric_crypto_service: SymmetricCryptoServiceConfigFile,
+    #[serde(default)]
+    pub rate_limits: RateLimitsConfig,
+    /// Tracing directives replacing the ones from `SI_LOG`, such as `info,sdf_server=debug`.
+    #[serde(default)]
+    pub log_filter: Option<String>,
 }
 
 impl Default for ConfigFile {
@@ -192,6 +211,8 @@ impl Default for ConfigFile {
             posthog: Default::default(),
             module_index_url: default_module_index_url(),
             symmetric_crypto_service: default_symmetric_crypto_config(),
+            rate_limits: Default::default(),
+            log_filter: None,
         }
     }
 }
@@ -200,6 +221,11 @@ impl StandardConfigFile for ConfigFile {
     type Error = ConfigError;
 }
 
+impl ReloadableConfigFile for ConfigFile {
+    const LIVE_SETTINGS: &'static [&'static str] =
+        &["log_filter", "module_index_url", "pg.pool_max_size"];
+}
+
 impl TryFrom<ConfigFile> for Config {
     type Error = ConfigError;
 
@@ -217,6 +243,7 @@ impl TryFrom<ConfigFile> for Config {
         config.posthog(value.posthog);
         config.module_index_url(value.module_index_url);
         config.symmetric_crypto_service(value.symmetric_crypto_service.try_into()?);
+        config.rate_limits(value.rate_limits);
         config.build().map_err(Into::into)
     }
 }
@@ -3,16 +3,16 @@ use std::{collections::HashMap, fmt};
 use axum::{
     async_trait,
     extract::{FromRequestParts, Query},
-    http::request::Parts,
+    http::{request::Parts, Method},
     Json,
 };
 use dal::{
     context::{self, DalContextBuilder},
-    User, UserClaim,
+    ApiToken, User, UserClaim, WorkspaceRole,
 };
 use hyper::StatusCode;
 
-use super::state::AppState;
+use super::{rate_limit::VerifiedApiToken, state::AppState};
 
 pub struct AccessBuilder(pub context::AccessBuilder);
 
@@ -34,6 +34,28 @@ impl FromRequestParts<AppState> for AccessBuilder {
     }
 }
 
+/// An [`AccessBuilder`] for routes that change real infrastructure, such as applying a change
+/// set, which only appliers and owners of the workspace may use.
+pub struct ApplierAccessBuilder(pub context::AccessBuilder);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ApplierAccessBuilder {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claim = authorize_bearer_token(parts, state, WorkspaceRole::Applier).await?;
+        let Tenancy(tenancy) = tenancy_from_claim(&claim).await?;
+
+        Ok(Self(context::AccessBuilder::new(
+            tenancy,
+            dal::HistoryActor::from(claim.user_pk),
+        )))
+    }
+}
+
 pub struct RawAccessToken(pub String);
 
 #[async_trait]
@@ -111,6 +133,8 @@ impl FromRequestParts<AppState> for Nats {
     }
 }
 
+/// The claim of a member of the workspace allowed to use the route. Reading routes (`GET` and
+/// `HEAD`) are open to every member, while every other route requires at least an editor.
 pub struct Authorization(pub UserClaim);
 
 #[async_trait]
@@ -121,25 +145,56 @@ impl FromRequestParts<AppState> for Authorization {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let HandlerContext(builder) = HandlerContext::from_request_parts(parts, state).await?;
-        let mut ctx = builder.build_default().await.map_err(internal_error)?;
-        let jwt_public_signing_key = state.jwt_public_signing_key().clone();
+        let required_role = required_role_for_method(&parts.method);
+        let claim = authorize_bearer_token(parts, state, required_role).await?;
+
+        Ok(Self(claim))
+    }
+}
+
+/// The claim of an owner of the workspace, for routes managing the workspace's members and
+/// webhooks and reading its audit log.
+pub struct OwnerAuthorization(pub UserClaim);
+
+#[async_trait]
+impl FromRequestParts<AppState> for OwnerAuthorization {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claim = authorize_bearer_token(parts, state, WorkspaceRole::Owner).await?;
 
-        let headers = &parts.headers;
-        let authorization_header_value = headers
+        Ok(Self(claim))
+    }
+}
+
+/// The claim of any member of the workspace who signed in through the auth portal, for routes
+/// managing the user's own API tokens. API tokens are rejected so that a token can't be used to
+/// create tokens with a wider scope than its own.
+pub struct SessionAuthorization(pub UserClaim);
+
+#[async_trait]
+impl FromRequestParts<AppState> for SessionAuthorization {
+    type Rejection = (StatusCode, Json<serde_json::Value>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let is_api_token = parts
+            .headers
             .get("Authorization")
-            .ok_or_else(unauthorized_error)?;
-        let authorization = authorization_header_value
-            .to_str()
-            .map_err(internal_error)?;
-        let claim = UserClaim::from_bearer_token(jwt_public_signing_key, authorization)
-            .await
-            .map_err(|_| unauthorized_error())?;
-        ctx.update_tenancy(dal::Tenancy::new(claim.workspace_pk));
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, ApiToken::is_api_token);
+        if is_api_token {
+            return Err(forbidden_error_message(
+                "forbidden: api tokens can't be used to manage api tokens",
+            ));
+        }
 
-        User::authorize(&ctx, &claim.user_pk)
-            .await
-            .map_err(|_| unauthorized_error())?;
+        let claim = authorize_bearer_token(parts, state, WorkspaceRole::Viewer).await?;
 
         Ok(Self(claim))
     }
@@ -155,26 +210,96 @@ impl FromRequestParts<AppState> for WsAuthorization {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let HandlerContext(builder) = HandlerContext::from_request_parts(parts, state).await?;
-        let mut ctx = builder.build_default().await.map_err(internal_error)?;
-        let jwt_public_signing_key = state.jwt_public_signing_key().clone();
-
         let query: Query<HashMap<String, String>> = Query::from_request_parts(parts, state)
             .await
             .map_err(|_| unauthorized_error())?;
         let authorization = query.get("token").ok_or_else(unauthorized_error)?;
 
-        let claim = UserClaim::from_bearer_token(jwt_public_signing_key, authorization)
-            .await
-            .map_err(|_| unauthorized_error())?;
-        ctx.update_tenancy(dal::Tenancy::new(claim.workspace_pk));
+        let claim = authorize(parts, state, authorization, WorkspaceRole::Viewer).await?;
+
+        Ok(Self(claim))
+    }
+}
 
-        User::authorize(&ctx, &claim.user_pk)
+fn required_role_for_method(method: &Method) -> WorkspaceRole {
+    if method == Method::GET || method == Method::HEAD {
+        WorkspaceRole::Viewer
+    } else {
+        WorkspaceRole::Editor
+    }
+}
+
+async fn authorize_bearer_token(
+    parts: &mut Parts,
+    state: &AppState,
+    required_role: WorkspaceRole,
+) -> Result<UserClaim, (StatusCode, Json<serde_json::Value>)> {
+    let authorization = parts
+        .headers
+        .get("Authorization")
+        .ok_or_else(unauthorized_error)?
+        .to_str()
+        .map_err(internal_error)?
+        .to_owned();
+
+    authorize(parts, state, &authorization, required_role).await
+}
+
+async fn authorize(
+    parts: &mut Parts,
+    state: &AppState,
+    authorization: &str,
+    required_role: WorkspaceRole,
+) -> Result<UserClaim, (StatusCode, Json<serde_json::Value>)> {
+    let HandlerContext(builder) = HandlerContext::from_request_parts(parts, state).await?;
+    let mut ctx = builder.build_default().await.map_err(internal_error)?;
+    let jwt_public_signing_key = state.jwt_public_signing_key().clone();
+
+    let api_token = if ApiToken::is_api_token(authorization) {
+        // The rate limit layer may have looked the token up already
+        let verified = parts
+            .extensions
+            .get::<VerifiedApiToken>()
+            .filter(|verified| verified.authorization == authorization)
+            .map(|verified| verified.api_token.clone());
+        let api_token = match verified {
+            Some(api_token) => api_token,
+            None => ApiToken::find_active(&ctx, authorization)
+                .await
+                .map_err(internal_error)?
+                .ok_or_else(unauthorized_error)?,
+        };
+        // A token may do no more than its scope, whatever the role of its user is
+        if !api_token.scope().permits(required_role) {
+            return Err(forbidden_error(required_role));
+        }
+        Some(api_token)
+    } else {
+        None
+    };
+
+    let claim = match &api_token {
+        Some(api_token) => api_token.claim(),
+        None => UserClaim::from_bearer_token(jwt_public_signing_key, authorization)
             .await
-            .map_err(|_| unauthorized_error())?;
+            .map_err(|_| unauthorized_error())?,
+    };
+    ctx.update_tenancy(dal::Tenancy::new(claim.workspace_pk));
+
+    if !User::authorize(&ctx, &claim.user_pk, required_role)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err(forbidden_error(required_role));
+    }
 
-        Ok(Self(claim))
+    // Keeps the record of when the token was last used
+    if let Some(api_token) = &api_token {
+        api_token.touch(&ctx).await.map_err(internal_error)?;
+        ctx.commit().await.map_err(internal_error)?;
     }
+
+    Ok(claim)
 }
 
 pub struct Tenancy(pub dal::Tenancy);
@@ -225,3 +350,23 @@ fn unauthorized_error() -> (StatusCode, Json<serde_json::Value>) {
         })),
     )
 }
+
+fn forbidden_error(required_role: WorkspaceRole) -> (StatusCode, Json<serde_json::Value>) {
+    forbidden_error_message(format!(
+        "forbidden: requires the {required_role} role in the workspace"
+    ))
+}
+
+fn forbidden_error_message(message: impl fmt::Display) -> (StatusCode, Json<serde_json::Value>) {
+    let status_code = StatusCode::FORBIDDEN;
+    (
+        status_code,
+        Json(serde_json::json!({
+            "error": {
+                "message": message.to_string(),
+                "statusCode": status_code.as_u16(),
+                "code": 42,
+            },
+        })),
+    )
+}
@@ -0,0 +1,23 @@
+//! The OpenAPI document of sdf's routes, generated from the source of the route handlers and of
+//! the request and response types they use.
+//!
+//! The document is checked in next to this module and served at `/api/openapi.json`. It is
+//! generated by the integration tests, which fail when it no longer matches the source.
+//! Regenerate it with:
+//!
+//! ```shell
+//! SI_UPDATE_OPENAPI=1 cargo test -p sdf-server openapi
+//! ```
+
+use axum::{http::header, response::IntoResponse};
+
+/// The checked-in OpenAPI document.
+pub const OPENAPI_DOCUMENT: &str = include_str!("openapi.json");
+
+/// Serves the OpenAPI document of sdf's routes.
+pub async fn openapi_document() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        OPENAPI_DOCUMENT,
+    )
+}
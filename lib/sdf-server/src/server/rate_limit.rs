@@ -0,0 +1,565 @@
+//! Token bucket rate limits on the routes that change a workspace, so that a runaway script can't
+//! flood sdf, and the jobs its requests enqueue, faster than the limits allow.
+//!
+//! Every user and every workspace has a bucket for each [`RateLimitBudget`]. A request gets
+//! through when both the bucket of its user and the bucket of its workspace have a token left,
+//! taking one from each. Otherwise it's answered with a `429 Too Many Requests` telling the client
+//! when to retry. Buckets refill continuously up to their capacity, which is the burst of requests
+//! allowed after a quiet period.
+//!
+//! Reading routes aren't limited, and neither are requests which can't be authenticated, which are
+//! left for their route to reject.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use dal::{ApiToken, JwtPublicSigningKey, ServicesContext, UserClaim, UserPk, WorkspacePk};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use telemetry::prelude::*;
+use tower::{Layer, Service};
+
+/// Routes which run functions, and so can enqueue expensive jobs, rather than only changing the
+/// workspace.
+const FUNC_EXECUTION_ROUTES: &[&str] = &[
+    "/api/component/refresh",
+    "/api/fix/run",
+    "/api/func/execute",
+    "/api/func/save_and_exec",
+];
+
+/// The longest wait suggested to a client, for buckets which refill very slowly or not at all.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(3600);
+
+/// How many requests are checked between two sweeps of the buckets which have refilled, which are
+/// no different from the buckets of users and workspaces not seen yet.
+const PRUNE_INTERVAL: u64 = 1024;
+
+/// The size and refill rate of a token bucket.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct BucketConfig {
+    /// How many requests may be made in a burst.
+    pub capacity: u32,
+    /// How many requests may be made per second once the burst is spent.
+    pub refill_per_second: f64,
+}
+
+impl BucketConfig {
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_second,
+        }
+    }
+}
+
+/// The buckets of a [`RateLimitBudget`], for each user and for each workspace.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct BudgetConfig {
+    pub per_user: BucketConfig,
+    pub per_workspace: BucketConfig,
+}
+
+impl BudgetConfig {
+    fn bucket(&self, scope: RateLimitScope) -> &BucketConfig {
+        match scope {
+            RateLimitScope::User => &self.per_user,
+            RateLimitScope::Workspace => &self.per_workspace,
+        }
+    }
+}
+
+/// The budgets of the kinds of limited routes, which are all enabled or disabled together.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct RateLimitsConfig {
+    pub enabled: bool,
+    pub mutation: BudgetConfig,
+    pub func_execution: BudgetConfig,
+}
+
+impl Default for RateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mutation: BudgetConfig {
+                per_user: BucketConfig::new(120, 10.0),
+                per_workspace: BucketConfig::new(600, 50.0),
+            },
+            func_execution: BudgetConfig {
+                per_user: BucketConfig::new(20, 1.0),
+                per_workspace: BucketConfig::new(60, 3.0),
+            },
+        }
+    }
+}
+
+impl RateLimitsConfig {
+    fn budget(&self, budget: RateLimitBudget) -> &BudgetConfig {
+        match budget {
+            RateLimitBudget::FuncExecution => &self.func_execution,
+            RateLimitBudget::Mutation => &self.mutation,
+        }
+    }
+}
+
+/// A kind of route limited separately from the others.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum RateLimitBudget {
+    /// Routes running functions.
+    FuncExecution,
+    /// Every other route changing a workspace.
+    Mutation,
+}
+
+impl RateLimitBudget {
+    pub const ALL: [Self; 2] = [Self::Mutation, Self::FuncExecution];
+
+    /// The budget a request is counted against, if any.
+    pub fn for_request(method: &Method, path: &str) -> Option<Self> {
+        if FUNC_EXECUTION_ROUTES.contains(&path) {
+            Some(Self::FuncExecution)
+        } else if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+            None
+        } else if path.starts_with("/api/") {
+            Some(Self::Mutation)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whose bucket a request was limited by.
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum RateLimitScope {
+    User,
+    Workspace,
+}
+
+/// A request rejected because a bucket it's counted against is empty.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimited {
+    pub budget: RateLimitBudget,
+    pub scope: RateLimitScope,
+    pub retry_after: Duration,
+}
+
+impl RateLimited {
+    /// The wait in whole seconds, rounded up so that clients don't retry too early.
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0)
+    }
+}
+
+impl IntoResponse for RateLimited {
+    fn into_response(self) -> Response {
+        let status_code = StatusCode::TOO_MANY_REQUESTS;
+        let retry_after_secs = self.retry_after_secs();
+        let body = Json(serde_json::json!({
+            "error": {
+                "message": format!(
+                    "too many requests: the {} budget of the {} is spent, retry in {retry_after_secs}s",
+                    self.budget, self.scope,
+                ),
+                "statusCode": status_code.as_u16(),
+                "code": 42,
+                "retryAfterSecs": retry_after_secs,
+            },
+        }));
+
+        (
+            status_code,
+            [(header::RETRY_AFTER, retry_after_secs.to_string())],
+            body,
+        )
+            .into_response()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitMetrics {
+    pub enabled: bool,
+    pub budgets: Vec<BudgetMetrics>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetMetrics {
+    pub budget: RateLimitBudget,
+    pub allowed: u64,
+    pub limited_by_user: u64,
+    pub limited_by_workspace: u64,
+    /// How many users and workspaces have made requests recently enough for their buckets not to
+    /// have refilled yet.
+    pub active_buckets: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum BucketKey {
+    User(RateLimitBudget, UserPk),
+    Workspace(RateLimitBudget, WorkspacePk),
+}
+
+impl BucketKey {
+    fn budget(&self) -> RateLimitBudget {
+        match self {
+            Self::User(budget, _) | Self::Workspace(budget, _) => *budget,
+        }
+    }
+
+    fn config<'a>(&self, config: &'a RateLimitsConfig) -> &'a BucketConfig {
+        let scope = match self {
+            Self::User(..) => RateLimitScope::User,
+            Self::Workspace(..) => RateLimitScope::Workspace,
+        };
+        config.budget(self.budget()).bucket(scope)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(config: &BucketConfig, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(config.capacity),
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, config: &BucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * config.refill_per_second.max(0.0))
+            .min(f64::from(config.capacity));
+        self.updated_at = now;
+    }
+
+    /// How long until the bucket has a token to take, if it has none now.
+    fn wait(&self, config: &BucketConfig) -> Option<Duration> {
+        if self.tokens >= 1.0 {
+            return None;
+        }
+
+        let secs = (1.0 - self.tokens) / config.refill_per_second;
+        Some(if secs.is_finite() && secs >= 0.0 {
+            Duration::from_secs_f64(secs).min(MAX_RETRY_AFTER)
+        } else {
+            MAX_RETRY_AFTER
+        })
+    }
+
+    fn is_full(&self, config: &BucketConfig) -> bool {
+        self.tokens >= f64::from(config.capacity)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct BudgetCounters {
+    allowed: u64,
+    limited_by_user: u64,
+    limited_by_workspace: u64,
+}
+
+#[derive(Debug, Default)]
+struct RateLimiterState {
+    buckets: HashMap<BucketKey, TokenBucket>,
+    counters: HashMap<RateLimitBudget, BudgetCounters>,
+    checks: u64,
+}
+
+impl RateLimiterState {
+    fn bucket(&mut self, key: BucketKey, config: &BucketConfig, now: Instant) -> &mut TokenBucket {
+        let bucket = self
+            .buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::full(config, now));
+        bucket.refill(config, now);
+        bucket
+    }
+
+    fn prune(&mut self, config: &RateLimitsConfig, now: Instant) {
+        self.buckets.retain(|key, bucket| {
+            let config = key.config(config);
+            bucket.refill(config, now);
+            !bucket.is_full(config)
+        });
+    }
+}
+
+/// The buckets of every user and workspace, shared by the clones of the limiter.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    config: RateLimitsConfig,
+    state: Arc<Mutex<RateLimiterState>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitsConfig) -> Self {
+        Self {
+            config,
+            state: Default::default(),
+        }
+    }
+
+    pub fn config(&self) -> &RateLimitsConfig {
+        &self.config
+    }
+
+    /// Takes a token from the buckets of the user and of the workspace of the claim, or reports
+    /// how long to wait for one when either of them is empty.
+    pub fn check(&self, budget: RateLimitBudget, claim: &UserClaim) -> Result<(), RateLimited> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let budget_config = self.config.budget(budget);
+        let mut guard = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = &mut *guard;
+
+        state.checks += 1;
+        if state.checks % PRUNE_INTERVAL == 0 {
+            state.prune(&self.config, now);
+        }
+
+        let user_key = BucketKey::User(budget, claim.user_pk);
+        let workspace_key = BucketKey::Workspace(budget, claim.workspace_pk);
+        let user_wait = state
+            .bucket(user_key, &budget_config.per_user, now)
+            .wait(&budget_config.per_user);
+        let workspace_wait = state
+            .bucket(workspace_key, &budget_config.per_workspace, now)
+            .wait(&budget_config.per_workspace);
+        // The client is told of the longest wait, which is the user's when both are as long
+        let limited = match (user_wait, workspace_wait) {
+            (None, None) => None,
+            (Some(user_wait), Some(workspace_wait)) if workspace_wait > user_wait => {
+                Some((RateLimitScope::Workspace, workspace_wait))
+            }
+            (Some(user_wait), _) => Some((RateLimitScope::User, user_wait)),
+            (None, Some(workspace_wait)) => Some((RateLimitScope::Workspace, workspace_wait)),
+        };
+
+        let counters = state.counters.entry(budget).or_default();
+        match limited {
+            None => {
+                counters.allowed += 1;
+                for key in [user_key, workspace_key] {
+                    if let Some(bucket) = state.buckets.get_mut(&key) {
+                        bucket.tokens -= 1.0;
+                    }
+                }
+                Ok(())
+            }
+            Some((scope, retry_after)) => {
+                match scope {
+                    RateLimitScope::User => counters.limited_by_user += 1,
+                    RateLimitScope::Workspace => counters.limited_by_workspace += 1,
+                }
+                Err(RateLimited {
+                    budget,
+                    scope,
+                    retry_after,
+                })
+            }
+        }
+    }
+
+    pub fn metrics(&self) -> RateLimitMetrics {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let budgets = RateLimitBudget::ALL
+            .into_iter()
+            .map(|budget| {
+                let counters = state.counters.get(&budget).copied().unwrap_or_default();
+                let active_buckets = state
+                    .buckets
+                    .iter()
+                    .filter(|(key, bucket)| {
+                        let config = key.config(&self.config);
+                        let mut bucket = **bucket;
+                        bucket.refill(config, now);
+                        key.budget() == budget && !bucket.is_full(config)
+                    })
+                    .count() as u64;
+
+                BudgetMetrics {
+                    budget,
+                    allowed: counters.allowed,
+                    limited_by_user: counters.limited_by_user,
+                    limited_by_workspace: counters.limited_by_workspace,
+                    active_buckets,
+                }
+            })
+            .collect();
+
+        RateLimitMetrics {
+            enabled: self.config.enabled,
+            budgets,
+        }
+    }
+}
+
+/// A [`Layer`] checking the requests of the users it can authenticate against a [`RateLimiter`].
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: RateLimiter,
+    services_context: ServicesContext,
+    jwt_public_signing_key: JwtPublicSigningKey,
+}
+
+impl RateLimitLayer {
+    pub fn new(
+        limiter: RateLimiter,
+        services_context: ServicesContext,
+        jwt_public_signing_key: JwtPublicSigningKey,
+    ) -> Self {
+        Self {
+            limiter,
+            services_context,
+            jwt_public_signing_key,
+        }
+    }
+
+    /// The claim of the bearer token of the request. Whether the user may use the route is left
+    /// for the route to check.
+    ///
+    /// An API token is looked up without recording that it was used, and is left in the request's
+    /// extensions as a [`VerifiedApiToken`] so that authorizing the request doesn't look it up
+    /// again.
+    async fn claim(&self, request: &mut Request<Body>) -> Option<UserClaim> {
+        let authorization = request
+            .headers()
+            .get(header::AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .to_owned();
+
+        if ApiToken::is_api_token(&authorization) {
+            let ctx = self
+                .services_context
+                .clone()
+                .into_builder(false)
+                .build_default()
+                .await
+                .ok()?;
+            let api_token = ApiToken::find_active(&ctx, &authorization)
+                .await
+                .ok()
+                .flatten()?;
+            let claim = api_token.claim();
+            request.extensions_mut().insert(VerifiedApiToken {
+                authorization,
+                api_token,
+            });
+            Some(claim)
+        } else {
+            UserClaim::from_bearer_token(self.jwt_public_signing_key.clone(), &authorization)
+                .await
+                .ok()
+        }
+    }
+
+    async fn check(&self, request: &mut Request<Body>) -> Result<(), RateLimited> {
+        if !self.limiter.config().enabled {
+            return Ok(());
+        }
+        let budget = match RateLimitBudget::for_request(request.method(), request.uri().path()) {
+            Some(budget) => budget,
+            None => return Ok(()),
+        };
+        let claim = match self.claim(request).await {
+            Some(claim) => claim,
+            None => return Ok(()),
+        };
+
+        self.limiter.check(budget, &claim).map_err(|limited| {
+            warn!(
+                budget = %limited.budget,
+                scope = %limited.scope,
+                user_pk = %claim.user_pk,
+                workspace_pk = %claim.workspace_pk,
+                retry_after_ms = limited.retry_after.as_millis() as u64,
+                path = request.uri().path(),
+                "rate limited request",
+            );
+            limited
+        })
+    }
+}
+
+/// An active API token found by the [`RateLimitLayer`], along with the `Authorization` header it
+/// was found with.
+#[derive(Clone, Debug)]
+pub struct VerifiedApiToken {
+    pub authorization: String,
+    pub api_token: ApiToken,
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The [`Service`] of a [`RateLimitLayer`].
+#[derive(Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    layer: RateLimitLayer,
+}
+
+impl<S> Service<Request<Body>> for RateLimit<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        // The inner service was driven to readiness, so it's the one to call rather than its clone
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        Box::pin(async move {
+            if let Err(limited) = layer.check(&mut request).await {
+                return Ok(limited.into_response());
+            }
+            inner.call(request).await
+        })
+    }
+}
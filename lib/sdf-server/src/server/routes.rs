@@ -22,6 +22,15 @@ pub fn routes(state: AppState) -> Router {
             "/api/",
             Router::new().route("/", get(system_status_route).layer(CorsLayer::permissive())),
         )
+        .route(
+            "/api/openapi.json",
+            get(crate::server::openapi::openapi_document),
+        )
+        .nest(
+            "/api/api_token",
+            crate::server::service::api_token::routes(),
+        )
+        .nest("/api/audit", crate::server::service::audit::routes())
         .nest(
             "/api/change_set",
             crate::server::service::change_set::routes(),
@@ -30,6 +39,10 @@ pub fn routes(state: AppState) -> Router {
             "/api/component",
             crate::server::service::component::routes(),
         )
+        .nest(
+            "/api/diagnostics",
+            crate::server::service::diagnostics::routes(),
+        )
         .nest("/api/fix", crate::server::service::fix::routes())
         .nest("/api/func", crate::server::service::func::routes())
         .nest("/api/pkg", crate::server::service::pkg::routes())
@@ -38,6 +51,10 @@ pub fn routes(state: AppState) -> Router {
             "/api/qualification",
             crate::server::service::qualification::routes(),
         )
+        .nest(
+            "/api/rate_limit",
+            crate::server::service::rate_limit::routes(),
+        )
         .nest("/api/schema", crate::server::service::schema::routes())
         .nest("/api/diagram", crate::server::service::diagram::routes())
         .nest("/api/secret", crate::server::service::secret::routes())
@@ -47,6 +64,11 @@ pub fn routes(state: AppState) -> Router {
             "/api/variant_def",
             crate::server::service::variant_definition::routes(),
         )
+        .nest("/api/webhook", crate::server::service::webhook::routes())
+        .nest(
+            "/api/workspace",
+            crate::server::service::workspace::routes(),
+        )
         .nest("/api/ws", crate::server::service::ws::routes());
 
     // Load dev routes if we are in dev mode (decided by "opt-level" at the moment).
@@ -19,8 +19,10 @@ use ulid::Ulid;
 use dal::pkg::{import_pkg_from_pkg, ImportOptions, PkgError};
 use dal::tasks::{StatusReceiver, StatusReceiverError};
 use dal::{
-    builtins, BuiltinsError, DalContext, JwtPublicSigningKey, Tenancy, TransactionsError,
-    Workspace, WorkspaceError,
+    builtins,
+    job::definition::{SymmetricKeyRotationJob, DEFAULT_SYMMETRIC_KEY_ROTATION_BATCH_SIZE},
+    BuiltinsError, DalContext, EncryptedSecret, JwtPublicSigningKey, SecretError, Tenancy,
+    TransactionsError, Workspace, WorkspaceError,
 };
 use dal::{cyclone_key_pair::CycloneKeyPairError, tasks::ResourceScheduler, ServicesContext};
 use module_index_client::types::BuiltinsDetailsResponse;
@@ -36,6 +38,7 @@ use veritech_client::{Client as VeritechClient, EncryptionKey, EncryptionKeyErro
 
 use crate::server::config::CycloneKeyPair;
 
+use super::rate_limit::{RateLimitLayer, RateLimitsConfig};
 use super::state::AppState;
 use super::{routes, Config, IncomingStream, UdsIncomingStream, UdsIncomingStreamError};
 
@@ -78,6 +81,8 @@ pub enum ServerError {
     PkgInstall,
     #[error(transparent)]
     Posthog(#[from] si_posthog::PosthogError),
+    #[error(transparent)]
+    Secret(#[from] SecretError),
     #[error("failed to setup signal handler")]
     Signal(#[source] io::Error),
     #[error(transparent)]
@@ -128,6 +133,7 @@ impl Server<(), ()> {
                     jwt_public_signing_key,
                     config.signup_secret().clone(),
                     posthog_client,
+                    *config.rate_limits(),
                 )?;
 
                 info!("binding to HTTP socket; socket_addr={}", &socket_addr);
@@ -164,6 +170,7 @@ impl Server<(), ()> {
                     jwt_public_signing_key,
                     config.signup_secret().clone(),
                     posthog_client,
+                    *config.rate_limits(),
                 )?;
 
                 info!("binding to Unix domain socket; path={}", path.display());
@@ -228,6 +235,38 @@ impl Server<(), ()> {
         Ok(())
     }
 
+    /// Reports which symmetric keys [`EncryptedSecrets`](EncryptedSecret) are encrypted with and
+    /// enqueues a [`SymmetricKeyRotationJob`] to re-encrypt them all with the active key.
+    #[instrument(name = "sdf.init.rotate_symmetric_keys", skip_all)]
+    pub async fn rotate_symmetric_keys(
+        services_context: &ServicesContext,
+        batch_size: Option<i64>,
+    ) -> Result<()> {
+        let batch_size = batch_size.unwrap_or(DEFAULT_SYMMETRIC_KEY_ROTATION_BATCH_SIZE);
+        let ctx = services_context
+            .clone()
+            .into_builder(false)
+            .build_default()
+            .await?;
+
+        for usage in EncryptedSecret::key_hash_usage(&ctx).await? {
+            info!(
+                key_hash = %usage.key_hash,
+                count = %usage.count,
+                active = %usage.active,
+                loaded = %usage.loaded,
+                "symmetric key usage",
+            );
+        }
+
+        ctx.enqueue_job(SymmetricKeyRotationJob::new(batch_size))
+            .await?;
+        ctx.commit().await?;
+        info!(%batch_size, "enqueued symmetric key rotation job");
+
+        Ok(())
+    }
+
     /// Start the basic resource refresh scheduler
     pub async fn start_resource_refresh_scheduler(
         services_context: ServicesContext,
@@ -321,11 +360,10 @@ pub async fn migrate_builtins_from_module_index(services_context: &ServicesConte
 
     let module_index_url = services_context
         .module_index_url()
-        .as_ref()
         .ok_or(ServerError::ModuleIndexNotSet)?;
 
     let module_index_client =
-        IndexClient::unauthenticated_client(module_index_url.clone().as_str().try_into()?);
+        IndexClient::unauthenticated_client(module_index_url.as_str().try_into()?);
     let module_list = module_index_client.list_builtins().await?;
     let install_builtins = install_builtins(ctx, module_list, module_index_client);
     tokio::pin!(install_builtins);
@@ -429,6 +467,7 @@ pub fn build_service_for_tests(
         jwt_public_signing_key,
         signup_secret,
         posthog_client,
+        RateLimitsConfig::default(),
         true,
     )
 }
@@ -438,12 +477,14 @@ pub fn build_service(
     jwt_public_signing_key: JwtPublicSigningKey,
     signup_secret: SensitiveString,
     posthog_client: PosthogClient,
+    rate_limits: RateLimitsConfig,
 ) -> Result<(Router, oneshot::Receiver<()>, broadcast::Receiver<()>)> {
     build_service_inner(
         services_context,
         jwt_public_signing_key,
         signup_secret,
         posthog_client,
+        rate_limits,
         false,
     )
 }
@@ -453,6 +494,7 @@ fn build_service_inner(
     jwt_public_signing_key: JwtPublicSigningKey,
     signup_secret: SensitiveString,
     posthog_client: PosthogClient,
+    rate_limits: RateLimitsConfig,
     for_tests: bool,
 ) -> Result<(Router, oneshot::Receiver<()>, broadcast::Receiver<()>)> {
     let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
@@ -465,10 +507,17 @@ fn build_service_inner(
         posthog_client,
         shutdown_broadcast_tx.clone(),
         shutdown_tx,
+        rate_limits,
         for_tests,
     );
+    let rate_limit_layer = RateLimitLayer::new(
+        state.rate_limiter().clone(),
+        state.services_context().clone().into_inner(),
+        state.jwt_public_signing_key().clone(),
+    );
 
     let routes = routes(state)
+        .layer(rate_limit_layer)
         // TODO(fnichol): customize http tracing further, using:
         // https://docs.rs/tower-http/0.1.1/tower_http/trace/index.html
         .layer(
@@ -1,16 +1,22 @@
+pub mod api_token;
+pub mod audit;
 pub mod change_set;
 pub mod component;
+pub mod diagnostics;
 pub mod diagram;
 pub mod fix;
 pub mod func;
 pub mod pkg;
 pub mod provider;
 pub mod qualification;
+pub mod rate_limit;
 pub mod schema;
 pub mod secret;
 pub mod session;
 pub mod status;
 pub mod variant_definition;
+pub mod webhook;
+pub mod workspace;
 pub mod ws;
 
 /// A module containing dev routes for local development only.
@@ -0,0 +1,70 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use dal::{ApiTokenError as DalApiTokenError, TransactionsError, UserError, WorkspaceRole};
+use thiserror::Error;
+
+use crate::server::state::AppState;
+
+pub mod create_api_token;
+pub mod list_api_tokens;
+pub mod revoke_api_token;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum ApiTokenError {
+    #[error(transparent)]
+    ContextTransactions(#[from] TransactionsError),
+    #[error(transparent)]
+    DalApiToken(#[from] DalApiTokenError),
+    #[error(
+        "a token can't expire in more than {} days: {0}",
+        create_api_token::MAX_EXPIRES_IN_DAYS
+    )]
+    ExpiresInDaysTooLarge(u32),
+    #[error("a token scoped as {0} can't be created by a user whose role is {1}")]
+    ScopeExceedsRole(WorkspaceRole, WorkspaceRole),
+    #[error(transparent)]
+    User(#[from] UserError),
+}
+
+pub type ApiTokenResult<T> = Result<T, ApiTokenError>;
+
+impl IntoResponse for ApiTokenError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            ApiTokenError::DalApiToken(DalApiTokenError::NotFound(_)) => {
+                (StatusCode::NOT_FOUND, self.to_string())
+            }
+            ApiTokenError::ExpiresInDaysTooLarge(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            ApiTokenError::ScopeExceedsRole(..) => (StatusCode::FORBIDDEN, self.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        };
+
+        let body = Json(serde_json::json!({
+            "error": {
+                "message": error_message,
+                "code": 42,
+                "statusCode": status.as_u16()
+            }
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/create_api_token",
+            post(create_api_token::create_api_token),
+        )
+        .route("/list_api_tokens", get(list_api_tokens::list_api_tokens))
+        .route(
+            "/revoke_api_token",
+            post(revoke_api_token::revoke_api_token),
+        )
+}
@@ -0,0 +1,85 @@
+use axum::{extract::OriginalUri, Json};
+use chrono::{Duration, Utc};
+use dal::{context::AccessBuilder, ApiToken, HistoryActor, Tenancy, User, WorkspaceRole};
+use serde::{Deserialize, Serialize};
+
+use super::{ApiTokenError, ApiTokenResult};
+use crate::server::extract::{HandlerContext, PosthogClient, SessionAuthorization};
+use crate::server::tracking::track;
+
+/// The furthest in the future, in days, that a token may expire.
+pub const MAX_EXPIRES_IN_DAYS: u32 = 3650;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    /// The most the token may do, which can't be more than what the user's own role allows.
+    pub scope: WorkspaceRole,
+    /// How many days the token can be used for, at most 3650, or forever if not set.
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiTokenResponse {
+    pub api_token: ApiToken,
+    /// The value to authenticate with, which is only ever returned here.
+    pub token: String,
+}
+
+pub async fn create_api_token(
+    HandlerContext(builder): HandlerContext,
+    SessionAuthorization(claim): SessionAuthorization,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<CreateApiTokenRequest>,
+) -> ApiTokenResult<Json<CreateApiTokenResponse>> {
+    let ctx = builder
+        .build_head(AccessBuilder::new(
+            Tenancy::new(claim.workspace_pk),
+            HistoryActor::from(claim.user_pk),
+        ))
+        .await?;
+
+    // The session is authorized, so the user is a member of the workspace
+    let role = User::workspace_role(&ctx, claim.user_pk, claim.workspace_pk)
+        .await?
+        .unwrap_or(WorkspaceRole::Viewer);
+    if !role.permits(request.scope) {
+        return Err(ApiTokenError::ScopeExceedsRole(request.scope, role));
+    }
+
+    let expires_at = match request.expires_in_days {
+        Some(days) if days > MAX_EXPIRES_IN_DAYS => {
+            return Err(ApiTokenError::ExpiresInDaysTooLarge(days));
+        }
+        Some(days) => Some(Utc::now() + Duration::days(days.into())),
+        None => None,
+    };
+    let (api_token, token) = ApiToken::new(
+        &ctx,
+        claim.user_pk,
+        claim.workspace_pk,
+        &request.name,
+        request.scope,
+        expires_at,
+    )
+    .await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "create_api_token",
+        serde_json::json!({
+            "api_token_pk": api_token.pk(),
+            "scope": api_token.scope(),
+            "expires_at": api_token.expires_at(),
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(CreateApiTokenResponse { api_token, token }))
+}
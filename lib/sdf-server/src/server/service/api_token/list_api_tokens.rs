@@ -0,0 +1,28 @@
+use axum::Json;
+use dal::{context::AccessBuilder, ApiToken, HistoryActor, Tenancy};
+use serde::{Deserialize, Serialize};
+
+use super::ApiTokenResult;
+use crate::server::extract::{HandlerContext, SessionAuthorization};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListApiTokensResponse {
+    pub api_tokens: Vec<ApiToken>,
+}
+
+pub async fn list_api_tokens(
+    HandlerContext(builder): HandlerContext,
+    SessionAuthorization(claim): SessionAuthorization,
+) -> ApiTokenResult<Json<ListApiTokensResponse>> {
+    let ctx = builder
+        .build_head(AccessBuilder::new(
+            Tenancy::new(claim.workspace_pk),
+            HistoryActor::from(claim.user_pk),
+        ))
+        .await?;
+
+    let api_tokens = ApiToken::list_for_user(&ctx, claim.user_pk, claim.workspace_pk).await?;
+
+    Ok(Json(ListApiTokensResponse { api_tokens }))
+}
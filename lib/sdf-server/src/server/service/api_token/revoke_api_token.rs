@@ -0,0 +1,61 @@
+use axum::{extract::OriginalUri, Json};
+use dal::{
+    context::AccessBuilder, ApiToken, ApiTokenError as DalApiTokenError, ApiTokenPk, HistoryActor,
+    Tenancy,
+};
+use serde::{Deserialize, Serialize};
+
+use super::ApiTokenResult;
+use crate::server::extract::{HandlerContext, PosthogClient, SessionAuthorization};
+use crate::server::tracking::track;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeApiTokenRequest {
+    pub pk: ApiTokenPk,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeApiTokenResponse {
+    pub api_token: ApiToken,
+}
+
+pub async fn revoke_api_token(
+    HandlerContext(builder): HandlerContext,
+    SessionAuthorization(claim): SessionAuthorization,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<RevokeApiTokenRequest>,
+) -> ApiTokenResult<Json<RevokeApiTokenResponse>> {
+    let ctx = builder
+        .build_head(AccessBuilder::new(
+            Tenancy::new(claim.workspace_pk),
+            HistoryActor::from(claim.user_pk),
+        ))
+        .await?;
+
+    // Tokens of other users are reported as missing rather than forbidden, so that their
+    // existence isn't disclosed
+    let mut api_token = ApiToken::get_by_pk(&ctx, request.pk)
+        .await?
+        .filter(|api_token| {
+            api_token.user_pk() == claim.user_pk && api_token.workspace_pk() == claim.workspace_pk
+        })
+        .ok_or(DalApiTokenError::NotFound(request.pk))?;
+    api_token.revoke(&ctx).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "revoke_api_token",
+        serde_json::json!({
+            "api_token_pk": api_token.pk(),
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(RevokeApiTokenResponse { api_token }))
+}
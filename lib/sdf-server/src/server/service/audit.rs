@@ -0,0 +1,90 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use dal::{
+    ChangeSetPk, ComponentId, FuncId, HistoryActor, HistoryEventError, HistoryEventFilter,
+    HistoryEventPk, TransactionsError, UserPk,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::server::state::AppState;
+
+pub mod export_history_events;
+pub mod list_history_events;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error(transparent)]
+    ContextTransactions(#[from] TransactionsError),
+    #[error(transparent)]
+    HistoryEvent(#[from] HistoryEventError),
+}
+
+pub type AuditResult<T> = Result<T, AuditError>;
+
+impl IntoResponse for AuditError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = (StatusCode::INTERNAL_SERVER_ERROR, self.to_string());
+
+        let body = Json(serde_json::json!({
+            "error": {
+                "message": error_message,
+                "code": 42,
+                "statusCode": status.as_u16()
+            }
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+/// The query string filtering the history events of the workspace. Every filter that is set must
+/// match.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEventsRequest {
+    /// Matches events done by this user.
+    pub user_pk: Option<UserPk>,
+    pub label_prefix: Option<String>,
+    pub change_set_pk: Option<ChangeSetPk>,
+    pub component_id: Option<ComponentId>,
+    pub func_id: Option<FuncId>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub cursor: Option<HistoryEventPk>,
+    pub limit: Option<u32>,
+}
+
+impl From<HistoryEventsRequest> for HistoryEventFilter {
+    fn from(request: HistoryEventsRequest) -> Self {
+        Self {
+            actor: request.user_pk.map(HistoryActor::from),
+            label_prefix: request.label_prefix,
+            change_set_pk: request.change_set_pk,
+            component_id: request.component_id,
+            func_id: request.func_id,
+            since: request.since,
+            until: request.until,
+            cursor: request.cursor,
+            limit: request.limit,
+        }
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/list_history_events",
+            get(list_history_events::list_history_events),
+        )
+        .route(
+            "/export_history_events",
+            get(export_history_events::export_history_events),
+        )
+}
@@ -0,0 +1,49 @@
+use axum::{
+    body::StreamBody,
+    extract::{OriginalUri, Query},
+    http::header,
+    response::IntoResponse,
+};
+use dal::HistoryEvent;
+
+use super::{AuditResult, HistoryEventsRequest};
+use crate::server::extract::{AccessBuilder, HandlerContext, OwnerAuthorization, PosthogClient};
+use crate::server::tracking::track;
+
+/// Exports every history event matching the request as JSON lines, newest first. The request's
+/// cursor and limit are ignored.
+///
+/// The events are streamed a page at a time, so the status and headers are sent before every
+/// event has been read; a failure while reading a later page aborts the body.
+pub async fn export_history_events(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    OwnerAuthorization(_claim): OwnerAuthorization,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Query(request): Query<HistoryEventsRequest>,
+) -> AuditResult<impl IntoResponse> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    // The number of exported events isn't known until the body has been streamed
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "export_history_events",
+        serde_json::json!({}),
+    );
+
+    let lines = HistoryEvent::export_json_lines(ctx, &request.into());
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"history-events.jsonl\"",
+            ),
+        ],
+        StreamBody::new(lines),
+    ))
+}
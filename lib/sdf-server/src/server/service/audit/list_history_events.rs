@@ -0,0 +1,18 @@
+use axum::{extract::Query, Json};
+use dal::{HistoryEvent, HistoryEventPage};
+
+use super::{AuditResult, HistoryEventsRequest};
+use crate::server::extract::{AccessBuilder, HandlerContext, OwnerAuthorization};
+
+pub async fn list_history_events(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    OwnerAuthorization(_claim): OwnerAuthorization,
+    Query(request): Query<HistoryEventsRequest>,
+) -> AuditResult<Json<HistoryEventPage>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let page = HistoryEvent::list(&ctx, &request.into()).await?;
+
+    Ok(Json(page))
+}
@@ -4,20 +4,28 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use dal::{change_status::ChangeStatusError, component::ComponentViewError};
+use dal::{
+    attribute::value::impact::AttributeValueImpactError,
+    attribute::value::provenance::AttributeValueProvenanceError, change_status::ChangeStatusError,
+    component::query::ComponentQueryError, component::ComponentViewError,
+};
 use dal::{
     component::view::debug::ComponentDebugViewError, node::NodeError,
     property_editor::PropertyEditorError, AttributeContextBuilderError,
-    AttributePrototypeArgumentError, AttributePrototypeError, AttributeValueError, ChangeSetError,
-    ComponentError as DalComponentError, ComponentId, DiagramError, ExternalProviderError,
-    FuncBindingError, FuncError, InternalProviderError, PropId, ReconciliationPrototypeError,
-    SchemaError as DalSchemaError, StandardModelError, TransactionsError, WsEventError,
+    AttributePrototypeArgumentError, AttributePrototypeError, AttributeValueError,
+    BulkAttributeUpdateError, ChangeSetError, ComponentError as DalComponentError, ComponentId,
+    DiagramError, ExternalProviderError, FuncBindingError, FuncError, InternalProviderError,
+    PropId, ReconciliationPrototypeError, SchemaError as DalSchemaError, StandardModelError,
+    TransactionsError, WsEventError,
 };
 use thiserror::Error;
 
 use crate::{server::state::AppState, service::schema::SchemaError};
 
 pub mod alter_simulation;
+pub mod attribute_value_impact;
+pub mod attribute_value_provenance;
+pub mod bulk_update_property_value;
 pub mod debug;
 pub mod get_code;
 pub mod get_components_metadata;
@@ -29,6 +37,7 @@ pub mod insert_property_editor_value;
 pub mod json;
 pub mod list_qualifications;
 pub mod list_resources;
+pub mod query;
 pub mod refresh;
 pub mod resource_domain_diff;
 pub mod set_type;
@@ -47,8 +56,14 @@ pub enum ComponentError {
     AttributePrototypeNotFound,
     #[error("attribute value error: {0}")]
     AttributeValue(#[from] AttributeValueError),
+    #[error("attribute value impact error: {0}")]
+    AttributeValueImpact(#[from] AttributeValueImpactError),
     #[error("attribute value not found")]
     AttributeValueNotFound,
+    #[error("attribute value provenance error: {0}")]
+    AttributeValueProvenance(#[from] AttributeValueProvenanceError),
+    #[error("bulk attribute update error: {0}")]
+    BulkAttributeUpdate(#[from] BulkAttributeUpdateError),
     #[error("change set error: {0}")]
     ChangeSet(#[from] ChangeSetError),
     #[error("change status error: {0}")]
@@ -63,6 +78,8 @@ pub enum ComponentError {
     ComponentNameNotFound,
     #[error("component not found for id: {0}")]
     ComponentNotFound(ComponentId),
+    #[error("component query error: {0}")]
+    ComponentQuery(#[from] ComponentQueryError),
     #[error("component view error: {0}")]
     ComponentView(#[from] ComponentViewError),
     #[error("dal schema error: {0}")]
@@ -122,6 +139,16 @@ impl IntoResponse for ComponentError {
         let (status, error_message) = match self {
             ComponentError::SchemaNotFound => (StatusCode::NOT_FOUND, self.to_string()),
             ComponentError::InvalidVisibility => (StatusCode::NOT_FOUND, self.to_string()),
+            ComponentError::ComponentQuery(
+                ComponentQueryError::InvalidJsonPointer(_)
+                | ComponentQueryError::InvalidLiteral(_, _)
+                | ComponentQueryError::InvalidOperator(_, _)
+                | ComponentQueryError::InvalidQualificationStatus(_)
+                | ComponentQueryError::InvalidResourceStatus(_)
+                | ComponentQueryError::UnexpectedEnd
+                | ComponentQueryError::UnexpectedToken(_, _)
+                | ComponentQueryError::UnterminatedString(_),
+            ) => (StatusCode::BAD_REQUEST, self.to_string()),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
@@ -144,6 +171,7 @@ pub fn routes() -> Router<AppState> {
             get(list_qualifications::list_qualifications),
         )
         .route("/list_resources", get(list_resources::list_resources))
+        .route("/query", get(query::query))
         .route("/get_code", get(get_code::get_code))
         .route("/get_diff", get(get_diff::get_diff))
         .route(
@@ -158,6 +186,10 @@ pub fn routes() -> Router<AppState> {
             "/update_property_editor_value",
             post(update_property_editor_value::update_property_editor_value),
         )
+        .route(
+            "/bulk_update_property_value",
+            post(bulk_update_property_value::bulk_update_property_value),
+        )
         .route(
             "/insert_property_editor_value",
             post(insert_property_editor_value::insert_property_editor_value),
@@ -174,5 +206,13 @@ pub fn routes() -> Router<AppState> {
             post(alter_simulation::alter_simulation),
         )
         .route("/debug", get(debug::debug_component))
+        .route(
+            "/attribute_value_impact",
+            get(attribute_value_impact::attribute_value_impact),
+        )
+        .route(
+            "/attribute_value_provenance",
+            get(attribute_value_provenance::attribute_value_provenance),
+        )
         .route("/json", get(json::json))
 }
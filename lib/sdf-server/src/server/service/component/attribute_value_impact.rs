@@ -0,0 +1,30 @@
+use axum::extract::Query;
+use axum::Json;
+use dal::attribute::value::impact::AttributeValueImpact;
+use dal::{AttributeValueId, Visibility};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentResult;
+use crate::server::extract::{AccessBuilder, HandlerContext};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueImpactRequest {
+    pub attribute_value_id: AttributeValueId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+pub type AttributeValueImpactResponse = AttributeValueImpact;
+
+pub async fn attribute_value_impact(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Query(request): Query<AttributeValueImpactRequest>,
+) -> ComponentResult<Json<AttributeValueImpactResponse>> {
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let impact = AttributeValueImpact::new(&ctx, &[request.attribute_value_id]).await?;
+
+    Ok(Json(impact))
+}
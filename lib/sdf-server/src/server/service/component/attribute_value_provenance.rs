@@ -0,0 +1,30 @@
+use axum::extract::Query;
+use axum::Json;
+use dal::attribute::value::provenance::AttributeValueProvenance;
+use dal::{AttributeValueId, Visibility};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentResult;
+use crate::server::extract::{AccessBuilder, HandlerContext};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeValueProvenanceRequest {
+    pub attribute_value_id: AttributeValueId,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+pub type AttributeValueProvenanceResponse = AttributeValueProvenance;
+
+pub async fn attribute_value_provenance(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Query(request): Query<AttributeValueProvenanceRequest>,
+) -> ComponentResult<Json<AttributeValueProvenanceResponse>> {
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let provenance = AttributeValueProvenance::new(&ctx, request.attribute_value_id).await?;
+
+    Ok(Json(provenance))
+}
@@ -0,0 +1,110 @@
+use axum::extract::OriginalUri;
+use axum::{response::IntoResponse, Json};
+use dal::component::query::ComponentQuery;
+use dal::{
+    BulkAttributeUpdate, BulkAttributeUpdateEntry, BulkAttributeValue, ChangeSet, ComponentId,
+    SchemaVariantId, Visibility, WsEvent,
+};
+use serde::{Deserialize, Serialize};
+
+use super::ComponentResult;
+use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient};
+use crate::server::tracking::track;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdatePropertyValueRequest {
+    pub schema_variant_id: SchemaVariantId,
+    pub prop_path: Vec<String>,
+    pub component_ids: Option<Vec<ComponentId>>,
+    /// A [`ComponentQuery`] used to select components when "component_ids" is not provided.
+    pub query: Option<String>,
+    pub value: BulkAttributeValue,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkUpdatePropertyValueResponse {
+    pub updated: Vec<BulkAttributeUpdateEntry>,
+}
+
+pub async fn bulk_update_property_value(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<BulkUpdatePropertyValueRequest>,
+) -> ComponentResult<impl IntoResponse> {
+    let mut ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let mut force_changeset_pk = None;
+    if ctx.visibility().is_head() {
+        let change_set = ChangeSet::new(&ctx, ChangeSet::generate_name(), None).await?;
+
+        let new_visibility = Visibility::new(change_set.pk, request.visibility.deleted_at);
+
+        ctx.update_visibility(new_visibility);
+
+        force_changeset_pk = Some(change_set.pk);
+
+        WsEvent::change_set_created(&ctx, change_set.pk)
+            .await?
+            .publish_on_commit(&ctx)
+            .await?;
+    };
+
+    let component_ids = match (request.component_ids, request.query) {
+        (Some(component_ids), _) => Some(component_ids),
+        (None, Some(query)) => Some(
+            ComponentQuery::parse(&query)?
+                .evaluate(&ctx)
+                .await?
+                .into_iter()
+                .filter(|matched| matched.schema_variant_id == request.schema_variant_id)
+                .map(|matched| matched.component_id)
+                .collect(),
+        ),
+        (None, None) => None,
+    };
+
+    let updated = BulkAttributeUpdate::new(
+        request.schema_variant_id,
+        request.prop_path.clone(),
+        component_ids,
+        request.value,
+    )
+    .apply(&ctx)
+    .await?;
+
+    WsEvent::change_set_written(&ctx)
+        .await?
+        .publish_on_commit(&ctx)
+        .await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "property_value_bulk_updated",
+        serde_json::json!({
+            "schema_variant_id": request.schema_variant_id,
+            "prop_path": request.prop_path,
+            "component_count": updated.len(),
+        }),
+    );
+
+    ctx.commit().await?;
+
+    let mut response = axum::response::Response::builder();
+    if let Some(force_changeset_pk) = force_changeset_pk {
+        response = response.header("force_changeset_pk", force_changeset_pk.to_string());
+    }
+    response = response.header("content-type", "application/json");
+    Ok(
+        response.body(serde_json::to_string(&BulkUpdatePropertyValueResponse {
+            updated,
+        })?)?,
+    )
+}
@@ -0,0 +1,31 @@
+use axum::extract::Query;
+use axum::Json;
+use dal::component::query::{ComponentQuery, ComponentQueryMatch};
+use dal::Visibility;
+use serde::{Deserialize, Serialize};
+
+use super::ComponentResult;
+use crate::server::extract::{AccessBuilder, HandlerContext};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryComponentsRequest {
+    pub query: String,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+pub type QueryComponentsResponse = Vec<ComponentQueryMatch>;
+
+pub async fn query(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Query(request): Query<QueryComponentsRequest>,
+) -> ComponentResult<Json<QueryComponentsResponse>> {
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let query = ComponentQuery::parse(&request.query)?;
+    let matches = query.evaluate(&ctx).await?;
+
+    Ok(Json(matches))
+}
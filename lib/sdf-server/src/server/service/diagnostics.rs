@@ -0,0 +1,179 @@
+//! End-to-end probes of every service sdf depends on, used by `si status --deep` to tell which
+//! part of a stack is broken. Reaching the route at all means sdf is serving HTTP; every other
+//! check goes through the service the way a real request would.
+//!
+//! The probes reach into every service, so unlike the root health route this one requires a member
+//! of a workspace to be signed in. It still must not report anything more than the health of the
+//! stack.
+
+use std::{error::Error, future::Future, time::Duration};
+
+use axum::{extract::State, routing::get, Json, Router};
+use base64::{engine::general_purpose, Engine};
+use dal::job::definition::HealthCheckJob;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use telemetry::prelude::*;
+use tokio::{sync::mpsc, time::Instant};
+use veritech_client::{
+    FunctionResult, ResolverFunctionComponent, ResolverFunctionRequest,
+    ResolverFunctionResponseType,
+};
+
+use crate::server::{extract::Authorization, state::AppState};
+
+/// How long a single probe may take before it is reported as failed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+const PING_HANDLER: &str = "ping";
+const PING_CODE: &str = "function ping() { return \"pong\"; }";
+
+type ProbeResult = Result<Option<String>, Box<dyn Error + Send + Sync>>;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsResponse {
+    pub ok: bool,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticCheck {
+    pub name: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    /// What the probe found when it succeeded, or why it failed.
+    pub detail: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/", get(diagnostics))
+}
+
+pub async fn diagnostics(
+    Authorization(_claim): Authorization,
+    State(state): State<AppState>,
+) -> Json<DiagnosticsResponse> {
+    let services_context = state.services_context().clone().into_inner();
+
+    // The probes run one after the other so that the latencies aren't skewed by each other
+    let checks = vec![
+        probe("postgres", postgres(&services_context)).await,
+        probe("nats", nats(&services_context)).await,
+        probe("council", council(&services_context)).await,
+        probe("veritech", veritech(&services_context)).await,
+        probe("pinga", pinga(&services_context, state.for_tests())).await,
+    ];
+
+    Json(DiagnosticsResponse {
+        ok: checks.iter().all(|check| check.ok),
+        checks,
+    })
+}
+
+async fn probe(name: &str, check: impl Future<Output = ProbeResult>) -> DiagnosticCheck {
+    let start = Instant::now();
+    let result = tokio::time::timeout(PROBE_TIMEOUT, check).await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    let (ok, detail) = match result {
+        Ok(Ok(detail)) => (true, detail),
+        Ok(Err(err)) => (false, Some(err.to_string())),
+        Err(_) => (
+            false,
+            Some(format!(
+                "timed out after {} seconds",
+                PROBE_TIMEOUT.as_secs()
+            )),
+        ),
+    };
+    if !ok {
+        warn!(%name, ?detail, "diagnostics probe failed");
+    }
+
+    DiagnosticCheck {
+        name: name.to_owned(),
+        ok,
+        latency_ms,
+        detail,
+    }
+}
+
+async fn postgres(services_context: &dal::ServicesContext) -> ProbeResult {
+    let client = services_context.pg_pool().get().await?;
+    let row = client
+        .query_one(
+            "SELECT max(version) AS version FROM refinery_schema_history",
+            &[],
+        )
+        .await?;
+    let version: Option<i32> = row.try_get("version")?;
+
+    Ok(Some(match version {
+        Some(version) => format!("migration version {version}"),
+        None => "no migrations applied".to_owned(),
+    }))
+}
+
+async fn nats(services_context: &dal::ServicesContext) -> ProbeResult {
+    let nats = services_context.nats_conn();
+    let inbox = nats.new_inbox();
+    let mut subscriber = nats.subscribe(&inbox).await?;
+    nats.publish(&inbox, "ping").await?;
+
+    let received = subscriber.next().await;
+    subscriber.unsubscribe().await?;
+    match received {
+        Some(_) => Ok(None),
+        None => Err("subscription closed before the message was received".into()),
+    }
+}
+
+async fn council(services_context: &dal::ServicesContext) -> ProbeResult {
+    let nats = services_context.nats_conn();
+    let council_subject = if let Some(subject_prefix) = nats.metadata().subject_prefix() {
+        format!("{subject_prefix}.council")
+    } else {
+        "council".to_string()
+    };
+
+    council_server::client::ping(nats, &council_subject).await?;
+    Ok(None)
+}
+
+async fn veritech(services_context: &dal::ServicesContext) -> ProbeResult {
+    let request = ResolverFunctionRequest {
+        execution_id: format!("diagnostics-{}", ulid::Ulid::new()),
+        handler: PING_HANDLER.to_owned(),
+        component: ResolverFunctionComponent::default(),
+        response_type: ResolverFunctionResponseType::String,
+        code_base64: general_purpose::STANDARD_NO_PAD.encode(PING_CODE),
+    };
+    // The receiver has to be kept alive for the output of the function to be sent anywhere
+    let (output_tx, _output_rx) = mpsc::channel(64);
+
+    match services_context
+        .veritech()
+        .execute_resolver_function(output_tx, &request)
+        .await?
+    {
+        FunctionResult::Success(success) if success.data == serde_json::json!("pong") => Ok(None),
+        FunctionResult::Success(success) => {
+            Err(format!("function returned {} instead of \"pong\"", success.data).into())
+        }
+        FunctionResult::Failure(failure) => Err(failure.error.message.into()),
+    }
+}
+
+async fn pinga(services_context: &dal::ServicesContext, blocking: bool) -> ProbeResult {
+    let ctx = services_context
+        .clone()
+        .into_builder(blocking)
+        .build_default()
+        .await?;
+
+    // Blocking on the job waits for pinga to consume it and report back
+    ctx.block_on_job(HealthCheckJob::new()).await?;
+    Ok(None)
+}
@@ -53,14 +53,14 @@ pub struct InputSocketView {
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SchemaVariantView {
-    id: SchemaVariantId,
-    builtin: bool,
-    name: String,
-    schema_name: String,
-    schema_id: SchemaId,
-    color: String,
-    input_sockets: Vec<InputSocketView>,
-    output_sockets: Vec<OutputSocketView>,
+    pub id: SchemaVariantId,
+    pub builtin: bool,
+    pub name: String,
+    pub schema_name: String,
+    pub schema_id: SchemaId,
+    pub color: String,
+    pub input_sockets: Vec<InputSocketView>,
+    pub output_sockets: Vec<OutputSocketView>,
 }
 pub type ListSchemaVariantsResponse = Vec<SchemaVariantView>;
 
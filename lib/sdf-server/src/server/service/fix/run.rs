@@ -3,7 +3,7 @@ use axum::Json;
 use serde::{Deserialize, Serialize};
 
 use super::{FixError, FixResult};
-use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient};
+use crate::server::extract::{ApplierAccessBuilder, HandlerContext, PosthogClient};
 use crate::server::tracking::track;
 use dal::job::definition::{FixItem, FixesJob};
 use dal::{
@@ -34,7 +34,7 @@ pub struct FixesRunResponse {
 
 pub async fn run(
     HandlerContext(builder): HandlerContext,
-    AccessBuilder(request_ctx): AccessBuilder,
+    ApplierAccessBuilder(request_ctx): ApplierAccessBuilder,
     PosthogClient(posthog_client): PosthogClient,
     OriginalUri(original_uri): OriginalUri,
     Json(request): Json<FixesRunRequest>,
@@ -22,9 +22,11 @@ const MAX_NAME_SEARCH_ATTEMPTS: usize = 100;
 
 pub mod builtin_module_spec;
 pub mod export_pkg;
+pub mod export_pkg_file;
 pub mod export_workspace;
 pub mod get_pkg;
 pub mod install_pkg;
+pub mod install_pkg_file;
 pub mod list_pkgs;
 mod reject_pkg;
 pub mod remote_module_spec;
@@ -32,6 +34,8 @@ pub mod remote_module_spec;
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum PkgError {
+    #[error("base64 decode error: {0}")]
+    Base64Decode(#[from] base64::DecodeError),
     #[error("Could not canononicalize path: {0}")]
     Canononicalize(#[from] CanonicalFileError),
     #[error(transparent)]
@@ -194,12 +198,17 @@ pub async fn pkg_open(builder: &DalContextBuilder, file_name: &str) -> PkgResult
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/export_pkg", post(export_pkg::export_pkg))
+        .route("/export_pkg_file", post(export_pkg_file::export_pkg_file))
         .route(
             "/export_workspace",
             post(export_workspace::export_workspace),
         )
         .route("/get_module_by_hash", get(get_pkg::get_module_by_hash))
         .route("/install_pkg", post(install_pkg::install_pkg))
+        .route(
+            "/install_pkg_file",
+            post(install_pkg_file::install_pkg_file),
+        )
         .route("/list_pkgs", get(list_pkgs::list_pkgs))
         .route(
             "/remote_module_spec",
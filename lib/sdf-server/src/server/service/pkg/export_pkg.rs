@@ -3,7 +3,10 @@ use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient, RawAc
 use crate::server::tracking::track;
 use axum::extract::OriginalUri;
 use axum::Json;
-use dal::{HistoryActor, SchemaVariant, SchemaVariantId, StandardModel, User, Visibility, WsEvent};
+use dal::{
+    DalContext, HistoryActor, SchemaVariant, SchemaVariantId, StandardModel, User, Visibility,
+    WsEvent,
+};
 use serde::{Deserialize, Serialize};
 use telemetry::prelude::*;
 
@@ -35,6 +38,65 @@ pub async fn export_pkg(
 ) -> PkgResult<Json<ExportPkgResponse>> {
     let ctx = builder.build(request_ctx.build(request.visibility)).await?;
 
+    validate_export_request(&request)?;
+
+    let module_index_url = match ctx.module_index_url() {
+        Some(url) => url,
+        None => return Err(PkgError::ModuleIndexNotConfigured),
+    };
+
+    let PackagedModule {
+        payload: module_payload,
+        created_by_name,
+        created_by_email,
+    } = package_module(&ctx, &request).await?;
+
+    let index_client = module_index_client::IndexClient::new(
+        module_index_url.as_str().try_into()?,
+        &raw_access_token,
+    );
+    let response = index_client
+        .upload_module(request.name.trim(), request.version.trim(), module_payload)
+        .await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "export_pkg",
+        serde_json::json!({
+                    "pkg_name": request.name,
+                    "pkg_version": request.version,
+                    "pkg_description": request.description,
+                    "pkg_created_by_name": created_by_name,
+                    "pkg_created_by_email": created_by_email,
+                    "pkg_schema_count": request.schema_variants.len(),
+                    "pkg_hash": response.latest_hash,
+        }),
+    );
+
+    // TODO: Is this really the WsEvent we want to send right now?
+    WsEvent::change_set_written(&ctx)
+        .await?
+        .publish_on_commit(&ctx)
+        .await?;
+
+    ctx.commit().await?;
+
+    Ok(Json(ExportPkgResponse {
+        success: true,
+        full_path: "Get this from module-index service".to_owned(),
+    }))
+}
+
+/// A module packaged from the schema variants of an [`ExportPkgRequest`].
+pub(super) struct PackagedModule {
+    pub payload: Vec<u8>,
+    pub created_by_name: String,
+    pub created_by_email: String,
+}
+
+pub(super) fn validate_export_request(request: &ExportPkgRequest) -> PkgResult<()> {
     if request.name.trim().is_empty() {
         return Err(PkgError::PackageNameEmpty);
     }
@@ -47,13 +109,15 @@ pub async fn export_pkg(
         return Err(PkgError::PackageExportEmpty);
     }
 
-    let module_index_url = match ctx.module_index_url() {
-        Some(url) => url,
-        None => return Err(PkgError::ModuleIndexNotConfigured),
-    };
+    Ok(())
+}
 
+pub(super) async fn package_module(
+    ctx: &DalContext,
+    request: &ExportPkgRequest,
+) -> PkgResult<PackagedModule> {
     let user = match ctx.history_actor() {
-        HistoryActor::User(user_pk) => User::get_by_pk(&ctx, *user_pk).await?,
+        HistoryActor::User(user_pk) => User::get_by_pk(ctx, *user_pk).await?,
         _ => None,
     };
 
@@ -69,10 +133,10 @@ pub async fn export_pkg(
     // XXX:rework frontend to send schema ids
     let mut schema_ids = vec![];
     for variant_id in &request.schema_variants {
-        let schema = SchemaVariant::get_by_id(&ctx, variant_id)
+        let schema = SchemaVariant::get_by_id(ctx, variant_id)
             .await?
             .ok_or(PkgError::SchemaVariantNotFound(*variant_id))?
-            .schema(&ctx)
+            .schema(ctx)
             .await?
             .ok_or(PkgError::SchemaNotFoundForVariant(*variant_id))?;
         schema_ids.push(*schema.id());
@@ -86,40 +150,9 @@ pub async fn export_pkg(
         schema_ids,
     );
 
-    let module_payload = exporter.export_as_bytes(&ctx).await?;
-
-    let index_client =
-        module_index_client::IndexClient::new(module_index_url.try_into()?, &raw_access_token);
-    let response = index_client
-        .upload_module(request.name.trim(), request.version.trim(), module_payload)
-        .await?;
-
-    track(
-        &posthog_client,
-        &ctx,
-        &original_uri,
-        "export_pkg",
-        serde_json::json!({
-                    "pkg_name": request.name,
-                    "pkg_version": request.version,
-                    "pkg_description": request.description,
-                    "pkg_created_by_name": created_by_name,
-                    "pkg_created_by_email": created_by_email,
-                    "pkg_schema_count": request.schema_variants.len(),
-                    "pkg_hash": response.latest_hash,
-        }),
-    );
-
-    // TODO: Is this really the WsEvent we want to send right now?
-    WsEvent::change_set_written(&ctx)
-        .await?
-        .publish_on_commit(&ctx)
-        .await?;
-
-    ctx.commit().await?;
-
-    Ok(Json(ExportPkgResponse {
-        success: true,
-        full_path: "Get this from module-index service".to_owned(),
-    }))
+    Ok(PackagedModule {
+        payload: exporter.export_as_bytes(ctx).await?,
+        created_by_name,
+        created_by_email,
+    })
 }
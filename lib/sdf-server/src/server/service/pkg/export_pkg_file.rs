@@ -0,0 +1,54 @@
+use super::export_pkg::{package_module, validate_export_request, ExportPkgRequest};
+use super::PkgResult;
+use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient};
+use crate::server::tracking::track;
+use axum::extract::OriginalUri;
+use axum::Json;
+use base64::{engine::general_purpose, Engine};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPkgFileResponse {
+    pub name: String,
+    pub version: String,
+    /// The contents of the package file, base64 encoded.
+    pub data: String,
+}
+
+/// Packages schema variants as a module and returns the package file instead of publishing it to
+/// the module index, so that it can be kept alongside other files and installed with
+/// `install_pkg_file`.
+pub async fn export_pkg_file(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<ExportPkgRequest>,
+) -> PkgResult<Json<ExportPkgFileResponse>> {
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    validate_export_request(&request)?;
+    let module = package_module(&ctx, &request).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "export_pkg_file",
+        serde_json::json!({
+                    "pkg_name": request.name,
+                    "pkg_version": request.version,
+                    "pkg_description": request.description,
+                    "pkg_created_by_name": module.created_by_name,
+                    "pkg_created_by_email": module.created_by_email,
+                    "pkg_schema_count": request.schema_variants.len(),
+        }),
+    );
+
+    Ok(Json(ExportPkgFileResponse {
+        name: request.name,
+        version: request.version,
+        data: general_purpose::STANDARD.encode(module.payload),
+    }))
+}
@@ -68,8 +68,10 @@ pub async fn export_workspace(
         None => return Err(PkgError::ModuleIndexNotConfigured),
     };
 
-    let index_client =
-        module_index_client::IndexClient::new(module_index_url.try_into()?, &raw_access_token);
+    let index_client = module_index_client::IndexClient::new(
+        module_index_url.as_str().try_into()?,
+        &raw_access_token,
+    );
     let response = index_client
         .upload_module(workspace.name().as_str(), &version, module_payload)
         .await?;
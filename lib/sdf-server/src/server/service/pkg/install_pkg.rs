@@ -11,7 +11,7 @@ use axum::extract::OriginalUri;
 use axum::Json;
 use dal::pkg::ModuleImported;
 use dal::WorkspacePk;
-use dal::{pkg::import_pkg_from_pkg, Visibility, WsEvent};
+use dal::{pkg::import_pkg_from_pkg, DalContext, Visibility, WsEvent};
 use module_index_client::IndexClient;
 use serde::{Deserialize, Serialize};
 use si_pkg::{SiPkg, SiPkgKind};
@@ -48,12 +48,11 @@ pub async fn install_pkg(
         None => return Err(PkgError::ModuleIndexNotConfigured),
     };
 
-    let module_index_client = IndexClient::new(module_index_url.try_into()?, &raw_access_token);
+    let module_index_client =
+        IndexClient::new(module_index_url.as_str().try_into()?, &raw_access_token);
     let pkg_data = module_index_client.download_module(request.id).await?;
 
-    let pkg = SiPkg::load_from_bytes(pkg_data)?;
-    let metadata = pkg.metadata()?;
-    let (_, svs, import_skips) = import_pkg_from_pkg(&ctx, &pkg, None).await?;
+    let (pkg_name, response) = import_pkg_bytes(&ctx, pkg_data).await?;
 
     track(
         &posthog_client,
@@ -61,12 +60,28 @@ pub async fn install_pkg(
         &original_uri,
         "install_pkg",
         serde_json::json!({
-                    "pkg_name": metadata.name().to_owned(),
+                    "pkg_name": pkg_name,
         }),
     );
 
+    ctx.commit().await?;
+
+    Ok(Json(response))
+}
+
+/// Imports a package into the workspace, publishing which schema variants or workspace were
+/// imported once the transaction commits. Returns the name of the package along with what was
+/// skipped.
+pub(super) async fn import_pkg_bytes(
+    ctx: &DalContext,
+    pkg_data: Vec<u8>,
+) -> PkgResult<(String, InstallPkgResponse)> {
+    let pkg = SiPkg::load_from_bytes(pkg_data)?;
+    let metadata = pkg.metadata()?;
+    let (_, svs, import_skips) = import_pkg_from_pkg(ctx, &pkg, None).await?;
+
     WsEvent::module_imported(
-        &ctx,
+        ctx,
         match metadata.kind() {
             SiPkgKind::Module => ModuleImported::Module {
                 schema_variant_ids: svs,
@@ -82,11 +97,9 @@ pub async fn install_pkg(
         },
     )
     .await?
-    .publish_on_commit(&ctx)
+    .publish_on_commit(ctx)
     .await?;
 
-    ctx.commit().await?;
-
     let skipped_edges = import_skips
         .as_ref()
         .is_some_and(|skips| skips.iter().any(|skip| !skip.edge_skips.is_empty()));
@@ -95,9 +108,12 @@ pub async fn install_pkg(
         .as_ref()
         .is_some_and(|skips| skips.iter().any(|skip| !skip.attribute_skips.is_empty()));
 
-    Ok(Json(InstallPkgResponse {
-        success: true,
-        skipped_edges,
-        skipped_attributes,
-    }))
+    Ok((
+        metadata.name().to_owned(),
+        InstallPkgResponse {
+            success: true,
+            skipped_edges,
+            skipped_attributes,
+        },
+    ))
 }
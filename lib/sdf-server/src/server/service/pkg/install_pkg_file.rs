@@ -0,0 +1,47 @@
+use super::install_pkg::{import_pkg_bytes, InstallPkgResponse};
+use super::PkgResult;
+use crate::server::extract::{AccessBuilder, HandlerContext, PosthogClient};
+use crate::server::tracking::track;
+use axum::extract::OriginalUri;
+use axum::Json;
+use base64::{engine::general_purpose, Engine};
+use dal::Visibility;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallPkgFileRequest {
+    /// The contents of the package file, base64 encoded.
+    pub data: String,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+/// Installs a package file, such as one returned by `export_pkg_file`, instead of downloading
+/// the package from the module index.
+pub async fn install_pkg_file(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<InstallPkgFileRequest>,
+) -> PkgResult<Json<InstallPkgResponse>> {
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let pkg_data = general_purpose::STANDARD.decode(request.data)?;
+    let (pkg_name, response) = import_pkg_bytes(&ctx, pkg_data).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "install_pkg_file",
+        serde_json::json!({
+                    "pkg_name": pkg_name,
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(response))
+}
@@ -22,8 +22,8 @@ pub struct PkgListResponse {
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PkgView {
-    name: String,
-    hash: String,
+    pub name: String,
+    pub hash: String,
 }
 
 pub async fn list_pkgs(
@@ -55,7 +55,8 @@ pub async fn reject_pkg(
 
     let module_id = request.id;
 
-    let module_index_client = IndexClient::new(module_index_url.try_into()?, &raw_access_token);
+    let module_index_client =
+        IndexClient::new(module_index_url.as_str().try_into()?, &raw_access_token);
 
     module_index_client
         .reject_module(module_id, created_by_email.clone())
@@ -38,7 +38,8 @@ pub async fn remote_module_spec(
         None => return Err(PkgError::ModuleIndexNotConfigured),
     };
 
-    let module_index_client = IndexClient::new(module_index_url.try_into()?, &raw_access_token);
+    let module_index_client =
+        IndexClient::new(module_index_url.as_str().try_into()?, &raw_access_token);
     let pkg_data = module_index_client.download_module(request.id).await?;
 
     let pkg = SiPkg::load_from_bytes(pkg_data)?;
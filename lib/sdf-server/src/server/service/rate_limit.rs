@@ -0,0 +1,25 @@
+//! Counts of the requests let through and rejected by the [rate limits](crate::server::rate_limit) of
+//! each budget, for operators tuning them.
+//!
+//! Any authenticated user can read them, so they are only reported as totals across all users and
+//! workspaces.
+
+use axum::{extract::State, routing::get, Json, Router};
+
+use crate::server::{
+    extract::Authorization,
+    rate_limit::{RateLimitMetrics, RateLimiter},
+    state::AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/metrics", get(metrics))
+}
+
+/// Counts the requests let through and rejected by each rate limit budget.
+pub async fn metrics(
+    Authorization(_claim): Authorization,
+    State(rate_limiter): State<RateLimiter>,
+) -> Json<RateLimitMetrics> {
+    Json(rate_limiter.metrics())
+}
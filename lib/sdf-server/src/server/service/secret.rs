@@ -1,7 +1,7 @@
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
 use dal::{
@@ -13,8 +13,12 @@ use thiserror::Error;
 use crate::server::state::AppState;
 
 pub mod create_secret;
+pub mod create_secret_reference;
+pub mod delete_secret;
 pub mod get_public_key;
+pub mod list_secret_usages;
 pub mod list_secrets;
+pub mod rotate_key_pair;
 
 #[remain::sorted]
 #[derive(Debug, Error)]
@@ -31,6 +35,8 @@ pub enum SecretError {
     Pg(#[from] si_data_pg::PgError),
     #[error(transparent)]
     Secret(#[from] dal::SecretError),
+    #[error("secret not found: {0}")]
+    SecretNotFound(SecretId),
     #[error("definition not found for secret: {0}")]
     SecretWithInvalidDefinition(SecretId),
     #[error(transparent)]
@@ -47,8 +53,17 @@ pub type SecretResult<T> = Result<T, SecretError>;
 
 impl IntoResponse for SecretError {
     fn into_response(self) -> Response {
-        let (status, error_message) = (StatusCode::INTERNAL_SERVER_ERROR, self.to_string());
-        //SecretError::SecretNotFound => (StatusCode::NOT_FOUND, self.to_string()),
+        let (status, error_message) = match self {
+            SecretError::Secret(dal::SecretError::InUse(..)) => {
+                (StatusCode::CONFLICT, self.to_string())
+            }
+            SecretError::Secret(
+                dal::SecretError::DatabaseBackendReference
+                | dal::SecretError::InvalidReferencePath(_),
+            ) => (StatusCode::BAD_REQUEST, self.to_string()),
+            SecretError::SecretNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        };
 
         let body = Json(serde_json::json!({
             "error": {
@@ -65,6 +80,13 @@ impl IntoResponse for SecretError {
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/get_public_key", get(get_public_key::get_public_key))
+        .route("/rotate_key_pair", post(rotate_key_pair::rotate_key_pair))
         .route("/", post(create_secret::create_secret))
+        .route(
+            "/reference",
+            post(create_secret_reference::create_secret_reference),
+        )
         .route("/", get(list_secrets::list_secrets))
+        .route("/", delete(delete_secret::delete_secret))
+        .route("/usages", get(list_secret_usages::list_secret_usages))
 }
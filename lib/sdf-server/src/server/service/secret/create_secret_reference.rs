@@ -0,0 +1,49 @@
+use axum::Json;
+use dal::secret::SecretView;
+use dal::{EncryptedSecret, SecretBackendKind, SecretReference, Visibility, WsEvent};
+use serde::{Deserialize, Serialize};
+
+use crate::server::extract::{AccessBuilder, HandlerContext};
+
+use super::SecretResult;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSecretReferenceRequest {
+    pub name: String,
+    pub definition: String,
+    pub description: Option<String>,
+    pub backend: SecretBackendKind,
+    pub reference: SecretReference,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+pub type CreateSecretReferenceResponse = SecretView;
+
+pub async fn create_secret_reference(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_tx): AccessBuilder,
+    Json(request): Json<CreateSecretReferenceRequest>,
+) -> SecretResult<Json<CreateSecretReferenceResponse>> {
+    let ctx = builder.build(request_tx.build(request.visibility)).await?;
+
+    let secret = EncryptedSecret::new_reference(
+        &ctx,
+        request.name,
+        request.definition,
+        request.description,
+        request.backend,
+        &request.reference,
+    )
+    .await?;
+
+    WsEvent::change_set_written(&ctx)
+        .await?
+        .publish_on_commit(&ctx)
+        .await?;
+
+    ctx.commit().await?;
+
+    Ok(Json(SecretView::from_secret(&ctx, secret).await?))
+}
@@ -0,0 +1,31 @@
+use axum::extract::Query;
+use axum::Json;
+use dal::{SecretId, SecretUsage, Visibility};
+use serde::{Deserialize, Serialize};
+
+use crate::server::extract::{AccessBuilder, HandlerContext};
+
+use super::SecretResult;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListSecretUsagesRequest {
+    /// Only list the usages of this secret. Every secret is listed if it is not provided.
+    pub id: Option<SecretId>,
+    #[serde(flatten)]
+    pub visibility: Visibility,
+}
+
+pub type ListSecretUsagesResponse = Vec<SecretUsage>;
+
+pub async fn list_secret_usages(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(request_ctx): AccessBuilder,
+    Query(request): Query<ListSecretUsagesRequest>,
+) -> SecretResult<Json<ListSecretUsagesResponse>> {
+    let ctx = builder.build(request_ctx.build(request.visibility)).await?;
+
+    let usages = SecretUsage::list(&ctx, request.id).await?;
+
+    Ok(Json(usages))
+}
@@ -0,0 +1,39 @@
+use axum::Json;
+use dal::{key_pair::KeyPairPk, EncryptedSecret, KeyPair, PublicKey, WsEvent};
+use serde::{Deserialize, Serialize};
+
+use super::SecretResult;
+use crate::server::extract::{AccessBuilder, HandlerContext};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateKeyPairResponse {
+    pub public_key: PublicKey,
+    pub rewrapped_secret_count: usize,
+    pub deactivated_key_pair_pks: Vec<KeyPairPk>,
+}
+
+pub async fn rotate_key_pair(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+) -> SecretResult<Json<RotateKeyPairResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let key_pair = KeyPair::rotate(&ctx).await?;
+    let rewrapped_secret_count = EncryptedSecret::rewrap_for_key_pair(&ctx, &key_pair).await?;
+    let deactivated_key_pair_pks = KeyPair::deactivate_unused(&ctx).await?;
+    let public_key = PublicKey::get_current(&ctx).await?;
+
+    WsEvent::key_pair_rotated(&ctx, key_pair.pk())
+        .await?
+        .publish_on_commit(&ctx)
+        .await?;
+
+    ctx.commit().await?;
+
+    Ok(Json(RotateKeyPairResponse {
+        public_key,
+        rewrapped_secret_count,
+        deactivated_key_pair_pks,
+    }))
+}
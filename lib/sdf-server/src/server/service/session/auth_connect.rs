@@ -1,7 +1,9 @@
 use super::{SessionError, SessionResult};
 use crate::server::extract::{HandlerContext, RawAccessToken};
 use axum::Json;
-use dal::{DalContext, HistoryActor, KeyPair, Tenancy, User, UserPk, Workspace, WorkspacePk};
+use dal::{
+    DalContext, HistoryActor, KeyPair, Tenancy, User, UserPk, Workspace, WorkspacePk, WorkspaceRole,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -115,8 +117,15 @@ async fn find_or_create_user_and_workspace(
         }
     };
 
-    // ensure workspace is associated to user
-    user.associate_workspace(&ctx, *workspace.pk()).await?;
+    // ensure workspace is associated to user, where everyone but the creator of the workspace
+    // joins as an editor until an owner gives them another role
+    let role = if auth_api_workspace.creator_user_id == user.pk() {
+        WorkspaceRole::Owner
+    } else {
+        WorkspaceRole::Editor
+    };
+    user.associate_workspace(&ctx, *workspace.pk(), role)
+        .await?;
 
     ctx.commit().await?;
 
@@ -0,0 +1,60 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use dal::{TransactionsError, WebhookEndpointPk, WebhookError as DalWebhookError};
+use thiserror::Error;
+
+use crate::server::state::AppState;
+
+pub mod create_endpoint;
+pub mod delete_endpoint;
+pub mod list_deliveries;
+pub mod list_endpoints;
+pub mod update_endpoint;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error(transparent)]
+    ContextTransactions(#[from] TransactionsError),
+    #[error(transparent)]
+    DalWebhook(#[from] DalWebhookError),
+    #[error("webhook endpoint not found: {0}")]
+    EndpointNotFound(WebhookEndpointPk),
+}
+
+pub type WebhookResult<T> = Result<T, WebhookError>;
+
+impl IntoResponse for WebhookError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            WebhookError::DalWebhook(DalWebhookError::InvalidUrl(..)) => {
+                (StatusCode::BAD_REQUEST, self.to_string())
+            }
+            WebhookError::EndpointNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        };
+
+        let body = Json(serde_json::json!({
+            "error": {
+                "message": error_message,
+                "code": 42,
+                "statusCode": status.as_u16()
+            }
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/create_endpoint", post(create_endpoint::create_endpoint))
+        .route("/delete_endpoint", post(delete_endpoint::delete_endpoint))
+        .route("/list_deliveries", get(list_deliveries::list_deliveries))
+        .route("/list_endpoints", get(list_endpoints::list_endpoints))
+        .route("/update_endpoint", post(update_endpoint::update_endpoint))
+}
@@ -0,0 +1,51 @@
+use axum::{extract::OriginalUri, Json};
+use dal::{WebhookEndpoint, WebhookEventKind};
+use serde::{Deserialize, Serialize};
+
+use super::WebhookResult;
+use crate::server::extract::{AccessBuilder, HandlerContext, OwnerAuthorization, PosthogClient};
+use crate::server::tracking::track;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEndpointRequest {
+    pub url: String,
+    pub event_kinds: Vec<WebhookEventKind>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEndpointResponse {
+    pub endpoint: WebhookEndpoint,
+    /// The secret requests to the endpoint are signed with, which is only ever returned here.
+    pub secret: String,
+}
+
+pub async fn create_endpoint(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    OwnerAuthorization(claim): OwnerAuthorization,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<CreateEndpointRequest>,
+) -> WebhookResult<Json<CreateEndpointResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let (endpoint, secret) =
+        WebhookEndpoint::new(&ctx, claim.workspace_pk, &request.url, request.event_kinds).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "create_webhook_endpoint",
+        serde_json::json!({
+            "webhook_endpoint_pk": endpoint.pk(),
+            "event_kinds": endpoint.event_kinds(),
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(CreateEndpointResponse { endpoint, secret }))
+}
@@ -0,0 +1,50 @@
+use axum::{extract::OriginalUri, Json};
+use dal::{WebhookEndpoint, WebhookEndpointPk};
+use serde::{Deserialize, Serialize};
+
+use super::{WebhookError, WebhookResult};
+use crate::server::extract::{AccessBuilder, HandlerContext, OwnerAuthorization, PosthogClient};
+use crate::server::tracking::track;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteEndpointRequest {
+    pub pk: WebhookEndpointPk,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteEndpointResponse {
+    pub success: bool,
+}
+
+pub async fn delete_endpoint(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    OwnerAuthorization(claim): OwnerAuthorization,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<DeleteEndpointRequest>,
+) -> WebhookResult<Json<DeleteEndpointResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let endpoint = WebhookEndpoint::get_by_pk(&ctx, request.pk)
+        .await?
+        .filter(|endpoint| endpoint.workspace_pk() == claim.workspace_pk)
+        .ok_or(WebhookError::EndpointNotFound(request.pk))?;
+    endpoint.delete(&ctx).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "delete_webhook_endpoint",
+        serde_json::json!({
+            "webhook_endpoint_pk": request.pk,
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(DeleteEndpointResponse { success: true }))
+}
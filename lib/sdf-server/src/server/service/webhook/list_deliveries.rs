@@ -0,0 +1,42 @@
+use axum::{extract::Query, Json};
+use dal::{WebhookDelivery, WebhookEndpoint, WebhookEndpointPk};
+use serde::{Deserialize, Serialize};
+
+use super::{WebhookError, WebhookResult};
+use crate::server::extract::{AccessBuilder, HandlerContext, OwnerAuthorization};
+
+/// How many deliveries are listed when no limit is given.
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeliveriesRequest {
+    pub endpoint_pk: WebhookEndpointPk,
+    pub limit: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeliveriesResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+/// Lists the latest deliveries of an endpoint, newest first.
+pub async fn list_deliveries(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    OwnerAuthorization(claim): OwnerAuthorization,
+    Query(request): Query<ListDeliveriesRequest>,
+) -> WebhookResult<Json<ListDeliveriesResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let endpoint = WebhookEndpoint::get_by_pk(&ctx, request.endpoint_pk)
+        .await?
+        .filter(|endpoint| endpoint.workspace_pk() == claim.workspace_pk)
+        .ok_or(WebhookError::EndpointNotFound(request.endpoint_pk))?;
+    let limit = request.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let deliveries = WebhookDelivery::list_for_endpoint(&ctx, endpoint.pk(), limit).await?;
+
+    Ok(Json(ListDeliveriesResponse { deliveries }))
+}
@@ -0,0 +1,24 @@
+use axum::Json;
+use dal::WebhookEndpoint;
+use serde::{Deserialize, Serialize};
+
+use super::WebhookResult;
+use crate::server::extract::{AccessBuilder, HandlerContext, OwnerAuthorization};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListEndpointsResponse {
+    pub endpoints: Vec<WebhookEndpoint>,
+}
+
+pub async fn list_endpoints(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    OwnerAuthorization(claim): OwnerAuthorization,
+) -> WebhookResult<Json<ListEndpointsResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let endpoints = WebhookEndpoint::list_for_workspace(&ctx, claim.workspace_pk).await?;
+
+    Ok(Json(ListEndpointsResponse { endpoints }))
+}
@@ -0,0 +1,56 @@
+use axum::{extract::OriginalUri, Json};
+use dal::{WebhookEndpoint, WebhookEndpointPk, WebhookEventKind};
+use serde::{Deserialize, Serialize};
+
+use super::{WebhookError, WebhookResult};
+use crate::server::extract::{AccessBuilder, HandlerContext, OwnerAuthorization, PosthogClient};
+use crate::server::tracking::track;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEndpointRequest {
+    pub pk: WebhookEndpointPk,
+    pub event_kinds: Vec<WebhookEventKind>,
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEndpointResponse {
+    pub endpoint: WebhookEndpoint,
+}
+
+pub async fn update_endpoint(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    OwnerAuthorization(claim): OwnerAuthorization,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<UpdateEndpointRequest>,
+) -> WebhookResult<Json<UpdateEndpointResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let mut endpoint = WebhookEndpoint::get_by_pk(&ctx, request.pk)
+        .await?
+        .filter(|endpoint| endpoint.workspace_pk() == claim.workspace_pk)
+        .ok_or(WebhookError::EndpointNotFound(request.pk))?;
+    endpoint
+        .update(&ctx, request.event_kinds, request.enabled)
+        .await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "update_webhook_endpoint",
+        serde_json::json!({
+            "webhook_endpoint_pk": endpoint.pk(),
+            "event_kinds": endpoint.event_kinds(),
+            "enabled": endpoint.enabled(),
+        }),
+    );
+
+    ctx.commit().await?;
+
+    Ok(Json(UpdateEndpointResponse { endpoint }))
+}
@@ -0,0 +1,57 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use dal::{TransactionsError, UserError};
+use thiserror::Error;
+
+use crate::server::state::AppState;
+
+pub mod list_members;
+pub mod update_member_role;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum WorkspaceError {
+    #[error(transparent)]
+    ContextTransactions(#[from] TransactionsError),
+    #[error(transparent)]
+    User(#[from] UserError),
+}
+
+pub type WorkspaceResult<T> = Result<T, WorkspaceError>;
+
+impl IntoResponse for WorkspaceError {
+    fn into_response(self) -> Response {
+        let (status, error_message) = match self {
+            WorkspaceError::User(UserError::LastOwner(_)) => {
+                (StatusCode::CONFLICT, self.to_string())
+            }
+            WorkspaceError::User(UserError::NotAWorkspaceMember(..)) => {
+                (StatusCode::NOT_FOUND, self.to_string())
+            }
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        };
+
+        let body = Json(serde_json::json!({
+            "error": {
+                "message": error_message,
+                "code": 42,
+                "statusCode": status.as_u16()
+            }
+        }));
+
+        (status, body).into_response()
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/list_members", get(list_members::list_members))
+        .route(
+            "/update_member_role",
+            post(update_member_role::update_member_role),
+        )
+}
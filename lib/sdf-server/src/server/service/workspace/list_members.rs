@@ -0,0 +1,24 @@
+use axum::Json;
+use dal::{User, WorkspaceMember};
+use serde::{Deserialize, Serialize};
+
+use super::WorkspaceResult;
+use crate::server::extract::{AccessBuilder, Authorization, HandlerContext};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListMembersResponse {
+    pub members: Vec<WorkspaceMember>,
+}
+
+pub async fn list_members(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    Authorization(claim): Authorization,
+) -> WorkspaceResult<Json<ListMembersResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    let members = User::list_for_workspace(&ctx, claim.workspace_pk).await?;
+
+    Ok(Json(ListMembersResponse { members }))
+}
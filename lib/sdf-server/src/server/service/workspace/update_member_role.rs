@@ -0,0 +1,50 @@
+use axum::{extract::OriginalUri, Json};
+use dal::{User, UserPk, WorkspaceMember, WorkspaceRole};
+use serde::{Deserialize, Serialize};
+
+use super::WorkspaceResult;
+use crate::server::extract::{AccessBuilder, HandlerContext, OwnerAuthorization, PosthogClient};
+use crate::server::tracking::track;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMemberRoleRequest {
+    pub user_pk: UserPk,
+    pub role: WorkspaceRole,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMemberRoleResponse {
+    pub members: Vec<WorkspaceMember>,
+}
+
+pub async fn update_member_role(
+    HandlerContext(builder): HandlerContext,
+    AccessBuilder(access_builder): AccessBuilder,
+    OwnerAuthorization(claim): OwnerAuthorization,
+    PosthogClient(posthog_client): PosthogClient,
+    OriginalUri(original_uri): OriginalUri,
+    Json(request): Json<UpdateMemberRoleRequest>,
+) -> WorkspaceResult<Json<UpdateMemberRoleResponse>> {
+    let ctx = builder.build_head(access_builder).await?;
+
+    User::set_workspace_role(&ctx, request.user_pk, claim.workspace_pk, request.role).await?;
+
+    track(
+        &posthog_client,
+        &ctx,
+        &original_uri,
+        "update_workspace_member_role",
+        serde_json::json!({
+            "user_pk": request.user_pk,
+            "role": request.role,
+        }),
+    );
+
+    let members = User::list_for_workspace(&ctx, claim.workspace_pk).await?;
+
+    ctx.commit().await?;
+
+    Ok(Json(UpdateMemberRoleResponse { members }))
+}
@@ -18,6 +18,8 @@ pub enum WsError {
     Transactions(#[from] TransactionsError),
 }
 
+pub mod event_filter;
+pub mod event_log;
 pub mod workspace_updates;
 
 impl IntoResponse for WsError {
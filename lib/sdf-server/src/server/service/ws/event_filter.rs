@@ -0,0 +1,49 @@
+//! Lets websocket clients choose which of the workspace's events they are sent, so that a client
+//! only interested in one change set isn't sent the events of every other change set.
+
+use std::collections::HashSet;
+
+use dal::ChangeSetPk;
+use serde::{Deserialize, Serialize};
+
+use super::event_log::LoggedEvent;
+
+/// The payload kind of the event telling a client to resync, which is sent whatever the filter.
+const RESYNC_REQUIRED_KIND: &str = "ResyncRequired";
+
+/// A message sent by a websocket client to the server.
+#[remain::sorted]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum ClientMessage {
+    /// Replaces the filter of the client's events.
+    Subscribe(EventFilter),
+}
+
+/// Which events a client is sent. Every filter that is set must match, and a client that never
+/// sets a filter is sent every event.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+    /// Matches events of these change sets. Events of the head change set have the
+    /// [`ChangeSetPk::NONE`] pk.
+    pub change_set_pks: Option<HashSet<ChangeSetPk>>,
+    /// Matches events whose payload is one of these kinds, such as `StatusUpdate`.
+    pub payload_kinds: Option<HashSet<String>>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &LoggedEvent) -> bool {
+        if event.payload_kind == RESYNC_REQUIRED_KIND {
+            return true;
+        }
+
+        self.change_set_pks
+            .as_ref()
+            .map_or(true, |pks| pks.contains(&event.change_set_pk))
+            && self
+                .payload_kinds
+                .as_ref()
+                .map_or(true, |kinds| kinds.contains(&event.payload_kind))
+    }
+}
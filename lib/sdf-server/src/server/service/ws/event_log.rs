@@ -0,0 +1,344 @@
+//! A bounded log of the events published for each workspace, which numbers the events so that a
+//! websocket client that reconnects can be sent the events it missed.
+//!
+//! A workspace's log is kept while it has subscribers and for an idle timeout after the last one
+//! left, so that a client can still resume after a reconnection. The log is then dropped and its
+//! NATS subscription is unsubscribed, and clients resuming from it are told to resync.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use dal::{ChangeSetPk, WorkspacePk, WsEvent};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use si_data_nats::{NatsClient, NatsError, Subscriber};
+use telemetry::prelude::*;
+use thiserror::Error;
+use tokio::{
+    sync::{broadcast, Mutex},
+    time::Instant,
+};
+
+/// The number of events kept for each workspace when no capacity is given.
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 1000;
+
+/// How long the log of a workspace is kept without subscribers when no idle timeout is given.
+pub const DEFAULT_EVENT_LOG_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum EventLogError {
+    #[error("failed to serialize event: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("failed to subscribe to subject {1}")]
+    Subscribe(#[source] NatsError, String),
+}
+
+pub type EventLogResult<T> = Result<T, EventLogError>;
+
+/// Where a reconnecting client left off: the last event it received and the stream that event
+/// belongs to.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Resume {
+    pub stream_id: Option<String>,
+    pub resume_from: Option<u64>,
+}
+
+/// A logged event, serialized once for every client along with what clients filter events by.
+#[derive(Debug)]
+pub struct LoggedEvent {
+    pub change_set_pk: ChangeSetPk,
+    pub payload_kind: String,
+    pub message: String,
+}
+
+impl LoggedEvent {
+    fn new(event: &WsEvent) -> EventLogResult<Self> {
+        Ok(Self {
+            change_set_pk: event.change_set_pk(),
+            payload_kind: event.payload_kind().to_owned(),
+            message: serde_json::to_string(event)?,
+        })
+    }
+}
+
+type Logs = Arc<Mutex<HashMap<WorkspacePk, Arc<Mutex<EventLog>>>>>;
+
+/// The event logs of every workspace with a websocket client, or which had one within the idle
+/// timeout.
+#[derive(Clone, Debug)]
+pub struct WorkspaceEventLogs {
+    nats: NatsClient,
+    capacity: usize,
+    idle_timeout: Duration,
+    logs: Logs,
+}
+
+impl WorkspaceEventLogs {
+    pub fn new(nats: NatsClient, capacity: usize, idle_timeout: Duration) -> Self {
+        Self {
+            nats,
+            capacity,
+            idle_timeout,
+            logs: Default::default(),
+        }
+    }
+
+    /// Whether the events of a workspace are currently being logged.
+    pub async fn is_logging(&self, workspace_pk: WorkspacePk) -> bool {
+        match self.logs.lock().await.get(&workspace_pk) {
+            Some(log) => !log.lock().await.closed,
+            None => false,
+        }
+    }
+
+    /// Subscribes to the events of a workspace, starting to log them if this is the first
+    /// subscription for the workspace.
+    pub async fn subscribe(
+        &self,
+        workspace_pk: WorkspacePk,
+        resume: &Resume,
+    ) -> EventLogResult<EventLogSubscription> {
+        let mut logs = self.logs.lock().await;
+        let existing = match logs.get(&workspace_pk) {
+            Some(log) if !log.lock().await.closed => Some(log.clone()),
+            _ => None,
+        };
+        let log = match existing {
+            Some(log) => log,
+            None => {
+                let log = self.start_log(workspace_pk).await?;
+                logs.insert(workspace_pk, log.clone());
+                log
+            }
+        };
+
+        // Holding the locks while subscribing means that the live events start right after the
+        // replayed ones, and that the log can't be evicted before it has a subscriber
+        let locked = log.lock().await;
+        let backlog = match locked.replay(resume) {
+            Some(events) => events,
+            None => vec![locked.resync_required()?],
+        };
+        let receiver = locked.sender.subscribe();
+        drop(locked);
+        drop(logs);
+
+        Ok(EventLogSubscription {
+            backlog,
+            receiver,
+            log,
+        })
+    }
+
+    async fn start_log(&self, workspace_pk: WorkspacePk) -> EventLogResult<Arc<Mutex<EventLog>>> {
+        let subject = format!("si.workspace_pk.{workspace_pk}.>");
+        let subscriber = self
+            .nats
+            .subscribe(&subject)
+            .await
+            .map_err(|err| EventLogError::Subscribe(err, subject))?;
+
+        let log = Arc::new(Mutex::new(EventLog::new(workspace_pk, self.capacity)));
+        tokio::spawn(record_events(
+            self.logs.clone(),
+            log.clone(),
+            subscriber,
+            self.idle_timeout,
+        ));
+
+        Ok(log)
+    }
+}
+
+/// The events a client has to be sent when it subscribes, followed by the live events of the
+/// workspace.
+#[derive(Debug)]
+pub struct EventLogSubscription {
+    backlog: Vec<Arc<LoggedEvent>>,
+    receiver: broadcast::Receiver<Arc<LoggedEvent>>,
+    log: Arc<Mutex<EventLog>>,
+}
+
+impl EventLogSubscription {
+    /// Takes the events that have to be sent before any live event.
+    pub fn take_backlog(&mut self) -> Vec<Arc<LoggedEvent>> {
+        std::mem::take(&mut self.backlog)
+    }
+
+    /// Waits for the next live event. An error means the subscription lagged behind or the log
+    /// was closed.
+    pub async fn recv(&mut self) -> Result<Arc<LoggedEvent>, broadcast::error::RecvError> {
+        self.receiver.recv().await
+    }
+
+    /// The event telling the client to resync, for when the subscription lagged behind.
+    pub async fn resync_required(&self) -> EventLogResult<Arc<LoggedEvent>> {
+        self.log.lock().await.resync_required()
+    }
+}
+
+#[derive(Debug)]
+struct EventLog {
+    workspace_pk: WorkspacePk,
+    stream_id: String,
+    capacity: usize,
+    next_sequence: u64,
+    events: VecDeque<(u64, Arc<LoggedEvent>)>,
+    sender: broadcast::Sender<Arc<LoggedEvent>>,
+    closed: bool,
+}
+
+impl EventLog {
+    fn new(workspace_pk: WorkspacePk, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            workspace_pk,
+            stream_id: ulid::Ulid::new().to_string(),
+            capacity,
+            next_sequence: 1,
+            events: VecDeque::with_capacity(capacity),
+            sender,
+            closed: false,
+        }
+    }
+
+    fn latest_sequence(&self) -> Option<u64> {
+        self.next_sequence.checked_sub(1).filter(|seq| *seq > 0)
+    }
+
+    fn append(&mut self, mut event: WsEvent) -> EventLogResult<()> {
+        let sequence = self.next_sequence;
+        event.set_sequence(self.stream_id.clone(), sequence);
+        let message = Arc::new(LoggedEvent::new(&event)?);
+
+        self.next_sequence += 1;
+        if self.events.len() >= self.capacity {
+            self.events.pop_front();
+        }
+        if self.capacity > 0 {
+            self.events.push_back((sequence, message.clone()));
+        }
+        // Sending only fails when nobody is connected, in which case the log is all we need
+        let _ = self.sender.send(message);
+
+        Ok(())
+    }
+
+    /// The events to send to a client resuming from the given point, or [`None`] if it has to
+    /// resync. A client that doesn't resume gets no events besides the live ones.
+    fn replay(&self, resume: &Resume) -> Option<Vec<Arc<LoggedEvent>>> {
+        let (stream_id, resume_from) = match (resume.stream_id.as_deref(), resume.resume_from) {
+            (None, None) => return Some(Vec::new()),
+            (Some(stream_id), Some(resume_from)) => (stream_id, resume_from),
+            _ => return None,
+        };
+        if stream_id != self.stream_id || resume_from >= self.next_sequence {
+            return None;
+        }
+        let oldest_sequence = self
+            .events
+            .front()
+            .map_or(self.next_sequence, |(sequence, _)| *sequence);
+        if resume_from + 1 < oldest_sequence {
+            return None;
+        }
+
+        Some(
+            self.events
+                .iter()
+                .filter(|(sequence, _)| *sequence > resume_from)
+                .map(|(_, message)| message.clone())
+                .collect(),
+        )
+    }
+
+    fn resync_required(&self) -> EventLogResult<Arc<LoggedEvent>> {
+        let event = WsEvent::resync_required(
+            self.workspace_pk,
+            self.stream_id.clone(),
+            self.latest_sequence(),
+        );
+        Ok(Arc::new(LoggedEvent::new(&event)?))
+    }
+}
+
+async fn record_events(
+    logs: Logs,
+    log: Arc<Mutex<EventLog>>,
+    mut subscriber: Subscriber,
+    idle_timeout: Duration,
+) {
+    let mut idle_check = tokio::time::interval(idle_timeout);
+    let mut idle_since = None;
+    loop {
+        tokio::select! {
+            msg = subscriber.next() => {
+                let msg = match msg {
+                    Some(msg) => msg,
+                    None => break,
+                };
+                let event: WsEvent = match serde_json::from_slice(msg.payload()) {
+                    Ok(event) => event,
+                    Err(err) => {
+                        warn!(
+                            error = ?err,
+                            subject = msg.subject(),
+                            "skipping unparseable workspace event"
+                        );
+                        continue;
+                    }
+                };
+                if let Err(err) = log.lock().await.append(event) {
+                    warn!(error = ?err, "failed to log workspace event");
+                }
+            }
+            _ = idle_check.tick() => {
+                if evict_if_idle(&logs, &log, &mut idle_since, idle_timeout).await {
+                    trace!("workspace event log idle, unsubscribing");
+                    if let Err(err) = subscriber.unsubscribe().await {
+                        warn!(error = ?err, "failed to unsubscribe from workspace events");
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    trace!("workspace event subscriber closed, closing event log");
+    log.lock().await.closed = true;
+}
+
+/// Closes the log and removes it from the logs once it has had no subscribers for the idle
+/// timeout, returning whether it did.
+async fn evict_if_idle(
+    logs: &Logs,
+    log: &Arc<Mutex<EventLog>>,
+    idle_since: &mut Option<Instant>,
+    idle_timeout: Duration,
+) -> bool {
+    // Locking in the same order as subscribing, so that no subscription starts while evicting
+    let mut logs = logs.lock().await;
+    let mut locked = log.lock().await;
+    if locked.sender.receiver_count() > 0 {
+        *idle_since = None;
+        return false;
+    }
+    let since = *idle_since.get_or_insert_with(Instant::now);
+    if since.elapsed() < idle_timeout {
+        return false;
+    }
+
+    locked.closed = true;
+    if logs
+        .get(&locked.workspace_pk)
+        .map_or(false, |logged| Arc::ptr_eq(logged, log))
+    {
+        logs.remove(&locked.workspace_pk);
+    }
+    true
+}
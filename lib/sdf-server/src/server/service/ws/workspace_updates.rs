@@ -1,34 +1,43 @@
-use super::WsError;
+use super::{
+    event_log::{Resume, WorkspaceEventLogs},
+    WsError,
+};
 use axum::{
-    extract::{ws::WebSocket, State, WebSocketUpgrade},
+    extract::{ws::WebSocket, Query, State, WebSocketUpgrade},
     response::IntoResponse,
 };
 use dal::WorkspacePk;
-use si_data_nats::NatsClient;
 use telemetry::prelude::*;
 use tokio::sync::broadcast;
 
-use crate::server::{
-    extract::{Nats, WsAuthorization},
-    state::ShutdownBroadcast,
-};
+use crate::server::{extract::WsAuthorization, state::ShutdownBroadcast};
 
-#[instrument(skip(wsu, nats))]
+/// Streams the events of the workspace to a websocket client. A client that reconnects can pass
+/// the `streamId` and `sequence` of the last event it received as the `streamId` and `resumeFrom`
+/// query parameters to first be sent the events it missed, or a `ResyncRequired` event if they
+/// can't be replayed anymore.
+///
+/// A client can send a `Subscribe` message at any time to only be sent the events of some change
+/// sets or payload kinds from then on, such as
+/// `{"kind": "Subscribe", "changeSetPks": ["..."], "payloadKinds": ["StatusUpdate"]}`.
+#[instrument(skip(wsu, event_logs))]
 #[allow(clippy::unused_async)]
 pub async fn workspace_updates(
     wsu: WebSocketUpgrade,
-    Nats(nats): Nats,
     WsAuthorization(claim): WsAuthorization,
+    Query(resume): Query<Resume>,
+    State(event_logs): State<WorkspaceEventLogs>,
     State(shutdown_broadcast): State<ShutdownBroadcast>,
 ) -> Result<impl IntoResponse, WsError> {
     async fn handle_socket(
         socket: WebSocket,
-        nats: NatsClient,
+        event_logs: WorkspaceEventLogs,
         mut shutdown: broadcast::Receiver<()>,
         workspace_pk: WorkspacePk,
+        resume: Resume,
     ) {
         tokio::select! {
-            _ = run_workspace_updates_proto(socket, nats, workspace_pk) => {
+            _ = run_workspace_updates_proto(socket, event_logs, workspace_pk, resume) => {
                 trace!("finished workspace_updates proto");
             }
             _ = shutdown.recv() => {
@@ -41,15 +50,21 @@ pub async fn workspace_updates(
     }
 
     let shutdown = shutdown_broadcast.subscribe();
-    Ok(wsu.on_upgrade(move |socket| handle_socket(socket, nats, shutdown, claim.workspace_pk)))
+    Ok(wsu.on_upgrade(move |socket| {
+        handle_socket(socket, event_logs, shutdown, claim.workspace_pk, resume)
+    }))
 }
 
 async fn run_workspace_updates_proto(
     mut socket: WebSocket,
-    nats: NatsClient,
+    event_logs: WorkspaceEventLogs,
     workspace_pk: WorkspacePk,
+    resume: Resume,
 ) {
-    let proto = match workspace_updates::run(nats, workspace_pk).start().await {
+    let proto = match workspace_updates::run(event_logs, workspace_pk, resume)
+        .start()
+        .await
+    {
         Ok(started) => started,
         Err(err) => {
             // This is likely due to nats failing to subscribe to the required topic, which is
@@ -78,14 +93,26 @@ mod workspace_updates {
 
     use axum::extract::ws::{self, WebSocket};
     use dal::WorkspacePk;
-    use futures::StreamExt;
-    use si_data_nats::{NatsClient, NatsError, Subscriber};
     use telemetry::prelude::*;
     use thiserror::Error;
+    use tokio::sync::broadcast::error::RecvError;
     use tokio_tungstenite::tungstenite;
 
-    pub fn run(nats: NatsClient, workspace_pk: WorkspacePk) -> WorkspaceUpdates {
-        WorkspaceUpdates { nats, workspace_pk }
+    use super::super::{
+        event_filter::{ClientMessage, EventFilter},
+        event_log::{EventLogError, EventLogSubscription, Resume, WorkspaceEventLogs},
+    };
+
+    pub fn run(
+        event_logs: WorkspaceEventLogs,
+        workspace_pk: WorkspacePk,
+        resume: Resume,
+    ) -> WorkspaceUpdates {
+        WorkspaceUpdates {
+            event_logs,
+            workspace_pk,
+            resume,
+        }
     }
 
     #[remain::sorted]
@@ -93,8 +120,8 @@ mod workspace_updates {
     pub enum WorkspaceUpdatesError {
         #[error("axum error: {0}")]
         Axum(#[from] axum::Error),
-        #[error("failed to subscribe to subject {1}")]
-        Subscribe(#[source] NatsError, String),
+        #[error("event log error: {0}")]
+        EventLog(#[from] EventLogError),
         #[error("error when closing websocket")]
         WsClose(#[source] axum::Error),
         #[error("error when sending websocket message")]
@@ -105,64 +132,73 @@ mod workspace_updates {
 
     #[derive(Debug)]
     pub struct WorkspaceUpdates {
-        nats: NatsClient,
+        event_logs: WorkspaceEventLogs,
         workspace_pk: WorkspacePk,
+        resume: Resume,
     }
 
     impl WorkspaceUpdates {
         pub async fn start(self) -> Result<WorkspaceUpdatesStarted> {
-            let subject = format!("si.workspace_pk.{}.>", self.workspace_pk);
-            let subscriber = self
-                .nats
-                .subscribe(&subject)
-                .await
-                .map_err(|err| WorkspaceUpdatesError::Subscribe(err, subject))?;
-
-            Ok(WorkspaceUpdatesStarted { subscriber })
+            let subscription = self
+                .event_logs
+                .subscribe(self.workspace_pk, &self.resume)
+                .await?;
+
+            Ok(WorkspaceUpdatesStarted {
+                subscription,
+                filter: EventFilter::default(),
+            })
         }
     }
 
     #[derive(Debug)]
     pub struct WorkspaceUpdatesStarted {
-        subscriber: Subscriber,
+        subscription: EventLogSubscription,
+        filter: EventFilter,
     }
 
     impl WorkspaceUpdatesStarted {
         pub async fn process(mut self, ws: &mut WebSocket) -> Result<WorkspaceUpdatesClosing> {
+            // Catch the client up on the events it missed before sending any live event
+            for event in self.subscription.take_backlog() {
+                if send(ws, event.message.clone()).await? {
+                    return Ok(WorkspaceUpdatesClosing { ws_is_closed: true });
+                }
+            }
+
             // Send all messages down the WebSocket until and unless an error is encountered, the
-            // client websocket connection is closed, or the nats subscriber naturally closes
+            // client websocket connection is closed, or the event log naturally closes
             loop {
-                tokio::select! {
+                let event = tokio::select! {
                     msg = ws.recv() => {
                         match msg {
-                            Some(Ok(_)) => {},
+                            Some(Ok(ws::Message::Text(text))) => {
+                                self.handle_client_message(&text);
+                                continue;
+                            }
+                            Some(Ok(_)) => continue,
                             Some(Err(err)) => return Err(err.into()),
                             None => return Ok(WorkspaceUpdatesClosing { ws_is_closed: true }),
                         }
                     }
-                    Some(nats_msg) = self.subscriber.next() => {
-                        let msg = ws::Message::Text(String::from_utf8_lossy(nats_msg.payload()).to_string());
-
-                        if let Err(err) = ws.send(msg).await {
-                            match err
-                                .source()
-                                .and_then(|err| err.downcast_ref::<tungstenite::Error>())
-                            {
-                                Some(ws_err) => match ws_err {
-                                    // If the websocket has cleanly closed, we should cleanly finish as
-                                    // well--this is not an error condition
-                                    tungstenite::Error::ConnectionClosed
-                                    | tungstenite::Error::AlreadyClosed => {
-                                        trace!("websocket has cleanly closed, ending");
-                                        return Ok(WorkspaceUpdatesClosing { ws_is_closed: true });
-                                    }
-                                    _ => return Err(WorkspaceUpdatesError::WsSendIo(err)),
-                                },
-                                None => return Err(WorkspaceUpdatesError::WsSendIo(err)),
+                    event = self.subscription.recv() => {
+                        match event {
+                            Ok(event) => event,
+                            // The client missed events that we dropped while it lagged behind
+                            Err(RecvError::Lagged(count)) => {
+                                debug!(count, "websocket client lagged behind, asking it to resync");
+                                self.subscription.resync_required().await?
                             }
+                            Err(RecvError::Closed) => break,
                         }
                     }
-                    else => break,
+                };
+
+                if !self.filter.matches(&event) {
+                    continue;
+                }
+                if send(ws, event.message.clone()).await? {
+                    return Ok(WorkspaceUpdatesClosing { ws_is_closed: true });
                 }
             }
 
@@ -170,6 +206,40 @@ mod workspace_updates {
                 ws_is_closed: false,
             })
         }
+
+        fn handle_client_message(&mut self, text: &str) {
+            match serde_json::from_str(text) {
+                Ok(ClientMessage::Subscribe(filter)) => {
+                    trace!(?filter, "websocket client changed its event filter");
+                    self.filter = filter;
+                }
+                // A client we don't understand keeps its current filter rather than losing the
+                // connection
+                Err(err) => debug!(error = ?err, "ignoring unknown websocket client message"),
+            }
+        }
+    }
+
+    /// Sends a message down the websocket, returning whether the websocket has cleanly closed.
+    async fn send(ws: &mut WebSocket, message: String) -> Result<bool> {
+        if let Err(err) = ws.send(ws::Message::Text(message)).await {
+            return match err
+                .source()
+                .and_then(|err| err.downcast_ref::<tungstenite::Error>())
+            {
+                Some(ws_err) => match ws_err {
+                    // If the websocket has cleanly closed, we should cleanly finish as
+                    // well--this is not an error condition
+                    tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+                        trace!("websocket has cleanly closed, ending");
+                        Ok(true)
+                    }
+                    _ => Err(WorkspaceUpdatesError::WsSendIo(err)),
+                },
+                None => Err(WorkspaceUpdatesError::WsSendIo(err)),
+            };
+        }
+        Ok(false)
     }
 
     #[derive(Debug)]
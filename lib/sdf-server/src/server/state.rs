@@ -5,7 +5,13 @@ use dal::JwtPublicSigningKey;
 use si_std::SensitiveString;
 use tokio::sync::{broadcast, mpsc};
 
-use super::server::ShutdownSource;
+use super::{
+    rate_limit::{RateLimiter, RateLimitsConfig},
+    server::ShutdownSource,
+    service::ws::event_log::{
+        WorkspaceEventLogs, DEFAULT_EVENT_LOG_CAPACITY, DEFAULT_EVENT_LOG_IDLE_TIMEOUT,
+    },
+};
 
 #[derive(Clone, FromRef)]
 pub struct AppState {
@@ -14,6 +20,8 @@ pub struct AppState {
     jwt_public_signing_key: JwtPublicSigningKey,
     posthog_client: PosthogClient,
     shutdown_broadcast: ShutdownBroadcast,
+    workspace_event_logs: WorkspaceEventLogs,
+    rate_limiter: RateLimiter,
     for_tests: bool,
 
     // TODO(fnichol): we're likely going to use this, but we can't allow it to be dropped because
@@ -23,6 +31,7 @@ pub struct AppState {
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         services_context: impl Into<ServicesContext>,
         signup_secret: impl Into<SignupSecret>,
@@ -30,14 +39,24 @@ impl AppState {
         posthog_client: impl Into<PosthogClient>,
         shutdown_broadcast_tx: broadcast::Sender<()>,
         tmp_shutdown_tx: mpsc::Sender<ShutdownSource>,
+        rate_limits: RateLimitsConfig,
         for_tests: bool,
     ) -> Self {
+        let services_context: ServicesContext = services_context.into();
+        let workspace_event_logs = WorkspaceEventLogs::new(
+            services_context.nats_conn().clone(),
+            DEFAULT_EVENT_LOG_CAPACITY,
+            DEFAULT_EVENT_LOG_IDLE_TIMEOUT,
+        );
+
         Self {
-            services_context: services_context.into(),
+            services_context,
             signup_secret: signup_secret.into(),
             jwt_public_signing_key: jwt_public_signing_key.into(),
             posthog_client: posthog_client.into(),
             shutdown_broadcast: ShutdownBroadcast(shutdown_broadcast_tx),
+            workspace_event_logs,
+            rate_limiter: RateLimiter::new(rate_limits),
             for_tests,
             _tmp_shutdown_tx: Arc::new(tmp_shutdown_tx),
         }
@@ -55,6 +74,10 @@ impl AppState {
         &self.jwt_public_signing_key
     }
 
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
     pub fn for_tests(&self) -> bool {
         self.for_tests
     }
@@ -0,0 +1,208 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use dal::WorkspaceRole;
+use dal_test::{sdf_test, AuthTokenRef, DalContextHead};
+use pretty_assertions_sorted::assert_eq;
+use sdf_server::service::{
+    api_token::{
+        create_api_token::{CreateApiTokenRequest, CreateApiTokenResponse, MAX_EXPIRES_IN_DAYS},
+        list_api_tokens::ListApiTokensResponse,
+        revoke_api_token::{RevokeApiTokenRequest, RevokeApiTokenResponse},
+    },
+    change_set::create_change_set::{CreateChangeSetRequest, CreateChangeSetResponse},
+};
+
+use crate::service_tests::{
+    api_request_auth_empty, api_request_auth_json_body, api_request_auth_status,
+};
+
+async fn create_api_token(
+    app: Router,
+    auth_token: &str,
+    scope: WorkspaceRole,
+    expires_in_days: Option<u32>,
+) -> CreateApiTokenResponse {
+    api_request_auth_json_body(
+        app,
+        Method::POST,
+        "/api/api_token/create_api_token",
+        auth_token,
+        &CreateApiTokenRequest {
+            name: format!("ci {scope}"),
+            scope,
+            expires_in_days,
+        },
+    )
+    .await
+}
+
+#[sdf_test]
+async fn api_token_is_limited_to_its_scope(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let viewer = create_api_token(app.clone(), auth_token, WorkspaceRole::Viewer, None).await;
+    assert!(viewer.token.starts_with("si_pat_"));
+
+    let status = api_request_auth_status(
+        app.clone(),
+        Method::GET,
+        "/api/change_set/list_open_change_sets",
+        &viewer.token,
+        &serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(StatusCode::OK, status);
+
+    let request = CreateChangeSetRequest {
+        change_set_name: "from ci".to_owned(),
+    };
+    let status = api_request_auth_status(
+        app.clone(),
+        Method::POST,
+        "/api/change_set/create_change_set",
+        &viewer.token,
+        &request,
+    )
+    .await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    let editor = create_api_token(app.clone(), auth_token, WorkspaceRole::Editor, Some(30)).await;
+    let response: CreateChangeSetResponse = api_request_auth_json_body(
+        app.clone(),
+        Method::POST,
+        "/api/change_set/create_change_set",
+        &editor.token,
+        &request,
+    )
+    .await;
+    assert_eq!("from ci", response.change_set.name);
+
+    // Using a token records when it was last used, but never exposes its secret again
+    let response: ListApiTokensResponse = api_request_auth_empty(
+        app,
+        Method::GET,
+        "/api/api_token/list_api_tokens",
+        auth_token,
+    )
+    .await;
+    assert_eq!(2, response.api_tokens.len());
+    for api_token in &response.api_tokens {
+        assert!(api_token.last_used_at().is_some());
+    }
+    let listed = serde_json::to_string(&response).expect("cannot serialize response");
+    assert!(!listed.contains(&editor.token));
+    assert!(!listed.contains("tokenHash"));
+}
+
+#[sdf_test]
+async fn revoked_and_expired_api_tokens_are_rejected(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let revoked = create_api_token(app.clone(), auth_token, WorkspaceRole::Viewer, None).await;
+    let response: RevokeApiTokenResponse = api_request_auth_json_body(
+        app.clone(),
+        Method::POST,
+        "/api/api_token/revoke_api_token",
+        auth_token,
+        &RevokeApiTokenRequest {
+            pk: revoked.api_token.pk(),
+        },
+    )
+    .await;
+    assert!(response.api_token.revoked_at().is_some());
+
+    let expired = create_api_token(app.clone(), auth_token, WorkspaceRole::Viewer, Some(0)).await;
+
+    for token in [&revoked.token, &expired.token] {
+        let status = api_request_auth_status(
+            app.clone(),
+            Method::GET,
+            "/api/change_set/list_open_change_sets",
+            token,
+            &serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(StatusCode::UNAUTHORIZED, status);
+    }
+
+    // A token whose secret doesn't match is rejected the same way
+    let (prefix, _secret) = revoked.token.rsplit_once('_').expect("malformed token");
+    let status = api_request_auth_status(
+        app,
+        Method::GET,
+        "/api/change_set/list_open_change_sets",
+        format!("{prefix}_{}", "0".repeat(64)),
+        &serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+}
+
+#[sdf_test]
+async fn api_token_cannot_manage_api_tokens(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let viewer = create_api_token(app.clone(), auth_token, WorkspaceRole::Viewer, None).await;
+
+    let status = api_request_auth_status(
+        app,
+        Method::POST,
+        "/api/api_token/create_api_token",
+        &viewer.token,
+        &CreateApiTokenRequest {
+            name: "escalated".to_owned(),
+            scope: WorkspaceRole::Owner,
+            expires_in_days: None,
+        },
+    )
+    .await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+}
+
+#[sdf_test]
+async fn api_token_expiry_is_bounded(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let longest = create_api_token(
+        app.clone(),
+        auth_token,
+        WorkspaceRole::Viewer,
+        Some(MAX_EXPIRES_IN_DAYS),
+    )
+    .await;
+    assert!(longest.api_token.expires_at().is_some());
+
+    for expires_in_days in [MAX_EXPIRES_IN_DAYS + 1, u32::MAX] {
+        let status = api_request_auth_status(
+            app.clone(),
+            Method::POST,
+            "/api/api_token/create_api_token",
+            auth_token,
+            &CreateApiTokenRequest {
+                name: "too long".to_owned(),
+                scope: WorkspaceRole::Viewer,
+                expires_in_days: Some(expires_in_days),
+            },
+        )
+        .await;
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+    }
+}
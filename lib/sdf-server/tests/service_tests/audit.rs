@@ -0,0 +1,136 @@
+use axum::{
+    body::Body,
+    http::{self, Method, Request, StatusCode},
+    Router,
+};
+use dal::{HistoryEvent, HistoryEventPage, UserClaim, WorkspaceRole, WorkspaceSignup};
+use dal_test::{
+    helpers::{create_auth_token, create_user},
+    sdf_test, AuthTokenRef, DalContextHead,
+};
+use pretty_assertions_sorted::assert_eq;
+use sdf_server::service::audit::HistoryEventsRequest;
+use tower::ServiceExt;
+
+use crate::service_tests::{api_request_auth_query, api_request_auth_status};
+
+#[sdf_test]
+async fn list_and_export_history_events(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    for message in ["first", "second", "third"] {
+        HistoryEvent::new(&ctx, "audit_test.event", message, &serde_json::json!({}))
+            .await
+            .expect("cannot create history event");
+    }
+    // Only matches the prefix when its underscore is matched literally
+    HistoryEvent::new(&ctx, "auditXtest.event", "other", &serde_json::json!({}))
+        .await
+        .expect("cannot create history event");
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let request = HistoryEventsRequest {
+        label_prefix: Some("audit_test.".to_owned()),
+        limit: Some(2),
+        ..Default::default()
+    };
+    let first_page: HistoryEventPage = api_request_auth_query(
+        app.clone(),
+        "/api/audit/list_history_events",
+        auth_token,
+        &request,
+    )
+    .await;
+    assert_eq!(
+        vec!["third", "second"],
+        first_page
+            .events
+            .iter()
+            .map(|event| event.message.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert!(first_page.next_cursor.is_some());
+
+    let second_page: HistoryEventPage = api_request_auth_query(
+        app.clone(),
+        "/api/audit/list_history_events",
+        auth_token,
+        &HistoryEventsRequest {
+            cursor: first_page.next_cursor,
+            ..request.clone()
+        },
+    )
+    .await;
+    assert_eq!(
+        vec!["first"],
+        second_page
+            .events
+            .iter()
+            .map(|event| event.message.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(None, second_page.next_cursor);
+
+    let params = serde_url_params::to_string(&request).expect("cannot serialize params");
+    let api_request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/api/audit/export_history_events?{params}"))
+        .header(http::header::AUTHORIZATION, format!("Bearer {auth_token}"))
+        .body(Body::empty())
+        .expect("cannot create api request");
+    let response = app.oneshot(api_request).await.expect("cannot send request");
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!(
+        "application/x-ndjson",
+        response
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .expect("no content type")
+    );
+    let body = hyper::body::to_bytes(response.into_body())
+        .await
+        .expect("cannot read body");
+    let messages: Vec<String> = String::from_utf8(body.to_vec())
+        .expect("body is not utf-8")
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<HistoryEvent>(line)
+                .expect("line is not a history event")
+                .message
+        })
+        .collect();
+    assert_eq!(vec!["third", "second", "first"], messages);
+}
+
+#[sdf_test]
+async fn only_owners_can_read_history_events(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    nw: WorkspaceSignup,
+) {
+    let editor = create_user(&ctx).await;
+    editor
+        .associate_workspace(&ctx, *nw.workspace.pk(), WorkspaceRole::Editor)
+        .await
+        .expect("cannot associate user to workspace");
+    let auth_token = create_auth_token(UserClaim::new(editor.pk(), *nw.workspace.pk())).await;
+    ctx.commit().await.expect("cannot commit transaction");
+
+    for uri in [
+        "/api/audit/list_history_events",
+        "/api/audit/export_history_events",
+    ] {
+        let status = api_request_auth_status(
+            app.clone(),
+            Method::GET,
+            uri,
+            &auth_token,
+            &serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, status, "{uri}");
+    }
+}
@@ -0,0 +1,58 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use dal_test::{sdf_test, AuthTokenRef, DalContextHead};
+use pretty_assertions_sorted::assert_eq;
+use sdf_server::service::diagnostics::DiagnosticsResponse;
+
+use crate::service_tests::{api_request_auth_empty, api_request_auth_status};
+
+#[sdf_test]
+async fn diagnostics(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    // See `session::restore_authentication` for why the workspace is committed first
+    ctx.commit().await.expect("failed to commit");
+
+    let response: DiagnosticsResponse =
+        api_request_auth_empty(app.clone(), Method::GET, "/api/diagnostics", auth_token).await;
+
+    let names: Vec<&str> = response
+        .checks
+        .iter()
+        .map(|check| check.name.as_str())
+        .collect();
+    assert_eq!(
+        vec!["postgres", "nats", "council", "veritech", "pinga"],
+        names
+    );
+    for check in &response.checks {
+        assert!(check.ok, "{} check failed: {:?}", check.name, check.detail);
+    }
+    assert!(response.ok);
+
+    let postgres = response
+        .checks
+        .iter()
+        .find(|check| check.name == "postgres")
+        .expect("missing postgres check");
+    assert!(postgres
+        .detail
+        .as_deref()
+        .expect("missing migration version")
+        .starts_with("migration version "));
+
+    // Only the root health route is open to anyone who can reach sdf
+    let status = api_request_auth_status(
+        app,
+        Method::GET,
+        "/api/diagnostics",
+        "not a token",
+        &serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+}
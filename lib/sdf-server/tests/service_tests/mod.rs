@@ -6,13 +6,22 @@ use axum::{
 use serde::{de::DeserializeOwned, Serialize};
 use tower::ServiceExt;
 
+mod api_token;
+mod audit;
 mod change_set;
 mod component;
+mod diagnostics;
 mod functions;
+mod openapi;
+mod rate_limit;
 mod scenario;
 mod schema;
+mod sdf_client;
 mod secret;
 mod session;
+mod webhook;
+mod workspace;
+mod workspace_updates;
 
 pub async fn api_request_auth_query<Req: Serialize, Res: DeserializeOwned>(
     app: Router,
@@ -162,3 +171,29 @@ pub async fn api_request_auth_no_response<Req: Serialize>(
 
     assert_eq!(body, "", "response is not empty");
 }
+
+/// Sends a request and returns the status of the response, for tests of requests that are meant
+/// to be rejected.
+pub async fn api_request_auth_status<Req: Serialize>(
+    app: Router,
+    method: Method,
+    uri: impl AsRef<str>,
+    auth_token: impl AsRef<str>,
+    request: &Req,
+) -> StatusCode {
+    let auth_token = auth_token.as_ref();
+    let uri = uri.as_ref();
+    let api_request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(http::header::AUTHORIZATION, format!("Bearer {auth_token}"));
+
+    let api_request = api_request
+        .body(Body::from(
+            serde_json::to_vec(&serde_json::json!(&request)).expect("cannot turn request to json"),
+        ))
+        .expect("cannot create api request");
+    let response = app.oneshot(api_request).await.expect("cannot send request");
+    response.status()
+}
@@ -0,0 +1,117 @@
+mod generate;
+
+use std::{env, fs, path::Path};
+
+use axum::{http::Method, Router};
+use buck2_resources::Buck2Resources;
+use dal_test::{sdf_test, AuthTokenRef};
+use sdf_server::openapi::OPENAPI_DOCUMENT;
+use serde_json::Value;
+
+use crate::service_tests::api_request_auth_empty;
+
+use self::generate::{generate, SourceDirs};
+
+fn checked_in_document() -> Value {
+    serde_json::from_str(OPENAPI_DOCUMENT).expect("openapi document is not valid json")
+}
+
+fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+                refs.push(reference);
+            }
+            object.values().for_each(|value| collect_refs(value, refs));
+        }
+        Value::Array(array) => array.iter().for_each(|value| collect_refs(value, refs)),
+        _ => {}
+    }
+}
+
+/// The sources the document is generated from, which are resources of the test when run by Buck2
+/// and next to the manifest of sdf when run by cargo.
+#[allow(clippy::disallowed_methods)] // Used to determine if running in testing
+fn source_dirs() -> SourceDirs {
+    if env::var("BUCK_RUN_BUILD_ID").is_ok() || env::var("BUCK_BUILD_ID").is_ok() {
+        let resources = Buck2Resources::read().expect("cannot read buck2 resources");
+        let src = |name: &str| {
+            resources
+                .get_ends_with(name)
+                .expect("cannot find source resource")
+                .join("src")
+        };
+        SourceDirs {
+            sdf: src("sdf-server-src"),
+            dal: src("dal-src"),
+            cyclone_core: src("cyclone-core-src"),
+        }
+    } else {
+        SourceDirs::from_sdf_dir(env!("CARGO_MANIFEST_DIR"))
+    }
+}
+
+/// Fails when the checked-in document no longer matches the source, or rewrites it when
+/// `SI_UPDATE_OPENAPI` is set.
+#[test]
+#[allow(clippy::disallowed_methods)] // Used to update the document in development
+fn openapi_document_matches_source() {
+    let generated = generate(&source_dirs()).expect("cannot generate openapi document");
+
+    if env::var("SI_UPDATE_OPENAPI").is_ok() {
+        let mut document =
+            serde_json::to_string_pretty(&generated).expect("cannot serialize openapi document");
+        document.push('\n');
+        fs::write(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("src/server/openapi.json"),
+            document,
+        )
+        .expect("cannot write openapi document");
+        return;
+    }
+
+    assert_eq!(
+        checked_in_document(),
+        generated,
+        "the openapi document is out of date, regenerate it with `SI_UPDATE_OPENAPI=1 cargo test -p sdf-server openapi`"
+    );
+}
+
+#[test]
+fn openapi_document_references_resolve() {
+    let document = checked_in_document();
+    let schemas = document["components"]["schemas"]
+        .as_object()
+        .expect("no schemas in openapi document");
+
+    let mut refs = Vec::new();
+    collect_refs(&document, &mut refs);
+    assert!(!refs.is_empty());
+    for reference in refs {
+        let name = reference
+            .strip_prefix("#/components/schemas/")
+            .expect("reference is not to a schema");
+        assert!(schemas.contains_key(name), "unresolved reference: {name}");
+    }
+
+    let create_change_set = &document["paths"]["/api/change_set/create_change_set"]["post"];
+    assert_eq!(
+        "#/components/schemas/CreateChangeSetRequest",
+        create_change_set["requestBody"]["content"]["application/json"]["schema"]["$ref"]
+    );
+    assert_eq!(
+        serde_json::json!({
+            "type": "object",
+            "properties": { "changeSetName": { "type": "string" } },
+            "required": ["changeSetName"],
+        }),
+        schemas["CreateChangeSetRequest"]
+    );
+}
+
+#[sdf_test]
+async fn serves_openapi_document(app: Router, AuthTokenRef(auth_token): AuthTokenRef<'_>) {
+    let served: Value =
+        api_request_auth_empty(app, Method::GET, "/api/openapi.json", auth_token).await;
+    assert_eq!(checked_in_document(), served);
+}
@@ -0,0 +1,1654 @@
+//! Builds the OpenAPI document by reading the source of sdf, the dal and cyclone-core (whose types
+//! the dal re-exports): the routes come from the `routes()` functions, the operations from the
+//! signatures of the handlers, and the schemas from the request and response types, honouring
+//! their serde attributes.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+use syn::{
+    meta::ParseNestedMeta, Attribute, Block, Expr, ExprCall, Fields, FieldsNamed, FieldsUnnamed,
+    FnArg, GenericArgument, Item, ItemEnum, ItemFn, ItemStruct, Lit, LitStr, Local, Meta, Pat,
+    PathArguments, ReturnType, Stmt, Type, UseTree, Variant,
+};
+
+use thiserror::Error;
+
+#[remain::sorted]
+#[derive(Debug, Error)]
+pub enum OpenApiError {
+    #[error("handler not found: {0}")]
+    HandlerNotFound(String),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cannot parse {0}: {1}")]
+    Parse(String, syn::Error),
+    #[error("routes not found in {0}")]
+    RoutesNotFound(String),
+}
+
+pub type OpenApiResult<T> = Result<T, OpenApiError>;
+
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+const API_ERROR_SCHEMA: &str = "ApiError";
+const HTTP_METHODS: &[&str] = &["delete", "get", "patch", "post", "put"];
+
+/// Extractors that authorize the request with the bearer token in the `Authorization` header.
+const BEARER_EXTRACTORS: &[&str] = &[
+    "AccessBuilder",
+    "ApplierAccessBuilder",
+    "Authorization",
+    "OwnerAuthorization",
+    "RawAccessToken",
+    "SessionAuthorization",
+    "Tenancy",
+];
+
+/// The `src` directories of the crates the document is generated from.
+#[derive(Clone, Debug)]
+pub struct SourceDirs {
+    pub sdf: PathBuf,
+    pub dal: PathBuf,
+    pub cyclone_core: PathBuf,
+}
+
+impl SourceDirs {
+    /// The source directories of the crates in a checkout, found next to the one of sdf.
+    pub fn from_sdf_dir(sdf_dir: impl AsRef<Path>) -> Self {
+        let sdf_dir = sdf_dir.as_ref();
+        Self {
+            sdf: sdf_dir.join("src"),
+            dal: sdf_dir.join("../dal/src"),
+            cyclone_core: sdf_dir.join("../cyclone-core/src"),
+        }
+    }
+}
+
+/// Generates the OpenAPI document for the routes of sdf from its source, resolving the types it
+/// uses from the source of the dal and cyclone-core.
+pub fn generate(dirs: &SourceDirs) -> OpenApiResult<Value> {
+    let sources = Sources::load(&[
+        (Crate::Sdf, dirs.sdf.clone()),
+        (Crate::Dal, dirs.dal.clone()),
+        (Crate::CycloneCore, dirs.cyclone_core.clone()),
+    ])?;
+
+    let routes_file = sources
+        .file(Crate::Sdf, &["server", "routes"])
+        .ok_or_else(|| OpenApiError::RoutesNotFound("server::routes".to_owned()))?;
+    let routes_fn = sources
+        .function(routes_file, "routes")
+        .ok_or_else(|| OpenApiError::RoutesNotFound("server::routes".to_owned()))?;
+    let mut routes = Vec::new();
+    sources.collect_fn_routes(routes_file, routes_fn, "", &mut routes)?;
+    routes.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+
+    let mut generator = Generator::new(&sources);
+    let mut paths: BTreeMap<String, Map<String, Value>> = BTreeMap::new();
+    let mut operation_ids = HashSet::new();
+    for route in &routes {
+        let mut operation = generator.operation(route);
+        let mut operation_id = operation_id(route);
+        if !operation_ids.insert(operation_id.clone()) {
+            operation_id = format!("{operation_id}_{}", route.method);
+            operation_ids.insert(operation_id.clone());
+        }
+        operation.insert("operationId".to_owned(), operation_id.into());
+        paths
+            .entry(route.path.clone())
+            .or_default()
+            .insert(route.method.clone(), Value::Object(operation));
+    }
+
+    let mut schemas = Map::new();
+    schemas.insert(
+        API_ERROR_SCHEMA.to_owned(),
+        json!({
+            "type": "object",
+            "properties": {
+                "error": {
+                    "type": "object",
+                    "properties": {
+                        "message": { "type": "string" },
+                        "code": { "type": "integer", "format": "int32" },
+                        "statusCode": { "type": "integer", "format": "int32" },
+                    },
+                    "required": ["message", "code", "statusCode"],
+                },
+            },
+            "required": ["error"],
+        }),
+    );
+    schemas.extend(generator.schemas);
+
+    Ok(json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "System Initiative sdf API",
+            "description": "Generated from the routes and the request and response types of sdf; do not edit by hand.",
+            "version": "0.1.0",
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" },
+                "wsToken": { "type": "apiKey", "in": "query", "name": "token" },
+            },
+        },
+    }))
+}
+
+/// The crates whose source is read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Crate {
+    CycloneCore,
+    Dal,
+    Sdf,
+}
+
+impl Crate {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            // veritech-client re-exports the types of cyclone-core which the dal uses
+            "cyclone_core" | "veritech_client" => Some(Self::CycloneCore),
+            "dal" => Some(Self::Dal),
+            "sdf_server" => Some(Self::Sdf),
+            _ => None,
+        }
+    }
+}
+
+/// An absolute path to an item, where `krate` is `None` for crates whose source isn't read.
+#[derive(Clone, Debug)]
+struct ItemPath {
+    krate: Option<Crate>,
+    segments: Vec<String>,
+}
+
+/// A type defined by an item of a source file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct DefKey {
+    file: usize,
+    item: usize,
+}
+
+struct SourceFile {
+    krate: Crate,
+    module: Vec<String>,
+    ast: syn::File,
+    uses: HashMap<String, ItemPath>,
+    globs: Vec<ItemPath>,
+}
+
+struct Sources {
+    files: Vec<SourceFile>,
+    modules: HashMap<(Crate, Vec<String>), usize>,
+    types: HashMap<String, Vec<DefKey>>,
+}
+
+struct Route {
+    path: String,
+    method: String,
+    file: usize,
+    handler: ItemFn,
+}
+
+impl Sources {
+    fn load(crates: &[(Crate, PathBuf)]) -> OpenApiResult<Self> {
+        let mut files = Vec::new();
+        for (krate, src_dir) in crates {
+            let mut paths = Vec::new();
+            collect_rs_files(src_dir, &mut paths)?;
+            paths.sort();
+
+            for path in paths {
+                let relative = path.strip_prefix(src_dir).unwrap_or(&path);
+                let source = fs::read_to_string(&path)?;
+                let ast = syn::parse_file(&source)
+                    .map_err(|err| OpenApiError::Parse(path.display().to_string(), err))?;
+                files.push(SourceFile {
+                    krate: *krate,
+                    module: module_path(relative),
+                    ast,
+                    uses: HashMap::new(),
+                    globs: Vec::new(),
+                });
+            }
+        }
+
+        let modules = files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| ((file.krate, file.module.clone()), index))
+            .collect();
+        let mut sources = Self {
+            files,
+            modules,
+            types: HashMap::new(),
+        };
+
+        for index in 0..sources.files.len() {
+            let mut uses = Vec::new();
+            for item in &sources.files[index].ast.items {
+                if let Item::Use(item_use) = item {
+                    collect_use_tree(&item_use.tree, Vec::new(), &mut uses);
+                }
+            }
+
+            let mut resolved = HashMap::new();
+            let mut globs = Vec::new();
+            for (name, segments) in uses {
+                if segments.is_empty() {
+                    continue;
+                }
+                let path = sources.resolve_path(index, &segments, false);
+                match name {
+                    Some(name) => {
+                        resolved.insert(name, path);
+                    }
+                    None => globs.push(path),
+                }
+            }
+
+            let file = &mut sources.files[index];
+            file.uses = resolved;
+            file.globs = globs;
+        }
+
+        for (file_index, file) in sources.files.iter().enumerate() {
+            for (item_index, item) in file.ast.items.iter().enumerate() {
+                let name = match item {
+                    Item::Struct(item) => Some(item.ident.to_string()),
+                    Item::Enum(item) => Some(item.ident.to_string()),
+                    Item::Type(item) => Some(item.ident.to_string()),
+                    Item::Macro(item) if is_pk_macro(item) => pk_macro_name(item),
+                    _ => None,
+                };
+                if let Some(name) = name {
+                    sources.types.entry(name).or_default().push(DefKey {
+                        file: file_index,
+                        item: item_index,
+                    });
+                }
+            }
+        }
+
+        Ok(sources)
+    }
+
+    fn file(&self, krate: Crate, module: &[&str]) -> Option<usize> {
+        let module = module.iter().map(ToString::to_string).collect();
+        self.modules.get(&(krate, module)).copied()
+    }
+
+    fn function(&self, file: usize, name: &str) -> Option<&ItemFn> {
+        self.files[file]
+            .ast
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Fn(item_fn) if item_fn.sig.ident == name => Some(item_fn),
+                _ => None,
+            })
+    }
+
+    /// Finds a function defined in or imported into a file.
+    fn function_in_scope(&self, file: usize, name: &str) -> Option<&ItemFn> {
+        if let Some(function) = self.function(file, name) {
+            return Some(function);
+        }
+        let used = self.files[file].uses.get(name)?;
+        let (used_name, module) = used.segments.split_last()?;
+        let target = self.modules.get(&(used.krate?, module.to_vec()))?;
+        self.function(*target, used_name)
+    }
+
+    fn item(&self, key: DefKey) -> &Item {
+        &self.files[key.file].ast.items[key.item]
+    }
+
+    /// Resolves a path as written in `file` to an absolute path. Paths in `use` items are resolved
+    /// without looking at the other `use` items of the file.
+    fn resolve_path(&self, file: usize, segments: &[String], with_uses: bool) -> ItemPath {
+        let source = &self.files[file];
+        let own = |segments: Vec<String>| ItemPath {
+            krate: Some(source.krate),
+            segments,
+        };
+
+        let mut path = match segments[0].as_str() {
+            "crate" => own(Vec::new()),
+            "self" => own(source.module.clone()),
+            "super" => {
+                let mut module = source.module.clone();
+                module.pop();
+                own(module)
+            }
+            first => {
+                let mut child = source.module.clone();
+                child.push(first.to_owned());
+
+                match source.uses.get(first) {
+                    Some(used) if with_uses => used.clone(),
+                    _ if self.modules.contains_key(&(source.krate, child.clone())) => own(child),
+                    _ => match Crate::from_name(first) {
+                        Some(krate) => ItemPath {
+                            krate: Some(krate),
+                            segments: Vec::new(),
+                        },
+                        None => ItemPath {
+                            krate: None,
+                            segments: vec![first.to_owned()],
+                        },
+                    },
+                }
+            }
+        };
+
+        for segment in &segments[1..] {
+            if segment == "super" {
+                path.segments.pop();
+            } else {
+                path.segments.push(segment.clone());
+            }
+        }
+
+        // `service` is re-exported from the root of sdf, and is used through that re-export
+        if path.krate == Some(Crate::Sdf)
+            && path.segments.first().map(String::as_str) == Some("service")
+        {
+            path.segments.insert(0, "server".to_owned());
+        }
+
+        path
+    }
+
+    /// Finds the definition of the type at an absolute path, following re-exports by looking for a
+    /// type of the same name in the same crate.
+    fn lookup(&self, path: &ItemPath, exact: bool) -> Option<DefKey> {
+        let krate = path.krate?;
+        let (name, module) = path.segments.split_last()?;
+        let candidates: Vec<DefKey> = self
+            .types
+            .get(name)?
+            .iter()
+            .copied()
+            .filter(|key| self.files[key.file].krate == krate)
+            .collect();
+        // A type the crate only re-exports, like the cyclone-core types of the dal, is defined
+        // in another crate whose source is read
+        if candidates.is_empty() && !exact {
+            return self
+                .types
+                .get(name)?
+                .iter()
+                .find(|key| self.files[key.file].krate != krate)
+                .copied();
+        }
+
+        if let Some(key) = candidates
+            .iter()
+            .find(|key| self.files[key.file].module == module)
+        {
+            return Some(*key);
+        }
+        if exact {
+            return None;
+        }
+
+        candidates
+            .iter()
+            .filter(|key| self.files[key.file].module.starts_with(module))
+            .min_by_key(|key| self.files[key.file].module.len())
+            .or_else(|| candidates.first())
+            .copied()
+    }
+
+    fn resolve_type(&self, file: usize, path: &syn::Path) -> Option<DefKey> {
+        let segments = path_segments(path);
+        if segments.len() > 1 {
+            return self.lookup(&self.resolve_path(file, &segments, true), false);
+        }
+
+        let name = &segments[0];
+        let candidates = self.types.get(name)?;
+        if let Some(key) = candidates.iter().find(|key| key.file == file) {
+            return Some(*key);
+        }
+
+        let source = &self.files[file];
+        if let Some(used) = source.uses.get(name) {
+            return self.lookup(used, false);
+        }
+        for glob in &source.globs {
+            let mut path = glob.clone();
+            path.segments.push(name.clone());
+            if let Some(key) = self.lookup(&path, true) {
+                return Some(key);
+            }
+        }
+
+        candidates
+            .iter()
+            .find(|key| self.files[key.file].krate == source.krate)
+            .or_else(|| candidates.first())
+            .copied()
+    }
+
+    fn resolve_fn(&self, file: usize, path: &syn::Path) -> OpenApiResult<(usize, &ItemFn)> {
+        let segments = path_segments(path);
+        let not_found = || OpenApiError::HandlerNotFound(segments.join("::"));
+
+        let (target, name) = if segments.len() == 1 {
+            (Some(file), segments[0].clone())
+        } else {
+            let mut resolved = self.resolve_path(file, &segments, true);
+            let name = resolved.segments.pop().ok_or_else(not_found)?;
+            let target = resolved
+                .krate
+                .and_then(|krate| self.modules.get(&(krate, resolved.segments)).copied());
+            (target, name)
+        };
+
+        let target = target.ok_or_else(not_found)?;
+        let function = self.function(target, &name).ok_or_else(not_found)?;
+        Ok((target, function))
+    }
+
+    /// Collects the routes of a router built by the statements of a function.
+    fn collect_fn_routes(
+        &self,
+        file: usize,
+        function: &ItemFn,
+        prefix: &str,
+        routes: &mut Vec<Route>,
+    ) -> OpenApiResult<()> {
+        for stmt in &function.block.stmts {
+            match stmt {
+                Stmt::Local(Local {
+                    init: Some(init), ..
+                }) => self.collect_routes(file, &init.expr, prefix, routes)?,
+                Stmt::Expr(Expr::Assign(assign), _) => {
+                    self.collect_routes(file, &assign.right, prefix, routes)?
+                }
+                Stmt::Expr(expr, _) => self.collect_routes(file, expr, prefix, routes)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Collects the routes of a chain of `.route()` and `.nest()` calls on a router.
+    fn collect_routes(
+        &self,
+        file: usize,
+        expr: &Expr,
+        prefix: &str,
+        routes: &mut Vec<Route>,
+    ) -> OpenApiResult<()> {
+        let call = match expr {
+            Expr::MethodCall(call) => call,
+            Expr::Paren(paren) => return self.collect_routes(file, &paren.expr, prefix, routes),
+            _ => return Ok(()),
+        };
+        self.collect_routes(file, &call.receiver, prefix, routes)?;
+
+        let path = match call.args.first() {
+            Some(Expr::Lit(expr)) => match &expr.lit {
+                Lit::Str(path) => join_paths(prefix, &path.value()),
+                _ => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        match (call.method.to_string().as_str(), call.args.iter().nth(1)) {
+            ("route", Some(method_router)) => {
+                for (method, handler) in method_handlers(method_router) {
+                    let (handler_file, handler) = self.resolve_fn(file, &handler)?;
+                    routes.push(Route {
+                        path: path.clone(),
+                        method,
+                        file: handler_file,
+                        handler: handler.clone(),
+                    });
+                }
+            }
+            ("nest", Some(Expr::Call(nested))) => {
+                if let Expr::Path(func) = &*nested.func {
+                    let (nested_file, nested_fn) = self.resolve_fn(file, &func.path)?;
+                    self.collect_fn_routes(nested_file, nested_fn, &path, routes)?;
+                }
+            }
+            ("nest", Some(nested)) => self.collect_routes(file, nested, &path, routes)?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+struct Generator<'a> {
+    sources: &'a Sources,
+    schemas: Map<String, Value>,
+    names: HashMap<DefKey, String>,
+    taken: HashSet<String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(sources: &'a Sources) -> Self {
+        Self {
+            sources,
+            schemas: Map::new(),
+            names: HashMap::new(),
+            taken: HashSet::from([API_ERROR_SCHEMA.to_owned()]),
+        }
+    }
+
+    fn operation(&mut self, route: &Route) -> Map<String, Value> {
+        let mut operation = Map::new();
+        let segments: Vec<&str> = route.path.split('/').filter(|s| !s.is_empty()).collect();
+        if segments.len() > 2 {
+            operation.insert("tags".to_owned(), json!([segments[1]]));
+        }
+        if let Some(docs) = docs(&route.handler.attrs) {
+            let (summary, description) = match docs.split_once("\n\n") {
+                Some((summary, description)) => (summary, Some(description)),
+                None => (docs.as_str(), None),
+            };
+            operation.insert("summary".to_owned(), summary.replace('\n', " ").into());
+            if let Some(description) = description {
+                operation.insert("description".to_owned(), description.into());
+            }
+        }
+
+        let mut parameters = Vec::new();
+        let mut security = None;
+        for input in &route.handler.sig.inputs {
+            let ty = match input {
+                FnArg::Typed(pat_type) => &*pat_type.ty,
+                FnArg::Receiver(_) => continue,
+            };
+            let (name, argument) = match ty {
+                Type::Path(type_path) => match type_path.path.segments.last() {
+                    Some(segment) => (segment.ident.to_string(), first_type_argument(segment)),
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            match (name.as_str(), argument) {
+                ("Json", Some(body)) => {
+                    let schema = self.type_schema(route.file, body);
+                    operation.insert(
+                        "requestBody".to_owned(),
+                        json!({
+                            "required": true,
+                            "content": { "application/json": { "schema": schema } },
+                        }),
+                    );
+                }
+                ("Query", Some(query)) => {
+                    let schema = self.inline_schema(route.file, query);
+                    parameters.extend(query_parameters(schema));
+                }
+                ("WsAuthorization", _) => security = Some("wsToken"),
+                (name, _) if BEARER_EXTRACTORS.contains(&name) => {
+                    security = security.or(Some("bearerAuth"))
+                }
+                _ => {}
+            }
+        }
+        if !parameters.is_empty() {
+            operation.insert("parameters".to_owned(), parameters.into());
+        }
+
+        let success = match self.response_schema(route.file, &route.handler) {
+            Some(schema) => json!({
+                "description": "Success",
+                "content": { "application/json": { "schema": schema } },
+            }),
+            None => json!({ "description": "Success" }),
+        };
+        operation.insert(
+            "responses".to_owned(),
+            json!({
+                "200": success,
+                "default": {
+                    "description": "Error",
+                    "content": {
+                        "application/json": {
+                            "schema": { "$ref": format!("{SCHEMA_REF_PREFIX}{API_ERROR_SCHEMA}") },
+                        },
+                    },
+                },
+            }),
+        );
+        if let Some(security) = security {
+            operation.insert("security".to_owned(), json!([{ security: [] }]));
+        }
+
+        operation
+    }
+
+    /// The schema of the successful response of a handler: the `Json` in its return type, or for
+    /// handlers returning `impl IntoResponse`, the type serialized into the body in their code.
+    fn response_schema(&mut self, file: usize, handler: &ItemFn) -> Option<Value> {
+        let output = match &handler.sig.output {
+            ReturnType::Type(_, output) => &**output,
+            ReturnType::Default => return None,
+        };
+        if let Some(json) = find_json_type(output) {
+            return Some(self.type_schema(file, json));
+        }
+        if has_impl_trait(output) {
+            let sources = self.sources;
+            let function = |name: &str| sources.function_in_scope(file, name);
+            let path = serialized_body_type(&handler.block, &function)?;
+            return Some(self.path_schema(file, &path));
+        }
+        None
+    }
+
+    fn type_schema(&mut self, file: usize, ty: &Type) -> Value {
+        match ty {
+            Type::Array(array) => {
+                json!({ "type": "array", "items": self.type_schema(file, &array.elem) })
+            }
+            Type::Group(group) => self.type_schema(file, &group.elem),
+            Type::Paren(paren) => self.type_schema(file, &paren.elem),
+            Type::Path(type_path) => self.path_schema(file, &type_path.path),
+            Type::Reference(reference) => self.type_schema(file, &reference.elem),
+            Type::Slice(slice) => {
+                json!({ "type": "array", "items": self.type_schema(file, &slice.elem) })
+            }
+            Type::Tuple(tuple) if tuple.elems.is_empty() => json!({ "type": "null" }),
+            Type::Tuple(tuple) => {
+                let items: Vec<Value> = tuple
+                    .elems
+                    .iter()
+                    .map(|elem| self.type_schema(file, elem))
+                    .collect();
+                tuple_schema(items)
+            }
+            _ => json!({}),
+        }
+    }
+
+    fn path_schema(&mut self, file: usize, path: &syn::Path) -> Value {
+        let segment = match path.segments.last() {
+            Some(segment) => segment,
+            None => return json!({}),
+        };
+        let arguments = type_arguments(segment);
+
+        match (segment.ident.to_string().as_str(), arguments.as_slice()) {
+            ("String" | "str" | "char" | "PathBuf", _) => json!({ "type": "string" }),
+            ("bool", _) => json!({ "type": "boolean" }),
+            ("i8" | "i16" | "i32" | "u8" | "u16" | "u32", _) => {
+                json!({ "type": "integer", "format": "int32" })
+            }
+            ("i64" | "i128" | "isize" | "u64" | "u128" | "usize", _) => {
+                json!({ "type": "integer", "format": "int64" })
+            }
+            ("f32", _) => json!({ "type": "number", "format": "float" }),
+            ("f64", _) => json!({ "type": "number", "format": "double" }),
+            ("Option", [inner]) => nullable(self.type_schema(file, inner)),
+            ("Vec" | "VecDeque", [inner]) => {
+                json!({ "type": "array", "items": self.type_schema(file, inner) })
+            }
+            ("BTreeSet" | "HashSet" | "IndexSet", [inner]) => json!({
+                "type": "array",
+                "items": self.type_schema(file, inner),
+                "uniqueItems": true,
+            }),
+            ("BTreeMap" | "HashMap" | "IndexMap", [_, value]) => json!({
+                "type": "object",
+                "additionalProperties": self.type_schema(file, value),
+            }),
+            ("Arc" | "Box" | "Cow" | "Rc", [inner]) => self.type_schema(file, inner),
+            (name, _) => match self.sources.resolve_type(file, path) {
+                Some(key) => self.def_schema(key),
+                None => match name {
+                    "DateTime" | "NaiveDateTime" => {
+                        json!({ "type": "string", "format": "date-time" })
+                    }
+                    "Ulid" => json!({ "type": "string", "format": "ulid" }),
+                    "Url" => json!({ "type": "string", "format": "uri" }),
+                    // Anything else, like `serde_json::Value` or types of crates whose source
+                    // isn't read, may be any JSON value
+                    _ => json!({}),
+                },
+            },
+        }
+    }
+
+    /// The schema of a type defined in the source, as a reference to a component schema.
+    fn def_schema(&mut self, key: DefKey) -> Value {
+        let sources = self.sources;
+        let (ident, attrs) = match sources.item(key) {
+            Item::Type(alias) => return self.type_schema(key.file, &alias.ty),
+            Item::Struct(item) => (item.ident.to_string(), &item.attrs),
+            Item::Enum(item) => (item.ident.to_string(), &item.attrs),
+            Item::Macro(item) => (
+                pk_macro_name(item).unwrap_or_default(),
+                &Vec::<Attribute>::new(),
+            ),
+            _ => return json!({}),
+        };
+
+        if let Some(name) = self.names.get(&key) {
+            return json!({ "$ref": format!("{SCHEMA_REF_PREFIX}{name}") });
+        }
+        let name = self.reserve_name(key, &ident);
+        // Insert a placeholder first so the order of the schemas doesn't depend on recursion
+        self.schemas.insert(name.clone(), Value::Null);
+
+        let mut schema = match sources.item(key) {
+            Item::Struct(item) => self.struct_schema(key.file, item),
+            Item::Enum(item) => self.enum_schema(key.file, item),
+            _ => json!({ "type": "string", "format": "ulid" }),
+        };
+        if let Some(docs) = docs(attrs) {
+            describe(&mut schema, docs);
+        }
+        self.schemas.insert(name.clone(), schema);
+
+        json!({ "$ref": format!("{SCHEMA_REF_PREFIX}{name}") })
+    }
+
+    /// Names the component schema of a type after the type, prefixed with its module when another
+    /// type of the same name was named first.
+    fn reserve_name(&mut self, key: DefKey, ident: &str) -> String {
+        let mut name = ident.to_owned();
+        if self.taken.contains(&name) {
+            let module = self.sources.files[key.file]
+                .module
+                .last()
+                .map(|module| pascal_case(module))
+                .unwrap_or_default();
+            name = format!("{module}{ident}");
+            let mut count = 2;
+            while self.taken.contains(&name) {
+                name = format!("{module}{ident}{count}");
+                count += 1;
+            }
+        }
+        self.taken.insert(name.clone());
+        self.names.insert(key, name.clone());
+        name
+    }
+
+    /// The schema of a type written out rather than referenced, used to flatten fields and to
+    /// list query parameters.
+    fn inline_schema(&mut self, file: usize, ty: &Type) -> Value {
+        let path = match ty {
+            Type::Path(type_path) => &type_path.path,
+            _ => return self.type_schema(file, ty),
+        };
+        if let Some(segment) = path.segments.last() {
+            if segment.ident == "Option" {
+                if let Some(inner) = first_type_argument(segment) {
+                    let mut schema = self.inline_schema(file, inner);
+                    if let Some(object) = schema.as_object_mut() {
+                        object.remove("required");
+                    }
+                    return schema;
+                }
+            }
+        }
+
+        let sources = self.sources;
+        match sources
+            .resolve_type(file, path)
+            .map(|key| (key, sources.item(key)))
+        {
+            Some((key, Item::Struct(item))) => self.struct_schema(key.file, item),
+            Some((key, Item::Type(alias))) => self.inline_schema(key.file, &alias.ty),
+            _ => self.type_schema(file, ty),
+        }
+    }
+
+    fn struct_schema(&mut self, file: usize, item: &ItemStruct) -> Value {
+        let attrs = SerdeAttrs::parse(&item.attrs);
+        match &item.fields {
+            Fields::Named(fields) if attrs.transparent => {
+                match fields
+                    .named
+                    .iter()
+                    .find(|field| !SerdeAttrs::parse(&field.attrs).skip)
+                {
+                    Some(field) => self.type_schema(file, &field.ty),
+                    None => json!({}),
+                }
+            }
+            Fields::Named(fields) => {
+                self.fields_schema(file, fields, attrs.rename_all.as_deref(), attrs.default)
+            }
+            Fields::Unnamed(fields) => self.unnamed_fields_schema(file, fields),
+            Fields::Unit => json!({ "type": "null" }),
+        }
+    }
+
+    fn fields_schema(
+        &mut self,
+        file: usize,
+        fields: &FieldsNamed,
+        rename_all: Option<&str>,
+        all_default: bool,
+    ) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut additional_properties = None;
+
+        for field in &fields.named {
+            let attrs = SerdeAttrs::parse(&field.attrs);
+            if attrs.skip {
+                continue;
+            }
+
+            if attrs.flatten {
+                let flattened = self.inline_schema(file, &field.ty);
+                if let Some(flattened_properties) =
+                    flattened.get("properties").and_then(Value::as_object)
+                {
+                    properties.extend(flattened_properties.clone());
+                }
+                if let Some(flattened_required) =
+                    flattened.get("required").and_then(Value::as_array)
+                {
+                    required.extend(flattened_required.iter().cloned());
+                }
+                if let Some(flattened_additional) = flattened.get("additionalProperties") {
+                    additional_properties = Some(flattened_additional.clone());
+                }
+                continue;
+            }
+
+            let ident = field
+                .ident
+                .as_ref()
+                .map(|ident| ident.to_string().trim_start_matches("r#").to_owned())
+                .unwrap_or_default();
+            let name = attrs
+                .rename
+                .unwrap_or_else(|| rename_field(&ident, rename_all));
+            let mut schema = self.type_schema(file, &field.ty);
+            if let Some(docs) = docs(&field.attrs) {
+                describe(&mut schema, docs);
+            }
+
+            if !(all_default || attrs.default || attrs.skip_serializing_if || is_option(&field.ty))
+            {
+                required.push(Value::String(name.clone()));
+            }
+            properties.insert(name, schema);
+        }
+
+        let mut schema = Map::new();
+        schema.insert("type".to_owned(), "object".into());
+        schema.insert("properties".to_owned(), properties.into());
+        if !required.is_empty() {
+            schema.insert("required".to_owned(), required.into());
+        }
+        if let Some(additional_properties) = additional_properties {
+            schema.insert("additionalProperties".to_owned(), additional_properties);
+        }
+        Value::Object(schema)
+    }
+
+    fn unnamed_fields_schema(&mut self, file: usize, fields: &FieldsUnnamed) -> Value {
+        let mut items: Vec<Value> = fields
+            .unnamed
+            .iter()
+            .filter(|field| !SerdeAttrs::parse(&field.attrs).skip)
+            .map(|field| self.type_schema(file, &field.ty))
+            .collect();
+        if items.len() == 1 {
+            items.remove(0)
+        } else {
+            tuple_schema(items)
+        }
+    }
+
+    fn variant_data_schema(&mut self, file: usize, variant: &Variant) -> Option<Value> {
+        let attrs = SerdeAttrs::parse(&variant.attrs);
+        match &variant.fields {
+            Fields::Named(fields) => {
+                Some(self.fields_schema(file, fields, attrs.rename_all.as_deref(), false))
+            }
+            Fields::Unnamed(fields) => Some(self.unnamed_fields_schema(file, fields)),
+            Fields::Unit => None,
+        }
+    }
+
+    fn enum_schema(&mut self, file: usize, item: &ItemEnum) -> Value {
+        let attrs = SerdeAttrs::parse(&item.attrs);
+        let variants: Vec<(String, &Variant)> = item
+            .variants
+            .iter()
+            .filter_map(|variant| {
+                let variant_attrs = SerdeAttrs::parse(&variant.attrs);
+                if variant_attrs.skip {
+                    return None;
+                }
+                let name = variant_attrs.rename.unwrap_or_else(|| {
+                    rename_variant(&variant.ident.to_string(), attrs.rename_all.as_deref())
+                });
+                Some((name, variant))
+            })
+            .collect();
+
+        if !attrs.untagged
+            && attrs.tag.is_none()
+            && variants
+                .iter()
+                .all(|(_, variant)| matches!(variant.fields, Fields::Unit))
+        {
+            let names: Vec<&String> = variants.iter().map(|(name, _)| name).collect();
+            return json!({ "type": "string", "enum": names });
+        }
+
+        let mut one_of = Vec::new();
+        for (name, variant) in variants {
+            let data = self.variant_data_schema(file, variant);
+            let schema = match (&attrs.tag, &attrs.content) {
+                _ if attrs.untagged => data.unwrap_or_else(|| json!({ "type": "null" })),
+                (Some(tag), Some(content)) => {
+                    let mut schema = tag_schema(tag, &name);
+                    if let Some(data) = data {
+                        schema["properties"][content] = data;
+                        schema["required"] = json!([tag, content]);
+                    }
+                    schema
+                }
+                (Some(tag), None) => match data {
+                    Some(data) if data.get("properties").is_some() => {
+                        let mut schema = tag_schema(tag, &name);
+                        if let (Some(properties), Some(data_properties)) = (
+                            schema["properties"].as_object_mut(),
+                            data["properties"].as_object(),
+                        ) {
+                            properties.extend(data_properties.clone());
+                        }
+                        if let (Some(required), Some(data_required)) = (
+                            schema["required"].as_array_mut(),
+                            data.get("required").and_then(Value::as_array),
+                        ) {
+                            required.extend(data_required.iter().cloned());
+                        }
+                        schema
+                    }
+                    Some(data) => json!({ "allOf": [tag_schema(tag, &name), data] }),
+                    None => tag_schema(tag, &name),
+                },
+                (None, _) => match data {
+                    Some(data) => json!({
+                        "type": "object",
+                        "properties": { &name: data },
+                        "required": [&name],
+                        "additionalProperties": false,
+                    }),
+                    None => json!({ "const": name }),
+                },
+            };
+            one_of.push(schema);
+        }
+
+        json!({ "oneOf": one_of })
+    }
+}
+
+/// The serde attributes that change the shape of the serialized data.
+#[derive(Default)]
+struct SerdeAttrs {
+    content: Option<String>,
+    default: bool,
+    flatten: bool,
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+    skip_serializing_if: bool,
+    tag: Option<String>,
+    transparent: bool,
+    untagged: bool,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute]) -> Self {
+        let mut parsed = Self::default();
+        let mut skip_serializing = false;
+        let mut skip_deserializing = false;
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            // Attributes that don't parse are ignored, serde rejects them when compiling anyway
+            let _ = attr.parse_nested_meta(|meta| {
+                let key = meta
+                    .path
+                    .get_ident()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                match key.as_str() {
+                    "content" => parsed.content = Some(meta.value()?.parse::<LitStr>()?.value()),
+                    "default" => {
+                        parsed.default = true;
+                        ignore_meta_value(&meta)?;
+                    }
+                    "flatten" => parsed.flatten = true,
+                    "rename" => parsed.rename = serialized_name(&meta)?,
+                    "rename_all" => parsed.rename_all = serialized_name(&meta)?,
+                    "skip" => parsed.skip = true,
+                    "skip_deserializing" => skip_deserializing = true,
+                    "skip_serializing" => skip_serializing = true,
+                    "skip_serializing_if" => {
+                        parsed.skip_serializing_if = true;
+                        ignore_meta_value(&meta)?;
+                    }
+                    "tag" => parsed.tag = Some(meta.value()?.parse::<LitStr>()?.value()),
+                    "transparent" => parsed.transparent = true,
+                    "untagged" => parsed.untagged = true,
+                    _ => ignore_meta_value(&meta)?,
+                }
+                Ok(())
+            });
+        }
+
+        parsed.skip |= skip_serializing && skip_deserializing;
+        parsed
+    }
+}
+
+/// Reads the value of `rename = "..."` or the serialized name of `rename(serialize = "...")`.
+fn serialized_name(meta: &ParseNestedMeta<'_>) -> syn::Result<Option<String>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse::<LitStr>()?.value()));
+    }
+
+    let mut name = None;
+    meta.parse_nested_meta(|inner| {
+        if inner.path.is_ident("serialize") {
+            name = Some(inner.value()?.parse::<LitStr>()?.value());
+        } else {
+            ignore_meta_value(&inner)?;
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+fn ignore_meta_value(meta: &ParseNestedMeta<'_>) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|inner| ignore_meta_value(&inner))?;
+    }
+    Ok(())
+}
+
+fn collect_rs_files(dir: &Path, paths: &mut Vec<PathBuf>) -> OpenApiResult<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_rs_files(&path, paths)?;
+        } else if path
+            .extension()
+            .map_or(false, |extension| extension == "rs")
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The module of a source file from its path relative to the `src` directory of its crate.
+fn module_path(relative: &Path) -> Vec<String> {
+    let mut module: Vec<String> = relative
+        .iter()
+        .map(|component| {
+            component
+                .to_string_lossy()
+                .trim_end_matches(".rs")
+                .to_owned()
+        })
+        .collect();
+    let is_root = module.len() == 1 && matches!(module[0].as_str(), "lib" | "main");
+    if is_root || module.last().map(String::as_str) == Some("mod") {
+        module.pop();
+    }
+    module
+}
+
+/// Collects the names brought in by a `use` tree with their paths, or `None` for glob imports.
+fn collect_use_tree(
+    tree: &UseTree,
+    prefix: Vec<String>,
+    uses: &mut Vec<(Option<String>, Vec<String>)>,
+) {
+    match tree {
+        UseTree::Path(path) => {
+            let mut prefix = prefix;
+            prefix.push(path.ident.to_string());
+            collect_use_tree(&path.tree, prefix, uses);
+        }
+        UseTree::Name(name) if name.ident == "self" => {
+            if let Some(last) = prefix.last() {
+                uses.push((Some(last.clone()), prefix));
+            }
+        }
+        UseTree::Name(name) => {
+            let mut path = prefix;
+            path.push(name.ident.to_string());
+            uses.push((Some(name.ident.to_string()), path));
+        }
+        UseTree::Rename(rename) => {
+            let mut path = prefix;
+            path.push(rename.ident.to_string());
+            uses.push((Some(rename.rename.to_string()), path));
+        }
+        UseTree::Glob(_) => uses.push((None, prefix)),
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                collect_use_tree(tree, prefix.clone(), uses);
+            }
+        }
+    }
+}
+
+fn is_pk_macro(item: &syn::ItemMacro) -> bool {
+    item.mac.path.is_ident("pk")
+}
+
+/// The name of the type defined by a `pk!()` invocation, which follows its attributes.
+fn pk_macro_name(item: &syn::ItemMacro) -> Option<String> {
+    item.mac
+        .tokens
+        .to_string()
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .next_back()
+        .map(ToOwned::to_owned)
+}
+
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect()
+}
+
+fn type_arguments(segment: &syn::PathSegment) -> Vec<&Type> {
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments
+            .args
+            .iter()
+            .filter_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn first_type_argument(segment: &syn::PathSegment) -> Option<&Type> {
+    type_arguments(segment).into_iter().next()
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Finds the `T` of the first `Json<T>` in a return type, like `ChangeSetResult<Json<T>>`.
+fn find_json_type(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(type_path) => type_path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident == "Json" {
+        return first_type_argument(segment);
+    }
+    type_arguments(segment).into_iter().find_map(find_json_type)
+}
+
+fn has_impl_trait(ty: &Type) -> bool {
+    match ty {
+        Type::ImplTrait(_) => true,
+        Type::Path(type_path) => type_path.path.segments.last().map_or(false, |segment| {
+            type_arguments(segment).into_iter().any(has_impl_trait)
+        }),
+        _ => false,
+    }
+}
+
+enum Visit<'a> {
+    Expr(&'a Expr),
+    Local(&'a Local),
+}
+
+fn walk_block<'a>(block: &'a Block, visit: &mut dyn FnMut(Visit<'a>)) {
+    for stmt in &block.stmts {
+        match stmt {
+            Stmt::Local(local) => {
+                visit(Visit::Local(local));
+                if let Some(init) = &local.init {
+                    walk_expr(&init.expr, visit);
+                    if let Some((_, diverge)) = &init.diverge {
+                        walk_expr(diverge, visit);
+                    }
+                }
+            }
+            Stmt::Expr(expr, _) => walk_expr(expr, visit),
+            Stmt::Item(_) | Stmt::Macro(_) => {}
+        }
+    }
+}
+
+fn walk_expr<'a>(expr: &'a Expr, visit: &mut dyn FnMut(Visit<'a>)) {
+    visit(Visit::Expr(expr));
+    let mut walk = |expr: &'a Expr| walk_expr(expr, visit);
+    match expr {
+        Expr::Array(array) => array.elems.iter().for_each(walk),
+        Expr::Assign(assign) => {
+            walk(&assign.left);
+            walk(&assign.right);
+        }
+        Expr::Async(async_block) => walk_block(&async_block.block, visit),
+        Expr::Await(await_expr) => walk(&await_expr.base),
+        Expr::Binary(binary) => {
+            walk(&binary.left);
+            walk(&binary.right);
+        }
+        Expr::Block(block) => walk_block(&block.block, visit),
+        Expr::Call(call) => {
+            walk(&call.func);
+            call.args.iter().for_each(walk);
+        }
+        Expr::Cast(cast) => walk(&cast.expr),
+        Expr::Closure(closure) => walk(&closure.body),
+        Expr::Field(field) => walk(&field.base),
+        Expr::ForLoop(for_loop) => {
+            walk(&for_loop.expr);
+            walk_block(&for_loop.body, visit);
+        }
+        Expr::Group(group) => walk(&group.expr),
+        Expr::If(if_expr) => {
+            walk_expr(&if_expr.cond, visit);
+            walk_block(&if_expr.then_branch, visit);
+            if let Some((_, else_branch)) = &if_expr.else_branch {
+                walk_expr(else_branch, visit);
+            }
+        }
+        Expr::Let(let_expr) => walk(&let_expr.expr),
+        Expr::Loop(loop_expr) => walk_block(&loop_expr.body, visit),
+        Expr::Match(match_expr) => {
+            walk_expr(&match_expr.expr, visit);
+            for arm in &match_expr.arms {
+                walk_expr(&arm.body, visit);
+            }
+        }
+        Expr::MethodCall(call) => {
+            walk(&call.receiver);
+            call.args.iter().for_each(walk);
+        }
+        Expr::Paren(paren) => walk(&paren.expr),
+        Expr::Reference(reference) => walk(&reference.expr),
+        Expr::Return(return_expr) => {
+            if let Some(expr) = &return_expr.expr {
+                walk(expr);
+            }
+        }
+        Expr::Struct(struct_expr) => struct_expr
+            .fields
+            .iter()
+            .for_each(|field| walk_expr(&field.expr, visit)),
+        Expr::Try(try_expr) => walk(&try_expr.expr),
+        Expr::Tuple(tuple) => tuple.elems.iter().for_each(walk),
+        Expr::Unary(unary) => walk(&unary.expr),
+        Expr::While(while_expr) => {
+            walk_expr(&while_expr.cond, visit);
+            walk_block(&while_expr.body, visit);
+        }
+        _ => {}
+    }
+}
+
+/// Finds the type serialized into the body of a response built by hand, as in
+/// `response.body(serde_json::to_string(&CreateFuncResponse { .. })?)`. Variables are typed from
+/// their annotation, the struct assigned to them, or the return type of the function of the same
+/// file they're assigned from.
+fn serialized_body_type<'a>(
+    block: &'a Block,
+    function: &dyn Fn(&str) -> Option<&'a ItemFn>,
+) -> Option<syn::Path> {
+    let mut locals = HashMap::new();
+    let mut serialized = Vec::new();
+    walk_block(block, &mut |visit| match visit {
+        Visit::Local(local) => locals.extend(local_types(local, function)),
+        Visit::Expr(Expr::Call(call)) if is_serialization(call) => {
+            if let Some(argument) = call.args.first() {
+                serialized.push(argument);
+            }
+        }
+        Visit::Expr(_) => {}
+    });
+
+    serialized.into_iter().find_map(|argument| {
+        let argument = match argument {
+            Expr::Reference(reference) => &*reference.expr,
+            argument => argument,
+        };
+        match argument {
+            Expr::Struct(struct_expr) => Some(struct_expr.path.clone()),
+            Expr::Path(path) => path
+                .path
+                .get_ident()
+                .and_then(|ident| locals.get(&ident.to_string()).cloned()),
+            _ => None,
+        }
+    })
+}
+
+fn is_serialization(call: &ExprCall) -> bool {
+    match &*call.func {
+        Expr::Path(func) => {
+            let segments = path_segments(&func.path);
+            segments.ends_with(&["serde_json".to_owned(), "to_string".to_owned()])
+        }
+        _ => false,
+    }
+}
+
+/// The names and types of the variables bound by a `let`.
+fn local_types<'a>(
+    local: &Local,
+    function: &dyn Fn(&str) -> Option<&'a ItemFn>,
+) -> Vec<(String, syn::Path)> {
+    let (pat, annotation) = match &local.pat {
+        Pat::Type(pat_type) => (&*pat_type.pat, Some(&*pat_type.ty)),
+        pat => (pat, None),
+    };
+    let init = local.init.as_ref().map(|init| &*init.expr);
+
+    let ty = match (annotation, init) {
+        (Some(annotation), _) => annotation,
+        (None, Some(Expr::Struct(struct_expr))) => {
+            return match pat {
+                Pat::Ident(pat_ident) => {
+                    vec![(pat_ident.ident.to_string(), struct_expr.path.clone())]
+                }
+                _ => Vec::new(),
+            };
+        }
+        (None, Some(init)) => match called_function_output(init, function) {
+            Some(output) => output,
+            None => return Vec::new(),
+        },
+        (None, None) => return Vec::new(),
+    };
+
+    match (pat, ty) {
+        (Pat::Ident(pat_ident), Type::Path(type_path)) => {
+            vec![(pat_ident.ident.to_string(), type_path.path.clone())]
+        }
+        (Pat::Tuple(pat_tuple), Type::Tuple(type_tuple)) => pat_tuple
+            .elems
+            .iter()
+            .zip(&type_tuple.elems)
+            .filter_map(|(pat, ty)| match (pat, ty) {
+                (Pat::Ident(pat_ident), Type::Path(type_path)) => {
+                    Some((pat_ident.ident.to_string(), type_path.path.clone()))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The type returned by a call like `do_save_func(&ctx, request).await?`, unwrapping the `Ok` of
+/// its result.
+fn called_function_output<'a>(
+    expr: &Expr,
+    function: &dyn Fn(&str) -> Option<&'a ItemFn>,
+) -> Option<&'a Type> {
+    match expr {
+        Expr::Await(await_expr) => called_function_output(&await_expr.base, function),
+        Expr::Try(try_expr) => called_function_output(&try_expr.expr, function),
+        Expr::Call(call) => {
+            let name = match &*call.func {
+                Expr::Path(func) => func.path.get_ident()?.to_string(),
+                _ => return None,
+            };
+            let output = match &function(&name)?.sig.output {
+                ReturnType::Type(_, output) => &**output,
+                ReturnType::Default => return None,
+            };
+            match output {
+                Type::Path(type_path) => {
+                    let segment = type_path.path.segments.last()?;
+                    if segment.ident.to_string().ends_with("Result") {
+                        first_type_argument(segment)
+                    } else {
+                        Some(output)
+                    }
+                }
+                output => Some(output),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Lists the properties of the schema of a `Query` as query parameters.
+fn query_parameters(schema: Value) -> Vec<Value> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let properties = match schema.get("properties").and_then(Value::as_object) {
+        Some(properties) => properties,
+        None => return Vec::new(),
+    };
+
+    properties
+        .iter()
+        .map(|(name, schema)| {
+            let mut parameter = json!({
+                "name": name,
+                "in": "query",
+                "required": required.contains(&name.as_str()),
+                "schema": schema,
+            });
+            if let Some(description) = schema.get("description") {
+                parameter["description"] = description.clone();
+            }
+            parameter
+        })
+        .collect()
+}
+
+fn method_handlers(expr: &Expr) -> Vec<(String, syn::Path)> {
+    let handler =
+        |method: String, args: &syn::punctuated::Punctuated<Expr, syn::Token![,]>| match args
+            .first()
+        {
+            Some(Expr::Path(handler)) if HTTP_METHODS.contains(&method.as_str()) => {
+                Some((method, handler.path.clone()))
+            }
+            _ => None,
+        };
+
+    match expr {
+        Expr::Call(call) => match &*call.func {
+            Expr::Path(func) => func
+                .path
+                .segments
+                .last()
+                .and_then(|segment| handler(segment.ident.to_string(), &call.args))
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        },
+        Expr::MethodCall(call) => {
+            let mut handlers = method_handlers(&call.receiver);
+            handlers.extend(handler(call.method.to_string(), &call.args));
+            handlers
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn join_paths(prefix: &str, path: &str) -> String {
+    if path == "/" && !prefix.is_empty() {
+        prefix.to_owned()
+    } else {
+        format!("{}{path}", prefix.trim_end_matches('/'))
+    }
+}
+
+/// Names an operation after its path and handler, like `change_set_create_change_set`.
+fn operation_id(route: &Route) -> String {
+    let mut segments: Vec<&str> = route
+        .path
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != "api")
+        .collect();
+    segments.pop();
+    let handler = route.handler.sig.ident.to_string();
+    segments.push(&handler);
+    segments.join("_")
+}
+
+fn docs(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(name_value) if name_value.path.is_ident("doc") => {
+                match &name_value.value {
+                    Expr::Lit(expr) => match &expr.lit {
+                        Lit::Str(line) => Some(line.value().trim().to_owned()),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect();
+    let docs = lines.join("\n").trim().to_owned();
+    (!docs.is_empty()).then_some(docs)
+}
+
+fn describe(schema: &mut Value, description: String) {
+    if let Some(schema) = schema.as_object_mut() {
+        schema.insert("description".to_owned(), description.into());
+    }
+}
+
+fn nullable(schema: Value) -> Value {
+    let object = match schema.as_object() {
+        Some(object) if !object.is_empty() => object,
+        _ => return schema,
+    };
+    match object.get("type") {
+        Some(Value::String(ty))
+            if object
+                .keys()
+                .all(|key| matches!(key.as_str(), "type" | "format" | "items")) =>
+        {
+            let mut schema = object.clone();
+            schema.insert("type".to_owned(), json!([ty, "null"]));
+            Value::Object(schema)
+        }
+        _ => json!({ "anyOf": [schema, { "type": "null" }] }),
+    }
+}
+
+fn tuple_schema(items: Vec<Value>) -> Value {
+    let len = items.len();
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "minItems": len,
+        "maxItems": len,
+    })
+}
+
+fn tag_schema(tag: &str, name: &str) -> Value {
+    json!({
+        "type": "object",
+        "properties": { tag: { "const": name } },
+        "required": [tag],
+    })
+}
+
+/// Renames a variant like serde's `rename_all`, from its `PascalCase` name.
+fn rename_variant(name: &str, rule: Option<&str>) -> String {
+    match rule {
+        Some("lowercase") => name.to_ascii_lowercase(),
+        Some("UPPERCASE") => name.to_ascii_uppercase(),
+        Some("camelCase") => lowercase_first(name),
+        Some("snake_case") => snake_case(name),
+        Some("SCREAMING_SNAKE_CASE") => snake_case(name).to_ascii_uppercase(),
+        Some("kebab-case") => snake_case(name).replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => snake_case(name).to_ascii_uppercase().replace('_', "-"),
+        _ => name.to_owned(),
+    }
+}
+
+/// Renames a field like serde's `rename_all`, from its `snake_case` name.
+fn rename_field(name: &str, rule: Option<&str>) -> String {
+    match rule {
+        Some("UPPERCASE" | "SCREAMING_SNAKE_CASE") => name.to_ascii_uppercase(),
+        Some("PascalCase") => pascal_case(name),
+        Some("camelCase") => lowercase_first(&pascal_case(name)),
+        Some("kebab-case") => name.replace('_', "-"),
+        Some("SCREAMING-KEBAB-CASE") => name.to_ascii_uppercase().replace('_', "-"),
+        _ => name.to_owned(),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (index, ch) in name.char_indices() {
+        if index > 0 && ch.is_uppercase() {
+            snake.push('_');
+        }
+        snake.push(ch.to_ascii_lowercase());
+    }
+    snake
+}
+
+fn pascal_case(name: &str) -> String {
+    let mut pascal = String::new();
+    let mut capitalize = true;
+    for ch in name.chars() {
+        if ch == '_' {
+            capitalize = true;
+        } else if capitalize {
+            pascal.push(ch.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            pascal.push(ch);
+        }
+    }
+    pascal
+}
+
+fn lowercase_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,276 @@
+use axum::{
+    body::Body,
+    http::{self, Method, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use dal::{ApiToken, DalContext, UserClaim, WorkspaceRole, WorkspaceSignup};
+use dal_test::{
+    helpers::{create_auth_token, create_user},
+    sdf_test, AuthTokenRef, DalContextHead,
+};
+use pretty_assertions_sorted::assert_eq;
+use sdf_client::{types::CreateChangeSetRequest, SdfClient, SdfClientError};
+use sdf_server::{
+    rate_limit::{
+        BucketConfig, BudgetConfig, BudgetMetrics, RateLimitBudget, RateLimitLayer,
+        RateLimitMetrics, RateLimiter, RateLimitsConfig,
+    },
+    service::change_set::list_open_change_sets::ListOpenChangeSetsResponse,
+};
+use tower::ServiceExt;
+
+use crate::service_tests::{api_request_auth_empty, sdf_client::serve};
+
+/// Limits which don't refill during a test, with room for a single function execution.
+fn limits(mutations_per_user: u32, mutations_per_workspace: u32) -> RateLimitsConfig {
+    let bucket = |capacity| BucketConfig::new(capacity, 0.001);
+    RateLimitsConfig {
+        enabled: true,
+        mutation: BudgetConfig {
+            per_user: bucket(mutations_per_user),
+            per_workspace: bucket(mutations_per_workspace),
+        },
+        func_execution: BudgetConfig {
+            per_user: bucket(1),
+            per_workspace: bucket(1),
+        },
+    }
+}
+
+async fn limited(ctx: &DalContext, app: Router, limiter: &RateLimiter) -> Router {
+    let jwt_public_signing_key = dal_test::jwt_public_signing_key()
+        .await
+        .expect("cannot load jwt public signing key");
+    app.layer(RateLimitLayer::new(
+        limiter.clone(),
+        ctx.services_context().clone(),
+        jwt_public_signing_key,
+    ))
+}
+
+async fn post(app: Router, uri: &str, auth_token: &str, request: serde_json::Value) -> Response {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .header(http::header::AUTHORIZATION, format!("Bearer {auth_token}"))
+        .body(Body::from(
+            serde_json::to_vec(&request).expect("cannot turn request to json"),
+        ))
+        .expect("cannot create api request");
+    app.oneshot(request).await.expect("cannot send request")
+}
+
+async fn create_change_set(app: Router, auth_token: &str) -> Response {
+    post(
+        app,
+        "/api/change_set/create_change_set",
+        auth_token,
+        serde_json::json!({ "changeSetName": "limited" }),
+    )
+    .await
+}
+
+fn budget_metrics(limiter: &RateLimiter, budget: RateLimitBudget) -> BudgetMetrics {
+    limiter
+        .metrics()
+        .budgets
+        .into_iter()
+        .find(|metrics| metrics.budget == budget)
+        .expect("no metrics for budget")
+}
+
+#[sdf_test]
+async fn mutations_are_limited_per_user(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    // See `session::restore_authentication` for why the workspace is committed first
+    ctx.commit().await.expect("failed to commit");
+    let limiter = RateLimiter::new(limits(2, 10));
+    let app = limited(&ctx, app, &limiter).await;
+
+    for _ in 0..2 {
+        let response = create_change_set(app.clone(), auth_token).await;
+        assert_eq!(StatusCode::OK, response.status());
+    }
+    let response = create_change_set(app.clone(), auth_token).await;
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+    let retry_after: u64 = response
+        .headers()
+        .get(http::header::RETRY_AFTER)
+        .expect("no retry-after header")
+        .to_str()
+        .expect("retry-after header is not a string")
+        .parse()
+        .expect("retry-after header is not a number of seconds");
+    assert!(retry_after > 0);
+
+    let client = SdfClient::new(serve(app.clone()), auth_token);
+    match client
+        .create_change_set(&CreateChangeSetRequest {
+            change_set_name: "limited".to_owned(),
+        })
+        .await
+    {
+        Err(SdfClientError::RateLimited(retry_after, message)) => {
+            assert!(!retry_after.is_zero());
+            assert!(!message.is_empty());
+        }
+        other => panic!("expected a rate limited error, got {other:?}"),
+    }
+
+    // Reading routes aren't limited
+    let _response: ListOpenChangeSetsResponse = api_request_auth_empty(
+        app.clone(),
+        Method::GET,
+        "/api/change_set/list_open_change_sets",
+        auth_token,
+    )
+    .await;
+
+    // Executing functions has a budget of its own
+    let response = post(
+        app.clone(),
+        "/api/func/execute",
+        auth_token,
+        serde_json::json!({}),
+    )
+    .await;
+    assert_ne!(StatusCode::TOO_MANY_REQUESTS, response.status());
+    let response = post(
+        app.clone(),
+        "/api/func/execute",
+        auth_token,
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+
+    // Requests which can't be authenticated are left for their route to reject
+    let response = create_change_set(app, "not a token").await;
+    assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+
+    let mutation = budget_metrics(&limiter, RateLimitBudget::Mutation);
+    assert_eq!(
+        (2, 2, 0),
+        (
+            mutation.allowed,
+            mutation.limited_by_user,
+            mutation.limited_by_workspace
+        )
+    );
+    let func_execution = budget_metrics(&limiter, RateLimitBudget::FuncExecution);
+    assert_eq!(
+        (1, 1),
+        (func_execution.allowed, func_execution.limited_by_user)
+    );
+}
+
+#[sdf_test]
+async fn workspace_budget_is_shared_by_members(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+    nw: WorkspaceSignup,
+) {
+    let editor = create_user(&ctx).await;
+    editor
+        .associate_workspace(&ctx, *nw.workspace.pk(), WorkspaceRole::Editor)
+        .await
+        .expect("cannot associate user to workspace");
+    let editor_auth_token =
+        create_auth_token(UserClaim::new(editor.pk(), *nw.workspace.pk())).await;
+    ctx.commit().await.expect("failed to commit");
+    let limiter = RateLimiter::new(limits(10, 2));
+    let app = limited(&ctx, app, &limiter).await;
+
+    for auth_token in [auth_token, editor_auth_token.as_str()] {
+        let response = create_change_set(app.clone(), auth_token).await;
+        assert_eq!(StatusCode::OK, response.status());
+    }
+    let response = create_change_set(app, &editor_auth_token).await;
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+
+    let mutation = budget_metrics(&limiter, RateLimitBudget::Mutation);
+    assert_eq!(
+        (2, 0, 1),
+        (
+            mutation.allowed,
+            mutation.limited_by_user,
+            mutation.limited_by_workspace
+        )
+    );
+    assert_eq!(3, mutation.active_buckets);
+}
+
+#[sdf_test]
+async fn api_tokens_are_limited_per_user(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    nw: WorkspaceSignup,
+) {
+    let (_, token) = ApiToken::new(
+        &ctx,
+        nw.user.pk(),
+        *nw.workspace.pk(),
+        "ci",
+        WorkspaceRole::Editor,
+        None,
+    )
+    .await
+    .expect("cannot create api token");
+    ctx.commit().await.expect("failed to commit");
+    let limiter = RateLimiter::new(limits(1, 10));
+    let app = limited(&ctx, app, &limiter).await;
+
+    let response = create_change_set(app.clone(), &token).await;
+    assert_eq!(StatusCode::OK, response.status());
+    let response = create_change_set(app, &token).await;
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+
+    // The token found by the layer is reused by the route, which still records its use
+    let api_tokens = ApiToken::list_for_user(&ctx, nw.user.pk(), *nw.workspace.pk())
+        .await
+        .expect("cannot list api tokens");
+    assert!(api_tokens[0].last_used_at().is_some());
+}
+
+#[sdf_test]
+async fn metrics(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    ctx.commit().await.expect("failed to commit");
+
+    let response = create_change_set(app.clone(), auth_token).await;
+    assert_eq!(StatusCode::OK, response.status());
+
+    let metrics: RateLimitMetrics = api_request_auth_empty(
+        app.clone(),
+        Method::GET,
+        "/api/rate_limit/metrics",
+        auth_token,
+    )
+    .await;
+    assert!(metrics.enabled);
+    assert_eq!(
+        vec![RateLimitBudget::Mutation, RateLimitBudget::FuncExecution],
+        metrics
+            .budgets
+            .iter()
+            .map(|metrics| metrics.budget)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(1, metrics.budgets[0].allowed);
+
+    let request = Request::builder()
+        .uri("/api/rate_limit/metrics")
+        .body(Body::empty())
+        .expect("cannot create api request");
+    let response = app.oneshot(request).await.expect("cannot send request");
+    assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+}
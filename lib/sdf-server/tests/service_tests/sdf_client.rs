@@ -0,0 +1,326 @@
+use std::{collections::HashSet, fmt::Debug, net::TcpListener, str::FromStr, time::Duration};
+
+use axum::{
+    extract::ws::{Message, WebSocketUpgrade},
+    http::StatusCode,
+    response::Response,
+    routing::get,
+    Router,
+};
+use dal::StandardModel;
+use dal_test::{
+    sdf_test,
+    test_harness::{create_schema, create_schema_variant},
+    AuthTokenRef, DalContextHead,
+};
+use pretty_assertions_sorted::assert_eq;
+use sdf_client::{
+    types::{
+        ApplyChangeSetRequest, ChangeSetPk, ChangeSetStatus, CreateChangeSetRequest,
+        CreateNodeRequest, EventFilter, ExportPkgRequest, GetChangeSetRequest, GetDiagramRequest,
+        GetPropertyEditorValuesRequest, InstallPkgFileRequest, Resume, Visibility, WsPayload,
+    },
+    SdfClient, SdfClientError,
+};
+use url::Url;
+
+/// Serves the router on a local port, since the client needs a real server to talk to.
+pub fn serve(app: Router) -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("cannot bind listener");
+    let address = listener.local_addr().expect("listener has no address");
+    let server = axum::Server::from_tcp(listener)
+        .expect("cannot create server")
+        .serve(app.into_make_service());
+    tokio::spawn(server);
+
+    Url::parse(&format!("http://{address}/")).expect("cannot parse server url")
+}
+
+/// Turns an id from the dal into the client's id for the same object.
+fn client_id<T>(id: impl ToString) -> T
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    id.to_string().parse().expect("cannot parse id")
+}
+
+#[sdf_test]
+async fn change_sets(app: Router, AuthTokenRef(auth_token): AuthTokenRef<'_>) {
+    let client = SdfClient::new(serve(app), auth_token);
+
+    let created = client
+        .create_change_set(&CreateChangeSetRequest {
+            change_set_name: "scripted".to_owned(),
+        })
+        .await
+        .expect("cannot create change set");
+    let pk = created.change_set.pk;
+
+    let open = client
+        .list_open_change_sets()
+        .await
+        .expect("cannot list open change sets");
+    assert!(open.iter().any(|change_set| change_set.pk == pk));
+
+    let fetched = client
+        .get_change_set(&GetChangeSetRequest { pk })
+        .await
+        .expect("cannot get change set");
+    assert_eq!(created.change_set, fetched.change_set);
+
+    let applied = client
+        .apply_change_set(&ApplyChangeSetRequest { change_set_pk: pk })
+        .await
+        .expect("cannot apply change set");
+    assert_eq!(ChangeSetStatus::Applied, applied.change_set.status);
+}
+
+#[sdf_test]
+async fn api_errors(app: Router, AuthTokenRef(auth_token): AuthTokenRef<'_>) {
+    let client = SdfClient::new(serve(app.clone()), auth_token);
+    match client
+        .get_change_set(&GetChangeSetRequest {
+            pk: ChangeSetPk::generate(),
+        })
+        .await
+    {
+        Err(SdfClientError::Api(status, message)) => {
+            assert!(status.is_client_error() || status.is_server_error());
+            assert!(!message.is_empty());
+        }
+        other => panic!("expected an api error, got {other:?}"),
+    }
+
+    let unauthenticated = SdfClient::new(serve(app), "not a token");
+    match unauthenticated.list_open_change_sets().await {
+        Err(SdfClientError::Api(status, _)) => assert_eq!(StatusCode::UNAUTHORIZED, status),
+        other => panic!("expected an unauthorized error, got {other:?}"),
+    }
+}
+
+#[sdf_test]
+async fn diagram_nodes(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    let mut schema = create_schema(&ctx).await;
+    let mut schema_variant = create_schema_variant(&ctx, *schema.id()).await;
+    schema_variant
+        .finalize(&ctx, None)
+        .await
+        .expect("could not finalize schema variant");
+    schema
+        .set_default_schema_variant_id(&ctx, Some(*schema_variant.id()))
+        .await
+        .expect("cannot set default schema variant");
+    ctx.blocking_commit()
+        .await
+        .expect("cannot commit transaction");
+
+    let client = SdfClient::new(serve(app), auth_token);
+    let created = client
+        .create_node(&CreateNodeRequest {
+            schema_id: client_id(schema.id()),
+            parent_id: None,
+            x: "0".to_owned(),
+            y: "0".to_owned(),
+            visibility: Visibility::new_head(),
+        })
+        .await
+        .expect("cannot create node");
+    // Nodes created at head are created in a new change set
+    let change_set_pk = created
+        .forced_change_set_pk
+        .expect("no change set was created for the node");
+    let visibility = Visibility::new(change_set_pk);
+
+    let diagram = client
+        .get_diagram(&GetDiagramRequest { visibility })
+        .await
+        .expect("cannot get diagram");
+    assert_eq!(
+        vec![created.response.component_id],
+        diagram
+            .components
+            .iter()
+            .map(|component| component.id)
+            .collect::<Vec<_>>()
+    );
+
+    let values = client
+        .get_property_editor_values(&GetPropertyEditorValuesRequest {
+            component_id: created.response.component_id,
+            visibility,
+        })
+        .await
+        .expect("cannot get property editor values");
+    assert!(values.values.contains_key(&values.root_value_id));
+}
+
+#[sdf_test]
+async fn pkg_files(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    let schema = create_schema(&ctx).await;
+    let mut schema_variant = create_schema_variant(&ctx, *schema.id()).await;
+    schema_variant
+        .finalize(&ctx, None)
+        .await
+        .expect("could not finalize schema variant");
+    ctx.blocking_commit()
+        .await
+        .expect("cannot commit transaction");
+
+    let client = SdfClient::new(serve(app), auth_token);
+    let exported = client
+        .export_pkg_file(&ExportPkgRequest {
+            name: "scripted".to_owned(),
+            version: "1.0.0".to_owned(),
+            description: None,
+            schema_variants: vec![client_id(schema_variant.id())],
+            visibility: Visibility::new_head(),
+        })
+        .await
+        .expect("cannot export package file");
+    assert_eq!(
+        ("scripted", "1.0.0"),
+        (exported.name.as_str(), exported.version.as_str())
+    );
+    assert!(!exported.data.is_empty());
+
+    let request = InstallPkgFileRequest {
+        data: exported.data,
+        visibility: Visibility::new_head(),
+    };
+    let installed = client
+        .install_pkg_file(&request)
+        .await
+        .expect("cannot install package file");
+    assert!(installed.success);
+
+    // A package is only installed once
+    assert!(matches!(
+        client.install_pkg_file(&request).await,
+        Err(SdfClientError::Api(_, _))
+    ));
+}
+
+#[sdf_test]
+async fn workspace_updates(app: Router, AuthTokenRef(auth_token): AuthTokenRef<'_>) {
+    let client = SdfClient::new(serve(app), auth_token);
+    let mut updates = client
+        .workspace_updates(Resume::default())
+        .await
+        .expect("cannot connect to workspace updates");
+    updates
+        .subscribe(EventFilter {
+            change_set_pks: None,
+            payload_kinds: Some(HashSet::from(["ChangeSetCreated".to_owned()])),
+        })
+        .await
+        .expect("cannot subscribe");
+
+    let created = client
+        .create_change_set(&CreateChangeSetRequest {
+            change_set_name: "watched".to_owned(),
+        })
+        .await
+        .expect("cannot create change set");
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let payload = updates
+                .next_payload()
+                .await
+                .expect("cannot receive event")
+                .expect("workspace updates closed");
+            if payload == WsPayload::ChangeSetCreated(created.change_set.pk) {
+                break;
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for the change set to be created");
+    assert!(updates.resume().resume_from.is_some());
+
+    updates
+        .close()
+        .await
+        .expect("cannot close workspace updates");
+}
+
+/// Sends the events to every websocket client, the way sdf would with newer payload kinds.
+async fn send_events(ws: WebSocketUpgrade, events: Vec<serde_json::Value>) -> Response {
+    ws.on_upgrade(|mut socket| async move {
+        for event in events {
+            socket
+                .send(Message::Text(event.to_string()))
+                .await
+                .expect("cannot send event");
+        }
+    })
+}
+
+#[tokio::test]
+async fn workspace_updates_receive_unknown_payloads() {
+    let event = |sequence: u64, payload: serde_json::Value| {
+        serde_json::json!({
+            "version": 1,
+            "workspace_pk": ulid::Ulid::new().to_string(),
+            "change_set_pk": ulid::Ulid::new().to_string(),
+            "sequence": sequence,
+            "stream_id": "stream",
+            "payload": payload,
+        })
+    };
+    let change_set_pk = ulid::Ulid::new().to_string();
+    let events = vec![
+        event(
+            1,
+            serde_json::json!({ "kind": "ComponentTeleported", "data": { "to": "mars" } }),
+        ),
+        event(
+            2,
+            serde_json::json!({ "kind": "ChangeSetWritten", "data": change_set_pk }),
+        ),
+    ];
+    let app = Router::new().route(
+        "/api/ws/workspace_updates",
+        get(move |ws: WebSocketUpgrade| send_events(ws, events.clone())),
+    );
+
+    let client = SdfClient::new(serve(app), "token");
+    let mut updates = client
+        .workspace_updates(Resume::default())
+        .await
+        .expect("cannot connect to workspace updates");
+
+    let unknown = updates
+        .next_event()
+        .await
+        .expect("cannot receive event")
+        .expect("workspace updates closed");
+    assert_eq!(
+        WsPayload::Unknown {
+            kind: "ComponentTeleported".to_owned(),
+            data: serde_json::json!({ "to": "mars" }),
+        },
+        unknown.payload
+    );
+    assert_eq!(Some(1), updates.resume().resume_from);
+
+    // The events after it are received as usual
+    let payload = updates
+        .next_payload()
+        .await
+        .expect("cannot receive event")
+        .expect("workspace updates closed");
+    assert_eq!(
+        WsPayload::ChangeSetWritten(client_id(&change_set_pk)),
+        payload
+    );
+}
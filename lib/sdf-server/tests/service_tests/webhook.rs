@@ -0,0 +1,144 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use dal::{UserClaim, WebhookEventKind, WorkspaceRole, WorkspaceSignup};
+use dal_test::{
+    helpers::{create_auth_token, create_user},
+    sdf_test, AuthTokenRef, DalContextHead,
+};
+use pretty_assertions_sorted::assert_eq;
+use sdf_server::service::webhook::{
+    create_endpoint::{CreateEndpointRequest, CreateEndpointResponse},
+    delete_endpoint::{DeleteEndpointRequest, DeleteEndpointResponse},
+    list_deliveries::{ListDeliveriesRequest, ListDeliveriesResponse},
+    list_endpoints::ListEndpointsResponse,
+    update_endpoint::{UpdateEndpointRequest, UpdateEndpointResponse},
+};
+
+use crate::service_tests::{
+    api_request_auth_empty, api_request_auth_json_body, api_request_auth_query,
+    api_request_auth_status,
+};
+
+#[sdf_test]
+async fn manage_webhook_endpoints(app: Router, AuthTokenRef(auth_token): AuthTokenRef<'_>) {
+    let created: CreateEndpointResponse = api_request_auth_json_body(
+        app.clone(),
+        Method::POST,
+        "/api/webhook/create_endpoint",
+        auth_token,
+        &CreateEndpointRequest {
+            url: "https://ci.example.com/hooks/si".to_owned(),
+            event_kinds: vec![WebhookEventKind::ChangeSetApplied],
+        },
+    )
+    .await;
+    assert!(created
+        .secret
+        .starts_with(dal::webhook::WEBHOOK_SECRET_PREFIX));
+    assert!(created.endpoint.enabled());
+
+    let updated: UpdateEndpointResponse = api_request_auth_json_body(
+        app.clone(),
+        Method::POST,
+        "/api/webhook/update_endpoint",
+        auth_token,
+        &UpdateEndpointRequest {
+            pk: created.endpoint.pk(),
+            event_kinds: vec![
+                WebhookEventKind::FixBatchReturn,
+                WebhookEventKind::QualificationsFailing,
+            ],
+            enabled: false,
+        },
+    )
+    .await;
+    assert!(!updated.endpoint.enabled());
+
+    let listed: ListEndpointsResponse = api_request_auth_empty(
+        app.clone(),
+        Method::GET,
+        "/api/webhook/list_endpoints",
+        auth_token,
+    )
+    .await;
+    assert_eq!(vec![updated.endpoint.clone()], listed.endpoints);
+
+    let deliveries: ListDeliveriesResponse = api_request_auth_query(
+        app.clone(),
+        "/api/webhook/list_deliveries",
+        auth_token,
+        &ListDeliveriesRequest {
+            endpoint_pk: created.endpoint.pk(),
+            limit: None,
+        },
+    )
+    .await;
+    assert!(deliveries.deliveries.is_empty());
+
+    let deleted: DeleteEndpointResponse = api_request_auth_json_body(
+        app.clone(),
+        Method::POST,
+        "/api/webhook/delete_endpoint",
+        auth_token,
+        &DeleteEndpointRequest {
+            pk: created.endpoint.pk(),
+        },
+    )
+    .await;
+    assert!(deleted.success);
+
+    let listed: ListEndpointsResponse = api_request_auth_empty(
+        app.clone(),
+        Method::GET,
+        "/api/webhook/list_endpoints",
+        auth_token,
+    )
+    .await;
+    assert!(listed.endpoints.is_empty());
+}
+
+#[sdf_test]
+async fn rejects_invalid_webhook_urls(app: Router, AuthTokenRef(auth_token): AuthTokenRef<'_>) {
+    let status = api_request_auth_status(
+        app,
+        Method::POST,
+        "/api/webhook/create_endpoint",
+        auth_token,
+        &CreateEndpointRequest {
+            url: "ftp://ci.example.com/hooks/si".to_owned(),
+            event_kinds: vec![WebhookEventKind::ChangeSetApplied],
+        },
+    )
+    .await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+}
+
+#[sdf_test]
+async fn only_owners_can_manage_webhooks(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    nw: WorkspaceSignup,
+) {
+    let applier = create_user(&ctx).await;
+    applier
+        .associate_workspace(&ctx, *nw.workspace.pk(), WorkspaceRole::Applier)
+        .await
+        .expect("cannot associate user to workspace");
+    let auth_token = create_auth_token(UserClaim::new(applier.pk(), *nw.workspace.pk())).await;
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let status = api_request_auth_status(
+        app,
+        Method::POST,
+        "/api/webhook/create_endpoint",
+        &auth_token,
+        &CreateEndpointRequest {
+            url: "https://ci.example.com/hooks/si".to_owned(),
+            event_kinds: vec![WebhookEventKind::ChangeSetApplied],
+        },
+    )
+    .await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+}
@@ -0,0 +1,268 @@
+use axum::{
+    http::{Method, StatusCode},
+    Router,
+};
+use dal::{DalContext, User, UserClaim, UserPk, WorkspacePk, WorkspaceRole, WorkspaceSignup};
+use dal_test::{
+    helpers::{create_auth_token, create_user},
+    sdf_test,
+    test_harness::create_change_set as dal_create_change_set,
+    AuthTokenRef, DalContextHead,
+};
+use pretty_assertions_sorted::assert_eq;
+use sdf_server::service::{
+    change_set::{
+        apply_change_set::{ApplyChangeSetRequest, ApplyChangeSetResponse},
+        list_open_change_sets::ListOpenChangeSetsResponse,
+    },
+    workspace::{
+        list_members::ListMembersResponse,
+        update_member_role::{UpdateMemberRoleRequest, UpdateMemberRoleResponse},
+    },
+};
+
+use crate::service_tests::{
+    api_request_auth_empty, api_request_auth_json_body, api_request_auth_status,
+};
+
+/// Every route that changes a workspace, none of which a viewer may use.
+const MUTATING_ROUTES: &[(&str, &str)] = &[
+    ("POST", "/api/change_set/add_action"),
+    ("POST", "/api/change_set/apply_change_set"),
+    ("POST", "/api/change_set/create_change_set"),
+    ("POST", "/api/change_set/remove_action"),
+    ("POST", "/api/change_set/update_selected_change_set"),
+    ("POST", "/api/component/alter_simulation"),
+    ("POST", "/api/component/bulk_update_property_value"),
+    ("POST", "/api/component/insert_property_editor_value"),
+    ("POST", "/api/component/refresh"),
+    ("POST", "/api/component/set_type"),
+    ("POST", "/api/component/update_property_editor_value"),
+    ("POST", "/api/diagram/connect_component_to_frame"),
+    ("POST", "/api/diagram/create_connection"),
+    ("POST", "/api/diagram/create_node"),
+    ("POST", "/api/diagram/delete_component"),
+    ("POST", "/api/diagram/delete_components"),
+    ("POST", "/api/diagram/delete_connection"),
+    ("POST", "/api/diagram/get_node_add_menu"),
+    ("POST", "/api/diagram/restore_component"),
+    ("POST", "/api/diagram/restore_components"),
+    ("POST", "/api/diagram/restore_connection"),
+    ("POST", "/api/diagram/set_node_position"),
+    ("POST", "/api/fix/run"),
+    ("POST", "/api/func/create_func"),
+    ("POST", "/api/func/delete_func"),
+    ("POST", "/api/func/execute"),
+    ("POST", "/api/func/revert_func"),
+    ("POST", "/api/func/save_and_exec"),
+    ("POST", "/api/func/save_func"),
+    ("POST", "/api/pkg/export_pkg"),
+    ("POST", "/api/pkg/export_workspace"),
+    ("POST", "/api/pkg/install_pkg"),
+    ("POST", "/api/pkg/install_pkg_file"),
+    ("POST", "/api/pkg/reject_pkg"),
+    ("POST", "/api/pkg/set_as_builtin"),
+    ("POST", "/api/schema/create_schema"),
+    ("POST", "/api/secret/"),
+    ("DELETE", "/api/secret/"),
+    ("POST", "/api/secret/reference"),
+    ("POST", "/api/secret/rotate_key_pair"),
+    ("POST", "/api/variant_def/clone_variant_def"),
+    ("POST", "/api/variant_def/create_variant_def"),
+    ("POST", "/api/variant_def/exec_variant_def"),
+    ("POST", "/api/variant_def/save_variant_def"),
+    ("POST", "/api/workspace/update_member_role"),
+];
+
+/// Routes that change real infrastructure, which only appliers and owners may use.
+const APPLYING_ROUTES: &[(&str, &str)] = &[
+    ("POST", "/api/change_set/apply_change_set"),
+    ("POST", "/api/fix/run"),
+];
+
+async fn member_auth_token(
+    ctx: &DalContext,
+    workspace_pk: WorkspacePk,
+    role: WorkspaceRole,
+) -> (User, String) {
+    let user = create_user(ctx).await;
+    user.associate_workspace(ctx, workspace_pk, role)
+        .await
+        .expect("cannot associate user to workspace");
+    let auth_token = create_auth_token(UserClaim::new(user.pk(), workspace_pk)).await;
+
+    (user, auth_token)
+}
+
+#[sdf_test]
+async fn viewer_can_read_but_not_mutate(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    nw: WorkspaceSignup,
+) {
+    let (_viewer, auth_token) =
+        member_auth_token(&ctx, *nw.workspace.pk(), WorkspaceRole::Viewer).await;
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let _response: ListOpenChangeSetsResponse = api_request_auth_empty(
+        app.clone(),
+        Method::GET,
+        "/api/change_set/list_open_change_sets",
+        &auth_token,
+    )
+    .await;
+
+    for (method, uri) in MUTATING_ROUTES {
+        let status = api_request_auth_status(
+            app.clone(),
+            method.parse().expect("invalid method"),
+            uri,
+            &auth_token,
+            &serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, status, "{method} {uri}");
+    }
+}
+
+#[sdf_test]
+async fn editor_cannot_apply(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    nw: WorkspaceSignup,
+) {
+    let (_editor, auth_token) =
+        member_auth_token(&ctx, *nw.workspace.pk(), WorkspaceRole::Editor).await;
+    ctx.commit().await.expect("cannot commit transaction");
+
+    for (method, uri) in APPLYING_ROUTES {
+        let status = api_request_auth_status(
+            app.clone(),
+            method.parse().expect("invalid method"),
+            uri,
+            &auth_token,
+            &serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(StatusCode::FORBIDDEN, status, "{method} {uri}");
+    }
+}
+
+#[sdf_test]
+async fn applier_can_apply(DalContextHead(ctx): DalContextHead, app: Router, nw: WorkspaceSignup) {
+    let (_applier, auth_token) =
+        member_auth_token(&ctx, *nw.workspace.pk(), WorkspaceRole::Applier).await;
+    let change_set = dal_create_change_set(&ctx).await;
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let request = ApplyChangeSetRequest {
+        change_set_pk: change_set.pk,
+    };
+    let response: ApplyChangeSetResponse = api_request_auth_json_body(
+        app,
+        Method::POST,
+        "/api/change_set/apply_change_set",
+        &auth_token,
+        &request,
+    )
+    .await;
+    assert_eq!(change_set.pk, response.change_set.pk);
+}
+
+#[sdf_test]
+async fn non_member_is_forbidden(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    nw: WorkspaceSignup,
+) {
+    let stranger = create_user(&ctx).await;
+    let auth_token = create_auth_token(UserClaim::new(stranger.pk(), *nw.workspace.pk())).await;
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let status = api_request_auth_status(
+        app,
+        Method::GET,
+        "/api/change_set/list_open_change_sets",
+        &auth_token,
+        &serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+}
+
+#[sdf_test]
+async fn owner_manages_member_roles(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+    nw: WorkspaceSignup,
+) {
+    let (editor, editor_auth_token) =
+        member_auth_token(&ctx, *nw.workspace.pk(), WorkspaceRole::Editor).await;
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let request = UpdateMemberRoleRequest {
+        user_pk: editor.pk(),
+        role: WorkspaceRole::Applier,
+    };
+
+    // Only owners may change roles, so the editor can't promote themselves
+    let status = api_request_auth_status(
+        app.clone(),
+        Method::POST,
+        "/api/workspace/update_member_role",
+        &editor_auth_token,
+        &request,
+    )
+    .await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    let response: UpdateMemberRoleResponse = api_request_auth_json_body(
+        app.clone(),
+        Method::POST,
+        "/api/workspace/update_member_role",
+        auth_token,
+        &request,
+    )
+    .await;
+    let promoted = response
+        .members
+        .iter()
+        .find(|member| member.user.pk() == editor.pk())
+        .expect("editor is no longer a member");
+    assert_eq!(WorkspaceRole::Applier, promoted.role);
+
+    let response: ListMembersResponse = api_request_auth_empty(
+        app.clone(),
+        Method::GET,
+        "/api/workspace/list_members",
+        &editor_auth_token,
+    )
+    .await;
+    let mut roles: Vec<(UserPk, WorkspaceRole)> = response
+        .members
+        .iter()
+        .map(|member| (member.user.pk(), member.role))
+        .collect();
+    roles.sort_by_key(|(user_pk, _)| *user_pk);
+    let mut expected = vec![
+        (nw.user.pk(), WorkspaceRole::Owner),
+        (editor.pk(), WorkspaceRole::Applier),
+    ];
+    expected.sort_by_key(|(user_pk, _)| *user_pk);
+    assert_eq!(expected, roles);
+
+    // The workspace must keep an owner
+    let status = api_request_auth_status(
+        app,
+        Method::POST,
+        "/api/workspace/update_member_role",
+        auth_token,
+        &UpdateMemberRoleRequest {
+            user_pk: nw.user.pk(),
+            role: WorkspaceRole::Viewer,
+        },
+    )
+    .await;
+    assert_eq!(StatusCode::CONFLICT, status);
+}
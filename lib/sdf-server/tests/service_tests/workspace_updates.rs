@@ -0,0 +1,264 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use axum::Router;
+use dal::{ChangeSetPk, DalContext, Visibility, WorkspaceSignup, WsEvent, WsPayload};
+use dal_test::{sdf_test, AuthTokenRef, DalContextHead};
+use pretty_assertions_sorted::assert_eq;
+use sdf_client::{types, SdfClient};
+use sdf_server::service::ws::{
+    event_filter::{ClientMessage, EventFilter},
+    event_log::{
+        EventLogSubscription, LoggedEvent, Resume, WorkspaceEventLogs,
+        DEFAULT_EVENT_LOG_IDLE_TIMEOUT,
+    },
+};
+
+use crate::service_tests::sdf_client::serve;
+
+async fn next_event(subscription: &mut EventLogSubscription) -> WsEvent {
+    let event = tokio::time::timeout(Duration::from_secs(10), subscription.recv())
+        .await
+        .expect("timed out waiting for event")
+        .expect("event log closed");
+    serde_json::from_str(&event.message).expect("message is not a ws event")
+}
+
+fn parse(events: Vec<Arc<LoggedEvent>>) -> Vec<WsEvent> {
+    events
+        .iter()
+        .map(|event| serde_json::from_str(&event.message).expect("message is not a ws event"))
+        .collect()
+}
+
+#[sdf_test]
+async fn resume_workspace_updates(DalContextHead(ctx): DalContextHead, nw: WorkspaceSignup) {
+    let workspace_pk = *nw.workspace.pk();
+    let event_logs = WorkspaceEventLogs::new(
+        ctx.services_context().nats_conn().clone(),
+        2,
+        DEFAULT_EVENT_LOG_IDLE_TIMEOUT,
+    );
+
+    let mut live = event_logs
+        .subscribe(workspace_pk, &Resume::default())
+        .await
+        .expect("cannot subscribe");
+    assert!(live.take_backlog().is_empty());
+
+    for _ in 0..3 {
+        WsEvent::new(&ctx, WsPayload::ChangeSetCreated(ChangeSetPk::generate()))
+            .await
+            .expect("cannot create event")
+            .publish_on_commit(&ctx)
+            .await
+            .expect("cannot publish event");
+    }
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let mut events = Vec::new();
+    for _ in 0..3 {
+        events.push(next_event(&mut live).await);
+    }
+    assert_eq!(
+        vec![Some(1), Some(2), Some(3)],
+        events.iter().map(WsEvent::sequence).collect::<Vec<_>>()
+    );
+    let stream_id = events[0].stream_id().expect("no stream id").to_owned();
+
+    // Only the last two events are kept
+    let mut resumed = event_logs
+        .subscribe(
+            workspace_pk,
+            &Resume {
+                stream_id: Some(stream_id.clone()),
+                resume_from: Some(1),
+            },
+        )
+        .await
+        .expect("cannot subscribe");
+    assert_eq!(events[1..].to_vec(), parse(resumed.take_backlog()));
+
+    let mut up_to_date = event_logs
+        .subscribe(
+            workspace_pk,
+            &Resume {
+                stream_id: Some(stream_id.clone()),
+                resume_from: Some(3),
+            },
+        )
+        .await
+        .expect("cannot subscribe");
+    assert!(up_to_date.take_backlog().is_empty());
+
+    for resume in [
+        Resume {
+            stream_id: Some(stream_id.clone()),
+            resume_from: Some(0),
+        },
+        Resume {
+            stream_id: Some("another stream".to_owned()),
+            resume_from: Some(2),
+        },
+        Resume {
+            stream_id: Some(stream_id.clone()),
+            resume_from: Some(4),
+        },
+    ] {
+        let mut subscription = event_logs
+            .subscribe(workspace_pk, &resume)
+            .await
+            .expect("cannot subscribe");
+        let backlog = parse(subscription.take_backlog());
+        assert_eq!(1, backlog.len(), "{resume:?}");
+        assert_eq!(
+            &WsPayload::ResyncRequired(dal::ResyncRequiredPayload {
+                stream_id: stream_id.clone(),
+                latest_sequence: Some(3),
+            }),
+            backlog[0].payload(),
+            "{resume:?}"
+        );
+    }
+}
+
+#[sdf_test]
+async fn idle_event_logs_are_evicted(DalContextHead(ctx): DalContextHead, nw: WorkspaceSignup) {
+    let workspace_pk = *nw.workspace.pk();
+    let idle_timeout = Duration::from_millis(100);
+    let event_logs =
+        WorkspaceEventLogs::new(ctx.services_context().nats_conn().clone(), 2, idle_timeout);
+
+    let mut subscription = event_logs
+        .subscribe(workspace_pk, &Resume::default())
+        .await
+        .expect("cannot subscribe");
+    WsEvent::new(&ctx, WsPayload::ChangeSetCreated(ChangeSetPk::generate()))
+        .await
+        .expect("cannot create event")
+        .publish_on_commit(&ctx)
+        .await
+        .expect("cannot publish event");
+    ctx.commit().await.expect("cannot commit transaction");
+    let event = next_event(&mut subscription).await;
+    let stream_id = event.stream_id().expect("no stream id").to_owned();
+
+    // A log with a subscriber is kept however long it is idle
+    tokio::time::sleep(idle_timeout * 5).await;
+    assert!(event_logs.is_logging(workspace_pk).await);
+
+    drop(subscription);
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while event_logs.is_logging(workspace_pk).await {
+            tokio::time::sleep(idle_timeout).await;
+        }
+    })
+    .await
+    .expect("timed out waiting for the event log to be evicted");
+
+    // Resuming from an evicted log requires a resync, from a new log
+    let mut subscription = event_logs
+        .subscribe(
+            workspace_pk,
+            &Resume {
+                stream_id: Some(stream_id.clone()),
+                resume_from: event.sequence(),
+            },
+        )
+        .await
+        .expect("cannot subscribe");
+    let backlog = parse(subscription.take_backlog());
+    assert_eq!(1, backlog.len());
+    match backlog[0].payload() {
+        WsPayload::ResyncRequired(payload) => {
+            assert_ne!(stream_id, payload.stream_id);
+            assert_eq!(None, payload.latest_sequence);
+        }
+        payload => panic!("unexpected payload: {payload:?}"),
+    }
+    assert!(event_logs.is_logging(workspace_pk).await);
+}
+
+async fn publish_in_change_set(ctx: &DalContext, change_set_pk: ChangeSetPk) {
+    let ctx = ctx.clone_with_new_visibility(Visibility::new(change_set_pk, None));
+    WsEvent::new(&ctx, WsPayload::ChangeSetWritten(change_set_pk))
+        .await
+        .expect("cannot create event")
+        .publish_on_commit(&ctx)
+        .await
+        .expect("cannot publish event");
+}
+
+#[sdf_test]
+async fn subscribe_filters_out_other_change_sets(
+    DalContextHead(ctx): DalContextHead,
+    app: Router,
+    AuthTokenRef(auth_token): AuthTokenRef<'_>,
+) {
+    ctx.commit().await.expect("cannot commit transaction");
+    let client = SdfClient::new(serve(app), auth_token);
+    let mut updates = client
+        .workspace_updates(types::Resume::default())
+        .await
+        .expect("cannot connect to workspace updates");
+
+    let watched = ChangeSetPk::generate();
+    let watched_client_pk: types::ChangeSetPk = watched
+        .to_string()
+        .parse()
+        .expect("cannot parse change set pk");
+    updates
+        .subscribe(types::EventFilter {
+            change_set_pks: Some(HashSet::from([watched_client_pk])),
+            payload_kinds: None,
+        })
+        .await
+        .expect("cannot subscribe");
+
+    // The event of the other change set is logged first, so it would be received first if it
+    // wasn't filtered out
+    publish_in_change_set(&ctx, ChangeSetPk::generate()).await;
+    publish_in_change_set(&ctx, watched).await;
+    ctx.commit().await.expect("cannot commit transaction");
+
+    let payload = tokio::time::timeout(Duration::from_secs(10), updates.next_payload())
+        .await
+        .expect("timed out waiting for event")
+        .expect("cannot receive event")
+        .expect("workspace updates closed");
+    assert_eq!(
+        types::WsPayload::ChangeSetWritten(watched_client_pk),
+        payload
+    );
+
+    updates
+        .close()
+        .await
+        .expect("cannot close workspace updates");
+}
+
+#[test]
+fn event_filter_matches_change_sets_and_payload_kinds() {
+    let change_set_pk = ChangeSetPk::generate();
+    let message: ClientMessage = serde_json::from_value(serde_json::json!({
+        "kind": "Subscribe",
+        "changeSetPks": [change_set_pk],
+        "payloadKinds": ["StatusUpdate", "FixReturn"],
+    }))
+    .expect("cannot parse subscribe message");
+    let ClientMessage::Subscribe(filter) = message;
+
+    let event = |change_set_pk: ChangeSetPk, payload_kind: &str| LoggedEvent {
+        change_set_pk,
+        payload_kind: payload_kind.to_owned(),
+        message: String::new(),
+    };
+    assert!(filter.matches(&event(change_set_pk, "StatusUpdate")));
+    assert!(filter.matches(&event(change_set_pk, "FixReturn")));
+    assert!(!filter.matches(&event(change_set_pk, "LogLine")));
+    assert!(!filter.matches(&event(ChangeSetPk::generate(), "StatusUpdate")));
+    assert!(!filter.matches(&event(ChangeSetPk::NONE, "StatusUpdate")));
+    // Resyncing matters whatever the client is interested in
+    assert!(filter.matches(&event(ChangeSetPk::NONE, "ResyncRequired")));
+
+    assert!(EventFilter::default().matches(&event(ChangeSetPk::generate(), "LogLine")));
+}
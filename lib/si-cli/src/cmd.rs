@@ -1,11 +1,16 @@
+mod backup;
 mod check;
 mod configure;
 mod delete;
+mod export;
 mod install;
 mod launch;
 mod report;
 mod restart;
+mod restore;
 mod start;
 mod status;
 mod stop;
 mod update;
+
+pub use export::ExportFormat;
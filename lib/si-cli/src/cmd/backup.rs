@@ -0,0 +1,172 @@
+use crate::key_management::{get_si_data_dir, get_user_email};
+use crate::state::AppState;
+use crate::{CliResult, SiCliError};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever the layout of the archive changes.
+pub(crate) const BACKUP_FORMAT_VERSION: u32 = 1;
+pub(crate) const MANIFEST_ENTRY: &str = "manifest.json";
+pub(crate) const POSTGRES_ENTRY: &str = "postgres.sql";
+pub(crate) const DATA_DIR_ENTRY: &str = "data";
+pub(crate) const POSTGRES_SERVICE: &str = "postgres";
+
+/// The files of the data dir that a stack can't be rebuilt without: the symmetric key, the
+/// cyclone key pair, the jwt signing key and the credentials configured with `si configure`.
+pub(crate) const DATA_DIR_FILES: &[&str] = &[
+    "donkey.key",
+    "cyclone_encryption.key",
+    "decryption.key",
+    "jwt_signing_public_key.pem",
+    "si_credentials.toml",
+];
+
+const CONTAINER_DUMP_PATH: &str = "/tmp/si-backup.sql";
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackupManifest {
+    pub format_version: u32,
+    pub si_version: String,
+    pub container_engine: String,
+    pub created_at: u64,
+}
+
+impl AppState {
+    pub async fn backup(&self, output: Option<PathBuf>) -> CliResult<()> {
+        self.track(
+            get_user_email().await?,
+            serde_json::json!({"command-name": "backup"}),
+        );
+        invoke(self, self.is_preview(), output).await?;
+        Ok(())
+    }
+}
+
+async fn invoke(app: &AppState, is_preview: bool, output: Option<PathBuf>) -> CliResult<()> {
+    app.check(true).await?;
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("si-backup-{created_at}.tar.gz")));
+
+    if is_preview {
+        println!("Backed up the postgres database and the following files:");
+        for file in DATA_DIR_FILES {
+            println!("{file}");
+        }
+        println!("to {}", output.display());
+        return Ok(());
+    }
+
+    let postgres_id = running_container_id(app, &app.container_name(POSTGRES_SERVICE)).await?;
+
+    println!("Dumping the postgres database...");
+    app.container_engine()
+        .exec_in_container(
+            postgres_id.clone(),
+            vec![
+                "pg_dumpall".to_owned(),
+                "--username=si".to_owned(),
+                "--clean".to_owned(),
+                "--if-exists".to_owned(),
+                format!("--file={CONTAINER_DUMP_PATH}"),
+            ],
+        )
+        .await?;
+    let dump = app
+        .container_engine()
+        .copy_from_container(postgres_id.clone(), CONTAINER_DUMP_PATH.to_owned())
+        .await;
+    app.container_engine()
+        .exec_in_container(
+            postgres_id,
+            vec![
+                "rm".to_owned(),
+                "-f".to_owned(),
+                CONTAINER_DUMP_PATH.to_owned(),
+            ],
+        )
+        .await?;
+    let dump = dump?;
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        si_version: app.version().to_owned(),
+        container_engine: app.container_engine().get_engine_identifier(),
+        created_at,
+    };
+
+    let si_data_dir = get_si_data_dir().await?;
+    write_archive(&output, &manifest, &dump, &si_data_dir)?;
+
+    println!("Backup written to {}", output.display());
+    Ok(())
+}
+
+fn write_archive(
+    output: &Path,
+    manifest: &BackupManifest,
+    dump: &[u8],
+    si_data_dir: &Path,
+) -> CliResult<()> {
+    // The archive holds keys and credentials, keep it readable by its owner only
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let file = options.open(output)?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    append_bytes(
+        &mut archive,
+        MANIFEST_ENTRY,
+        &serde_json::to_vec_pretty(manifest)?,
+    )?;
+    append_bytes(&mut archive, POSTGRES_ENTRY, dump)?;
+    for file in DATA_DIR_FILES {
+        let path = si_data_dir.join(file);
+        if path.exists() {
+            append_bytes(
+                &mut archive,
+                &format!("{DATA_DIR_ENTRY}/{file}"),
+                &fs::read(path)?,
+            )?;
+        }
+    }
+
+    archive.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn append_bytes<W: std::io::Write>(
+    archive: &mut tar::Builder<W>,
+    path: &str,
+    contents: &[u8],
+) -> CliResult<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o600);
+    header.set_cksum();
+    archive.append_data(&mut header, path, contents)?;
+    Ok(())
+}
+
+pub(crate) async fn running_container_id(app: &AppState, name: &str) -> CliResult<String> {
+    match app
+        .container_engine()
+        .get_existing_container(name.to_owned())
+        .await?
+    {
+        Some(container) if container.state.as_deref() == Some("running") => container
+            .id
+            .ok_or_else(|| SiCliError::ContainerNotRunning(name.to_owned())),
+        _ => Err(SiCliError::ContainerNotRunning(name.to_owned())),
+    }
+}
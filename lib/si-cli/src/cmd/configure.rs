@@ -2,8 +2,10 @@ use crate::key_management::{
     does_credentials_file_exist, get_credentials, get_si_data_dir, get_user_email,
     write_veritech_credentials,
 };
+use crate::profile::Profile;
 use crate::{state::AppState, CliResult, SiCliError};
-use inquire::{Password, PasswordDisplayMode, Text};
+use inquire::{CustomType, Password, PasswordDisplayMode, Text};
+use std::path::PathBuf;
 
 impl AppState {
     pub async fn configure(&self, reconfigure: bool) -> CliResult<()> {
@@ -14,6 +16,61 @@ impl AppState {
         invoke(self.is_preview(), reconfigure).await?;
         Ok(())
     }
+
+    /// Writes the profile in use, after prompting for the settings that have to differ between
+    /// stacks running side by side, to `path` or to the default profile location.
+    pub async fn write_profile(&self, path: Option<PathBuf>) -> CliResult<()> {
+        self.track(
+            get_user_email().await?,
+            serde_json::json!({"command-name": "configure-profile"}),
+        );
+        invoke_write_profile(self.profile().clone(), self.is_preview(), path).await?;
+        Ok(())
+    }
+}
+
+async fn invoke_write_profile(
+    mut profile: Profile,
+    is_preview: bool,
+    path: Option<PathBuf>,
+) -> CliResult<()> {
+    let path = match path {
+        Some(path) => path,
+        None => Profile::default_path()?,
+    };
+
+    match Text::new("Stack name")
+        .with_default(&profile.stack)
+        .with_help_message("Prefixes the name of every container of the stack")
+        .prompt()
+    {
+        Ok(stack) => profile.stack = stack,
+        Err(inquire::InquireError::OperationInterrupted) => return Err(SiCliError::CtrlC),
+        Err(_) => println!("Keeping the stack name {}", profile.stack),
+    }
+
+    for service in ["web", "sdf", "jaeger"] {
+        let (_, current_port) = profile.published_address(service);
+        match CustomType::<u32>::new(&format!("Host port for {service}"))
+            .with_default(current_port)
+            .prompt()
+        {
+            Ok(port) => profile.service_mut(service).host_port = Some(port),
+            Err(inquire::InquireError::OperationInterrupted) => return Err(SiCliError::CtrlC),
+            Err(_) => println!("Keeping port {current_port} for {service}"),
+        }
+    }
+
+    if is_preview {
+        println!("Wrote the profile to {}", path.display());
+        return Ok(());
+    }
+
+    profile.write(&path)?;
+    println!("Profile written to {}", path.display());
+    println!("Images, resource limits and environment of each service can be set in it as well\n");
+
+    Ok(())
 }
 
 async fn invoke(_is_preview: bool, reconfigure: bool) -> CliResult<()> {
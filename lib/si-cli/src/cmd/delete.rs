@@ -22,7 +22,7 @@ async fn invoke(app: &AppState, is_preview: bool, keep_images: bool) -> CliResul
     }
 
     for name in CONTAINER_NAMES.iter() {
-        let container_name = format!("local-{0}-1", name);
+        let container_name = app.container_name(name);
         if is_preview {
             println!("{}", container_name);
             continue;
@@ -0,0 +1,337 @@
+use crate::engine::{ContainerSpec, ServiceWiring};
+use crate::key_management::{format_credentials_for_veritech, get_si_data_dir, get_user_email};
+use crate::state::AppState;
+use crate::{CliResult, CONTAINER_NAMES};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+/// The files of the data dir the services mount, shipped to Kubernetes as a secret.
+const KEY_FILES: &[&str] = &[
+    "cyclone_encryption.key",
+    "decryption.key",
+    "donkey.key",
+    "jwt_signing_public_key.pem",
+];
+
+const KEYS_SECRET: &str = "si-keys";
+const CREDENTIALS_SECRET: &str = "si-credentials";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Compose,
+    Kubernetes,
+}
+
+impl ExportFormat {
+    fn default_output(&self) -> &'static str {
+        match self {
+            Self::Compose => "docker-compose.yml",
+            Self::Kubernetes => "si-kubernetes.yml",
+        }
+    }
+}
+
+impl AppState {
+    pub async fn export(&self, format: ExportFormat, output: Option<PathBuf>) -> CliResult<()> {
+        self.track(
+            get_user_email().await?,
+            serde_json::json!({"command-name": "export"}),
+        );
+        invoke(self, self.is_preview(), format, output).await?;
+        Ok(())
+    }
+}
+
+async fn invoke(
+    app: &AppState,
+    is_preview: bool,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+) -> CliResult<()> {
+    let output = output.unwrap_or_else(|| PathBuf::from(format.default_output()));
+    let si_data_dir = get_si_data_dir().await?;
+    let credentials = format_credentials_for_veritech().await?;
+
+    let services: Vec<(ContainerSpec, ServiceWiring)> = CONTAINER_NAMES
+        .iter()
+        .map(|name| {
+            (
+                app.container_spec(name),
+                app.service_wiring(name, &si_data_dir, &credentials),
+            )
+        })
+        .collect();
+    let stack = &app.profile().stack;
+
+    let documents = match format {
+        ExportFormat::Compose => vec![compose(stack, &services)],
+        ExportFormat::Kubernetes => kubernetes(stack, &services),
+    };
+
+    if is_preview {
+        println!("Exported the stack to {}", output.display());
+        return Ok(());
+    }
+
+    let mut contents = String::new();
+    for (index, document) in documents.iter().enumerate() {
+        if index > 0 {
+            contents.push_str("---\n");
+        }
+        contents.push_str(&serde_yaml::to_string(document)?);
+    }
+    // Keys and credentials are only ever referenced, never written into the manifests
+    fs::write(&output, contents)?;
+
+    println!("Stack exported to {}", output.display());
+    match format {
+        ExportFormat::Compose => {
+            println!(
+                "\nThe credentials configured with `si configure` are read from the environment \
+                when running `docker compose up`."
+            );
+        }
+        ExportFormat::Kubernetes => {
+            let namespace = namespace(stack);
+            println!("\nThe manifests reference two secrets that have to be created first:");
+            println!("kubectl create namespace {namespace}");
+            println!(
+                "kubectl --namespace {namespace} create secret generic {} {}",
+                KEYS_SECRET,
+                KEY_FILES
+                    .iter()
+                    .map(|file| format!("--from-file={}", si_data_dir.join(file).display()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+            println!(
+                "kubectl --namespace {namespace} create secret generic {} \
+                --from-env-file=<file with the credentials configured with `si configure`>",
+                CREDENTIALS_SECRET
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn compose(stack: &str, services: &[(ContainerSpec, ServiceWiring)]) -> Value {
+    let mut compose_services = serde_json::Map::new();
+    for (spec, wiring) in services {
+        let mut service = serde_json::Map::new();
+        service.insert("image".to_owned(), json!(spec.image));
+        if !wiring.depends_on.is_empty() {
+            service.insert("depends_on".to_owned(), json!(wiring.depends_on));
+        }
+        if !wiring.command.is_empty() {
+            service.insert("command".to_owned(), json!(wiring.command));
+        }
+
+        let environment: serde_json::Map<String, Value> = spec
+            .env(wiring.env.clone())
+            .into_iter()
+            .map(|(key, value)| {
+                // Credentials are interpolated by compose instead of being written down
+                if wiring.credentials.contains(&key) {
+                    let reference = format!("${{{key}}}");
+                    (key, json!(reference))
+                } else {
+                    (key, json!(value))
+                }
+            })
+            .collect();
+        if !environment.is_empty() {
+            service.insert("environment".to_owned(), Value::Object(environment));
+        }
+
+        if let Some(container_port) = wiring.published_port {
+            let host_port = spec.host_port.unwrap_or(container_port as u32);
+            let port = match &spec.host_ip {
+                Some(host_ip) => format!("{host_ip}:{host_port}:{container_port}"),
+                None => format!("{host_port}:{container_port}"),
+            };
+            service.insert("ports".to_owned(), json!([port]));
+        }
+
+        if !wiring.mounts.is_empty() {
+            let volumes: Vec<String> = wiring
+                .mounts
+                .iter()
+                .map(|mount| format!("{}:{}:z", wiring.source(mount).display(), mount.destination))
+                .collect();
+            service.insert("volumes".to_owned(), json!(volumes));
+        }
+
+        if let Some(limits) = resource_limits(spec, "cpus", |mb| format!("{mb}M")) {
+            service.insert(
+                "deploy".to_owned(),
+                json!({ "resources": { "limits": limits } }),
+            );
+        }
+
+        compose_services.insert(wiring.service.to_owned(), Value::Object(service));
+    }
+
+    // Compose names the containers `<project>-<service>-1`, just like `si start` does
+    json!({
+        "name": compose_project_name(stack),
+        "services": compose_services,
+    })
+}
+
+fn kubernetes(stack: &str, services: &[(ContainerSpec, ServiceWiring)]) -> Vec<Value> {
+    let namespace = namespace(stack);
+    let mut documents = vec![json!({
+        "apiVersion": "v1",
+        "kind": "Namespace",
+        "metadata": { "name": namespace },
+    })];
+
+    for (spec, wiring) in services {
+        let labels = json!({
+            "app.kubernetes.io/name": wiring.service,
+            "app.kubernetes.io/instance": stack,
+            "app.kubernetes.io/part-of": "system-initiative",
+        });
+
+        let env: Vec<Value> = spec
+            .env(wiring.env.clone())
+            .into_iter()
+            .map(|(key, value)| {
+                if wiring.credentials.contains(&key) {
+                    let secret_key_ref = json!({ "name": CREDENTIALS_SECRET, "key": key });
+                    json!({ "name": key, "valueFrom": { "secretKeyRef": secret_key_ref } })
+                } else {
+                    json!({ "name": key, "value": value })
+                }
+            })
+            .collect();
+
+        let mut container = serde_json::Map::new();
+        container.insert("name".to_owned(), json!(wiring.service));
+        container.insert("image".to_owned(), json!(spec.image));
+        // The command of a container overrides the arguments of the image's entrypoint
+        if !wiring.command.is_empty() {
+            container.insert("args".to_owned(), json!(wiring.command));
+        }
+        if !env.is_empty() {
+            container.insert("env".to_owned(), json!(env));
+        }
+        if !wiring.ports.is_empty() {
+            let ports: Vec<Value> = wiring
+                .ports
+                .iter()
+                .map(|port| json!({ "containerPort": port }))
+                .collect();
+            container.insert("ports".to_owned(), json!(ports));
+        }
+        if !wiring.mounts.is_empty() {
+            let mounts: Vec<Value> = wiring
+                .mounts
+                .iter()
+                .map(|mount| match mount.file {
+                    Some(file) => json!({
+                        "name": KEYS_SECRET,
+                        "mountPath": mount.destination,
+                        "subPath": file,
+                        "readOnly": true,
+                    }),
+                    None => json!({
+                        "name": KEYS_SECRET,
+                        "mountPath": mount.destination,
+                        "readOnly": true,
+                    }),
+                })
+                .collect();
+            container.insert("volumeMounts".to_owned(), json!(mounts));
+        }
+        if let Some(limits) = resource_limits(spec, "cpu", |mb| format!("{mb}Mi")) {
+            container.insert("resources".to_owned(), json!({ "limits": limits }));
+        }
+
+        let mut pod_spec = serde_json::Map::new();
+        pod_spec.insert("containers".to_owned(), json!([container]));
+        if !wiring.mounts.is_empty() {
+            pod_spec.insert(
+                "volumes".to_owned(),
+                json!([{ "name": KEYS_SECRET, "secret": { "secretName": KEYS_SECRET } }]),
+            );
+        }
+
+        documents.push(json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": wiring.service, "namespace": namespace, "labels": labels },
+            "spec": {
+                "replicas": 1,
+                "selector": { "matchLabels": labels },
+                "template": {
+                    "metadata": { "labels": labels },
+                    "spec": pod_spec,
+                },
+            },
+        }));
+
+        // The services reach each other by name, which a Service of the same name provides
+        if !wiring.ports.is_empty() {
+            let ports: Vec<Value> = wiring
+                .ports
+                .iter()
+                .map(|port| {
+                    json!({ "name": format!("tcp-{port}"), "port": port, "targetPort": port })
+                })
+                .collect();
+            documents.push(json!({
+                "apiVersion": "v1",
+                "kind": "Service",
+                "metadata": { "name": wiring.service, "namespace": namespace, "labels": labels },
+                "spec": { "selector": labels, "ports": ports },
+            }));
+        }
+    }
+
+    documents
+}
+
+/// Compose and Kubernetes only differ in the name of the cpu limit and the unit of the memory one.
+fn resource_limits(
+    spec: &ContainerSpec,
+    cpu_key: &str,
+    memory: impl Fn(u64) -> String,
+) -> Option<Value> {
+    if spec.memory_limit_mb.is_none() && spec.cpus.is_none() {
+        return None;
+    }
+    let mut limits = serde_json::Map::new();
+    if let Some(cpus) = spec.cpus {
+        limits.insert(cpu_key.to_owned(), json!(cpus.to_string()));
+    }
+    if let Some(mb) = spec.memory_limit_mb {
+        limits.insert("memory".to_owned(), json!(memory(mb)));
+    }
+    Some(Value::Object(limits))
+}
+
+/// Compose project names only allow lowercase letters, digits, `-` and `_`.
+fn compose_project_name(stack: &str) -> String {
+    stack
+        .chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            c @ ('a'..='z' | '0'..='9' | '-' | '_') => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+/// Namespaces are DNS labels: lowercase letters, digits and `-`.
+fn namespace(stack: &str) -> String {
+    let stack: String = stack
+        .chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            c @ ('a'..='z' | '0'..='9') => c,
+            _ => '-',
+        })
+        .collect();
+    format!("si-{}", stack.trim_matches('-'))
+}
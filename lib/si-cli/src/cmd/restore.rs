@@ -0,0 +1,206 @@
+use crate::cmd::backup::{
+    running_container_id, BackupManifest, BACKUP_FORMAT_VERSION, DATA_DIR_ENTRY, DATA_DIR_FILES,
+    MANIFEST_ENTRY, POSTGRES_ENTRY, POSTGRES_SERVICE,
+};
+use crate::key_management::{get_si_data_dir, get_user_email};
+use crate::state::AppState;
+use crate::{CliResult, SiCliError, CONTAINER_NAMES};
+use colored::Colorize;
+use flate2::read::GzDecoder;
+use inquire::Confirm;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+const CONTAINER_RESTORE_PATH: &str = "/tmp/si-restore.sql";
+const POSTGRES_READY_ATTEMPTS: usize = 30;
+
+struct Backup {
+    manifest: BackupManifest,
+    dump: Vec<u8>,
+    data_dir_files: HashMap<String, Vec<u8>>,
+}
+
+impl AppState {
+    pub async fn restore(&self, archive: &Path, skip_confirmation: bool) -> CliResult<()> {
+        self.track(
+            get_user_email().await?,
+            serde_json::json!({"command-name": "restore"}),
+        );
+        invoke(self, self.is_preview(), archive, skip_confirmation).await?;
+        Ok(())
+    }
+}
+
+async fn invoke(
+    app: &AppState,
+    is_preview: bool,
+    archive: &Path,
+    skip_confirmation: bool,
+) -> CliResult<()> {
+    let backup = read_archive(archive)?;
+    println!(
+        "Backup created by si {} with {} at {}",
+        backup.manifest.si_version, backup.manifest.container_engine, backup.manifest.created_at
+    );
+
+    if is_preview {
+        println!("Restored the postgres database and the following files:");
+        for file in backup.data_dir_files.keys() {
+            println!("{file}");
+        }
+        return Ok(());
+    }
+
+    let ans = if skip_confirmation {
+        Ok(true)
+    } else {
+        println!(
+            "\n{}",
+            "Restoring will replace your data and keys with the ones in the backup!".red()
+        );
+        Confirm::new("Are you sure you want to restore this backup?")
+            .with_default(false)
+            .prompt()
+    };
+    match ans {
+        Ok(true) => {}
+        Err(inquire::InquireError::OperationInterrupted) => return Err(SiCliError::CtrlC),
+        Ok(false) | Err(_) => {
+            println!("Restore aborted");
+            return Ok(());
+        }
+    }
+
+    // The keys have to be in place before the services that mount the data dir start
+    let si_data_dir = get_si_data_dir().await?;
+    for (file, contents) in &backup.data_dir_files {
+        fs::write(si_data_dir.join(file), contents)?;
+    }
+
+    app.start().await?;
+
+    // Nothing but postgres may hold a connection while the databases are recreated
+    for name in CONTAINER_NAMES.iter().rev() {
+        if *name == POSTGRES_SERVICE {
+            continue;
+        }
+        let container_name = app.container_name(name);
+        if let Some(existing) = app
+            .container_engine()
+            .get_existing_container(container_name)
+            .await?
+        {
+            if let Some(id) = existing.id {
+                app.container_engine().stop_container(id).await?;
+            }
+        }
+    }
+
+    let postgres_id = running_container_id(app, &app.container_name(POSTGRES_SERVICE)).await?;
+    wait_for_postgres(app, &postgres_id).await?;
+
+    println!("Restoring the postgres database...");
+    app.container_engine()
+        .copy_into_container(
+            postgres_id.clone(),
+            CONTAINER_RESTORE_PATH.to_owned(),
+            &backup.dump,
+        )
+        .await?;
+    let restored = app
+        .container_engine()
+        .exec_in_container(
+            postgres_id.clone(),
+            vec![
+                "psql".to_owned(),
+                "--username=si".to_owned(),
+                "--dbname=postgres".to_owned(),
+                "--quiet".to_owned(),
+                format!("--file={CONTAINER_RESTORE_PATH}"),
+            ],
+        )
+        .await;
+    app.container_engine()
+        .exec_in_container(
+            postgres_id,
+            vec![
+                "rm".to_owned(),
+                "-f".to_owned(),
+                CONTAINER_RESTORE_PATH.to_owned(),
+            ],
+        )
+        .await?;
+    restored?;
+
+    app.start().await?;
+
+    println!("Backup restored from {}", archive.display());
+    Ok(())
+}
+
+async fn wait_for_postgres(app: &AppState, postgres_id: &str) -> CliResult<()> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match app
+            .container_engine()
+            .exec_in_container(
+                postgres_id.to_owned(),
+                vec!["pg_isready".to_owned(), "--username=si".to_owned()],
+            )
+            .await
+        {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt >= POSTGRES_READY_ATTEMPTS => return Err(err),
+            Err(_) => tokio::time::sleep(Duration::from_secs(1)).await,
+        }
+    }
+}
+
+fn read_archive(path: &Path) -> CliResult<Backup> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+
+    let mut manifest = None;
+    let mut dump = None;
+    let mut data_dir_files = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.to_string_lossy().into_owned();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+
+        if entry_path == MANIFEST_ENTRY {
+            manifest = Some(serde_json::from_slice::<BackupManifest>(&contents)?);
+        } else if entry_path == POSTGRES_ENTRY {
+            dump = Some(contents);
+        } else if let Some(file) = entry_path
+            .strip_prefix(DATA_DIR_ENTRY)
+            .and_then(|file| file.strip_prefix('/'))
+        {
+            // Only the known files are restored so that an archive can't write anywhere else
+            if DATA_DIR_FILES.contains(&file) {
+                data_dir_files.insert(file.to_owned(), contents);
+            }
+        }
+    }
+
+    let manifest =
+        manifest.ok_or_else(|| SiCliError::InvalidBackup(format!("missing {MANIFEST_ENTRY}")))?;
+    if manifest.format_version != BACKUP_FORMAT_VERSION {
+        return Err(SiCliError::InvalidBackup(format!(
+            "unsupported format version {}",
+            manifest.format_version
+        )));
+    }
+    let dump =
+        dump.ok_or_else(|| SiCliError::InvalidBackup(format!("missing {POSTGRES_ENTRY}")))?;
+
+    Ok(Backup {
+        manifest,
+        dump,
+        data_dir_files,
+    })
+}
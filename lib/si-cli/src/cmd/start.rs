@@ -33,9 +33,10 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
     app.container_engine().create_network().await?;
 
     for name in CONTAINER_NAMES.iter() {
-        let container = format!("systeminit/{0}", name);
-        let container_name = format!("local-{0}-1", name);
-        if container == "systeminit/otelcol" {
+        let spec = app.container_spec(name);
+        let container = spec.image.clone();
+        let container_name = spec.name.clone();
+        if *name == "otelcol" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -55,24 +56,20 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
 
             app.container_engine()
-                .create_otelcol(container_name.clone(), container.clone())
+                .create_container(&spec, &app.service_wiring(name, &si_data_dir, &[]))
                 .await?;
         }
-        if container == "systeminit/jaeger" {
+        if *name == "jaeger" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -92,24 +89,20 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
 
             app.container_engine()
-                .create_jaeger(container_name.clone(), container.clone())
+                .create_container(&spec, &app.service_wiring(name, &si_data_dir, &[]))
                 .await?;
         }
-        if container == "systeminit/nats" {
+        if *name == "nats" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -129,24 +122,20 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
 
             app.container_engine()
-                .create_nats(container_name.clone(), container.clone())
+                .create_container(&spec, &app.service_wiring(name, &si_data_dir, &[]))
                 .await?;
         }
-        if container == "systeminit/postgres" {
+        if *name == "postgres" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -166,24 +155,20 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
 
             app.container_engine()
-                .create_postgres(container_name.clone(), container.clone())
+                .create_container(&spec, &app.service_wiring(name, &si_data_dir, &[]))
                 .await?;
         }
-        if container == "systeminit/council" {
+        if *name == "council" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -203,24 +188,20 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
 
             app.container_engine()
-                .create_council(container_name.clone(), container.clone())
+                .create_container(&spec, &app.service_wiring(name, &si_data_dir, &[]))
                 .await?;
         }
-        if container == "systeminit/veritech" {
+        if *name == "veritech" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -241,31 +222,24 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
-            let mut veritech_credentials = format_credentials_for_veritech().await?;
+            let veritech_credentials = format_credentials_for_veritech().await?;
 
             app.container_engine()
-                .create_veritech(
-                    container_name.clone(),
-                    container.clone(),
-                    veritech_credentials.as_mut(),
-                    si_data_dir.clone(),
-                    app.with_function_debug_logs(),
+                .create_container(
+                    &spec,
+                    &app.service_wiring(name, &si_data_dir, &veritech_credentials),
                 )
                 .await?;
         }
-        if container == "systeminit/pinga" {
+        if *name == "pinga" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -285,28 +259,20 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
 
             app.container_engine()
-                .create_pinga(
-                    container_name.clone(),
-                    container.clone(),
-                    si_data_dir.clone(),
-                )
+                .create_container(&spec, &app.service_wiring(name, &si_data_dir, &[]))
                 .await?;
         }
-        if container == "systeminit/sdf" {
+        if *name == "sdf" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -362,30 +328,20 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
 
             app.container_engine()
-                .create_sdf(
-                    container_name.clone(),
-                    container.clone(),
-                    app.sdf_host(),
-                    app.sdf_port(),
-                    si_data_dir.clone(),
-                )
+                .create_container(&spec, &app.service_wiring(name, &si_data_dir, &[]))
                 .await?;
         }
-        if container == "systeminit/web" {
+        if *name == "web" {
             let container_summary = app
                 .container_engine()
                 .get_existing_container(container_name.clone())
@@ -441,26 +397,17 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
             }
 
             if is_preview {
-                println!(
-                    "{0}:stable as {1}",
-                    container.clone(),
-                    container_name.clone()
-                );
+                println!("{0} as {1}", container.clone(), container_name.clone());
                 continue;
             }
             println!(
-                "Starting {0}:stable as {1}",
+                "Starting {0} as {1}",
                 container.clone(),
                 container_name.clone()
             );
 
             app.container_engine()
-                .create_web(
-                    container_name.clone(),
-                    container.clone(),
-                    app.web_host(),
-                    app.web_port(),
-                )
+                .create_container(&spec, &app.service_wiring(name, &si_data_dir, &[]))
                 .await?;
         }
     }
@@ -1,47 +1,102 @@
 use crate::key_management::get_user_email;
 use crate::state::AppState;
-use crate::{CliResult, CONTAINER_NAMES};
+use crate::{CliResult, SiCliError, CONTAINER_NAMES};
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::*;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 
 const RUNNING: &str = "    ✅    ";
 const NOT_RUNNING: &str = "    ❌    ";
 const WAITING: &str = "    🕒    ";
 
+/// sdf gives every one of its probes 15 seconds, so this leaves room for all of them.
+const DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(90);
+
 impl AppState {
-    pub async fn status(&self, show_logs: bool, log_lines: usize) -> CliResult<()> {
+    pub async fn status(
+        &self,
+        show_logs: bool,
+        log_lines: usize,
+        deep: bool,
+        json: bool,
+        api_token: Option<&str>,
+    ) -> CliResult<()> {
+        // sdf only runs its probes for a signed in member of a workspace
+        let api_token = match (deep, api_token) {
+            (true, None) => return Err(SiCliError::MissingApiToken),
+            (true, Some(api_token)) => Some(api_token),
+            (false, _) => None,
+        };
         self.track(
             get_user_email().await?,
-            serde_json::json!({"command-name": "system-status"}),
+            serde_json::json!({"command-name": "system-status", "deep": deep, "json": json}),
         );
-        invoke(self, show_logs, log_lines).await?;
+        invoke(self, show_logs, log_lines, api_token, json).await?;
         Ok(())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 struct Status {
     name: String,
     state: ContainerState,
     version: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 enum ContainerState {
     Running,
     NotRunning,
     Waiting,
 }
 
-async fn invoke(app: &AppState, show_logs: bool, log_lines: usize) -> CliResult<()> {
-    println!("Checking the status of System Initiative Software");
+/// The result of one of the probes of `si status --deep`, as reported by sdf's
+/// `/api/diagnostics` route.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticCheck {
+    name: String,
+    ok: bool,
+    latency_ms: u64,
+    detail: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiagnosticsResponse {
+    checks: Vec<DiagnosticCheck>,
+}
+
+/// What `si status --json` prints.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusReport {
+    ok: bool,
+    containers: Vec<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checks: Option<Vec<DiagnosticCheck>>,
+}
+
+async fn invoke(
+    app: &AppState,
+    show_logs: bool,
+    log_lines: usize,
+    api_token: Option<&str>,
+    json: bool,
+) -> CliResult<()> {
+    if !json {
+        println!("Checking the status of System Initiative Software");
+    }
 
     let mut container_status = Vec::new();
 
     let mut all_running = true;
     for name in CONTAINER_NAMES.iter() {
-        let image_name = format!("systeminit/{0}:stable", name);
-        let container_identifier = format!("local-{0}-1", name);
+        let spec = app.container_spec(name);
+        let image_name = spec.image;
+        let container_identifier = spec.name;
         let existing_container = app
             .container_engine()
             .get_existing_container(container_identifier.clone())
@@ -61,6 +116,8 @@ async fn invoke(app: &AppState, show_logs: bool, log_lines: usize) -> CliResult<
             } else {
                 all_running = false;
             }
+        } else {
+            all_running = false;
         }
 
         if show_logs {
@@ -70,7 +127,7 @@ async fn invoke(app: &AppState, show_logs: bool, log_lines: usize) -> CliResult<
                 .await?;
         }
 
-        if container_identifier == "local-web-1" {
+        if *name == "web" {
             let web_path = format!("http://{0}:{1}/", app.web_host(), app.web_port());
             let resp = reqwest::get(web_path).await;
             if resp.is_err() && state == ContainerState::Running {
@@ -78,14 +135,17 @@ async fn invoke(app: &AppState, show_logs: bool, log_lines: usize) -> CliResult<
             }
         }
 
-        if container_identifier == "local-sdf-1" {
-            let sdf_path = "http://localhost:5156/api/";
+        if *name == "sdf" {
+            let sdf_path = format!("http://{0}:{1}/api/", app.sdf_host(), app.sdf_port());
             let resp = reqwest::get(sdf_path).await;
             if resp.is_err() && state == ContainerState::Running {
                 state = ContainerState::Waiting;
             }
         }
 
+        if state == ContainerState::Waiting {
+            all_running = false;
+        }
         container_status.push(Status {
             name: image_name,
             state,
@@ -93,6 +153,94 @@ async fn invoke(app: &AppState, show_logs: bool, log_lines: usize) -> CliResult<
         })
     }
 
+    let checks = if let Some(api_token) = api_token {
+        if !json {
+            println!("Running end-to-end checks through sdf, this can take a little while...");
+        }
+        Some(diagnostics(app, api_token).await)
+    } else {
+        None
+    };
+    let failed_checks: Vec<&str> = checks
+        .iter()
+        .flatten()
+        .filter(|check| !check.ok)
+        .map(|check| check.name.as_str())
+        .collect();
+
+    if json {
+        let report = StatusReport {
+            ok: all_running && failed_checks.is_empty(),
+            containers: container_status,
+            checks,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        render(&container_status, checks.as_deref());
+        if all_running && failed_checks.is_empty() {
+            println!("\nAll system components working as expected...")
+        }
+    }
+
+    // Scripts rely on the exit code of the deep checks, the container states are only reported
+    if !failed_checks.is_empty() {
+        return Err(SiCliError::Unhealthy(failed_checks.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// Checks that sdf answers HTTP requests, then has it probe every service it depends on. When
+/// sdf can't be reached, none of the other services can be checked.
+async fn diagnostics(app: &AppState, api_token: &str) -> Vec<DiagnosticCheck> {
+    let sdf_url = format!("http://{0}:{1}/api", app.sdf_host(), app.sdf_port());
+    let client = reqwest::Client::new();
+
+    let start = Instant::now();
+    let readiness = client
+        .get(format!("{sdf_url}/"))
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status());
+    let mut checks = vec![DiagnosticCheck {
+        name: "sdf".to_owned(),
+        ok: readiness.is_ok(),
+        latency_ms: start.elapsed().as_millis() as u64,
+        detail: readiness.err().map(|err| err.to_string()),
+    }];
+    if !checks[0].ok {
+        return checks;
+    }
+
+    let diagnostics = async {
+        client
+            .get(format!("{sdf_url}/diagnostics"))
+            .bearer_auth(api_token)
+            .timeout(DIAGNOSTICS_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<DiagnosticsResponse>()
+            .await
+    };
+    match diagnostics.await {
+        Ok(response) => checks.extend(response.checks),
+        Err(err) => {
+            // An older sdf without the route is still reported, just without the probes
+            checks.push(DiagnosticCheck {
+                name: "diagnostics".to_owned(),
+                ok: false,
+                latency_ms: 0,
+                detail: Some(err.to_string()),
+            })
+        }
+    }
+
+    checks
+}
+
+fn render(container_status: &[Status], checks: Option<&[DiagnosticCheck]>) {
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
@@ -105,20 +253,37 @@ async fn invoke(app: &AppState, show_logs: bool, log_lines: usize) -> CliResult<
         ]);
     for container_status in container_status {
         table.add_row(vec![
-            Cell::new(container_status.name).add_attribute(Attribute::Bold),
+            Cell::new(&container_status.name).add_attribute(Attribute::Bold),
             Cell::new(match container_status.state {
                 ContainerState::Running => RUNNING,
                 ContainerState::NotRunning => NOT_RUNNING,
                 ContainerState::Waiting => WAITING,
             }),
-            Cell::new(container_status.version),
+            Cell::new(&container_status.version),
         ]);
     }
     println!("{table}");
 
-    if all_running {
-        println!("\nAll system components working as expected...")
+    if let Some(checks) = checks {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_width(100)
+            .set_header(vec![
+                Cell::new("Check").add_attribute(Attribute::Bold),
+                Cell::new("State").add_attribute(Attribute::Bold),
+                Cell::new("Latency").add_attribute(Attribute::Bold),
+                Cell::new("Detail").add_attribute(Attribute::Bold),
+            ]);
+        for check in checks {
+            table.add_row(vec![
+                Cell::new(&check.name).add_attribute(Attribute::Bold),
+                Cell::new(if check.ok { RUNNING } else { NOT_RUNNING }),
+                Cell::new(format!("{}ms", check.latency_ms)),
+                Cell::new(check.detail.as_deref().unwrap_or_default()),
+            ]);
+        }
+        println!("{table}");
     }
-
-    Ok(())
 }
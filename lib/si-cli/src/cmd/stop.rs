@@ -21,7 +21,7 @@ async fn invoke(app: &AppState, is_preview: bool) -> CliResult<()> {
     }
 
     for container_name in CONTAINER_NAMES.iter().rev() {
-        let container_identifier = format!("local-{0}-1", container_name);
+        let container_identifier = app.container_name(container_name);
         if is_preview {
             println!("{}", container_identifier.clone());
             continue;
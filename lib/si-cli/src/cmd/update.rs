@@ -198,7 +198,8 @@ async fn invoke(
             if !only_binary && !update.containers.is_empty() {
                 println!(
                     "\n{}",
-                    "Updating the containers will destroy your data!".red()
+                    "Updating the containers will destroy your data! Run `si backup` first to keep it."
+                        .red()
                 );
                 prompt.push_str(" the containers listed above");
             }
@@ -218,7 +219,7 @@ async fn invoke(
                 app.stop().await?;
 
                 for container in &update.containers {
-                    let container_name = format!("local-{0}-1", container.repository.clone());
+                    let container_name = app.container_name(&container.repository);
                     let container_summary = app
                         .container_engine()
                         .get_existing_container(container_name.clone())
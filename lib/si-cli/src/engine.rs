@@ -1,10 +1,13 @@
 use crate::{CliResult, SiCliError};
 use async_trait::async_trait;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
 
 pub mod docker_engine;
 pub mod podman_engine;
+pub mod wiring;
+
+pub use wiring::{DataDirMount, ServiceWiring};
 
 #[async_trait]
 pub trait ContainerEngine {
@@ -24,35 +27,62 @@ pub trait ContainerEngine {
     async fn delete_network(&self) -> CliResult<()>;
     async fn start_container(&self, id: String) -> CliResult<()>;
     async fn stop_container(&self, id: String) -> CliResult<()>;
-    async fn create_otelcol(&self, name: String, image: String) -> CliResult<()>;
-    async fn create_jaeger(&self, name: String, image: String) -> CliResult<()>;
-    async fn create_nats(&self, name: String, image: String) -> CliResult<()>;
-    async fn create_postgres(&self, name: String, image: String) -> CliResult<()>;
-    async fn create_council(&self, name: String, image: String) -> CliResult<()>;
-    async fn create_veritech(
-        &self,
-        name: String,
-        image: String,
-        credentials: &mut Vec<String>,
-        data_dir: PathBuf,
-        with_debug_logs: bool,
-    ) -> CliResult<()>;
-    async fn create_pinga(&self, name: String, image: String, data_dir: PathBuf) -> CliResult<()>;
-    async fn create_sdf(
-        &self,
-        name: String,
-        image: String,
-        host_ip: String,
-        host_port: u32,
-        data_dir: PathBuf,
-    ) -> CliResult<()>;
-    async fn create_web(
-        &self,
-        name: String,
-        image: String,
-        host_ip: String,
-        host_port: u32,
-    ) -> CliResult<()>;
+    /// Runs the command inside of a running container, failing if it exits unsuccessfully.
+    async fn exec_in_container(&self, id: String, command: Vec<String>) -> CliResult<()>;
+    /// Returns the contents of the file at `path` inside of the container.
+    async fn copy_from_container(&self, id: String, path: String) -> CliResult<Vec<u8>>;
+    async fn copy_into_container(&self, id: String, path: String, contents: &[u8])
+        -> CliResult<()>;
+    /// Creates and starts the container of a service.
+    async fn create_container(&self, spec: &ContainerSpec, wiring: &ServiceWiring)
+        -> CliResult<()>;
+}
+
+/// Everything needed to create the container of a service, as declared by its
+/// [`Profile`](crate::profile::Profile).
+#[derive(Clone, Debug)]
+pub struct ContainerSpec {
+    pub name: String,
+    /// The image reference, including its tag.
+    pub image: String,
+    pub host_ip: Option<String>,
+    pub host_port: Option<u32>,
+    pub env: BTreeMap<String, String>,
+    pub memory_limit_mb: Option<u64>,
+    pub cpus: Option<f64>,
+}
+
+impl ContainerSpec {
+    /// The environment of the container: the engine's `defaults` overridden by the extra
+    /// environment of the profile.
+    pub fn env<K, V>(&self, defaults: impl IntoIterator<Item = (K, V)>) -> BTreeMap<String, String>
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let mut env: BTreeMap<String, String> = defaults
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        env.extend(self.env.clone());
+        env
+    }
+
+    pub fn memory_limit_bytes(&self) -> Option<u64> {
+        self.memory_limit_mb.map(|mb| mb * 1024 * 1024)
+    }
+}
+
+/// Both engines copy files out of containers as a tar archive, this unpacks the single file in it.
+fn untar_single_file(archive: &[u8], path: &str) -> CliResult<Vec<u8>> {
+    let mut archive = tar::Archive::new(archive);
+    let mut entry = archive
+        .entries()?
+        .next()
+        .ok_or_else(|| SiCliError::ContainerFileNotFound(path.to_owned()))??;
+    let mut contents = Vec::new();
+    entry.read_to_end(&mut contents)?;
+    Ok(contents)
 }
 
 #[derive(Debug)]
@@ -1,24 +1,31 @@
-use crate::engine::{ContainerEngine, ContainerReleaseInfo, SiContainerSummary, SiImageSummary};
+use crate::engine::{
+    untar_single_file, ContainerEngine, ContainerReleaseInfo, ContainerSpec, ServiceWiring,
+    SiContainerSummary, SiImageSummary,
+};
+use crate::profile::container_name;
 use crate::{CliResult, SiCliError, CONTAINER_NAMES};
 use async_trait::async_trait;
 use color_eyre::eyre::eyre;
 use docker_api::opts::{
-    ContainerCreateOpts, ContainerFilter, ContainerListOpts, ContainerStopOpts, HostPort,
-    ImageListOpts, ImageRemoveOpts, LogsOpts, PublishPort, PullOpts,
+    ContainerCreateOpts, ContainerCreateOptsBuilder, ContainerFilter, ContainerListOpts,
+    ContainerStopOpts, ExecCreateOpts, ExecStartOpts, HostPort, ImageListOpts, ImageRemoveOpts,
+    LogsOpts, PublishPort, PullOpts,
 };
-use docker_api::Docker;
-use futures::StreamExt;
+use docker_api::{Docker, Exec};
+use futures::{StreamExt, TryStreamExt};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use std::cmp::min;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::Path;
 
 pub struct DockerEngine {
     docker: Docker,
+    stack: String,
 }
 
 impl DockerEngine {
     #[allow(clippy::new_ret_no_self)]
-    pub async fn new(sock: Option<String>) -> CliResult<Box<dyn ContainerEngine>> {
+    pub async fn new(sock: Option<String>, stack: &str) -> CliResult<Box<dyn ContainerEngine>> {
         let docker_sock = if let Some(sock) = sock {
             sock
         } else {
@@ -51,7 +58,36 @@ impl DockerEngine {
             docker = Docker::unix(path);
         }
 
-        Ok(Box::new(DockerEngine { docker }))
+        Ok(Box::new(DockerEngine {
+            docker,
+            stack: stack.to_owned(),
+        }))
+    }
+
+    /// Links the container of another service of the same stack under the service's name.
+    fn link(&self, service: &str) -> String {
+        format!("{}:{service}", container_name(&self.stack, service))
+    }
+
+    fn create_opts(spec: &ContainerSpec) -> ContainerCreateOptsBuilder {
+        let mut builder = ContainerCreateOpts::builder()
+            .name(spec.name.clone())
+            .image(spec.image.clone());
+        if let Some(memory) = spec.memory_limit_bytes() {
+            builder = builder.memory(memory);
+        }
+        if let Some(cpus) = spec.cpus {
+            builder = builder.nano_cpus((cpus * 1_000_000_000.0) as u64);
+        }
+        builder
+    }
+
+    fn host_port(spec: &ContainerSpec, default_port: u16) -> HostPort {
+        let port = spec.host_port.unwrap_or(default_port as u32);
+        match spec.host_ip.clone() {
+            Some(ip) => HostPort::with_ip(port, ip),
+            None => HostPort::new(port),
+        }
     }
 }
 
@@ -303,202 +339,105 @@ impl ContainerEngine for DockerEngine {
         Ok(())
     }
 
-    async fn create_network(&self) -> CliResult<()> {
-        Ok(())
-    }
-
-    async fn delete_network(&self) -> CliResult<()> {
-        Ok(())
-    }
-
-    async fn create_otelcol(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image))
-            .links(["local-jaeger-1:jaeger"])
+    async fn exec_in_container(&self, id: String, command: Vec<String>) -> CliResult<()> {
+        let create_opts = ExecCreateOpts::builder()
+            .command(command.clone())
+            .attach_stdout(true)
+            .attach_stderr(true)
             .build();
+        let exec = Exec::create(self.docker.clone(), id, &create_opts).await?;
 
-        let container = self.docker.containers().create(&create_opts).await?;
-        container.start().await?;
-        Ok(())
-    }
-
-    async fn create_jaeger(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name)
-            .image(format!("{0}:stable", image))
-            .expose(PublishPort::tcp(16686), HostPort::new(16686))
-            .build();
-
-        let container = self.docker.containers().create(&create_opts).await?;
-        container.start().await?;
-        Ok(())
-    }
-
-    async fn create_nats(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name)
-            .image(format!("{0}:stable", image))
-            .command(vec!["--config", "nats-server.conf", "-DVV"])
-            .build();
-
-        let container = self.docker.containers().create(&create_opts).await?;
-        container.start().await?;
-        Ok(())
-    }
-
-    async fn create_postgres(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name)
-            .image(format!("{0}:stable", image))
-            .env(vec![
-                "POSTGRES_PASSWORD=bugbear",
-                "PGPASSWORD=bugbear",
-                "POSTGRES_USER=si",
-                "POSTGRES_DB=si",
-            ])
-            .build();
+        let mut output = exec.start(&ExecStartOpts::builder().build()).await?;
+        while let Some(chunk) = output.next().await {
+            chunk?;
+        }
 
-        let container = self.docker.containers().create(&create_opts).await?;
-        container.start().await?;
+        let exit_code = exec
+            .inspect()
+            .await?
+            .exit_code
+            .ok_or_else(|| SiCliError::ContainerExecNoExitCode(command.join(" ")))?;
+        if exit_code != 0 {
+            return Err(SiCliError::ContainerExec(
+                command.join(" "),
+                exit_code as i64,
+            ));
+        }
         Ok(())
     }
 
-    async fn create_council(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name)
-            .image(format!("{0}:stable", image))
-            .links(vec!["local-nats-1:nats", "local-otelcol-1:otelcol"])
-            .env(vec![
-                "SI_COUNCIL__NATS__URL=nats",
-                "OTEL_EXPORTER_OTLP_ENDPOINT=http://otelcol:4317",
-            ])
-            .build();
-
-        let container = self.docker.containers().create(&create_opts).await?;
-        container.start().await?;
-        Ok(())
+    async fn copy_from_container(&self, id: String, path: String) -> CliResult<Vec<u8>> {
+        let archive: Vec<u8> = self
+            .docker
+            .containers()
+            .get(id)
+            .copy_from(Path::new(&path))
+            .try_concat()
+            .await?;
+        untar_single_file(&archive, &path)
     }
 
-    async fn create_veritech(
+    async fn copy_into_container(
         &self,
-        name: String,
-        image: String,
-        credentials: &mut Vec<String>,
-        data_dir: PathBuf,
-        with_debug_logs: bool,
+        id: String,
+        path: String,
+        contents: &[u8],
     ) -> CliResult<()> {
-        let mut env_vars = vec![
-            "SI_VERITECH__NATS__URL=nats".to_string(),
-            "OTEL_EXPORTER_OTLP_ENDPOINT=http://otelcol:4317".to_string(),
-        ];
-        if with_debug_logs {
-            env_vars.push("SI_LOG=debug".to_string());
-        }
-        env_vars.append(credentials);
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name)
-            .image(format!("{0}:stable", image))
-            .links(vec!["local-nats-1:nats", "local-otelcol-1:otelcol"])
-            .env(env_vars)
-            .volumes([format!("{}:/run/cyclone:z", data_dir.display())])
-            .build();
-
-        let container = self.docker.containers().create(&create_opts).await?;
-        container.start().await?;
+        self.docker
+            .containers()
+            .get(id)
+            .copy_file_into(path, contents)
+            .await?;
         Ok(())
     }
 
-    async fn create_pinga(&self, name: String, image: String, data_dir: PathBuf) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name)
-            .image(format!("{0}:stable", image))
-            .links(vec![
-                "local-nats-1:nats",
-                "local-postgres-1:postgres",
-                "local-otelcol-1:otelcol",
-            ])
-            .env(vec![
-                "SI_PINGA__NATS__URL=nats",
-                "SI_PINGA__PG__HOSTNAME=postgres",
-                "OTEL_EXPORTER_OTLP_ENDPOINT=http://otelcol:4317",
-            ])
-            .volumes([format!("{}:/run/pinga:z", data_dir.display())])
-            .build();
-
-        let container = self.docker.containers().create(&create_opts).await?;
-        container.start().await?;
+    async fn create_network(&self) -> CliResult<()> {
         Ok(())
     }
 
-    async fn create_sdf(
-        &self,
-        name: String,
-        image: String,
-        host_ip: String,
-        host_port: u32,
-        data_dir: PathBuf,
-    ) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name)
-            .image(format!("{0}:stable", image))
-            .links(vec![
-                "local-nats-1:nats",
-                "local-postgres-1:postgres",
-                "local-otelcol-1:otelcol",
-            ])
-            .env(vec![
-                "SI_SDF__NATS__URL=nats",
-                "SI_SDF__PG__HOSTNAME=postgres",
-                "OTEL_EXPORTER_OTLP_ENDPOINT=http://otelcol:4317",
-            ])
-            .network_mode("bridge")
-            .expose(
-                PublishPort::tcp(5156),
-                HostPort::with_ip(host_port, host_ip),
-            )
-            .volumes([
-                format!(
-                    "{}:/run/sdf/cyclone_encryption.key:z",
-                    data_dir.join("cyclone_encryption.key").display()
-                ),
-                format!(
-                    "{}:/run/sdf/donkey.key:z",
-                    data_dir.join("donkey.key").display()
-                ),
-                format!(
-                    "{}:/run/sdf/jwt_signing_public_key.pem:z",
-                    data_dir.join("jwt_signing_public_key.pem").display()
-                ),
-            ])
-            .build();
-
-        let container = self.docker.containers().create(&create_opts).await?;
-        container.start().await?;
+    async fn delete_network(&self) -> CliResult<()> {
         Ok(())
     }
 
-    async fn create_web(
+    async fn create_container(
         &self,
-        name: String,
-        image: String,
-        host_ip: String,
-        host_port: u32,
+        spec: &ContainerSpec,
+        wiring: &ServiceWiring,
     ) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name)
-            .image(format!("{0}:stable", image))
-            .links(vec!["local-sdf-1:sdf"])
-            .env(["SI_LOG=trace"])
-            .network_mode("bridge")
-            .expose(
-                PublishPort::tcp(8080),
-                HostPort::with_ip(host_port, host_ip),
+        let mut builder = Self::create_opts(spec)
+            .links(
+                wiring
+                    .depends_on
+                    .iter()
+                    .map(|service| self.link(service))
+                    .collect::<Vec<_>>(),
             )
-            .build();
+            .env(to_env_vars(spec.env(wiring.env.clone())))
+            .network_mode("bridge")
+            .volumes(
+                wiring
+                    .mounts
+                    .iter()
+                    .map(|mount| {
+                        format!("{}:{}:z", wiring.source(mount).display(), mount.destination)
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        if !wiring.command.is_empty() {
+            builder = builder.command(wiring.command.to_vec());
+        }
+        if let Some(port) = wiring.published_port {
+            builder = builder.expose(PublishPort::tcp(port as u32), Self::host_port(spec, port));
+        }
 
-        let container = self.docker.containers().create(&create_opts).await?;
+        let container = self.docker.containers().create(&builder.build()).await?;
         container.start().await?;
         Ok(())
     }
 }
+
+fn to_env_vars(env: BTreeMap<String, String>) -> Vec<String> {
+    env.into_iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect()
+}
@@ -1,19 +1,29 @@
-use crate::engine::{ContainerEngine, ContainerReleaseInfo, SiContainerSummary, SiImageSummary};
+use crate::engine::{
+    untar_single_file, ContainerEngine, ContainerReleaseInfo, ContainerSpec, ServiceWiring,
+    SiContainerSummary, SiImageSummary,
+};
+use crate::profile::DEFAULT_STACK;
 use crate::{CliResult, SiCliError, CONTAINER_NAMES};
 use async_trait::async_trait;
 use color_eyre::eyre::eyre;
 use directories::UserDirs;
-use futures::StreamExt;
+use futures::{StreamExt, TryStreamExt};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use podman_api::models::{ContainerMount, Namespace, PerNetworkOptions, PortMapping};
+use podman_api::models::{
+    ContainerMount, LinuxCpu, LinuxMemory, LinuxResources, Namespace, PerNetworkOptions,
+    PortMapping,
+};
 use podman_api::opts::{
-    ContainerCreateOpts, ContainerDeleteOpts, ContainerListFilter, ContainerListOpts,
-    ContainerLogsOpts, ContainerStopOpts, ImageListOpts, NetworkCreateOpts, PullOpts,
+    ContainerCreateOpts, ContainerCreateOptsBuilder, ContainerDeleteOpts, ContainerListFilter,
+    ContainerListOpts, ContainerLogsOpts, ContainerStopOpts, ExecCreateOpts, ExecStartOpts,
+    ImageListOpts, NetworkCreateOpts, PullOpts,
 };
 use podman_api::Podman;
 use std::collections::HashMap;
 use std::env;
-use std::path::PathBuf;
+
+/// The CFS period, in microseconds, that cpu quotas are expressed against.
+const CPU_PERIOD: u64 = 100_000;
 
 pub struct PodmanEngine {
     podman: Podman,
@@ -22,7 +32,7 @@ pub struct PodmanEngine {
 
 impl PodmanEngine {
     #[allow(clippy::new_ret_no_self)]
-    pub async fn new(sock: Option<String>) -> CliResult<Box<dyn ContainerEngine>> {
+    pub async fn new(sock: Option<String>, stack: &str) -> CliResult<Box<dyn ContainerEngine>> {
         let podman_sock = if let Some(sock) = sock {
             sock
         } else {
@@ -77,10 +87,64 @@ impl PodmanEngine {
             podman = Podman::unix(path);
         }
 
-        Ok(Box::new(PodmanEngine {
-            podman,
-            network: "si".to_owned(),
-        }))
+        // Every stack gets its own network so that the service aliases don't clash
+        let network = if stack == DEFAULT_STACK {
+            "si".to_owned()
+        } else {
+            format!("si-{stack}")
+        };
+
+        Ok(Box::new(PodmanEngine { podman, network }))
+    }
+
+    /// Creation options shared by every service: the container is attached to the stack's
+    /// network, reachable under the service's name.
+    fn create_opts(&self, spec: &ContainerSpec, alias: &str) -> ContainerCreateOptsBuilder {
+        let mut builder = ContainerCreateOpts::builder()
+            .name(spec.name.clone())
+            .image(spec.image.clone())
+            .net_namespace(Namespace {
+                nsmode: Some("bridge".to_owned()),
+                value: None,
+            })
+            .networks(HashMap::from([(
+                self.network.to_owned(),
+                PerNetworkOptions {
+                    aliases: Some(vec![alias.to_owned()]),
+                    interface_name: None,
+                    static_ips: None,
+                    static_mac: None,
+                },
+            )]));
+        if spec.memory_limit_mb.is_some() || spec.cpus.is_some() {
+            builder = builder.resource_limits(LinuxResources {
+                memory: spec.memory_limit_bytes().map(|limit| LinuxMemory {
+                    limit: Some(limit as i64),
+                    ..Default::default()
+                }),
+                cpu: spec.cpus.map(|cpus| LinuxCpu {
+                    period: Some(CPU_PERIOD),
+                    quota: Some((cpus * CPU_PERIOD as f64) as i64),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+        }
+        builder
+    }
+
+    fn port_mapping(spec: &ContainerSpec, container_port: u16) -> PortMapping {
+        PortMapping {
+            container_port: Some(container_port),
+            host_port: Some(
+                spec.host_port
+                    .map(|port| port.try_into().unwrap())
+                    .unwrap_or(container_port),
+            ),
+            host_ip: spec.host_ip.clone(),
+            protocol: None,
+            range: None,
+        }
     }
 }
 
@@ -314,6 +378,62 @@ impl ContainerEngine for PodmanEngine {
         Ok(())
     }
 
+    async fn exec_in_container(&self, id: String, command: Vec<String>) -> CliResult<()> {
+        let create_opts = ExecCreateOpts::builder()
+            .command(command.clone())
+            .attach_stdout(true)
+            .attach_stderr(true)
+            .build();
+        let exec = self
+            .podman
+            .containers()
+            .get(id)
+            .create_exec(&create_opts)
+            .await?;
+
+        if let Some(mut output) = exec.start(&ExecStartOpts::builder().build()).await? {
+            while let Some(chunk) = output.next().await {
+                chunk?;
+            }
+        }
+
+        let exit_code = exec
+            .inspect()
+            .await?
+            .get("ExitCode")
+            .and_then(serde_json::Value::as_i64)
+            .ok_or_else(|| SiCliError::ContainerExecNoExitCode(command.join(" ")))?;
+        if exit_code != 0 {
+            return Err(SiCliError::ContainerExec(command.join(" "), exit_code));
+        }
+        Ok(())
+    }
+
+    async fn copy_from_container(&self, id: String, path: String) -> CliResult<Vec<u8>> {
+        let archive: Vec<u8> = self
+            .podman
+            .containers()
+            .get(id)
+            .copy_from(&path)
+            .try_concat()
+            .await?;
+        untar_single_file(&archive, &path)
+    }
+
+    async fn copy_into_container(
+        &self,
+        id: String,
+        path: String,
+        contents: &[u8],
+    ) -> CliResult<()> {
+        self.podman
+            .containers()
+            .get(id)
+            .copy_file_into(path, contents)
+            .await?;
+        Ok(())
+    }
+
     async fn create_network(&self) -> CliResult<()> {
         match self
             .podman
@@ -365,380 +485,36 @@ impl ContainerEngine for PodmanEngine {
         Ok(())
     }
 
-    async fn create_otelcol(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["otelcol".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
-        self.podman
-            .containers()
-            .get(container.id)
-            .start(None)
-            .await?;
-        Ok(())
-    }
-
-    async fn create_jaeger(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["jaeger".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .portmappings(vec![PortMapping {
-                container_port: Some(16686),
-                host_port: Some(16686),
-                host_ip: None,
-                protocol: None,
-                range: None,
-            }])
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
-        self.podman
-            .containers()
-            .get(container.id)
-            .start(None)
-            .await?;
-        Ok(())
-    }
-
-    async fn create_nats(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["nats".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .command(vec!["--config", "nats-server.conf", "-DVV"])
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
-        self.podman
-            .containers()
-            .get(container.id)
-            .start(None)
-            .await?;
-        Ok(())
-    }
-
-    async fn create_postgres(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["postgres".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .env(HashMap::from([
-                ("POSTGRES_PASSWORD", "bugbear"),
-                ("PGPASSWORD", "bugbear"),
-                ("POSTGRES_USER", "si"),
-                ("POSTGRES_DB", "si"),
-            ]))
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
-        self.podman
-            .containers()
-            .get(container.id)
-            .start(None)
-            .await?;
-        Ok(())
-    }
-
-    async fn create_council(&self, name: String, image: String) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["council".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .env(HashMap::from([
-                ("SI_COUNCIL__NATS__URL", "nats"),
-                ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://otelcol:4317"),
-            ]))
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
-        self.podman
-            .containers()
-            .get(container.id)
-            .start(None)
-            .await?;
-        Ok(())
-    }
-
-    async fn create_veritech(
+    async fn create_container(
         &self,
-        name: String,
-        image: String,
-        credentials: &mut Vec<String>,
-        data_dir: PathBuf,
-        with_debug_logs: bool,
+        spec: &ContainerSpec,
+        wiring: &ServiceWiring,
     ) -> CliResult<()> {
-        let mut env_vars = HashMap::from([
-            ("SI_VERITECH__NATS__URL", "nats"),
-            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://otelcol:4317"),
-        ]);
-
-        if with_debug_logs {
-            env_vars.insert("SI_LOG", "debug");
+        let mut builder = self
+            .create_opts(spec, wiring.service)
+            .env(spec.env(wiring.env.clone()))
+            .mounts(
+                wiring
+                    .mounts
+                    .iter()
+                    .map(|mount| ContainerMount {
+                        destination: Some(mount.destination.to_owned()),
+                        source: Some(wiring.source(mount).display().to_string()),
+                        options: Some(get_container_mount_opts()),
+                        _type: Some("bind".to_owned()),
+                        uid_mappings: None,
+                        gid_mappings: None,
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        if !wiring.command.is_empty() {
+            builder = builder.command(wiring.command.to_vec());
         }
-
-        for env_val in credentials.iter() {
-            let fields: Vec<&str> = env_val.split('=').collect();
-            env_vars.insert(fields[0], fields[1]);
+        if let Some(port) = wiring.published_port {
+            builder = builder.portmappings(vec![Self::port_mapping(spec, port)]);
         }
 
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["veritech".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .env(env_vars)
-            .mounts(vec![ContainerMount {
-                destination: Some("/run/cyclone".to_owned()),
-                source: Some(data_dir.display().to_string()),
-                options: Some(get_container_mount_opts()),
-                _type: Some("bind".to_owned()),
-                uid_mappings: None,
-                gid_mappings: None,
-            }])
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
-        self.podman
-            .containers()
-            .get(container.id)
-            .start(None)
-            .await?;
-        Ok(())
-    }
-
-    async fn create_pinga(&self, name: String, image: String, data_dir: PathBuf) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["pinga".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .env(HashMap::from([
-                ("SI_PINGA__NATS__URL", "nats"),
-                ("SI_PINGA__PG__HOSTNAME", "postgres"),
-                ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://otelcol:4317"),
-            ]))
-            .mounts(vec![ContainerMount {
-                destination: Some("/run/pinga".to_owned()),
-                source: Some(data_dir.display().to_string()),
-                options: Some(get_container_mount_opts()),
-                _type: Some("bind".to_owned()),
-                uid_mappings: None,
-                gid_mappings: None,
-            }])
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
-        self.podman
-            .containers()
-            .get(container.id)
-            .start(None)
-            .await?;
-        Ok(())
-    }
-
-    async fn create_sdf(
-        &self,
-        name: String,
-        image: String,
-        host_ip: String,
-        host_port: u32,
-        data_dir: PathBuf,
-    ) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["sdf".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .env(HashMap::from([
-                ("SI_SDF__NATS__URL", "nats"),
-                ("SI_SDF__PG__HOSTNAME", "postgres"),
-                ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://otelcol:4317"),
-            ]))
-            .portmappings(vec![PortMapping {
-                container_port: Some(5156),
-                host_port: Some(host_port.try_into().unwrap()),
-                host_ip: Some(host_ip),
-                protocol: None,
-                range: None,
-            }])
-            .mounts(vec![
-                ContainerMount {
-                    destination: Some("/run/sdf/cyclone_encryption.key".to_owned()),
-                    source: Some(
-                        data_dir
-                            .join("cyclone_encryption.key")
-                            .display()
-                            .to_string(),
-                    ),
-                    options: Some(get_container_mount_opts()),
-                    _type: Some("bind".to_owned()),
-                    uid_mappings: None,
-                    gid_mappings: None,
-                },
-                ContainerMount {
-                    destination: Some("/run/sdf/donkey.key".to_owned()),
-                    source: Some(data_dir.join("donkey.key").display().to_string()),
-                    options: Some(get_container_mount_opts()),
-                    _type: Some("bind".to_owned()),
-                    uid_mappings: None,
-                    gid_mappings: None,
-                },
-                ContainerMount {
-                    destination: Some("/run/sdf/jwt_signing_public_key.pem".to_owned()),
-                    source: Some(
-                        data_dir
-                            .join("jwt_signing_public_key.pem")
-                            .display()
-                            .to_string(),
-                    ),
-                    options: Some(get_container_mount_opts()),
-                    _type: Some("bind".to_owned()),
-                    uid_mappings: None,
-                    gid_mappings: None,
-                },
-            ])
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
-        self.podman
-            .containers()
-            .get(container.id)
-            .start(None)
-            .await?;
-        Ok(())
-    }
-
-    async fn create_web(
-        &self,
-        name: String,
-        image: String,
-        host_ip: String,
-        host_port: u32,
-    ) -> CliResult<()> {
-        let create_opts = ContainerCreateOpts::builder()
-            .name(name.clone())
-            .image(format!("{0}:stable", image.clone()))
-            .net_namespace(Namespace {
-                nsmode: Some("bridge".to_owned()),
-                value: None,
-            })
-            .networks(HashMap::from([(
-                self.network.to_owned(),
-                PerNetworkOptions {
-                    aliases: Some(vec!["web".to_owned()]),
-                    interface_name: None,
-                    static_ips: None,
-                    static_mac: None,
-                },
-            )]))
-            .env(HashMap::from([("SI_LOG", "trace")]))
-            .portmappings(vec![PortMapping {
-                container_port: Some(8080),
-                host_port: Some(host_port.try_into().unwrap()),
-                host_ip: Some(host_ip),
-                protocol: None,
-                range: None,
-            }])
-            .build();
-
-        let container = self.podman.containers().create(&create_opts).await?;
+        let container = self.podman.containers().create(&builder.build()).await?;
         self.podman
             .containers()
             .get(container.id)
@@ -0,0 +1,191 @@
+//! How every service of a stack is wired to the others: the services it talks to, its
+//! environment, the ports it listens on and the files of the si data dir it needs. The container
+//! engines create containers from it and `si export` renders it as manifests, so it is the single
+//! place to change when a service needs something new.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Where the services export their traces to.
+const OTEL_EXPORTER_OTLP_ENDPOINT: (&str, &str) =
+    ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://otelcol:4317");
+
+#[derive(Clone, Debug)]
+pub struct ServiceWiring {
+    /// The name of the service, which is also the host name the other services reach it by.
+    pub service: &'static str,
+    /// The services reached by this one, which have to be started first.
+    pub depends_on: &'static [&'static str],
+    pub env: BTreeMap<String, String>,
+    /// The names of the `env` entries holding the credentials from `si configure`.
+    pub credentials: Vec<String>,
+    /// Overrides the default command of the image.
+    pub command: &'static [&'static str],
+    /// The ports the service listens on for the rest of the stack.
+    pub ports: &'static [u16],
+    /// The port published on the host, if any.
+    pub published_port: Option<u16>,
+    pub mounts: Vec<DataDirMount>,
+    data_dir: PathBuf,
+}
+
+/// A bind mount of the si data dir, or of a single file of it, into the container.
+#[derive(Clone, Debug)]
+pub struct DataDirMount {
+    pub file: Option<&'static str>,
+    pub destination: &'static str,
+}
+
+impl ServiceWiring {
+    /// The wiring of the service. `credentials` are the `KEY=value` pairs from `si configure`
+    /// that veritech passes on to functions.
+    pub fn new(
+        service: &str,
+        data_dir: &Path,
+        credentials: &[String],
+        with_debug_logs: bool,
+    ) -> Self {
+        let wiring = Self::empty(service, data_dir);
+        match service {
+            "jaeger" => Self {
+                ports: &[4317, 16686],
+                published_port: Some(16686),
+                ..wiring
+            },
+            "otelcol" => Self {
+                depends_on: &["jaeger"],
+                ports: &[4317],
+                ..wiring
+            },
+            "nats" => Self {
+                command: &["--config", "nats-server.conf", "-DVV"],
+                ports: &[4222],
+                ..wiring
+            },
+            "postgres" => Self {
+                env: env([
+                    ("POSTGRES_PASSWORD", "bugbear"),
+                    ("PGPASSWORD", "bugbear"),
+                    ("POSTGRES_USER", "si"),
+                    ("POSTGRES_DB", "si"),
+                ]),
+                ports: &[5432],
+                ..wiring
+            },
+            "council" => Self {
+                depends_on: &["nats", "otelcol"],
+                env: env([
+                    ("SI_COUNCIL__NATS__URL", "nats"),
+                    OTEL_EXPORTER_OTLP_ENDPOINT,
+                ]),
+                ..wiring
+            },
+            "veritech" => {
+                let mut env = env([
+                    ("SI_VERITECH__NATS__URL", "nats"),
+                    OTEL_EXPORTER_OTLP_ENDPOINT,
+                ]);
+                if with_debug_logs {
+                    env.insert("SI_LOG".to_owned(), "debug".to_owned());
+                }
+                let mut credential_names = Vec::new();
+                for credential in credentials {
+                    if let Some((key, value)) = credential.split_once('=') {
+                        env.insert(key.to_owned(), value.to_owned());
+                        credential_names.push(key.to_owned());
+                    }
+                }
+
+                Self {
+                    depends_on: &["nats", "otelcol"],
+                    env,
+                    credentials: credential_names,
+                    mounts: vec![DataDirMount {
+                        file: None,
+                        destination: "/run/cyclone",
+                    }],
+                    ..wiring
+                }
+            }
+            "pinga" => Self {
+                depends_on: &["nats", "postgres", "otelcol"],
+                env: env([
+                    ("SI_PINGA__NATS__URL", "nats"),
+                    ("SI_PINGA__PG__HOSTNAME", "postgres"),
+                    OTEL_EXPORTER_OTLP_ENDPOINT,
+                ]),
+                mounts: vec![DataDirMount {
+                    file: None,
+                    destination: "/run/pinga",
+                }],
+                ..wiring
+            },
+            "sdf" => Self {
+                depends_on: &["nats", "postgres", "otelcol"],
+                env: env([
+                    ("SI_SDF__NATS__URL", "nats"),
+                    ("SI_SDF__PG__HOSTNAME", "postgres"),
+                    OTEL_EXPORTER_OTLP_ENDPOINT,
+                ]),
+                ports: &[5156],
+                published_port: Some(5156),
+                mounts: vec![
+                    DataDirMount {
+                        file: Some("cyclone_encryption.key"),
+                        destination: "/run/sdf/cyclone_encryption.key",
+                    },
+                    DataDirMount {
+                        file: Some("donkey.key"),
+                        destination: "/run/sdf/donkey.key",
+                    },
+                    DataDirMount {
+                        file: Some("jwt_signing_public_key.pem"),
+                        destination: "/run/sdf/jwt_signing_public_key.pem",
+                    },
+                ],
+                ..wiring
+            },
+            "web" => Self {
+                depends_on: &["sdf"],
+                env: env([("SI_LOG", "trace")]),
+                ports: &[8080],
+                published_port: Some(8080),
+                ..wiring
+            },
+            unknown => unreachable!("unknown service: {unknown}"),
+        }
+    }
+
+    fn empty(service: &str, data_dir: &Path) -> Self {
+        let service = crate::CONTAINER_NAMES
+            .iter()
+            .find(|name| **name == service)
+            .copied()
+            .unwrap_or_else(|| unreachable!("unknown service: {service}"));
+        Self {
+            service,
+            depends_on: &[],
+            env: BTreeMap::new(),
+            credentials: Vec::new(),
+            command: &[],
+            ports: &[],
+            published_port: None,
+            mounts: Vec::new(),
+            data_dir: data_dir.to_path_buf(),
+        }
+    }
+
+    /// The path on the host that is mounted into the container.
+    pub fn source(&self, mount: &DataDirMount) -> PathBuf {
+        match mount.file {
+            Some(file) => self.data_dir.join(file),
+            None => self.data_dir.clone(),
+        }
+    }
+}
+
+fn env<const N: usize>(vars: [(&str, &str); N]) -> BTreeMap<String, String> {
+    vars.into_iter()
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect()
+}
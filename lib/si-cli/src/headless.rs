@@ -0,0 +1,66 @@
+//! Commands that drive a running System Initiative through sdf with an API token, so that it can
+//! be used from scripts and CI pipelines without a browser. Every command prints its result as
+//! JSON.
+
+use sdf_client::types::{ChangeSetPk, Visibility};
+use sdf_client::SdfClient;
+use serde::Serialize;
+use std::str::FromStr;
+use url::Url;
+
+use crate::profile::Profile;
+use crate::{CliResult, SiCliError};
+
+mod change_set;
+mod component;
+mod fix;
+mod pkg;
+
+pub struct Headless {
+    client: SdfClient,
+}
+
+impl Headless {
+    /// Drives the sdf at `sdf_url`, or else the one published by the profile.
+    pub fn new(
+        sdf_url: Option<&str>,
+        api_token: Option<&str>,
+        profile: &Profile,
+    ) -> CliResult<Self> {
+        let api_token = api_token.ok_or(SiCliError::MissingApiToken)?;
+        let mut sdf_url = match sdf_url {
+            Some(sdf_url) => Url::parse(sdf_url)?,
+            None => {
+                let (host, port) = profile.published_address("sdf");
+                let host = host.unwrap_or_else(|| "127.0.0.1".to_owned());
+                Url::parse(&format!("http://{host}:{port}/"))?
+            }
+        };
+        // The api's paths are joined onto the url, which would otherwise replace its last segment
+        if !sdf_url.path().ends_with('/') {
+            sdf_url.set_path(&format!("{}/", sdf_url.path()));
+        }
+
+        Ok(Self {
+            client: SdfClient::new(sdf_url, api_token),
+        })
+    }
+}
+
+/// The visibility of the change set given on the command line, or of head when there is none.
+fn visibility(change_set: Option<&str>) -> CliResult<Visibility> {
+    Ok(match change_set {
+        Some(change_set_pk) => Visibility::new(parse_change_set_pk(change_set_pk)?),
+        None => Visibility::new_head(),
+    })
+}
+
+fn parse_change_set_pk(change_set_pk: &str) -> CliResult<ChangeSetPk> {
+    ChangeSetPk::from_str(change_set_pk)
+        .map_err(|_| SiCliError::InvalidChangeSetPk(change_set_pk.to_owned()))
+}
+
+fn print_json(value: &impl Serialize) -> CliResult<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use sdf_client::types::{ApplyChangeSetRequest, CreateChangeSetRequest};
+
+use super::{parse_change_set_pk, print_json, Headless};
+use crate::CliResult;
+
+impl Headless {
+    pub async fn create_change_set(&self, name: String) -> CliResult<()> {
+        let response = self
+            .client
+            .create_change_set(&CreateChangeSetRequest {
+                change_set_name: name,
+            })
+            .await?;
+        print_json(&response.change_set)
+    }
+
+    pub async fn apply_change_set(&self, change_set_pk: &str) -> CliResult<()> {
+        let response = self
+            .client
+            .apply_change_set(&ApplyChangeSetRequest {
+                change_set_pk: parse_change_set_pk(change_set_pk)?,
+            })
+            .await?;
+        print_json(&response.change_set)
+    }
+
+    pub async fn list_change_sets(&self) -> CliResult<()> {
+        let change_sets = self.client.list_open_change_sets().await?;
+        print_json(&change_sets)
+    }
+}
@@ -0,0 +1,115 @@
+use sdf_client::types::{
+    GetPropertyEditorSchemaRequest, GetPropertyEditorValuesRequest, PropertyEditorSchema,
+    PropertyEditorValue, PropertyEditorValues, QueryComponentsRequest,
+    UpdatePropertyEditorValueRequest,
+};
+use serde_json::{json, Value};
+
+use super::{print_json, visibility, Headless};
+use crate::{CliResult, SiCliError};
+
+impl Headless {
+    /// Sets the value at a path such as `/root/domain/image` of the component with the given
+    /// name. A value that isn't valid JSON is set as a string.
+    pub async fn set_component_value(
+        &self,
+        name: &str,
+        path: &str,
+        value: &str,
+        change_set: Option<&str>,
+    ) -> CliResult<()> {
+        let visibility = visibility(change_set)?;
+
+        let matches = self
+            .client
+            .query_components(&QueryComponentsRequest {
+                query: format!("name = {}", serde_json::to_string(name)?),
+                visibility,
+            })
+            .await?;
+        let component_id = match matches.as_slice() {
+            [component] => component.component_id,
+            [] => return Err(SiCliError::ComponentNotFound(name.to_owned())),
+            _ => return Err(SiCliError::AmbiguousComponent(name.to_owned())),
+        };
+
+        let schema = self
+            .client
+            .get_property_editor_schema(&GetPropertyEditorSchemaRequest {
+                component_id,
+                visibility,
+            })
+            .await?;
+        let values = self
+            .client
+            .get_property_editor_values(&GetPropertyEditorValuesRequest {
+                component_id,
+                visibility,
+            })
+            .await?;
+        let (attribute_value, parent) = find_value(&schema, &values, path)
+            .ok_or_else(|| SiCliError::PropNotFound(path.to_owned()))?;
+
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_owned()));
+        let mutation = self
+            .client
+            .update_property_editor_value(&UpdatePropertyEditorValueRequest {
+                attribute_value_id: attribute_value.id,
+                parent_attribute_value_id: parent.map(|parent| parent.id),
+                prop_id: attribute_value.prop_id,
+                component_id,
+                value: Some(value.clone()),
+                key: attribute_value.key.clone(),
+                visibility,
+            })
+            .await?;
+
+        // Setting a value at head creates a change set for it, which later commands should use
+        print_json(&json!({
+            "componentId": component_id,
+            "path": path,
+            "value": value,
+            "changeSetPk": mutation
+                .forced_change_set_pk
+                .unwrap_or(visibility.change_set_pk),
+        }))
+    }
+}
+
+/// Finds the value at a path of prop names, or of keys for the entries of maps, along with its
+/// parent value.
+fn find_value<'a>(
+    schema: &PropertyEditorSchema,
+    values: &'a PropertyEditorValues,
+    path: &str,
+) -> Option<(&'a PropertyEditorValue, Option<&'a PropertyEditorValue>)> {
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+
+    let mut current = values.values.get(&values.root_value_id)?;
+    if segments.next()? != prop_name(schema, current)? {
+        return None;
+    }
+
+    let mut parent = None;
+    for segment in segments {
+        let child = values
+            .child_values
+            .get(&current.id)?
+            .iter()
+            .filter_map(|child_id| values.values.get(child_id))
+            .find(|child| {
+                child.key.as_deref() == Some(segment) || prop_name(schema, child) == Some(segment)
+            })?;
+        parent = Some(current);
+        current = child;
+    }
+
+    Some((current, parent))
+}
+
+fn prop_name<'a>(schema: &'a PropertyEditorSchema, value: &PropertyEditorValue) -> Option<&'a str> {
+    schema
+        .props
+        .get(&value.prop_id)
+        .map(|prop| prop.name.as_str())
+}
@@ -0,0 +1,17 @@
+use sdf_client::types::{ListFixesRequest, Visibility};
+
+use super::{print_json, Headless};
+use crate::CliResult;
+
+impl Headless {
+    /// Prints the fix batches of the workspace along with the status of each of their fixes.
+    pub async fn fix_status(&self) -> CliResult<()> {
+        let batches = self
+            .client
+            .list_fixes(&ListFixesRequest {
+                visibility: Visibility::new_head(),
+            })
+            .await?;
+        print_json(&batches)
+    }
+}
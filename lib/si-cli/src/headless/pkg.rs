@@ -0,0 +1,68 @@
+use base64::{engine::general_purpose, Engine};
+use sdf_client::types::{ExportPkgRequest, InstallPkgFileRequest, ListSchemaVariantsRequest};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+
+use super::{print_json, visibility, Headless};
+use crate::{CliResult, SiCliError};
+
+impl Headless {
+    /// Packages the named schemas as a module and writes it to `file`.
+    pub async fn export_pkg(
+        &self,
+        file: &Path,
+        name: String,
+        version: String,
+        description: Option<String>,
+        schemas: &[String],
+        change_set: Option<&str>,
+    ) -> CliResult<()> {
+        let visibility = visibility(change_set)?;
+        let variants = self
+            .client
+            .list_schema_variants(&ListSchemaVariantsRequest { visibility })
+            .await?;
+
+        let mut schema_variants = Vec::with_capacity(schemas.len());
+        for schema in schemas {
+            let variant = variants
+                .iter()
+                .find(|variant| &variant.schema_name == schema)
+                .ok_or_else(|| SiCliError::SchemaNotFound(schema.to_owned()))?;
+            schema_variants.push(variant.id);
+        }
+
+        let response = self
+            .client
+            .export_pkg_file(&ExportPkgRequest {
+                name,
+                version,
+                description,
+                schema_variants,
+                visibility,
+            })
+            .await?;
+        let data = general_purpose::STANDARD.decode(&response.data)?;
+        fs::write(file, &data)?;
+
+        print_json(&json!({
+            "name": response.name,
+            "version": response.version,
+            "file": file,
+            "size": data.len(),
+        }))
+    }
+
+    pub async fn install_pkg(&self, file: &Path, change_set: Option<&str>) -> CliResult<()> {
+        let data = fs::read(file)?;
+        let response = self
+            .client
+            .install_pkg_file(&InstallPkgFileRequest {
+                data: general_purpose::STANDARD.encode(data),
+                visibility: visibility(change_set)?,
+            })
+            .await?;
+        print_json(&response)
+    }
+}
@@ -5,7 +5,9 @@ use thiserror::Error;
 
 pub mod cmd;
 pub mod engine;
+pub mod headless;
 mod key_management;
+pub mod profile;
 pub mod state;
 
 pub const CONTAINER_NAMES: &[&str] = &[
@@ -15,8 +17,24 @@ pub const CONTAINER_NAMES: &[&str] = &[
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum SiCliError {
+    #[error("more than one component is named {0}")]
+    AmbiguousComponent(String),
+    #[error("base64 decode: {0}")]
+    Base64Decode(#[from] base64::DecodeError),
+    #[error("component not found: {0}")]
+    ComponentNotFound(String),
+    #[error("config file: {0}")]
+    ConfigFile(#[from] config_file::ConfigFileError),
     #[error("unable to connect to the container engine")]
     ContainerEngine,
+    #[error("command `{0}` failed in the container with exit code {1}")]
+    ContainerExec(String, i64),
+    #[error("command `{0}` did not report an exit code from the container")]
+    ContainerExecNoExitCode(String),
+    #[error("file not found in the container: {0}")]
+    ContainerFileNotFound(String),
+    #[error("container {0} is not running - please run `si start` first")]
+    ContainerNotRunning(String),
     #[error("ctrl+c")]
     CtrlC,
     #[error("docker api: {0}")]
@@ -31,28 +49,52 @@ pub enum SiCliError {
     IncorrectInstallMode(String),
     #[error("aborting installation")]
     Installation,
+    #[error("invalid backup archive: {0}")]
+    InvalidBackup(String),
+    #[error("invalid change set pk: {0}")]
+    InvalidChangeSetPk(String),
+    #[error("invalid profile: {0}")]
+    InvalidProfile(String),
     #[error("io: {0}")]
     Io(#[from] std::io::Error),
     #[error("join: {0}")]
     Join(#[from] tokio::task::JoinError),
+    #[error("an api token is required - please pass `--api-token` or set `SI_API_TOKEN`")]
+    MissingApiToken,
     #[error("Unable to find local data dir. Expected format `$HOME/.local/share` or `$HOME/Library/Application Support`")]
     MissingDataDir(),
     #[error("podman api: {0}")]
     Podman(#[from] podman_api::Error),
+    #[error("no prop at path {0}")]
+    PropNotFound(String),
     #[error("reqwest: {0}")]
     Reqwest(#[from] reqwest::Error),
+    #[error("schema not found: {0}")]
+    SchemaNotFound(String),
+    #[error("sdf: {0}")]
+    SdfClient(#[from] sdf_client::SdfClientError),
+    #[error("serde json: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("serde yaml: {0}")]
+    SerdeYaml(#[from] serde_yaml::Error),
     #[error("symmetric crypto: {0}")]
     SymmetricCrypto(#[from] si_crypto::SymmetricCryptoError),
     #[error("toml deserialize error: {0}")]
     TomlDeserialize(#[from] toml::de::Error),
+    #[error("toml serialize error: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
     #[error("unable to download update, status = {0}")]
     UnableToDownloadUpdate(u16),
     #[error("unable to fetch containers update, status = {0}")]
     UnableToFetchContainersUpdate(u16),
     #[error("unable to fetch si update, status = {0}")]
     UnableToFetchSiUpdate(u16),
+    #[error("unhealthy services: {0}")]
+    Unhealthy(String),
     #[error("unsupported operating system: {0}")]
     UnsupportedOperatingSystem(String),
+    #[error("url parse: {0}")]
+    UrlParse(#[from] url::ParseError),
     #[error("env var: {0}")]
     Var(#[from] VarError),
     #[error("web portal is currently offline - please check that the system is running")]
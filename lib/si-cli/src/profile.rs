@@ -0,0 +1,291 @@
+//! A [`Profile`] declares every service of a System Initiative stack: which image it runs, which
+//! host address it is published on, its resource limits and any extra environment. Profiles are
+//! TOML files, either passed explicitly with `--profile` or found by
+//! [`config_file`](config_file::find) as `si.toml` (or through `SI_PROFILE`).
+//!
+//! Several stacks can run side by side as long as each profile has its own `stack` name and host
+//! ports: the stack name prefixes every container (`<stack>-<service>-1`) and network.
+//!
+//! ```toml
+//! stack = "team-a"
+//!
+//! [services.sdf]
+//! host_port = 5157
+//!
+//! [services.web]
+//! host_port = 8081
+//!
+//! [services.veritech]
+//! memory_limit_mb = 4096
+//! env = { SI_LOG = "debug" }
+//! ```
+
+use crate::engine::ContainerSpec;
+use crate::{CliResult, SiCliError, CONTAINER_NAMES};
+use config_file::FileFormat;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_STACK: &str = "local";
+pub const DEFAULT_TAG: &str = "stable";
+pub const PROFILE_APP_NAME: &str = "si";
+pub const PROFILE_ENV_VAR: &str = "SI_PROFILE";
+
+/// The services that can be published on a host address.
+const PUBLISHED_SERVICES: &[&str] = &["jaeger", "sdf", "web"];
+/// Docker refuses memory limits below 6MB.
+const MIN_MEMORY_LIMIT_MB: u64 = 6;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub stack: String,
+    pub services: Services,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            stack: DEFAULT_STACK.to_owned(),
+            services: Services::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Services {
+    pub jaeger: ServiceProfile,
+    pub postgres: ServiceProfile,
+    pub nats: ServiceProfile,
+    pub otelcol: ServiceProfile,
+    pub council: ServiceProfile,
+    pub veritech: ServiceProfile,
+    pub pinga: ServiceProfile,
+    pub sdf: ServiceProfile,
+    pub web: ServiceProfile,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServiceProfile {
+    /// Defaults to `systeminit/<service>`. Custom images must already be available to the
+    /// container engine.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Defaults to [`DEFAULT_TAG`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_port: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_limit_mb: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl Profile {
+    /// Loads the profile at `path`, or the first `si.toml` found by [`config_file::find`], or the
+    /// default profile if there is none.
+    pub fn load(path: Option<&Path>) -> CliResult<Self> {
+        let profile: Self = match path {
+            Some(path) => {
+                let contents = fs::read_to_string(path)?;
+                config_file::load_from_str(&contents, FileFormat::Toml)?
+            }
+            None => config_file::load_or_default(
+                PROFILE_APP_NAME,
+                FileFormat::Toml,
+                &Some(PROFILE_ENV_VAR),
+            )?,
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Where `si configure --write-profile` writes the profile when `--profile` isn't provided.
+    pub fn default_path() -> CliResult<PathBuf> {
+        Ok(ProjectDirs::from("", "", PROFILE_APP_NAME)
+            .ok_or(SiCliError::MissingDataDir())?
+            .config_dir()
+            .join(format!("{PROFILE_APP_NAME}.toml")))
+    }
+
+    pub fn write(&self, path: &Path) -> CliResult<()> {
+        self.validate()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> CliResult<()> {
+        let invalid = |message: String| Err(SiCliError::InvalidProfile(message));
+
+        let valid_stack = self
+            .stack
+            .chars()
+            .next()
+            .map_or(false, |c| c.is_ascii_alphanumeric())
+            && self
+                .stack
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        if !valid_stack {
+            return invalid(format!(
+                "stack name `{}` must start with a letter or digit and only contain letters, \
+                digits, `_`, `.` or `-`",
+                self.stack
+            ));
+        }
+
+        for name in CONTAINER_NAMES {
+            let service = self.service(name);
+
+            if service.image.as_deref().map_or(false, str::is_empty) {
+                return invalid(format!("services.{name}.image must not be empty"));
+            }
+            if service.tag.as_deref().map_or(false, str::is_empty) {
+                return invalid(format!("services.{name}.tag must not be empty"));
+            }
+
+            if service.host_ip.is_some() || service.host_port.is_some() {
+                if !PUBLISHED_SERVICES.contains(name) {
+                    return invalid(format!(
+                        "services.{name} can't be published, only {} can",
+                        PUBLISHED_SERVICES.join(", ")
+                    ));
+                }
+                if let Some(port) = service.host_port {
+                    if port == 0 || port > u16::MAX as u32 {
+                        return invalid(format!("services.{name}.host_port {port} is invalid"));
+                    }
+                }
+            }
+
+            if let Some(memory_limit_mb) = service.memory_limit_mb {
+                if memory_limit_mb < MIN_MEMORY_LIMIT_MB {
+                    return invalid(format!(
+                        "services.{name}.memory_limit_mb must be at least {MIN_MEMORY_LIMIT_MB}"
+                    ));
+                }
+            }
+            if let Some(cpus) = service.cpus {
+                if !(cpus.is_finite() && cpus > 0.0) {
+                    return invalid(format!("services.{name}.cpus must be greater than 0"));
+                }
+            }
+
+            for key in service.env.keys() {
+                if key.is_empty() || key.contains('=') {
+                    return invalid(format!("services.{name}.env has an invalid name `{key}`"));
+                }
+            }
+        }
+
+        let mut published = BTreeMap::new();
+        for name in PUBLISHED_SERVICES {
+            let (_, host_port) = self.published_address(name);
+            if let Some(other) = published.insert(host_port, name) {
+                return invalid(format!(
+                    "services.{other} and services.{name} are both published on port {host_port}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn service(&self, name: &str) -> &ServiceProfile {
+        let services = &self.services;
+        match name {
+            "jaeger" => &services.jaeger,
+            "postgres" => &services.postgres,
+            "nats" => &services.nats,
+            "otelcol" => &services.otelcol,
+            "council" => &services.council,
+            "veritech" => &services.veritech,
+            "pinga" => &services.pinga,
+            "sdf" => &services.sdf,
+            "web" => &services.web,
+            unknown => unreachable!("unknown service: {unknown}"),
+        }
+    }
+
+    pub fn service_mut(&mut self, name: &str) -> &mut ServiceProfile {
+        let services = &mut self.services;
+        match name {
+            "jaeger" => &mut services.jaeger,
+            "postgres" => &mut services.postgres,
+            "nats" => &mut services.nats,
+            "otelcol" => &mut services.otelcol,
+            "council" => &mut services.council,
+            "veritech" => &mut services.veritech,
+            "pinga" => &mut services.pinga,
+            "sdf" => &mut services.sdf,
+            "web" => &mut services.web,
+            unknown => unreachable!("unknown service: {unknown}"),
+        }
+    }
+
+    pub fn container_name(&self, service: &str) -> String {
+        container_name(&self.stack, service)
+    }
+
+    /// The host address the service is published on, falling back to the defaults of the CLI.
+    /// Without an ip the port is published on every interface.
+    pub fn published_address(&self, service: &str) -> (Option<String>, u32) {
+        let (default_ip, default_port) = match service {
+            "jaeger" => (None, 16686),
+            "sdf" => (Some("127.0.0.1"), 5156),
+            "web" => (Some("127.0.0.1"), 8080),
+            unknown => unreachable!("service can't be published: {unknown}"),
+        };
+        let profile = self.service(service);
+        (
+            profile
+                .host_ip
+                .clone()
+                .or_else(|| default_ip.map(ToOwned::to_owned)),
+            profile.host_port.unwrap_or(default_port),
+        )
+    }
+
+    pub fn container_spec(&self, service: &str) -> ContainerSpec {
+        let profile = self.service(service);
+        let image = profile
+            .image
+            .clone()
+            .unwrap_or_else(|| format!("systeminit/{service}"));
+        let tag = profile.tag.as_deref().unwrap_or(DEFAULT_TAG);
+        let (host_ip, host_port) = if PUBLISHED_SERVICES.contains(&service) {
+            let (host_ip, host_port) = self.published_address(service);
+            (host_ip, Some(host_port))
+        } else {
+            (None, None)
+        };
+
+        ContainerSpec {
+            name: self.container_name(service),
+            image: format!("{image}:{tag}"),
+            host_ip,
+            host_port,
+            env: profile.env.clone(),
+            memory_limit_mb: profile.memory_limit_mb,
+            cpus: profile.cpus,
+        }
+    }
+}
+
+/// Every container of a stack is named `<stack>-<service>-1`.
+pub fn container_name(stack: &str, service: &str) -> String {
+    format!("{stack}-{service}-1")
+}
@@ -1,7 +1,9 @@
-use crate::engine::ContainerEngine;
+use crate::engine::{ContainerEngine, ContainerSpec, ServiceWiring};
+use crate::profile::Profile;
 use axum::extract::FromRef;
 use std::env;
 use std::ops::Deref;
+use std::path::Path;
 use std::sync::Arc;
 use telemetry::tracing;
 
@@ -10,10 +12,7 @@ pub struct AppState {
     version: Arc<str>,
     mode: Arc<str>,
     is_preview: bool,
-    web_host: String,
-    web_port: u32,
-    sdf_host: String,
-    sdf_port: u32,
+    profile: Profile,
     with_function_debug_logs: bool,
     container_engine: Arc<Box<dyn ContainerEngine>>,
 }
@@ -25,10 +24,7 @@ impl AppState {
         version: Arc<str>,
         mode: Arc<str>,
         is_preview: bool,
-        web_host: String,
-        web_port: u32,
-        sdf_host: String,
-        sdf_port: u32,
+        profile: Profile,
         with_function_debug_logs: bool,
         container_engine: Arc<Box<dyn ContainerEngine>>,
     ) -> Self {
@@ -37,10 +33,7 @@ impl AppState {
             version,
             mode,
             is_preview,
-            web_host,
-            web_port,
-            sdf_host,
-            sdf_port,
+            profile,
             with_function_debug_logs,
             container_engine,
         }
@@ -63,19 +56,53 @@ impl AppState {
     }
 
     pub fn web_host(&self) -> String {
-        self.web_host.clone()
+        self.published_host("web")
     }
 
     pub fn web_port(&self) -> u32 {
-        self.web_port
+        self.profile.published_address("web").1
     }
 
     pub fn sdf_host(&self) -> String {
-        self.sdf_host.clone()
+        self.published_host("sdf")
     }
 
     pub fn sdf_port(&self) -> u32 {
-        self.sdf_port
+        self.profile.published_address("sdf").1
+    }
+
+    fn published_host(&self, service: &str) -> String {
+        self.profile
+            .published_address(service)
+            .0
+            .unwrap_or_else(|| "127.0.0.1".to_owned())
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// The name of the service's container in the stack of the profile.
+    pub fn container_name(&self, service: &str) -> String {
+        self.profile.container_name(service)
+    }
+
+    pub fn container_spec(&self, service: &str) -> ContainerSpec {
+        self.profile.container_spec(service)
+    }
+
+    pub fn service_wiring(
+        &self,
+        service: &str,
+        data_dir: &Path,
+        credentials: &[String],
+    ) -> ServiceWiring {
+        ServiceWiring::new(
+            service,
+            data_dir,
+            credentials,
+            self.with_function_debug_logs,
+        )
     }
 
     pub fn posthog_client(&self) -> &PosthogClient {
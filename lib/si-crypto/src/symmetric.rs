@@ -152,6 +152,36 @@ impl SymmetricCryptoService {
         )
     }
 
+    /// Returns the [`Hash`] of the active [`SymmetricKey`], which is used for all encryption.
+    pub fn active_key_hash(&self) -> &Hash {
+        self.active_key_hash.as_ref()
+    }
+
+    /// Returns whether a [`SymmetricKey`] matching the given [`Hash`] is loaded.
+    pub fn has_key(&self, key_hash: &Hash) -> bool {
+        self.keys.contains_key(key_hash)
+    }
+
+    /// Decrypts a ciphertext that was encrypted with the [`SymmetricKey`] matching the given
+    /// [`Hash`] and encrypts it again with the active key, returning the new crypted bytes, nonce,
+    /// and [`Hash`] of the active key.
+    ///
+    /// This is used when rotating keys so that an old key can be retired once nothing is
+    /// encrypted with it anymore.
+    ///
+    /// # Errors
+    ///
+    /// Return `Err` if the ciphertext could not be decrypted (see [`Self::decrypt`]).
+    pub fn reencrypt(
+        &self,
+        ciphertext: &[u8],
+        nonce: &SymmetricNonce,
+        key_hash: &Hash,
+    ) -> SymmetricCryptoResult<(Vec<u8>, SymmetricNonce, &Hash)> {
+        let message = self.decrypt(ciphertext, nonce, key_hash)?;
+        Ok(self.encrypt(&message))
+    }
+
     /// Decrypts a ciphertext provided with a nonce and a [`Hash`] of the encrypting
     /// [`SymmetricKey`] and returns the decrypted message.
     ///
@@ -313,6 +343,35 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn reencrypt_with_active_key() {
+        let old_key = SymmetricCryptoService::generate_key();
+        let old_service = SymmetricCryptoService::new(old_key.clone(), vec![]);
+
+        let message = b"I'm gonna make him an offer he can't refuse.";
+
+        let (ciphertext, nonce, old_key_hash) = old_service.encrypt(message);
+
+        let new_key = SymmetricCryptoService::generate_key();
+        let new_service = SymmetricCryptoService::new(new_key.clone(), vec![old_key]);
+        assert!(new_service.has_key(old_key_hash));
+
+        let (reencrypted, new_nonce, new_key_hash) = new_service
+            .reencrypt(ciphertext.as_ref(), &nonce, old_key_hash)
+            .expect("Should be able to reencrypt");
+        assert_eq!(new_service.active_key_hash(), new_key_hash);
+        assert_ne!(old_key_hash, new_key_hash);
+
+        // The old key can now be retired
+        let retired_service = SymmetricCryptoService::new(new_key, vec![]);
+        assert!(!retired_service.has_key(old_key_hash));
+        let decrypted = retired_service
+            .decrypt(reencrypted.as_ref(), &new_nonce, new_key_hash)
+            .expect("Should be able to decrypt");
+
+        assert_eq!(message.as_slice(), decrypted);
+    }
+
     #[tokio::test]
     async fn filesystem_round_trip() {
         let key = SymmetricCryptoService::generate_key();
@@ -288,6 +288,17 @@ impl PgPool {
         &self.metadata.db_name
     }
 
+    /// Changes the maximum size of the pool, which is shared by every clone of it. Shrinking the
+    /// pool closes idle connections right away and the others once they are returned.
+    pub fn resize(&self, max_size: usize) {
+        self.pool.resize(max_size);
+        info!(
+            db.name = %self.metadata.db_name,
+            db.pool.max_size = max_size,
+            "resized database connection pool",
+        );
+    }
+
     /// Retrieve object from pool or wait for one to become available.
     #[instrument(
         name = "pool.get",
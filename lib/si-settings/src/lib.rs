@@ -4,6 +4,12 @@ use config_file::ConfigMap;
 use serde::{de::DeserializeOwned, Serialize};
 use thiserror::Error;
 
+mod reload;
+
+pub use reload::{
+    changed_settings, start_config_reload_signal_handler_task, ConfigReload, ReloadableConfigFile,
+};
+
 #[remain::sorted]
 #[derive(Error, Debug)]
 pub enum SettingsError {
@@ -0,0 +1,232 @@
+//! Re-loading the layered config of a running service on `SIGHUP`.
+//!
+//! Only the settings a service lists in [`ReloadableConfigFile::LIVE_SETTINGS`] are handed back
+//! to it to be applied. Every other change is reported as requiring a restart and is otherwise
+//! ignored, so a service keeps running with the config it started with.
+
+use std::{collections::BTreeMap, fmt::Display, io};
+
+use serde::Serialize;
+use serde_json::Value;
+use telemetry::prelude::*;
+use tokio::{
+    signal::unix,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+use crate::StandardConfigFile;
+
+pub trait ReloadableConfigFile: StandardConfigFile {
+    /// The settings that can be applied without a restart, as dotted keys such as
+    /// `pg.pool_max_size`. A key also covers every setting nested under it.
+    const LIVE_SETTINGS: &'static [&'static str];
+
+    fn is_live_setting(key: &str) -> bool {
+        Self::LIVE_SETTINGS
+            .iter()
+            .any(|live| is_or_nested_under(key, live))
+    }
+}
+
+/// A reloaded config file with the live settings that changed since the last reload.
+#[derive(Debug)]
+pub struct ConfigReload<C> {
+    pub config_file: C,
+    pub changed: Vec<String>,
+}
+
+impl<C> ConfigReload<C> {
+    /// Whether the live setting `key` changed.
+    pub fn has_changed(&self, key: &str) -> bool {
+        self.changed
+            .iter()
+            .any(|changed| is_or_nested_under(changed, key))
+    }
+}
+
+/// Whether `key` is `setting` or one of the settings nested under it.
+fn is_or_nested_under(key: &str, setting: &str) -> bool {
+    key.strip_prefix(setting)
+        .map_or(false, |nested| nested.is_empty() || nested.starts_with('.'))
+}
+
+/// Starts a task re-loading the config with `load` on every `SIGHUP`, starting from the
+/// `config_file` the service is running with.
+///
+/// A config that fails to load is reported and skipped.
+pub fn start_config_reload_signal_handler_task<C, F, E>(
+    config_file: C,
+    load: F,
+) -> io::Result<Receiver<ConfigReload<C>>>
+where
+    C: ReloadableConfigFile,
+    F: Fn() -> Result<C, E> + Send + 'static,
+    E: Display + 'static,
+{
+    let hangup = unix::signal(unix::SignalKind::hangup())?;
+    let (tx, rx) = mpsc::channel(4);
+    drop(tokio::spawn(config_reload_signal_handler_task(
+        config_file,
+        load,
+        hangup,
+        tx,
+    )));
+    Ok(rx)
+}
+
+async fn config_reload_signal_handler_task<C, F, E>(
+    config_file: C,
+    load: F,
+    mut hangup: unix::Signal,
+    tx: Sender<ConfigReload<C>>,
+) where
+    C: ReloadableConfigFile,
+    F: Fn() -> Result<C, E>,
+    E: Display + 'static,
+{
+    // Restart only settings are compared to what the service started with so that they are
+    // reported on every reload until the service is restarted
+    let started = config_file.clone();
+    let mut applied = config_file;
+
+    while hangup.recv().await.is_some() {
+        info!("SIGHUP received; reloading config");
+        let reloaded = match load() {
+            Ok(reloaded) => reloaded,
+            Err(err) => {
+                warn!(error = %err, "failed to reload config, keeping the current one");
+                continue;
+            }
+        };
+
+        let restart_required: Vec<String> = changed_settings(&started, &reloaded)
+            .into_iter()
+            .filter(|key| !C::is_live_setting(key))
+            .collect();
+        if !restart_required.is_empty() {
+            warn!(
+                settings = ?restart_required,
+                "reloaded config changes settings that only take effect after a restart",
+            );
+        }
+
+        let changed: Vec<String> = changed_settings(&applied, &reloaded)
+            .into_iter()
+            .filter(|key| C::is_live_setting(key))
+            .collect();
+        if changed.is_empty() {
+            info!("reloaded config has no settings to apply");
+            continue;
+        }
+
+        info!(settings = ?changed, "applying reloaded config");
+        applied = reloaded.clone();
+        if tx
+            .send(ConfigReload {
+                config_file: reloaded,
+                changed,
+            })
+            .await
+            .is_err()
+        {
+            debug!("config reload receiver closed, ending task");
+            break;
+        }
+    }
+}
+
+/// The settings that differ between two config files, as dotted keys.
+pub fn changed_settings<C: Serialize>(current: &C, reloaded: &C) -> Vec<String> {
+    let (current, reloaded) = match (
+        serde_json::to_value(current),
+        serde_json::to_value(reloaded),
+    ) {
+        (Ok(current), Ok(reloaded)) => (flatten(current), flatten(reloaded)),
+        (Err(err), _) | (_, Err(err)) => {
+            warn!(error = ?err, "failed to compare configs");
+            return Vec::new();
+        }
+    };
+
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|(key, value)| reloaded.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    changed.extend(
+        reloaded
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .cloned(),
+    );
+    changed.sort();
+    changed
+}
+
+fn flatten(value: Value) -> BTreeMap<String, Value> {
+    fn flatten_into(prefix: String, value: Value, settings: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    flatten_into(key, value, settings);
+                }
+            }
+            value => {
+                settings.insert(prefix, value);
+            }
+        }
+    }
+
+    let mut settings = BTreeMap::new();
+    flatten_into(String::new(), value, &mut settings);
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Pg {
+        hostname: String,
+        pool_max_size: u32,
+    }
+
+    #[derive(Serialize)]
+    struct Settings {
+        pg: Pg,
+        log_filter: Option<String>,
+    }
+
+    fn settings(hostname: &str, pool_max_size: u32, log_filter: Option<&str>) -> Settings {
+        Settings {
+            pg: Pg {
+                hostname: hostname.to_owned(),
+                pool_max_size,
+            },
+            log_filter: log_filter.map(ToOwned::to_owned),
+        }
+    }
+
+    #[test]
+    fn unchanged() {
+        let current = settings("localhost", 16, None);
+        assert!(changed_settings(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn nested_and_optional_changes() {
+        let current = settings("localhost", 16, None);
+        let reloaded = settings("postgres", 32, Some("debug"));
+
+        assert_eq!(
+            vec!["log_filter", "pg.hostname", "pg.pool_max_size"],
+            changed_settings(&current, &reloaded)
+        );
+    }
+}
@@ -16,8 +16,9 @@ pub use cyclone_core::{
     EncryptionKeyError, FunctionResult, FunctionResultFailure, OutputStream, ReconciliationRequest,
     ReconciliationResultSuccess, ResolverFunctionComponent, ResolverFunctionRequest,
     ResolverFunctionResponseType, ResolverFunctionResultSuccess, ResourceStatus,
-    SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess, SensitiveContainer,
-    ValidationRequest, ValidationResultSuccess,
+    SchemaVariantDefinitionRequest, SchemaVariantDefinitionResultSuccess, SecretBackendKind,
+    SecretReference, SensitiveContainer, ValidationRequest, ValidationResultSuccess,
+    WorkspaceSecretReference,
 };
 use si_data_nats::NatsClient;
 
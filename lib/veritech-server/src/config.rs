@@ -19,7 +19,10 @@ use si_data_nats::NatsConfig;
 use telemetry::prelude::*;
 use thiserror::Error;
 
-pub use si_settings::{StandardConfig, StandardConfigFile};
+pub use si_settings::{
+    start_config_reload_signal_handler_task, ConfigReload, ReloadableConfigFile, StandardConfig,
+    StandardConfigFile,
+};
 
 #[remain::sorted]
 #[derive(Debug, Error)]
@@ -67,6 +70,8 @@ impl StandardConfig for Config {
 pub struct ConfigFile {
     pub nats: NatsConfig,
     pub cyclone: CycloneConfig,
+    /// Tracing directives replacing the ones from `SI_LOG`, such as `info,veritech_server=debug`.
+    pub log_filter: Option<String>,
 }
 
 impl ConfigFile {
@@ -74,6 +79,7 @@ impl ConfigFile {
         Self {
             nats: Default::default(),
             cyclone: CycloneConfig::default_local_http(),
+            log_filter: None,
         }
     }
 
@@ -81,6 +87,7 @@ impl ConfigFile {
         Self {
             nats: Default::default(),
             cyclone: CycloneConfig::default_local_uds(),
+            log_filter: None,
         }
     }
 }
@@ -89,6 +96,10 @@ impl StandardConfigFile for ConfigFile {
     type Error = ConfigError;
 }
 
+impl ReloadableConfigFile for ConfigFile {
+    const LIVE_SETTINGS: &'static [&'static str] = &["log_filter"];
+}
+
 impl TryFrom<ConfigFile> for Config {
     type Error = ConfigError;
 
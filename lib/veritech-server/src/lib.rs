@@ -5,8 +5,9 @@ mod subscriber;
 
 pub use crate::{
     config::{
-        detect_and_configure_development, Config, ConfigBuilder, ConfigError, ConfigFile,
-        CycloneSpec, CycloneStream, StandardConfig, StandardConfigFile,
+        detect_and_configure_development, start_config_reload_signal_handler_task, Config,
+        ConfigBuilder, ConfigError, ConfigFile, ConfigReload, CycloneSpec, CycloneStream,
+        ReloadableConfigFile, StandardConfig, StandardConfigFile,
     },
     server::{Server, ServerError, VeritechShutdownHandle},
 };